@@ -3,7 +3,9 @@ use std::error::Error as StdError;
 
 use clap::{App, AppSettings, Arg, SubCommand};
 use indradb::{
-    EdgeKey, EdgePropertyQuery, EdgeQuery, SpecificEdgeQuery, SpecificVertexQuery, VertexPropertyQuery, VertexQuery,
+    EdgeKey, EdgePropertyQuery, EdgeQuery, PipePropertyPresenceEdgeQuery, PipePropertyPresenceVertexQuery,
+    PipePropertyValueEdgeQuery, PipePropertyValueVertexQuery, RangeVertexQuery, SpecificEdgeQuery, SpecificVertexQuery,
+    VertexPropertyQuery, VertexQuery, VertexQueryExt,
 };
 use indradb_proto as proto;
 use std::convert::TryInto;
@@ -35,6 +37,40 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
         .help("the property value as JSON")
         .required(true);
 
+    let count_property_name_arg = Arg::with_name("property")
+        .help("only count items with this property, which must be indexed")
+        .long("property")
+        .value_name("name")
+        .takes_value(true);
+
+    let count_property_value_arg = Arg::with_name("value")
+        .help("only count items whose property is equal to this JSON value")
+        .long("value")
+        .value_name("value")
+        .takes_value(true)
+        .requires("property");
+
+    let index_type_arg = Arg::with_name("type")
+        .help("the vertex or edge type to scope the index to")
+        .long("type")
+        .value_name("type")
+        .takes_value(true);
+
+    let index_with_arg = Arg::with_name("with")
+        .help("another property to include in a composite index, after the first; can be repeated")
+        .long("with")
+        .value_name("name")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .conflicts_with("type");
+
+    let unique_type_arg = Arg::with_name("type")
+        .help("the vertex or edge type to scope the constraint to")
+        .long("type")
+        .value_name("type")
+        .takes_value(true);
+
     let matches = App::new("indradb-client")
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .arg(
@@ -44,6 +80,16 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
                 .index(1),
         )
         .subcommand(SubCommand::with_name("ping").about("pings the server"))
+        .subcommand(
+            SubCommand::with_name("backup")
+                .about("backs up the datastore while the server is running")
+                .arg(
+                    Arg::with_name("path")
+                        .help("the path to back up to, relative to the server's backup directory")
+                        .required(true)
+                        .index(1),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("set")
                 .setting(AppSettings::SubcommandRequiredElseHelp)
@@ -77,12 +123,48 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
                         .args(&edge_query_arg)
                         .arg(&required_property_name_arg)
                         .arg(&property_value_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("index")
+                        .about("indexes a property")
+                        .arg(&required_property_name_arg)
+                        .arg(
+                            Arg::with_name("background")
+                                .help("build the index in the background, rather than waiting for it to be ready")
+                                .long("background")
+                                .conflicts_with_all(&["type", "with"]),
+                        )
+                        .arg(&index_type_arg)
+                        .arg(&index_with_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("unique")
+                        .about("adds a unique constraint on an indexed property")
+                        .arg(&required_property_name_arg)
+                        .arg(&unique_type_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("schema")
+                        .about("sets the schema that writes are checked against")
+                        .arg(Arg::with_name("schema").help("the schema as JSON").required(true)),
                 ),
         )
         .subcommand(
             SubCommand::with_name("count")
                 .setting(AppSettings::SubcommandRequiredElseHelp)
-                .subcommand(SubCommand::with_name("vertex").about("counts the number of vertices"))
+                .subcommand(
+                    SubCommand::with_name("vertex")
+                        .about("counts the number of vertices")
+                        .arg(
+                            Arg::with_name("type")
+                                .help("the type of vertices to count; if not set, all vertex types will be counted")
+                                .long("type")
+                                .value_name("type")
+                                .takes_value(true),
+                        )
+                        .arg(&count_property_name_arg)
+                        .arg(&count_property_value_arg),
+                )
                 .subcommand(
                     SubCommand::with_name("edge")
                         .about("counts the number of edges")
@@ -103,7 +185,9 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
                                 .long("type")
                                 .value_name("type")
                                 .takes_value(true),
-                        ),
+                        )
+                        .arg(&count_property_name_arg)
+                        .arg(&count_property_value_arg),
                 ),
         )
         .subcommand(
@@ -130,6 +214,35 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
                         .about("gets edge properties")
                         .args(&edge_query_arg)
                         .arg(&optional_property_name_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("index")
+                        .about("gets the names of the indexed properties")
+                        .arg(
+                            Arg::with_name("name")
+                                .help("the property name; if set, the status of its index will be fetched instead")
+                                .long("name")
+                                .value_name("name")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("typed")
+                                .help("get the types and property names of the indexes scoped to a type instead")
+                                .long("typed")
+                                .conflicts_with("name"),
+                        )
+                        .arg(
+                            Arg::with_name("composite")
+                                .help("get the property names of the composite indexes instead")
+                                .long("composite")
+                                .conflicts_with_all(&["name", "typed"]),
+                        ),
+                )
+                .subcommand(SubCommand::with_name("unique").about("gets the unique constraints"))
+                .subcommand(SubCommand::with_name("schema").about("gets the schema as JSON"))
+                .subcommand(
+                    SubCommand::with_name("summary")
+                        .about("gets the types of vertices and edges, with their counts and property names, as JSON"),
                 ),
         )
         .subcommand(
@@ -156,7 +269,21 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
                         .about("deletes edge properties")
                         .args(&edge_query_arg)
                         .arg(&required_property_name_arg),
-                ),
+                )
+                .subcommand(
+                    SubCommand::with_name("index")
+                        .about("drops the index of a property")
+                        .arg(&required_property_name_arg)
+                        .arg(&index_type_arg)
+                        .arg(&index_with_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("unique")
+                        .about("drops a unique constraint")
+                        .arg(&required_property_name_arg)
+                        .arg(&unique_type_arg),
+                )
+                .subcommand(SubCommand::with_name("schema").about("removes the schema")),
         )
         .get_matches();
 
@@ -173,6 +300,12 @@ async fn run(matches: clap::ArgMatches<'_>) -> Result<(), Box<dyn StdError>> {
         return Ok(());
     }
 
+    if let Some(matches) = matches.subcommand_matches("backup") {
+        client.backup(matches.value_of("path").unwrap()).await?;
+        println!("ok");
+        return Ok(());
+    }
+
     if let Some(matches) = matches.subcommand_matches("set") {
         if let Some(matches) = matches.subcommand_matches("vertex") {
             let vertex_type = indradb::Identifier::new(matches.value_of("type").unwrap())?;
@@ -209,24 +342,50 @@ async fn run(matches: clap::ArgMatches<'_>) -> Result<(), Box<dyn StdError>> {
             client
                 .set_edge_properties(EdgePropertyQuery::new(edge_query, property_name), property_value)
                 .await?;
+        } else if let Some(matches) = matches.subcommand_matches("index") {
+            let property_name = indradb::Identifier::new(matches.value_of("name").unwrap())?;
+            if let Some(t) = matches.value_of("type") {
+                client
+                    .index_type_property(indradb::Identifier::new(t)?, property_name)
+                    .await?;
+            } else if let Some(with) = matches.values_of("with") {
+                client
+                    .index_composite_properties(composite_property_names(property_name, with)?)
+                    .await?;
+            } else if matches.is_present("background") {
+                client.index_property_in_background(property_name).await?;
+            } else {
+                client.index_property(property_name).await?;
+            }
+        } else if let Some(matches) = matches.subcommand_matches("unique") {
+            let property_name = indradb::Identifier::new(matches.value_of("name").unwrap())?;
+            let t = matches.value_of("type").map(indradb::Identifier::new).transpose()?;
+            client.add_unique_constraint(property_name, t).await?;
+        } else if let Some(matches) = matches.subcommand_matches("schema") {
+            let schema: indradb::Schema = serde_json::from_str(matches.value_of("schema").unwrap())?;
+            client.set_schema(Some(schema)).await?;
         }
     } else if let Some(matches) = matches.subcommand_matches("count") {
-        if matches.subcommand_matches("vertex").is_some() {
-            let vertex_count = client.get_vertex_count().await?;
+        if let Some(matches) = matches.subcommand_matches("vertex") {
+            let mut range_query = RangeVertexQuery::new();
+            if let Some(vertex_type) = matches.value_of("type") {
+                range_query = range_query.t(indradb::Identifier::new(vertex_type)?);
+            }
+            let vertex_query = build_count_vertex_query(matches, range_query.into())?;
+            let vertex_count = client.count_vertices(vertex_query).await?;
             println!("{}", vertex_count);
         } else if let Some(matches) = matches.subcommand_matches("edge") {
             let vertex_id = uuid::Uuid::parse_str(matches.value_of("id").unwrap())?;
-            let edge_direction = match matches.value_of("inbound") {
-                Some(_) => indradb::EdgeDirection::Inbound,
-                None => indradb::EdgeDirection::Outbound,
-            };
-            let edge_type = match matches.value_of("type") {
-                Some(edge_type) => Some(indradb::Identifier::new(edge_type)?),
-                None => None,
+            let mut pipe_query = if matches.is_present("inbound") {
+                SpecificVertexQuery::single(vertex_id).inbound()
+            } else {
+                SpecificVertexQuery::single(vertex_id).outbound()
             };
-            let res = client
-                .get_edge_count(vertex_id, edge_type.as_ref(), edge_direction)
-                .await?;
+            if let Some(edge_type) = matches.value_of("type") {
+                pipe_query = pipe_query.t(indradb::Identifier::new(edge_type)?);
+            }
+            let edge_query = build_count_edge_query(matches, pipe_query.into())?;
+            let res = client.count_edges(edge_query).await?;
 
             println!("{}", res);
         }
@@ -276,6 +435,37 @@ async fn run(matches: clap::ArgMatches<'_>) -> Result<(), Box<dyn StdError>> {
                     println!("{:?}", edge_property);
                 }
             }
+        } else if let Some(matches) = matches.subcommand_matches("index") {
+            if matches.is_present("composite") {
+                let composite_properties = client.get_indexed_composite_properties().await?;
+
+                println!("{:?}", composite_properties);
+            } else if matches.is_present("typed") {
+                let type_properties = client.get_indexed_type_properties().await?;
+
+                println!("{:?}", type_properties);
+            } else if let Some(property_name) = matches.value_of("name") {
+                let property_name = indradb::Identifier::new(property_name)?;
+                let status = client.get_index_status(property_name).await?;
+
+                println!("{:?}", status);
+            } else {
+                let property_names = client.get_indexed_properties().await?;
+
+                println!("{:?}", property_names);
+            }
+        } else if matches.subcommand_matches("unique").is_some() {
+            let constraints = client.get_unique_constraints().await?;
+
+            println!("{:?}", constraints);
+        } else if matches.subcommand_matches("schema").is_some() {
+            let schema = client.get_schema().await?;
+
+            println!("{}", serde_json::to_string(&schema)?);
+        } else if matches.subcommand_matches("summary").is_some() {
+            let summary = client.get_schema_summary().await?;
+
+            println!("{}", serde_json::to_string(&summary)?);
         }
     } else if let Some(matches) = matches.subcommand_matches("delete") {
         if let Some(matches) = matches.subcommand_matches("vertex") {
@@ -297,6 +487,25 @@ async fn run(matches: clap::ArgMatches<'_>) -> Result<(), Box<dyn StdError>> {
                     property_name,
                 ))
                 .await?;
+        } else if let Some(matches) = matches.subcommand_matches("index") {
+            let property_name = indradb::Identifier::new(matches.value_of("name").unwrap())?;
+            if let Some(t) = matches.value_of("type") {
+                client
+                    .drop_type_index(indradb::Identifier::new(t)?, property_name)
+                    .await?;
+            } else if let Some(with) = matches.values_of("with") {
+                client
+                    .drop_composite_index(composite_property_names(property_name, with)?)
+                    .await?;
+            } else {
+                client.drop_index(property_name).await?;
+            }
+        } else if let Some(matches) = matches.subcommand_matches("unique") {
+            let property_name = indradb::Identifier::new(matches.value_of("name").unwrap())?;
+            let t = matches.value_of("type").map(indradb::Identifier::new).transpose()?;
+            client.drop_unique_constraint(property_name, t).await?;
+        } else if matches.subcommand_matches("schema").is_some() {
+            client.set_schema(None).await?;
         }
     }
 
@@ -309,6 +518,38 @@ fn build_vertex_query(matches: &clap::ArgMatches) -> Result<VertexQuery, Box<dyn
     Ok(VertexQuery::Specific(SpecificVertexQuery::single(vertex_id)))
 }
 
+// Filters the items counted by the `count` subcommands by property, if the
+// `--property` option is set.
+fn build_count_vertex_query(matches: &clap::ArgMatches, q: VertexQuery) -> Result<VertexQuery, Box<dyn StdError>> {
+    let property_name = match matches.value_of("property") {
+        Some(property_name) => indradb::Identifier::new(property_name)?,
+        None => return Ok(q),
+    };
+
+    Ok(match matches.value_of("value") {
+        Some(property_value) => {
+            let property_value = serde_json::from_str(property_value)?;
+            PipePropertyValueVertexQuery::new(Box::new(q), property_name, property_value, true).into()
+        }
+        None => PipePropertyPresenceVertexQuery::new(Box::new(q), property_name, true).into(),
+    })
+}
+
+fn build_count_edge_query(matches: &clap::ArgMatches, q: EdgeQuery) -> Result<EdgeQuery, Box<dyn StdError>> {
+    let property_name = match matches.value_of("property") {
+        Some(property_name) => indradb::Identifier::new(property_name)?,
+        None => return Ok(q),
+    };
+
+    Ok(match matches.value_of("value") {
+        Some(property_value) => {
+            let property_value = serde_json::from_str(property_value)?;
+            PipePropertyValueEdgeQuery::new(Box::new(q), property_name, property_value, true).into()
+        }
+        None => PipePropertyPresenceEdgeQuery::new(Box::new(q), property_name, true).into(),
+    })
+}
+
 fn build_edge_key(matches: &clap::ArgMatches) -> Result<EdgeKey, Box<dyn StdError>> {
     let edge_type = indradb::Identifier::new(matches.value_of("type").unwrap())?;
     let outbound_id = uuid::Uuid::parse_str(matches.value_of("outbound_id").unwrap())?;
@@ -321,3 +562,14 @@ fn build_edge_key(matches: &clap::ArgMatches) -> Result<EdgeKey, Box<dyn StdErro
 fn build_edge_query(edge_key: EdgeKey) -> EdgeQuery {
     EdgeQuery::Specific(SpecificEdgeQuery::single(edge_key))
 }
+
+fn composite_property_names(
+    first: indradb::Identifier,
+    rest: clap::Values,
+) -> Result<Vec<indradb::Identifier>, Box<dyn StdError>> {
+    let mut names = vec![first];
+    for name in rest {
+        names.push(indradb::Identifier::new(name)?);
+    }
+    Ok(names)
+}
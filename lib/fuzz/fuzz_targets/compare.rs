@@ -27,6 +27,20 @@ pub enum Op {
     SetEdgeProperties(EdgePropertyQuery, Json),
     DeleteEdgeProperties(EdgePropertyQuery),
     IndexProperty(Identifier),
+    DropIndex(Identifier),
+    GetIndexedProperties,
+    IndexTypeProperty(Identifier, Identifier),
+    DropTypeIndex(Identifier, Identifier),
+    GetIndexedTypeProperties,
+    IndexCompositeProperties(Vec<Identifier>),
+    DropCompositeIndex(Vec<Identifier>),
+    GetIndexedCompositeProperties,
+    AddUniqueConstraint(Identifier, Option<Identifier>),
+    DropUniqueConstraint(Identifier, Option<Identifier>),
+    GetUniqueConstraints,
+    SetSchema(Option<Schema>),
+    GetSchema,
+    GetSchemaSummary,
 }
 
 #[derive(Arbitrary, Clone, Debug, PartialEq)]
@@ -110,6 +124,11 @@ pub enum VertexQuery {
     PropertyValue(PropertyValueVertexQuery),
     PipePropertyPresence(PipePropertyPresenceVertexQuery),
     PipePropertyValue(PipePropertyValueVertexQuery),
+    PropertyValueRange(PropertyValueRangeVertexQuery),
+    PipePropertyValueRange(PipePropertyValueRangeVertexQuery),
+    Union(Box<VertexQuery>, Box<VertexQuery>),
+    Intersect(Box<VertexQuery>, Box<VertexQuery>),
+    Except(Box<VertexQuery>, Box<VertexQuery>),
 }
 
 impl Into<indradb::VertexQuery> for VertexQuery {
@@ -122,6 +141,19 @@ impl Into<indradb::VertexQuery> for VertexQuery {
             VertexQuery::PropertyValue(q) => indradb::VertexQuery::PropertyValue(q.into()),
             VertexQuery::PipePropertyPresence(q) => indradb::VertexQuery::PipePropertyPresence(q.into()),
             VertexQuery::PipePropertyValue(q) => indradb::VertexQuery::PipePropertyValue(q.into()),
+            VertexQuery::PropertyValueRange(q) => indradb::VertexQuery::PropertyValueRange(q.into()),
+            VertexQuery::PipePropertyValueRange(q) => indradb::VertexQuery::PipePropertyValueRange(q.into()),
+            VertexQuery::Union(inner, other) => indradb::VertexQuery::Union(indradb::UnionVertexQuery::new(
+                Box::new((*inner).into()),
+                Box::new((*other).into()),
+            )),
+            VertexQuery::Intersect(inner, other) => indradb::VertexQuery::Intersect(
+                indradb::IntersectVertexQuery::new(Box::new((*inner).into()), Box::new((*other).into())),
+            ),
+            VertexQuery::Except(inner, other) => indradb::VertexQuery::Except(indradb::ExceptVertexQuery::new(
+                Box::new((*inner).into()),
+                Box::new((*other).into()),
+            )),
         }
     }
 }
@@ -239,6 +271,40 @@ impl Into<indradb::PipePropertyValueVertexQuery> for PipePropertyValueVertexQuer
     }
 }
 
+#[derive(Arbitrary, PartialEq, Clone, Debug)]
+pub struct PropertyValueRangeVertexQuery {
+    pub name: Identifier,
+    pub range: PropertyValueRange,
+}
+
+impl Into<indradb::PropertyValueRangeVertexQuery> for PropertyValueRangeVertexQuery {
+    fn into(self) -> indradb::PropertyValueRangeVertexQuery {
+        indradb::PropertyValueRangeVertexQuery {
+            name: self.name.into(),
+            range: self.range.into(),
+        }
+    }
+}
+
+#[derive(Arbitrary, PartialEq, Clone, Debug)]
+pub struct PipePropertyValueRangeVertexQuery {
+    pub inner: Box<VertexQuery>,
+    pub name: Identifier,
+    pub range: PropertyValueRange,
+    pub within: bool,
+}
+
+impl Into<indradb::PipePropertyValueRangeVertexQuery> for PipePropertyValueRangeVertexQuery {
+    fn into(self) -> indradb::PipePropertyValueRangeVertexQuery {
+        indradb::PipePropertyValueRangeVertexQuery {
+            inner: Box::new((*self.inner).into()),
+            name: self.name.into(),
+            range: self.range.into(),
+            within: self.within,
+        }
+    }
+}
+
 #[derive(Arbitrary, Clone, Debug, PartialEq)]
 pub struct VertexPropertyQuery {
     pub inner: VertexQuery,
@@ -262,6 +328,11 @@ pub enum EdgeQuery {
     PropertyValue(PropertyValueEdgeQuery),
     PipePropertyPresence(PipePropertyPresenceEdgeQuery),
     PipePropertyValue(PipePropertyValueEdgeQuery),
+    PropertyValueRange(PropertyValueRangeEdgeQuery),
+    PipePropertyValueRange(PipePropertyValueRangeEdgeQuery),
+    Union(Box<EdgeQuery>, Box<EdgeQuery>),
+    Intersect(Box<EdgeQuery>, Box<EdgeQuery>),
+    Except(Box<EdgeQuery>, Box<EdgeQuery>),
 }
 
 impl Into<indradb::EdgeQuery> for EdgeQuery {
@@ -273,6 +344,20 @@ impl Into<indradb::EdgeQuery> for EdgeQuery {
             EdgeQuery::PropertyValue(q) => indradb::EdgeQuery::PropertyValue(q.into()),
             EdgeQuery::PipePropertyPresence(q) => indradb::EdgeQuery::PipePropertyPresence(q.into()),
             EdgeQuery::PipePropertyValue(q) => indradb::EdgeQuery::PipePropertyValue(q.into()),
+            EdgeQuery::PropertyValueRange(q) => indradb::EdgeQuery::PropertyValueRange(q.into()),
+            EdgeQuery::PipePropertyValueRange(q) => indradb::EdgeQuery::PipePropertyValueRange(q.into()),
+            EdgeQuery::Union(inner, other) => indradb::EdgeQuery::Union(indradb::UnionEdgeQuery::new(
+                Box::new((*inner).into()),
+                Box::new((*other).into()),
+            )),
+            EdgeQuery::Intersect(inner, other) => indradb::EdgeQuery::Intersect(indradb::IntersectEdgeQuery::new(
+                Box::new((*inner).into()),
+                Box::new((*other).into()),
+            )),
+            EdgeQuery::Except(inner, other) => indradb::EdgeQuery::Except(indradb::ExceptEdgeQuery::new(
+                Box::new((*inner).into()),
+                Box::new((*other).into()),
+            )),
         }
     }
 }
@@ -377,6 +462,40 @@ impl Into<indradb::PipePropertyValueEdgeQuery> for PipePropertyValueEdgeQuery {
     }
 }
 
+#[derive(Arbitrary, PartialEq, Clone, Debug)]
+pub struct PropertyValueRangeEdgeQuery {
+    pub name: Identifier,
+    pub range: PropertyValueRange,
+}
+
+impl Into<indradb::PropertyValueRangeEdgeQuery> for PropertyValueRangeEdgeQuery {
+    fn into(self) -> indradb::PropertyValueRangeEdgeQuery {
+        indradb::PropertyValueRangeEdgeQuery {
+            name: self.name.into(),
+            range: self.range.into(),
+        }
+    }
+}
+
+#[derive(Arbitrary, PartialEq, Clone, Debug)]
+pub struct PipePropertyValueRangeEdgeQuery {
+    pub inner: Box<EdgeQuery>,
+    pub name: Identifier,
+    pub range: PropertyValueRange,
+    pub within: bool,
+}
+
+impl Into<indradb::PipePropertyValueRangeEdgeQuery> for PipePropertyValueRangeEdgeQuery {
+    fn into(self) -> indradb::PipePropertyValueRangeEdgeQuery {
+        indradb::PipePropertyValueRangeEdgeQuery {
+            inner: Box::new((*self.inner).into()),
+            name: self.name.into(),
+            range: self.range.into(),
+            within: self.within,
+        }
+    }
+}
+
 #[derive(Arbitrary, Clone, Debug, PartialEq)]
 pub struct EdgePropertyQuery {
     pub inner: EdgeQuery,
@@ -535,6 +654,25 @@ impl<'a> Arbitrary<'a> for DateTime {
     }
 }
 
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub enum PropertyValueRange {
+    GreaterThan(Json),
+    LessThan(Json),
+    Between(Json, Json),
+    Prefix(String),
+}
+
+impl Into<indradb::PropertyValueRange> for PropertyValueRange {
+    fn into(self) -> indradb::PropertyValueRange {
+        match self {
+            PropertyValueRange::GreaterThan(value) => indradb::PropertyValueRange::GreaterThan(value.into()),
+            PropertyValueRange::LessThan(value) => indradb::PropertyValueRange::LessThan(value.into()),
+            PropertyValueRange::Between(low, high) => indradb::PropertyValueRange::Between(low.into(), high.into()),
+            PropertyValueRange::Prefix(prefix) => indradb::PropertyValueRange::Prefix(prefix),
+        }
+    }
+}
+
 #[derive(Arbitrary, Clone, Debug, PartialEq)]
 pub enum Json {
     Null,
@@ -604,6 +742,90 @@ impl Into<f64> for FiniteFloat {
     }
 }
 
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub struct Schema {
+    pub vertex_types: Vec<(Identifier, Vec<(Identifier, PropertySchema)>)>,
+    pub edge_types: Vec<EdgeSchema>,
+}
+
+impl Into<indradb::Schema> for Schema {
+    fn into(self) -> indradb::Schema {
+        indradb::Schema {
+            vertex_types: self
+                .vertex_types
+                .into_iter()
+                .map(|(t, properties)| {
+                    let properties = properties.into_iter().map(|(name, p)| (name.into(), p.into())).collect();
+                    (t.into(), indradb::VertexSchema { properties })
+                })
+                .collect(),
+            edge_types: self.edge_types.into_iter().map(|schema| schema.into()).collect(),
+        }
+    }
+}
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub struct EdgeSchema {
+    pub t: Identifier,
+    pub outbound_types: Vec<Identifier>,
+    pub inbound_types: Vec<Identifier>,
+    pub properties: Vec<(Identifier, PropertySchema)>,
+}
+
+impl Into<(indradb::Identifier, indradb::EdgeSchema)> for EdgeSchema {
+    fn into(self) -> (indradb::Identifier, indradb::EdgeSchema) {
+        let schema = indradb::EdgeSchema {
+            outbound_types: self.outbound_types.into_iter().map(|t| t.into()).collect(),
+            inbound_types: self.inbound_types.into_iter().map(|t| t.into()).collect(),
+            properties: self
+                .properties
+                .into_iter()
+                .map(|(name, p)| (name.into(), p.into()))
+                .collect(),
+        };
+        (self.t.into(), schema)
+    }
+}
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub struct PropertySchema {
+    pub t: JsonType,
+    pub required: bool,
+}
+
+impl Into<indradb::PropertySchema> for PropertySchema {
+    fn into(self) -> indradb::PropertySchema {
+        indradb::PropertySchema::new(self.t.into(), self.required)
+    }
+}
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub enum JsonType {
+    Any,
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Into<indradb::JsonType> for JsonType {
+    fn into(self) -> indradb::JsonType {
+        match self {
+            JsonType::Any => indradb::JsonType::Any,
+            JsonType::Null => indradb::JsonType::Null,
+            JsonType::Boolean => indradb::JsonType::Boolean,
+            JsonType::Integer => indradb::JsonType::Integer,
+            JsonType::Number => indradb::JsonType::Number,
+            JsonType::String => indradb::JsonType::String,
+            JsonType::Array => indradb::JsonType::Array,
+            JsonType::Object => indradb::JsonType::Object,
+        }
+    }
+}
+
 macro_rules! cmp {
     ($v1:expr, $v2:expr) => {
         match ($v1, $v2) {
@@ -734,6 +956,81 @@ fuzz_target!(|ops: Vec<Op>| {
                 let v2 = d2.index_property(t.into());
                 cmp!(v1, v2);
             }
+            Op::DropIndex(t) => {
+                let v1 = d1.drop_index(t.clone().into());
+                let v2 = d2.drop_index(t.into());
+                cmp!(v1, v2);
+            }
+            Op::GetIndexedProperties => {
+                let v1 = d1.get_indexed_properties();
+                let v2 = d2.get_indexed_properties();
+                cmp!(v1, v2);
+            }
+            Op::IndexTypeProperty(t, name) => {
+                let v1 = d1.index_type_property(t.clone().into(), name.clone().into());
+                let v2 = d2.index_type_property(t.into(), name.into());
+                cmp!(v1, v2);
+            }
+            Op::DropTypeIndex(t, name) => {
+                let v1 = d1.drop_type_index(t.clone().into(), name.clone().into());
+                let v2 = d2.drop_type_index(t.into(), name.into());
+                cmp!(v1, v2);
+            }
+            Op::GetIndexedTypeProperties => {
+                let v1 = d1.get_indexed_type_properties();
+                let v2 = d2.get_indexed_type_properties();
+                cmp!(v1, v2);
+            }
+            Op::IndexCompositeProperties(names) => {
+                let names: Vec<indradb::Identifier> = names.into_iter().map(|name| name.into()).collect();
+                let v1 = d1.index_composite_properties(names.clone());
+                let v2 = d2.index_composite_properties(names);
+                cmp!(v1, v2);
+            }
+            Op::DropCompositeIndex(names) => {
+                let names: Vec<indradb::Identifier> = names.into_iter().map(|name| name.into()).collect();
+                let v1 = d1.drop_composite_index(names.clone());
+                let v2 = d2.drop_composite_index(names);
+                cmp!(v1, v2);
+            }
+            Op::GetIndexedCompositeProperties => {
+                let v1 = d1.get_indexed_composite_properties();
+                let v2 = d2.get_indexed_composite_properties();
+                cmp!(v1, v2);
+            }
+            Op::AddUniqueConstraint(name, t) => {
+                let t: Option<indradb::Identifier> = t.map(|t| t.into());
+                let v1 = d1.add_unique_constraint(name.clone().into(), t.clone());
+                let v2 = d2.add_unique_constraint(name.into(), t);
+                cmp!(v1, v2);
+            }
+            Op::DropUniqueConstraint(name, t) => {
+                let t: Option<indradb::Identifier> = t.map(|t| t.into());
+                let v1 = d1.drop_unique_constraint(name.clone().into(), t.clone());
+                let v2 = d2.drop_unique_constraint(name.into(), t);
+                cmp!(v1, v2);
+            }
+            Op::GetUniqueConstraints => {
+                let v1 = d1.get_unique_constraints();
+                let v2 = d2.get_unique_constraints();
+                cmp!(v1, v2);
+            }
+            Op::SetSchema(schema) => {
+                let schema: Option<indradb::Schema> = schema.map(|schema| schema.into());
+                let v1 = d1.set_schema(schema.clone());
+                let v2 = d2.set_schema(schema);
+                cmp!(v1, v2);
+            }
+            Op::GetSchema => {
+                let v1 = d1.get_schema();
+                let v2 = d2.get_schema();
+                cmp!(v1, v2);
+            }
+            Op::GetSchemaSummary => {
+                let v1 = d1.get_schema_summary();
+                let v2 = d2.get_schema_summary();
+                cmp!(v1, v2);
+            }
         }
     }
 });
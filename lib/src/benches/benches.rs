@@ -3,6 +3,9 @@ use crate::models::{
 };
 use crate::traits::Datastore;
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
 use test::Bencher;
 
 pub fn bench_create_vertex<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
@@ -124,3 +127,89 @@ pub fn bench_bulk_insert<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
         datastore.bulk_insert(items.clone()).unwrap();
     });
 }
+
+const PREFIX_LOOKUP_VERTEX_COUNT: usize = 10_000;
+
+// Measures lookups of the edges and properties of single vertices in a larger
+// graph. Half of the lookups are for vertices without any, which datastores
+// with prefix bloom filters can rule out without reading any keys.
+pub fn bench_prefix_lookups<D: Datastore>(b: &mut Bencher, datastore: &mut D) {
+    let t = Identifier::new("bench_prefix_lookups").unwrap();
+    let name = Identifier::new("is_benchmark").unwrap();
+
+    let vertices: Vec<Vertex> = (0..PREFIX_LOOKUP_VERTEX_COUNT)
+        .map(|_| Vertex::new(t.clone()))
+        .collect();
+    let mut items = Vec::with_capacity(3 * PREFIX_LOOKUP_VERTEX_COUNT);
+    for vertex in &vertices {
+        items.push(BulkInsertItem::Vertex(vertex.clone()));
+        items.push(BulkInsertItem::VertexProperty(
+            vertex.id,
+            name.clone(),
+            serde_json::Value::Bool(true),
+        ));
+    }
+    // Every other vertex has an outbound edge
+    for pair in vertices.chunks(2) {
+        items.push(BulkInsertItem::Edge(EdgeKey::new(pair[0].id, t.clone(), pair[1].id)));
+    }
+    datastore.bulk_insert(items).unwrap();
+    // Make sure the data has been written out, where lookups are slowest
+    datastore.sync().unwrap();
+
+    let mut i = 0;
+    b.iter(|| {
+        let id = vertices[i % PREFIX_LOOKUP_VERTEX_COUNT].id;
+        i += 1;
+        datastore.get_edge_count(id, None, EdgeDirection::Outbound).unwrap();
+        datastore.get_edge_count(id, Some(&t), EdgeDirection::Outbound).unwrap();
+        datastore
+            .get_all_vertex_properties(SpecificVertexQuery::single(id).into())
+            .unwrap();
+    });
+}
+
+const CONCURRENT_WRITE_COUNT: usize = 1_000;
+
+// Measures reads while another thread continually bulk inserts vertices and
+// indexes their properties, to show how much writers block readers.
+pub fn bench_concurrent_read_write<D: Datastore + Sync>(b: &mut Bencher, datastore: &mut D) {
+    let t = Identifier::new("bench_concurrent_read_write").unwrap();
+    let name = Identifier::new("is_benchmark").unwrap();
+
+    let id = {
+        let v = Vertex::new(t.clone());
+        datastore.create_vertex(&v).unwrap();
+        v.id
+    };
+
+    let datastore = &*datastore;
+    let done = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                let mut items = Vec::with_capacity(2 * CONCURRENT_WRITE_COUNT);
+                for _ in 0..CONCURRENT_WRITE_COUNT {
+                    let vertex = Vertex::new(t.clone());
+                    let id = vertex.id;
+                    items.push(BulkInsertItem::Vertex(vertex));
+                    items.push(BulkInsertItem::VertexProperty(
+                        id,
+                        name.clone(),
+                        serde_json::Value::Bool(true),
+                    ));
+                }
+                datastore.bulk_insert(items).unwrap();
+                datastore.index_property(name.clone()).unwrap();
+            }
+        });
+
+        b.iter(|| {
+            let q = SpecificVertexQuery::single(id);
+            datastore.get_vertices(q.into()).unwrap();
+        });
+
+        done.store(true, Ordering::Relaxed);
+    });
+}
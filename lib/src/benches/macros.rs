@@ -22,5 +22,15 @@ macro_rules! full_bench_impl {
         define_bench!(bench_get_edges, $code);
         define_bench!(bench_get_edge_count, $code);
         define_bench!(bench_bulk_insert, $code);
+        define_bench!(bench_prefix_lookups, $code);
+    };
+}
+
+/// Use this macro to enable the concurrency benchmarks, for datastores that
+/// can be shared between threads.
+#[macro_export]
+macro_rules! concurrent_bench_impl {
+    ($code:expr) => {
+        define_bench!(bench_concurrent_read_write, $code);
     };
 }
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod benches;
 #[macro_use]
 mod macros;
@@ -0,0 +1,925 @@
+// Runs vertex and edge queries in chunks, so that results can be streamed
+// without loading them all at once. A cursor records where the previous
+// chunk left off, and each datastore provides the seeks needed to resume
+// from there through `CursorSource`.
+//
+// Queries that can't be resumed this way - unions, traversals, and piped
+// property queries without a ready index - are run in full the first time a
+// chunk is needed, and the results are kept in the cursor.
+//
+// Cursors are also what continuation tokens hold, so that a page of results
+// resumes with a seek rather than by skipping the results of earlier pages.
+// The results of queries that are run in full aren't kept in tokens; those
+// queries are run again for each page, skipping what was already returned.
+
+use std::cell::Cell;
+use std::cmp::{min, Ordering};
+use std::collections::HashSet;
+use std::ops::{Bound, Deref, DerefMut};
+use std::sync::Arc;
+
+use crate::errors::{Error, Result};
+use crate::traits::{BatchIter, DynIter, ITER_BATCH_SIZE};
+use crate::{
+    ContinuationToken, EdgeDirection, EdgeKey, EdgeQuery, Identifier, Json, Page, PropertyValueRange, VertexQuery,
+};
+
+use chrono::offset::Utc;
+use chrono::DateTime;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
+
+// How deeply cursors can be nested in a decoded token. Cursors nest as
+// deeply as the query they're for, so this is only reached by tokens that
+// weren't created by a cursor.
+const MAX_CURSOR_DEPTH: usize = 128;
+
+thread_local! {
+    static CURSOR_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+pub(crate) type VertexItem = (Uuid, Identifier);
+pub(crate) type EdgeItem = (EdgeKey, DateTime<Utc>);
+
+// Where a scan of a vertex's edges left off: the type, update datetime, and
+// adjacent vertex ID of the last edge.
+pub(crate) type EdgeResume = (Identifier, DateTime<Utc>, Uuid);
+
+// Which values of an indexed property to visit.
+pub(crate) enum PropertyFilter<'a> {
+    Presence,
+    Value(&'a Json),
+    Range(&'a PropertyValueRange),
+}
+
+impl<'a> PropertyFilter<'a> {
+    // Gets the lowest value that could match, or `None` to start from the
+    // first value.
+    pub(crate) fn start(&self) -> Option<Json> {
+        match self {
+            PropertyFilter::Presence => None,
+            PropertyFilter::Value(value) => Some((*value).clone()),
+            PropertyFilter::Range(range) => Some(Json::new(range.start())),
+        }
+    }
+
+    // Determines where a value lies relative to the values that match, like
+    // `PropertyValueRange::locate`.
+    pub(crate) fn locate(&self, value: &Json) -> Ordering {
+        match self {
+            PropertyFilter::Presence => Ordering::Equal,
+            PropertyFilter::Value(expected) => value.cmp(expected),
+            PropertyFilter::Range(range) => range.locate(&value.0),
+        }
+    }
+}
+
+// The reads that cursors are resumed with. Implementations should read from
+// a consistent view of the datastore, so that results aren't skipped or
+// repeated if it's changed between chunks.
+pub(crate) trait CursorSource {
+    // Gets where a range query with the given start ID begins. Datastores
+    // differ on whether the start ID is inclusive.
+    fn range_start(&self, start_id: Option<Uuid>) -> Bound<Uuid>;
+
+    // Gets up to `limit` vertices in ID order, optionally only of type `t`,
+    // starting from `from`.
+    fn vertex_range(&self, t: Option<&Identifier>, from: Bound<Uuid>, limit: usize) -> Result<Vec<VertexItem>>;
+
+    // Gets the type of a vertex, or `None` if it doesn't exist.
+    fn vertex(&self, id: Uuid) -> Result<Option<Identifier>>;
+
+    // Gets up to `limit` of the edges of a vertex in the given direction,
+    // after `after` if it's set, in the same order that the datastore pipes
+    // them. The keys are returned as they'd be by a pipe query, so inbound
+    // edges have the vertex as their inbound ID.
+    #[allow(clippy::too_many_arguments)]
+    fn adjacent_edges(
+        &self,
+        id: Uuid,
+        t: Option<&Identifier>,
+        direction: EdgeDirection,
+        high: Option<DateTime<Utc>>,
+        low: Option<DateTime<Utc>>,
+        after: Option<&EdgeResume>,
+        limit: usize,
+    ) -> Result<Vec<EdgeItem>>;
+
+    // Gets the update datetime of an edge, or `None` if it doesn't exist.
+    fn edge(&self, key: &EdgeKey) -> Result<Option<DateTime<Utc>>>;
+
+    // Gets whether a property has an index that's ready to be queried.
+    fn is_indexed(&self, name: &Identifier) -> bool;
+
+    // Gets up to `limit` of the vertices in a property's index that match
+    // `filter`, sorted by value and then ID, after `after` if it's set.
+    fn vertex_property_values(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<&(Json, Uuid)>,
+        limit: usize,
+    ) -> Result<Vec<(Json, Uuid)>>;
+
+    // Like `vertex_property_values`, but for edges.
+    fn edge_property_values(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<&(Json, EdgeKey)>,
+        limit: usize,
+    ) -> Result<Vec<(Json, EdgeKey)>>;
+
+    fn vertex_property(&self, id: Uuid, name: &Identifier) -> Result<Option<Json>>;
+
+    fn edge_property(&self, key: &EdgeKey, name: &Identifier) -> Result<Option<Json>>;
+
+    // Runs a vertex query in full.
+    fn vertices(&self, q: VertexQuery) -> Result<Vec<VertexItem>>;
+
+    // Runs an edge query in full.
+    fn edges(&self, q: EdgeQuery) -> Result<Vec<EdgeItem>>;
+}
+
+// A cursor within the position of another. Tokens come from clients, so
+// rather than recursing as deeply as a token says, decoding fails past
+// `MAX_CURSOR_DEPTH`.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(transparent)]
+struct Nested<T>(Box<T>);
+
+impl<T> Deref for Nested<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Nested<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Nested<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let depth = CURSOR_DEPTH.with(|depth| {
+            depth.set(depth.get() + 1);
+            depth.get()
+        });
+        let result = if depth > MAX_CURSOR_DEPTH {
+            Err(D::Error::custom("cursor is nested too deeply"))
+        } else {
+            T::deserialize(deserializer).map(|value| Nested(Box::new(value)))
+        };
+        CURSOR_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        result
+    }
+}
+
+// Where a vertex query left off.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct VertexCursor {
+    position: VertexPosition,
+    // The number of results so far, which count towards the query's limit.
+    count: u64,
+    done: bool,
+    // Results that were computed in full, which are kept so that they're
+    // only computed once.
+    #[serde(skip)]
+    materialized: Option<Arc<Vec<VertexItem>>>,
+    #[serde(skip)]
+    other_ids: Option<Arc<HashSet<Uuid>>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+enum VertexPosition {
+    #[default]
+    Start,
+    // The last ID returned by a range query.
+    Id(Uuid),
+    // The index of the next result of a specific or materialized query.
+    Index(u64),
+    // The position of the inner query of a pipe query.
+    Edges(Nested<EdgeCursor>),
+    // The position of the inner query of a filtering query.
+    Vertices(Nested<VertexCursor>),
+    // The last value and ID returned from a property's index.
+    PropertyValue(Json, Uuid),
+}
+
+// Where an edge query left off.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EdgeCursor {
+    position: EdgePosition,
+    count: u64,
+    done: bool,
+    #[serde(skip)]
+    materialized: Option<Arc<Vec<EdgeItem>>>,
+    #[serde(skip)]
+    other_keys: Option<Arc<HashSet<EdgeKey>>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+enum EdgePosition {
+    #[default]
+    Start,
+    Index(u64),
+    // The position of the inner query of a pipe query, along with the
+    // vertex whose edges are being scanned, and the last of its edges that
+    // was returned.
+    Vertices(Nested<VertexCursor>, Option<(Uuid, Option<EdgeResume>)>),
+    Edges(Nested<EdgeCursor>),
+    PropertyValue(Json, EdgeKey),
+}
+
+impl VertexCursor {
+    // Gets whether there are no more results.
+    pub(crate) fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl EdgeCursor {
+    pub(crate) fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+// Gets the next `n` results of a vertex query, or fewer if there aren't
+// that many left.
+pub(crate) fn next_vertices<S: CursorSource + ?Sized>(
+    source: &S,
+    q: &VertexQuery,
+    cursor: &mut VertexCursor,
+    n: usize,
+) -> Result<Vec<VertexItem>> {
+    debug_assert!(n > 0);
+    let limit = match q {
+        VertexQuery::Range(q) => Some(q.limit),
+        VertexQuery::Pipe(q) => Some(q.limit),
+        _ => None,
+    };
+    let n = match limit {
+        Some(limit) => min(n as u64, u64::from(limit).saturating_sub(cursor.count)) as usize,
+        None => n,
+    };
+    if cursor.done || n == 0 {
+        cursor.done = true;
+        return Ok(Vec::new());
+    }
+
+    let vertices = match q {
+        q if cursor.materialized.is_some() || !is_resumable_vertex_query(source, q) => {
+            materialized_vertices(source, q, cursor, n)?
+        }
+        VertexQuery::Range(q) => {
+            let from = match cursor.position {
+                VertexPosition::Start => source.range_start(q.start_id),
+                VertexPosition::Id(id) => Bound::Excluded(id),
+                _ => return Err(Error::InvalidContinuationToken),
+            };
+            let vertices = source.vertex_range(q.t.as_ref(), from, n)?;
+            if let Some((id, _)) = vertices.last() {
+                cursor.position = VertexPosition::Id(*id);
+            }
+            vertices
+        }
+        VertexQuery::Specific(q) => {
+            let mut index = match cursor.position {
+                VertexPosition::Start => 0,
+                VertexPosition::Index(index) => index as usize,
+                _ => return Err(Error::InvalidContinuationToken),
+            };
+            let mut vertices = Vec::new();
+            while vertices.len() < n && index < q.ids.len() {
+                let id = q.ids[index];
+                index += 1;
+                if let Some(t) = source.vertex(id)? {
+                    vertices.push((id, t));
+                }
+            }
+            cursor.position = VertexPosition::Index(index as u64);
+            vertices
+        }
+        VertexQuery::Pipe(q) => {
+            if let VertexPosition::Start = cursor.position {
+                cursor.position = VertexPosition::Edges(Nested::default());
+            }
+            let inner_cursor = match cursor.position {
+                VertexPosition::Edges(ref mut inner_cursor) => inner_cursor,
+                _ => return Err(Error::InvalidContinuationToken),
+            };
+
+            let mut vertices = Vec::new();
+            while vertices.len() < n {
+                let wanted = n - vertices.len();
+                let edges = next_edges(source, &q.inner, inner_cursor, wanted)?;
+                for (key, _) in &edges {
+                    let id = match q.direction {
+                        EdgeDirection::Outbound => key.outbound_id,
+                        EdgeDirection::Inbound => key.inbound_id,
+                    };
+                    if let Some(t) = source.vertex(id)? {
+                        if q.t.as_ref().is_none_or(|expected| expected == &t) {
+                            vertices.push((id, t));
+                        }
+                    }
+                }
+                if edges.len() < wanted {
+                    break;
+                }
+            }
+            vertices
+        }
+        VertexQuery::Intersect(_) | VertexQuery::Except(_) => {
+            let (inner, other, intersect) = match q {
+                VertexQuery::Intersect(q) => (&q.inner, &q.other, true),
+                VertexQuery::Except(q) => (&q.inner, &q.other, false),
+                _ => unreachable!(),
+            };
+            let other_ids = match cursor.other_ids {
+                Some(ref ids) => ids.clone(),
+                None => {
+                    let ids: HashSet<Uuid> = source
+                        .vertices((**other).clone())?
+                        .into_iter()
+                        .map(|(id, _)| id)
+                        .collect();
+                    let ids = Arc::new(ids);
+                    cursor.other_ids = Some(ids.clone());
+                    ids
+                }
+            };
+            filter_vertices(source, inner, &mut cursor.position, n, |(id, _)| {
+                Ok(other_ids.contains(id) == intersect)
+            })?
+        }
+        VertexQuery::PropertyPresence(q) => {
+            property_vertices(source, &q.name, &PropertyFilter::Presence, &mut cursor.position, n)?
+        }
+        VertexQuery::PropertyValue(q) => {
+            let value = Json::new(q.value.clone());
+            property_vertices(source, &q.name, &PropertyFilter::Value(&value), &mut cursor.position, n)?
+        }
+        VertexQuery::PropertyValueRange(q) => property_vertices(
+            source,
+            &q.name,
+            &PropertyFilter::Range(&q.range),
+            &mut cursor.position,
+            n,
+        )?,
+        VertexQuery::PipePropertyPresence(pq) => {
+            filter_vertices(source, &pq.inner, &mut cursor.position, n, |(id, _)| {
+                Ok(source.vertex_property(*id, &pq.name)?.is_some() == pq.exists)
+            })?
+        }
+        VertexQuery::PipePropertyValue(pq) => {
+            let value = Json::new(pq.value.clone());
+            filter_vertices(source, &pq.inner, &mut cursor.position, n, |(id, _)| {
+                Ok((source.vertex_property(*id, &pq.name)?.as_ref() == Some(&value)) == pq.equal)
+            })?
+        }
+        VertexQuery::PipePropertyValueRange(pq) => {
+            filter_vertices(source, &pq.inner, &mut cursor.position, n, |(id, _)| {
+                let value = source.vertex_property(*id, &pq.name)?;
+                Ok(value.is_some_and(|value| pq.range.contains(&value.0)) == pq.within)
+            })?
+        }
+        VertexQuery::Traversal(_) | VertexQuery::Union(_) => unreachable!(),
+    };
+
+    cursor.count += vertices.len() as u64;
+    if vertices.len() < n {
+        cursor.done = true;
+    }
+    Ok(vertices)
+}
+
+// Gets the next `n` results of an edge query, or fewer if there aren't that
+// many left.
+pub(crate) fn next_edges<S: CursorSource + ?Sized>(
+    source: &S,
+    q: &EdgeQuery,
+    cursor: &mut EdgeCursor,
+    n: usize,
+) -> Result<Vec<EdgeItem>> {
+    debug_assert!(n > 0);
+    let n = match q {
+        EdgeQuery::Pipe(q) => min(n as u64, u64::from(q.limit).saturating_sub(cursor.count)) as usize,
+        _ => n,
+    };
+    if cursor.done || n == 0 {
+        cursor.done = true;
+        return Ok(Vec::new());
+    }
+
+    let edges = match q {
+        q if cursor.materialized.is_some() || !is_resumable_edge_query(source, q) => {
+            materialized_edges(source, q, cursor, n)?
+        }
+        EdgeQuery::Specific(q) => {
+            let mut index = match cursor.position {
+                EdgePosition::Start => 0,
+                EdgePosition::Index(index) => index as usize,
+                _ => return Err(Error::InvalidContinuationToken),
+            };
+            let mut edges = Vec::new();
+            while edges.len() < n && index < q.keys.len() {
+                let key = &q.keys[index];
+                index += 1;
+                if let Some(update_datetime) = source.edge(key)? {
+                    edges.push((key.clone(), update_datetime));
+                }
+            }
+            cursor.position = EdgePosition::Index(index as u64);
+            edges
+        }
+        EdgeQuery::Pipe(q) => {
+            if let EdgePosition::Start = cursor.position {
+                cursor.position = EdgePosition::Vertices(Nested::default(), None);
+            }
+            let (inner_cursor, current) = match cursor.position {
+                EdgePosition::Vertices(ref mut inner_cursor, ref mut current) => (inner_cursor, current),
+                _ => return Err(Error::InvalidContinuationToken),
+            };
+
+            let mut edges = Vec::new();
+            while edges.len() < n {
+                let (id, after) = match current {
+                    Some((id, after)) => (*id, after),
+                    None => match next_vertices(source, &q.inner, inner_cursor, 1)?.pop() {
+                        Some((id, _)) => {
+                            *current = Some((id, None));
+                            continue;
+                        }
+                        None => break,
+                    },
+                };
+
+                let wanted = n - edges.len();
+                let chunk =
+                    source.adjacent_edges(id, q.t.as_ref(), q.direction, q.high, q.low, after.as_ref(), wanted)?;
+                if let Some((key, update_datetime)) = chunk.last() {
+                    let other_id = match q.direction {
+                        EdgeDirection::Outbound => key.inbound_id,
+                        EdgeDirection::Inbound => key.outbound_id,
+                    };
+                    *after = Some((key.t.clone(), *update_datetime, other_id));
+                }
+                if chunk.len() < wanted {
+                    *current = None;
+                }
+                edges.extend(chunk);
+            }
+            edges
+        }
+        EdgeQuery::Intersect(_) | EdgeQuery::Except(_) => {
+            let (inner, other, intersect) = match q {
+                EdgeQuery::Intersect(q) => (&q.inner, &q.other, true),
+                EdgeQuery::Except(q) => (&q.inner, &q.other, false),
+                _ => unreachable!(),
+            };
+            let other_keys = match cursor.other_keys {
+                Some(ref keys) => keys.clone(),
+                None => {
+                    let keys: HashSet<EdgeKey> = source
+                        .edges((**other).clone())?
+                        .into_iter()
+                        .map(|(key, _)| key)
+                        .collect();
+                    let keys = Arc::new(keys);
+                    cursor.other_keys = Some(keys.clone());
+                    keys
+                }
+            };
+            filter_edges(source, inner, &mut cursor.position, n, |(key, _)| {
+                Ok(other_keys.contains(key) == intersect)
+            })?
+        }
+        EdgeQuery::PropertyPresence(q) => {
+            property_edges(source, &q.name, &PropertyFilter::Presence, &mut cursor.position, n)?
+        }
+        EdgeQuery::PropertyValue(q) => {
+            let value = Json::new(q.value.clone());
+            property_edges(source, &q.name, &PropertyFilter::Value(&value), &mut cursor.position, n)?
+        }
+        EdgeQuery::PropertyValueRange(q) => property_edges(
+            source,
+            &q.name,
+            &PropertyFilter::Range(&q.range),
+            &mut cursor.position,
+            n,
+        )?,
+        EdgeQuery::PipePropertyPresence(pq) => filter_edges(source, &pq.inner, &mut cursor.position, n, |(key, _)| {
+            Ok(source.edge_property(key, &pq.name)?.is_some() == pq.exists)
+        })?,
+        EdgeQuery::PipePropertyValue(pq) => {
+            let value = Json::new(pq.value.clone());
+            filter_edges(source, &pq.inner, &mut cursor.position, n, |(key, _)| {
+                Ok((source.edge_property(key, &pq.name)?.as_ref() == Some(&value)) == pq.equal)
+            })?
+        }
+        EdgeQuery::PipePropertyValueRange(pq) => {
+            filter_edges(source, &pq.inner, &mut cursor.position, n, |(key, _)| {
+                let value = source.edge_property(key, &pq.name)?;
+                Ok(value.is_some_and(|value| pq.range.contains(&value.0)) == pq.within)
+            })?
+        }
+        EdgeQuery::Union(_) => unreachable!(),
+    };
+
+    cursor.count += edges.len() as u64;
+    if edges.len() < n {
+        cursor.done = true;
+    }
+    Ok(edges)
+}
+
+// Gets whether a cursor can resume a vertex query itself. The others are run
+// in full by the datastore, which is also left to handle queries on
+// properties without a ready index, since it may answer them with other
+// indexes, or report that the property isn't indexed.
+fn is_resumable_vertex_query<S: CursorSource + ?Sized>(source: &S, q: &VertexQuery) -> bool {
+    match q {
+        VertexQuery::Range(_) | VertexQuery::Specific(_) => true,
+        VertexQuery::Pipe(q) => is_resumable_edge_query(source, &q.inner),
+        VertexQuery::Traversal(_) | VertexQuery::Union(_) => false,
+        VertexQuery::Intersect(q) => {
+            is_resumable_vertex_query(source, &q.inner) && is_resumable_vertex_query(source, &q.other)
+        }
+        VertexQuery::Except(q) => is_resumable_vertex_query(source, &q.inner),
+        VertexQuery::PropertyPresence(q) => source.is_indexed(&q.name),
+        VertexQuery::PropertyValue(q) => source.is_indexed(&q.name),
+        VertexQuery::PropertyValueRange(q) => source.is_indexed(&q.name),
+        VertexQuery::PipePropertyPresence(q) => {
+            source.is_indexed(&q.name) && is_resumable_vertex_query(source, &q.inner)
+        }
+        VertexQuery::PipePropertyValue(q) => source.is_indexed(&q.name) && is_resumable_vertex_query(source, &q.inner),
+        VertexQuery::PipePropertyValueRange(q) => {
+            source.is_indexed(&q.name) && is_resumable_vertex_query(source, &q.inner)
+        }
+    }
+}
+
+// Gets whether a cursor can resume an edge query itself.
+fn is_resumable_edge_query<S: CursorSource + ?Sized>(source: &S, q: &EdgeQuery) -> bool {
+    match q {
+        EdgeQuery::Specific(_) => true,
+        EdgeQuery::Pipe(q) => is_resumable_vertex_query(source, &q.inner),
+        EdgeQuery::Union(_) => false,
+        EdgeQuery::Intersect(q) => {
+            is_resumable_edge_query(source, &q.inner) && is_resumable_edge_query(source, &q.other)
+        }
+        EdgeQuery::Except(q) => is_resumable_edge_query(source, &q.inner),
+        EdgeQuery::PropertyPresence(q) => source.is_indexed(&q.name),
+        EdgeQuery::PropertyValue(q) => source.is_indexed(&q.name),
+        EdgeQuery::PropertyValueRange(q) => source.is_indexed(&q.name),
+        EdgeQuery::PipePropertyPresence(q) => source.is_indexed(&q.name) && is_resumable_edge_query(source, &q.inner),
+        EdgeQuery::PipePropertyValue(q) => source.is_indexed(&q.name) && is_resumable_edge_query(source, &q.inner),
+        EdgeQuery::PipePropertyValueRange(q) => source.is_indexed(&q.name) && is_resumable_edge_query(source, &q.inner),
+    }
+}
+
+// Gets the next `n` results of `q` that `filter` keeps.
+fn filter_vertices<S, F>(
+    source: &S,
+    q: &VertexQuery,
+    position: &mut VertexPosition,
+    n: usize,
+    mut filter: F,
+) -> Result<Vec<VertexItem>>
+where
+    S: CursorSource + ?Sized,
+    F: FnMut(&VertexItem) -> Result<bool>,
+{
+    if let VertexPosition::Start = position {
+        *position = VertexPosition::Vertices(Nested::default());
+    }
+    let inner_cursor = match position {
+        VertexPosition::Vertices(ref mut inner_cursor) => inner_cursor,
+        _ => return Err(Error::InvalidContinuationToken),
+    };
+
+    let mut vertices = Vec::new();
+    while vertices.len() < n {
+        let wanted = n - vertices.len();
+        let chunk = next_vertices(source, q, inner_cursor, wanted)?;
+        let exhausted = chunk.len() < wanted;
+        for item in chunk {
+            if filter(&item)? {
+                vertices.push(item);
+            }
+        }
+        if exhausted {
+            break;
+        }
+    }
+    Ok(vertices)
+}
+
+fn filter_edges<S, F>(
+    source: &S,
+    q: &EdgeQuery,
+    position: &mut EdgePosition,
+    n: usize,
+    mut filter: F,
+) -> Result<Vec<EdgeItem>>
+where
+    S: CursorSource + ?Sized,
+    F: FnMut(&EdgeItem) -> Result<bool>,
+{
+    if let EdgePosition::Start = position {
+        *position = EdgePosition::Edges(Nested::default());
+    }
+    let inner_cursor = match position {
+        EdgePosition::Edges(ref mut inner_cursor) => inner_cursor,
+        _ => return Err(Error::InvalidContinuationToken),
+    };
+
+    let mut edges = Vec::new();
+    while edges.len() < n {
+        let wanted = n - edges.len();
+        let chunk = next_edges(source, q, inner_cursor, wanted)?;
+        let exhausted = chunk.len() < wanted;
+        for item in chunk {
+            if filter(&item)? {
+                edges.push(item);
+            }
+        }
+        if exhausted {
+            break;
+        }
+    }
+    Ok(edges)
+}
+
+// Gets the next `n` vertices from a property's index.
+fn property_vertices<S: CursorSource + ?Sized>(
+    source: &S,
+    name: &Identifier,
+    filter: &PropertyFilter<'_>,
+    position: &mut VertexPosition,
+    n: usize,
+) -> Result<Vec<VertexItem>> {
+    let mut after = match position {
+        VertexPosition::Start => None,
+        VertexPosition::PropertyValue(value, id) => Some((value.clone(), *id)),
+        _ => return Err(Error::InvalidContinuationToken),
+    };
+
+    let mut vertices = Vec::new();
+    while vertices.len() < n {
+        let wanted = n - vertices.len();
+        let members = source.vertex_property_values(name, filter, after.as_ref(), wanted)?;
+        let exhausted = members.len() < wanted;
+        for (value, id) in members {
+            if let Some(t) = source.vertex(id)? {
+                vertices.push((id, t));
+            }
+            after = Some((value, id));
+        }
+        if exhausted {
+            break;
+        }
+    }
+
+    if let Some((value, id)) = after {
+        *position = VertexPosition::PropertyValue(value, id);
+    }
+    Ok(vertices)
+}
+
+fn property_edges<S: CursorSource + ?Sized>(
+    source: &S,
+    name: &Identifier,
+    filter: &PropertyFilter<'_>,
+    position: &mut EdgePosition,
+    n: usize,
+) -> Result<Vec<EdgeItem>> {
+    let mut after = match position {
+        EdgePosition::Start => None,
+        EdgePosition::PropertyValue(value, key) => Some((value.clone(), key.clone())),
+        _ => return Err(Error::InvalidContinuationToken),
+    };
+
+    let mut edges = Vec::new();
+    while edges.len() < n {
+        let wanted = n - edges.len();
+        let members = source.edge_property_values(name, filter, after.as_ref(), wanted)?;
+        let exhausted = members.len() < wanted;
+        for (value, key) in members {
+            if let Some(update_datetime) = source.edge(&key)? {
+                edges.push((key.clone(), update_datetime));
+            }
+            after = Some((value, key));
+        }
+        if exhausted {
+            break;
+        }
+    }
+
+    if let Some((value, key)) = after {
+        *position = EdgePosition::PropertyValue(value, key);
+    }
+    Ok(edges)
+}
+
+// Gets the next `n` results of a query that's run in full.
+fn materialized_vertices<S: CursorSource + ?Sized>(
+    source: &S,
+    q: &VertexQuery,
+    cursor: &mut VertexCursor,
+    n: usize,
+) -> Result<Vec<VertexItem>> {
+    let index = match cursor.position {
+        VertexPosition::Start => 0,
+        VertexPosition::Index(index) => index as usize,
+        _ => return Err(Error::InvalidContinuationToken),
+    };
+    let all = match cursor.materialized {
+        Some(ref all) => all.clone(),
+        None => {
+            let all = Arc::new(source.vertices(q.clone())?);
+            cursor.materialized = Some(all.clone());
+            all
+        }
+    };
+    let vertices: Vec<VertexItem> = all.iter().skip(index).take(n).cloned().collect();
+    cursor.position = VertexPosition::Index((index + vertices.len()) as u64);
+    Ok(vertices)
+}
+
+fn materialized_edges<S: CursorSource + ?Sized>(
+    source: &S,
+    q: &EdgeQuery,
+    cursor: &mut EdgeCursor,
+    n: usize,
+) -> Result<Vec<EdgeItem>> {
+    let index = match cursor.position {
+        EdgePosition::Start => 0,
+        EdgePosition::Index(index) => index as usize,
+        _ => return Err(Error::InvalidContinuationToken),
+    };
+    let all = match cursor.materialized {
+        Some(ref all) => all.clone(),
+        None => {
+            let all = Arc::new(source.edges(q.clone())?);
+            cursor.materialized = Some(all.clone());
+            all
+        }
+    };
+    let edges: Vec<EdgeItem> = all.iter().skip(index).take(n).cloned().collect();
+    cursor.position = EdgePosition::Index((index + edges.len()) as u64);
+    Ok(edges)
+}
+
+// Streams the results of a vertex query, loading them in chunks of
+// `ITER_BATCH_SIZE`. The first chunk is loaded up front, so that errors with
+// the query itself are returned here rather than from the iterator.
+pub(crate) fn iter_vertices<'a, S: CursorSource + 'a>(
+    source: Arc<S>,
+    q: VertexQuery,
+) -> Result<DynIter<'a, VertexItem>> {
+    let mut cursor = VertexCursor::default();
+    let mut first = Some(next_vertices(&*source, &q, &mut cursor, ITER_BATCH_SIZE as usize)?);
+    Ok(Box::new(BatchIter::new(move || {
+        let vertices = match first.take() {
+            Some(vertices) => vertices,
+            None if cursor.is_done() => return Ok(None),
+            None => next_vertices(&*source, &q, &mut cursor, ITER_BATCH_SIZE as usize)?,
+        };
+        Ok(if vertices.is_empty() { None } else { Some(vertices) })
+    })))
+}
+
+// Streams the results of an edge query, like `iter_vertices`.
+pub(crate) fn iter_edges<'a, S: CursorSource + 'a>(source: Arc<S>, q: EdgeQuery) -> Result<DynIter<'a, EdgeItem>> {
+    let mut cursor = EdgeCursor::default();
+    let mut first = Some(next_edges(&*source, &q, &mut cursor, ITER_BATCH_SIZE as usize)?);
+    Ok(Box::new(BatchIter::new(move || {
+        let edges = match first.take() {
+            Some(edges) => edges,
+            None if cursor.is_done() => return Ok(None),
+            None => next_edges(&*source, &q, &mut cursor, ITER_BATCH_SIZE as usize)?,
+        };
+        Ok(if edges.is_empty() { None } else { Some(edges) })
+    })))
+}
+
+// Gets a page of up to `limit` results of a query, starting from where
+// `token` left off. Each result is passed through `f`, which can drop it.
+// A token is only returned if there are more results, which is checked by
+// reading ahead on a copy of the cursor.
+pub(crate) fn page_vertices<S, U, F>(
+    source: &S,
+    fingerprint: u64,
+    q: &VertexQuery,
+    limit: u32,
+    token: Option<ContinuationToken>,
+    f: F,
+) -> Result<Page<U>>
+where
+    S: CursorSource + ?Sized,
+    F: FnMut(VertexItem) -> Result<Option<U>>,
+{
+    if limit == 0 {
+        return Err(Error::InvalidPageLimit);
+    }
+    let cursor: VertexCursor = match token {
+        Some(token) => token.decode(fingerprint)?,
+        None => VertexCursor::default(),
+    };
+    let (results, cursor) = collect_page(
+        cursor,
+        limit as usize,
+        |cursor, n| next_vertices(source, q, cursor, n),
+        f,
+    )?;
+    let token = match cursor {
+        Some(cursor) => Some(ContinuationToken::new(fingerprint, &cursor)?),
+        None => None,
+    };
+    Ok(Page::new(results, token))
+}
+
+// Gets a page of results of an edge query, like `page_vertices`.
+pub(crate) fn page_edges<S, U, F>(
+    source: &S,
+    fingerprint: u64,
+    q: &EdgeQuery,
+    limit: u32,
+    token: Option<ContinuationToken>,
+    f: F,
+) -> Result<Page<U>>
+where
+    S: CursorSource + ?Sized,
+    F: FnMut(EdgeItem) -> Result<Option<U>>,
+{
+    if limit == 0 {
+        return Err(Error::InvalidPageLimit);
+    }
+    let cursor: EdgeCursor = match token {
+        Some(token) => token.decode(fingerprint)?,
+        None => EdgeCursor::default(),
+    };
+    let (results, cursor) = collect_page(cursor, limit as usize, |cursor, n| next_edges(source, q, cursor, n), f)?;
+    let token = match cursor {
+        Some(cursor) => Some(ContinuationToken::new(fingerprint, &cursor)?),
+        None => None,
+    };
+    Ok(Page::new(results, token))
+}
+
+// Reads results with `next` until `limit` of them are kept by `f`. Only as
+// many results are read as are still needed, so the cursor stops right after
+// the last result of the page. Returns the cursor if there are more results.
+fn collect_page<C, T, U, N, F>(mut cursor: C, limit: usize, mut next: N, mut f: F) -> Result<(Vec<U>, Option<C>)>
+where
+    C: Clone,
+    N: FnMut(&mut C, usize) -> Result<Vec<T>>,
+    F: FnMut(T) -> Result<Option<U>>,
+{
+    let mut results = Vec::new();
+    while results.len() < limit {
+        let wanted = limit - results.len();
+        let chunk = next(&mut cursor, wanted)?;
+        let exhausted = chunk.len() < wanted;
+        for item in chunk {
+            if let Some(result) = f(item)? {
+                results.push(result);
+            }
+        }
+        if exhausted {
+            return Ok((results, None));
+        }
+    }
+
+    let mut ahead = cursor.clone();
+    while let Some(item) = next(&mut ahead, 1)?.pop() {
+        if f(item)?.is_some() {
+            return Ok((results, Some(cursor)));
+        }
+    }
+    Ok((results, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VertexCursor;
+    use crate::errors::Error;
+    use crate::ContinuationToken;
+
+    #[test]
+    fn should_not_decode_deeply_nested_cursors() {
+        // Each byte starts another nested vertex cursor
+        let mut bytes = ContinuationToken::new(0, &()).unwrap().into_bytes();
+        bytes.extend(std::iter::repeat_n(4, 100_000));
+        match ContinuationToken::from_bytes(bytes).decode::<VertexCursor>(0) {
+            Err(Error::InvalidContinuationToken) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
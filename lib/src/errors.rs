@@ -1,5 +1,6 @@
 use std::error::Error as StdError;
 use std::fmt;
+use std::io::Error as IoError;
 use std::result::Result as StdResult;
 
 use bincode::Error as BincodeError;
@@ -19,8 +20,27 @@ pub enum Error {
     /// A query occurred on a property that isn't indexed
     NotIndexed,
 
+    /// A query occurred on a property whose index is still being built, or
+    /// failed to build
+    IndexNotReady,
+
     /// For functionality that isn't supported
     Unsupported,
+
+    /// A continuation token was malformed, or wasn't created by this
+    /// datastore
+    InvalidContinuationToken,
+
+    /// A page of results was requested with a limit of zero
+    InvalidPageLimit,
+
+    /// A write would have given two vertices or edges the same value of a
+    /// property with a unique constraint
+    ConstraintViolation,
+
+    /// A write doesn't conform to the datastore's schema, for the given
+    /// reason
+    SchemaViolation(String),
 }
 
 impl StdError for Error {
@@ -38,7 +58,12 @@ impl fmt::Display for Error {
             Error::UuidTaken => write!(f, "UUID already taken"),
             Error::Datastore(ref err) => write!(f, "error in the underlying datastore: {}", err),
             Error::NotIndexed => write!(f, "query attempted on a property that isn't indexed"),
+            Error::IndexNotReady => write!(f, "query attempted on a property whose index isn't ready"),
             Error::Unsupported => write!(f, "functionality not supported"),
+            Error::InvalidContinuationToken => write!(f, "invalid continuation token"),
+            Error::InvalidPageLimit => write!(f, "page limit must be greater than zero"),
+            Error::ConstraintViolation => write!(f, "write would violate a unique property constraint"),
+            Error::SchemaViolation(ref message) => write!(f, "write violates the schema: {}", message),
         }
     }
 }
@@ -49,6 +74,12 @@ impl From<JsonError> for Error {
     }
 }
 
+impl From<IoError> for Error {
+    fn from(err: IoError) -> Self {
+        Error::Datastore(Box::new(err))
+    }
+}
+
 impl From<BincodeError> for Error {
     fn from(err: BincodeError) -> Self {
         Error::Datastore(Box::new(err))
@@ -62,6 +93,12 @@ impl From<RocksDbError> for Error {
     }
 }
 
+impl From<ImageError> for Error {
+    fn from(err: ImageError) -> Self {
+        Error::Datastore(Box::new(err))
+    }
+}
+
 pub type Result<T> = StdResult<T, Error>;
 
 /// A validation error
@@ -88,3 +125,65 @@ impl fmt::Display for ValidationError {
 }
 
 pub type ValidationResult<T> = StdResult<T, ValidationError>;
+
+/// An error reading or writing a persisted memory datastore image
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ImageError {
+    /// The image couldn't be read or written
+    Io(IoError),
+    /// The file isn't a memory datastore image
+    UnrecognizedFormat,
+    /// The image has a format version that isn't supported, e.g. because
+    /// it was written by a newer version of IndraDB
+    UnsupportedVersion(u32),
+    /// The image ends partway through its header
+    Truncated,
+    /// The image's contents don't match its checksum, e.g. because the file
+    /// was truncated or corrupted
+    ChecksumMismatch,
+    /// The image was written before images were versioned, and has property
+    /// values, which can't be read from it reliably
+    LegacyProperties,
+    /// The image's contents couldn't be decoded
+    Decode(BincodeError),
+}
+
+impl StdError for ImageError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            ImageError::Io(ref err) => Some(err),
+            ImageError::Decode(ref err) => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImageError::Io(ref err) => write!(f, "could not access the image: {}", err),
+            ImageError::UnrecognizedFormat => write!(f, "the file is not a memory datastore image"),
+            ImageError::UnsupportedVersion(version) => write!(f, "unsupported image format version: {}", version),
+            ImageError::Truncated => write!(f, "the image header is truncated"),
+            ImageError::ChecksumMismatch => write!(f, "the image is corrupted: its checksum does not match"),
+            ImageError::LegacyProperties => write!(
+                f,
+                "the image predates versioned images and has property values, which can't be read from it"
+            ),
+            ImageError::Decode(ref err) => write!(f, "could not decode the image: {}", err),
+        }
+    }
+}
+
+impl From<IoError> for ImageError {
+    fn from(err: IoError) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+impl From<BincodeError> for ImageError {
+    fn from(err: BincodeError) -> Self {
+        ImageError::Decode(err)
+    }
+}
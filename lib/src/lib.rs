@@ -19,19 +19,22 @@ pub mod tests;
 #[macro_use]
 pub mod benches;
 
+mod cursor;
 mod errors;
 mod memory;
 mod models;
+mod read_only;
 mod traits;
 pub mod util;
 
 pub use crate::errors::*;
 pub use crate::memory::MemoryDatastore;
 pub use crate::models::*;
+pub use crate::read_only::ReadOnlyDatastore;
 pub use crate::traits::*;
 
 #[cfg(feature = "rocksdb-datastore")]
 mod rdb;
 
 #[cfg(feature = "rocksdb-datastore")]
-pub use crate::rdb::RocksdbDatastore;
+pub use crate::rdb::{Inconsistency, RocksdbCompression, RocksdbConfig, RocksdbDatastore};
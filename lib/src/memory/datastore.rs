@@ -1,21 +1,30 @@
+use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
-use std::path::PathBuf;
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hash;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::{Bound, Deref, DerefMut};
+use std::path::{Path, PathBuf};
 use std::result::Result as StdResult;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
 
-use crate::errors::{Error, Result};
+use super::image;
+use crate::cursor::{self, CursorSource, EdgeResume, PropertyFilter};
+use crate::errors::{Error, ImageError, Result};
 use crate::util;
 use crate::{
-    Datastore, Edge, EdgeDirection, EdgeKey, EdgeProperties, EdgeProperty, EdgePropertyQuery, EdgeQuery, Identifier,
-    Json, NamedProperty, Vertex, VertexProperties, VertexProperty, VertexPropertyQuery, VertexQuery,
+    fingerprint, BulkInsertItem, ContinuationToken, Datastore, DynIter, Edge, EdgeDirection, EdgeKey, EdgeProperties,
+    EdgeProperty, EdgePropertyQuery, EdgeQuery, Identifier, IndexStatus, Json, NamedProperty, Page, PathQuery,
+    PipePropertyValueEdgeQuery, PipePropertyValueVertexQuery, PropertyValueRange, ReadOnlyDatastore, Schema,
+    SchemaSummary, Vertex, VertexProperties, VertexProperty, VertexPropertyQuery, VertexQuery,
 };
 
-use bincode::Error as BincodeError;
 use chrono::offset::Utc;
 use chrono::DateTime;
-use serde::{Deserialize, Serialize};
+use im::ordmap::Entry;
+use im::{OrdMap, OrdSet};
+use serde::{Deserialize, Serialize, Serializer};
 use tempfile::NamedTempFile;
 use uuid::Uuid;
 
@@ -31,36 +40,218 @@ macro_rules! iter_edge_values {
     };
 }
 
-#[derive(Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug, Clone)]
 enum IndexedPropertyMember {
     Vertex(Uuid),
     Edge(EdgeKey),
 }
 
+// A build of a property's index that hasn't finished, or that failed. The ID
+// tells builds of the same property apart, in case the index is dropped and
+// re-added, or rebuilt, while an earlier build is still running.
+#[derive(Debug, Clone)]
+struct IndexBuild {
+    id: u64,
+    status: IndexStatus,
+}
+
+// A change to the datastore, with any queries already resolved to the
+// vertices/edges they affect. Transactions record these so they can be
+// replayed against the parent datastore on commit. They're also what's
+// recorded in the write-ahead log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Mutation {
+    CreateVertex(Uuid, Identifier),
+    CreateEdge(EdgeKey, DateTime<Utc>),
+    DeleteVertices(Vec<Uuid>),
+    DeleteEdges(Vec<EdgeKey>),
+    SetVertexProperties(Vec<Uuid>, Identifier, Json),
+    DeleteVertexProperties(Vec<(Uuid, Identifier)>),
+    SetEdgeProperties(Vec<EdgeKey>, Identifier, Json),
+    DeleteEdgeProperties(Vec<(EdgeKey, Identifier)>),
+    IndexProperty(Identifier),
+    DropIndex(Identifier),
+    IndexTypeProperty(Identifier, Identifier),
+    DropTypeIndex(Identifier, Identifier),
+    IndexCompositeProperties(Vec<Identifier>),
+    DropCompositeIndex(Vec<Identifier>),
+    AddUniqueConstraint(Identifier, Option<Identifier>),
+    DropUniqueConstraint(Identifier, Option<Identifier>),
+    SetSchema(Option<Schema>),
+}
+
 // All of the data is actually stored in this struct, which is stored
 // internally to the datastore itself. This way, we can wrap an rwlock around
 // the entire datastore, rather than on a per-data structure basis, as the
 // latter approach would risk deadlocking without extreme care.
-#[derive(Debug, Default, Serialize, Deserialize)]
+//
+// The vertices, edges, properties and indexes, along with the maps of the
+// indexes and of vertices by type, are kept in persistent collections, so
+// cloning the struct doesn't copy any of them: the clone shares its
+// structure with the original, and only the parts that are later changed in
+// either one are copied. Transactions, snapshots and staged writes rely on
+// this.
+#[derive(Debug, Default, Clone, Serialize)]
 struct InternalMemoryDatastore {
-    vertices: BTreeMap<Uuid, Identifier>,
-    edges: BTreeMap<EdgeKey, DateTime<Utc>>,
-    reversed_edges: BTreeMap<EdgeKey, DateTime<Utc>>,
-    vertex_properties: BTreeMap<(Uuid, Identifier), Json>,
-    edge_properties: BTreeMap<(EdgeKey, Identifier), Json>,
-    property_values: HashMap<Identifier, HashMap<Json, HashSet<IndexedPropertyMember>>>,
+    vertices: OrdMap<Uuid, Identifier>,
+    edges: OrdMap<EdgeKey, DateTime<Utc>>,
+    reversed_edges: OrdMap<EdgeKey, DateTime<Utc>>,
+    vertex_properties: OrdMap<(Uuid, Identifier), Json>,
+    edge_properties: OrdMap<(EdgeKey, Identifier), Json>,
+    property_values: OrdMap<Identifier, OrdMap<Json, OrdSet<IndexedPropertyMember>>>,
+    // The indexes of property values scoped to a type, keyed by the type and
+    // property name.
+    type_property_values: OrdMap<(Identifier, Identifier), OrdMap<Json, OrdSet<IndexedPropertyMember>>>,
+    // The composite indexes, keyed by their property names. Vertices and
+    // edges are indexed by the values of all of the properties, in the same
+    // order.
+    composite_property_values: OrdMap<Vec<Identifier>, OrdMap<Vec<Json>, OrdSet<IndexedPropertyMember>>>,
+    // The scopes of the unique constraints on each property, where a scope
+    // of `None` covers every vertex and edge, and otherwise covers those of
+    // one type.
+    unique_properties: HashMap<Identifier, HashSet<Option<Identifier>>>,
+    // The schema that writes are checked against, if any.
+    schema: Option<Schema>,
+    // The IDs of vertices of each type. This is derived from `vertices`, so
+    // rather than being persisted, it's rebuilt when an image is read.
+    #[serde(skip)]
+    vertices_by_type: OrdMap<Identifier, OrdSet<Uuid>>,
+    // The builds of indexes that aren't ready yet. These indexes are in
+    // `property_values`, so that writes keep them up to date while they're
+    // filled in, but only their names are persisted until they're ready.
+    #[serde(serialize_with = "serialize_index_builds")]
+    index_builds: HashMap<Identifier, IndexBuild>,
+    // The number of index builds that have been started, which is used to
+    // assign build IDs.
+    #[serde(skip)]
+    index_build_count: u64,
+}
+
+// Serializes the builds of indexes that aren't ready as the names of the
+// indexes.
+fn serialize_index_builds<S: Serializer>(
+    index_builds: &HashMap<Identifier, IndexBuild>,
+    serializer: S,
+) -> StdResult<S::Ok, S::Error> {
+    serializer.collect_seq(index_builds.keys())
 }
 
 type QueryIter<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+type EdgeValuesIter<'a> = QueryIter<'a, (EdgeKey, DateTime<Utc>)>;
+
+// Gets the members of a property index whose values are in a given range.
+// Since the index is sorted, this only visits the values in the range.
+fn iter_property_value_range(
+    container: &OrdMap<Json, OrdSet<IndexedPropertyMember>>,
+    range: PropertyValueRange,
+) -> impl Iterator<Item = &IndexedPropertyMember> {
+    let start = Json::new(range.start());
+    container
+        .range(start..)
+        .map(move |(value, members)| (range.locate(&value.0), members))
+        .skip_while(|(location, _)| location == &Ordering::Less)
+        .take_while(|(location, _)| location == &Ordering::Equal)
+        .flat_map(|(_, members)| members.iter())
+}
+
+// Gets whether a vertex or edge of type `t` is covered by a unique
+// constraint with the given scope.
+fn is_in_unique_scope(scope: &Option<Identifier>, t: Option<&Identifier>) -> bool {
+    match scope {
+        Some(scope) => t == Some(scope),
+        None => true,
+    }
+}
 
 impl InternalMemoryDatastore {
+    fn rebuild_vertex_type_index(&mut self) {
+        self.vertices_by_type.clear();
+        for (id, t) in &self.vertices {
+            self.vertices_by_type.entry(t.clone()).or_default().insert(*id);
+        }
+    }
+
+    // Gets the index of a property's values, or `None` if it isn't indexed.
+    // Indexes that aren't ready are incomplete, so they can't be queried.
+    fn get_property_index(&self, name: &Identifier) -> Result<Option<&OrdMap<Json, OrdSet<IndexedPropertyMember>>>> {
+        if self.index_builds.contains_key(name) {
+            return Err(Error::IndexNotReady);
+        }
+        Ok(self.property_values.get(name))
+    }
+
+    // Gets up to `limit` of the members of a property's index whose values
+    // match `filter`, sorted by value and then member, starting after
+    // `after` if it's set. Members that `f` maps to `None` are skipped.
+    fn property_index_members<T, F>(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<(&Json, IndexedPropertyMember)>,
+        limit: usize,
+        mut f: F,
+    ) -> Result<Vec<(Json, T)>>
+    where
+        F: FnMut(&IndexedPropertyMember) -> Option<T>,
+    {
+        let container = self.get_property_index(name)?.ok_or(Error::NotIndexed)?;
+        let start = match after {
+            Some((value, _)) => Bound::Included(value.clone()),
+            None => filter.start().map_or(Bound::Unbounded, Bound::Included),
+        };
+
+        let mut results = Vec::new();
+        for (value, members) in container.range((start, Bound::Unbounded)) {
+            match filter.locate(value) {
+                Ordering::Less => continue,
+                Ordering::Greater => break,
+                Ordering::Equal => {}
+            }
+            let members = match after {
+                Some((after_value, ref after_member)) if after_value == value => {
+                    members.range((Bound::Excluded(after_member.clone()), Bound::Unbounded))
+                }
+                _ => members.range((Bound::<IndexedPropertyMember>::Unbounded, Bound::Unbounded)),
+            };
+            for member in members {
+                if let Some(item) = f(member) {
+                    results.push((value.clone(), item));
+                    if results.len() == limit {
+                        return Ok(results);
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    // Gets all of the properties of a vertex.
+    fn get_all_properties_of_vertex(&self, id: Uuid) -> Vec<NamedProperty> {
+        let from = &(id, Identifier::default());
+        self.vertex_properties
+            .range(from..)
+            .take_while(|((owner_id, _), _)| *owner_id == id)
+            .map(|((_, name), value)| NamedProperty::new(name.clone(), value.0.clone()))
+            .collect()
+    }
+
+    // Gets all of the properties of an edge.
+    fn get_all_properties_of_edge(&self, key: &EdgeKey) -> Vec<NamedProperty> {
+        let from = &(key.clone(), Identifier::default());
+        self.edge_properties
+            .range(from..)
+            .take_while(|((owner_key, _), _)| owner_key == key)
+            .map(|((_, name), value)| NamedProperty::new(name.clone(), value.0.clone()))
+            .collect()
+    }
+
     fn get_all_vertices_with_property(
         &self,
         property_name: &Identifier,
         error_if_missing: bool,
     ) -> Result<HashSet<Uuid>> {
         let mut vertices = HashSet::<Uuid>::default();
-        if let Some(container) = self.property_values.get(property_name) {
+        if let Some(container) = self.get_property_index(property_name)? {
             for sub_container in container.values() {
                 for member in sub_container {
                     if let IndexedPropertyMember::Vertex(id) = member {
@@ -80,7 +271,7 @@ impl InternalMemoryDatastore {
         error_if_missing: bool,
     ) -> Result<HashSet<EdgeKey>> {
         let mut edges = HashSet::<EdgeKey>::default();
-        if let Some(container) = self.property_values.get(property_name) {
+        if let Some(container) = self.get_property_index(property_name)? {
             for sub_container in container.values() {
                 for member in sub_container {
                     if let IndexedPropertyMember::Edge(edge_key) = member {
@@ -94,19 +285,250 @@ impl InternalMemoryDatastore {
         Ok(edges)
     }
 
+    // Gets the index of a property's values scoped to a type, or `None` if
+    // there isn't one.
+    fn get_type_property_index(
+        &self,
+        t: &Identifier,
+        name: &Identifier,
+    ) -> Option<&OrdMap<Json, OrdSet<IndexedPropertyMember>>> {
+        self.type_property_values.get(&(t.clone(), name.clone()))
+    }
+
+    // Gets the type that a query's vertices are restricted to, if a property
+    // is indexed for that type.
+    fn type_indexed_vertex_query_type(&self, q: &VertexQuery, name: &Identifier) -> Option<Identifier> {
+        util::vertex_query_type(q)
+            .filter(|t| self.get_type_property_index(t, name).is_some())
+            .cloned()
+    }
+
+    // Gets the type that a query's edges are restricted to, if a property is
+    // indexed for that type.
+    fn type_indexed_edge_query_type(&self, q: &EdgeQuery, name: &Identifier) -> Option<Identifier> {
+        util::edge_query_type(q)
+            .filter(|t| self.get_type_property_index(t, name).is_some())
+            .cloned()
+    }
+
+    // Gets the vertices of `other_query`, which are all of type `t`, whose
+    // property value equals `value` (or doesn't, if `equal` isn't set), using
+    // the property's index scoped to `t`. If `other_query` returns every
+    // vertex of the type, the index is enough on its own.
+    fn get_vertex_values_by_type_property_value(
+        &self,
+        t: Identifier,
+        name: &Identifier,
+        value: serde_json::Value,
+        other_query: VertexQuery,
+        equal: bool,
+    ) -> Result<QueryIter<'_, (Uuid, Identifier)>> {
+        let members = self
+            .get_type_property_index(&t, name)
+            .and_then(|container| container.get(&Json::new(value)));
+        let mut ids: Vec<Uuid> = members
+            .into_iter()
+            .flatten()
+            .filter_map(|member| match member {
+                IndexedPropertyMember::Vertex(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+
+        if equal && util::is_full_vertex_type_query(&other_query) {
+            ids.sort();
+            return Ok(Box::new(ids.into_iter().map(move |id| (id, t.clone()))));
+        }
+
+        let ids: HashSet<Uuid> = ids.into_iter().collect();
+        let iter = self.get_vertex_values_by_query(other_query)?;
+        Ok(Box::new(iter.filter(move |(id, _)| ids.contains(id) == equal)))
+    }
+
+    // Gets the edges of `other_query`, which are all of type `t`, whose
+    // property value equals `value` (or doesn't, if `equal` isn't set), using
+    // the property's index scoped to `t`.
+    fn get_edge_values_by_type_property_value(
+        &self,
+        t: Identifier,
+        name: &Identifier,
+        value: serde_json::Value,
+        other_query: EdgeQuery,
+        equal: bool,
+    ) -> Result<QueryIter<'_, (EdgeKey, DateTime<Utc>)>> {
+        let members = self
+            .get_type_property_index(&t, name)
+            .and_then(|container| container.get(&Json::new(value)));
+        let keys: HashSet<EdgeKey> = members
+            .into_iter()
+            .flatten()
+            .filter_map(|member| match member {
+                IndexedPropertyMember::Edge(key) => Some(key.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let iter = self.get_edge_values_by_query(other_query)?;
+        Ok(Box::new(iter.filter(move |(key, _)| keys.contains(key) == equal)))
+    }
+
+    // Gets the members of a composite index whose leading property values
+    // match a lookup, and whose values for the lookup's remaining properties
+    // match too.
+    fn get_composite_index_members(&self, lookup: util::CompositeLookup) -> Vec<&IndexedPropertyMember> {
+        let container = &self.composite_property_values[&lookup.names];
+        container
+            .range(lookup.values.clone()..)
+            .take_while(|(values, _)| values.starts_with(&lookup.values))
+            .flat_map(|(_, members)| members.iter())
+            .filter(|member| {
+                lookup.remaining.iter().all(|(name, value)| {
+                    let property_value = match member {
+                        IndexedPropertyMember::Vertex(id) => self.vertex_properties.get(&(*id, name.clone())),
+                        IndexedPropertyMember::Edge(key) => self.edge_properties.get(&(key.clone(), name.clone())),
+                    };
+                    match property_value {
+                        Some(property_value) => &property_value.0 == value,
+                        None => false,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    // Gets the vertices of a chain of piped property value queries from a
+    // composite index, if one covers at least two of the chain's
+    // properties. Returns `None` if there isn't such an index.
+    fn get_vertex_values_by_composite_index(
+        &self,
+        q: &PipePropertyValueVertexQuery,
+    ) -> Result<Option<QueryIter<'_, (Uuid, Identifier)>>> {
+        let filters = match util::vertex_property_value_filters(q) {
+            Some(filters) => filters,
+            None => return Ok(None),
+        };
+        let lookup = match filters.composite_lookup(self.composite_property_values.keys()) {
+            Some(lookup) => lookup,
+            None => return Ok(None),
+        };
+        let mut ids: Vec<Uuid> = self
+            .get_composite_index_members(lookup)
+            .into_iter()
+            .filter_map(|member| match member {
+                IndexedPropertyMember::Vertex(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+
+        match filters.inner {
+            Some(inner) => {
+                let ids: HashSet<Uuid> = ids.into_iter().collect();
+                let iter = self.get_vertex_values_by_query(inner.clone())?;
+                Ok(Some(Box::new(iter.filter(move |(id, _)| ids.contains(id)))))
+            }
+            None => {
+                ids.sort();
+                Ok(Some(iter_vertex_values!(self, ids.into_iter())))
+            }
+        }
+    }
+
+    // Gets the edges of a chain of piped property value queries from a
+    // composite index, if one covers at least two of the chain's
+    // properties. Returns `None` if there isn't such an index.
+    fn get_edge_values_by_composite_index(&self, q: &PipePropertyValueEdgeQuery) -> Result<Option<EdgeValuesIter<'_>>> {
+        let filters = match util::edge_property_value_filters(q) {
+            Some(filters) => filters,
+            None => return Ok(None),
+        };
+        let lookup = match filters.composite_lookup(self.composite_property_values.keys()) {
+            Some(lookup) => lookup,
+            None => return Ok(None),
+        };
+        let mut keys: Vec<EdgeKey> = self
+            .get_composite_index_members(lookup)
+            .into_iter()
+            .filter_map(|member| match member {
+                IndexedPropertyMember::Edge(key) => Some(key.clone()),
+                _ => None,
+            })
+            .collect();
+
+        match filters.inner {
+            Some(inner) => {
+                let keys: HashSet<EdgeKey> = keys.into_iter().collect();
+                let iter = self.get_edge_values_by_query(inner.clone())?;
+                Ok(Some(Box::new(iter.filter(move |(key, _)| keys.contains(key)))))
+            }
+            None => {
+                keys.sort();
+                Ok(Some(iter_edge_values!(self, keys.into_iter())))
+            }
+        }
+    }
+
+    // Iterates over the edges adjacent to a vertex. Reversed edges are
+    // stored with their ends swapped, so the adjacent vertex is the inbound
+    // one in either direction.
+    fn iter_adjacent_edges<'a>(
+        &'a self,
+        id: Uuid,
+        t: Option<&'a Identifier>,
+        direction: EdgeDirection,
+    ) -> impl Iterator<Item = (&'a EdgeKey, &'a DateTime<Utc>)> + 'a {
+        let lower_bound = match t {
+            Some(t) => EdgeKey::new(id, t.clone(), Uuid::default()),
+            None => EdgeKey::new(id, Identifier::default(), Uuid::default()),
+        };
+
+        let range = if direction == EdgeDirection::Outbound {
+            self.edges.range(lower_bound..)
+        } else {
+            self.reversed_edges.range(lower_bound..)
+        };
+
+        range.take_while(move |(key, _)| {
+            key.outbound_id == id
+                && match t {
+                    Some(t) => &key.t == t,
+                    None => true,
+                }
+        })
+    }
+
+    fn get_adjacent_vertex_ids(&self, id: Uuid, t: Option<&Identifier>, direction: EdgeDirection) -> Vec<Uuid> {
+        self.iter_adjacent_edges(id, t, direction)
+            .map(|(key, _)| key.inbound_id)
+            .collect()
+    }
+
     fn get_vertex_values_by_query(&self, q: VertexQuery) -> Result<QueryIter<'_, (Uuid, Identifier)>> {
         match q {
             VertexQuery::Range(range) => {
-                let mut iter: QueryIter<(&Uuid, &Identifier)> = if let Some(start_id) = range.start_id {
+                if let Some(t) = range.t {
+                    let ids = match self.vertices_by_type.get(&t) {
+                        Some(ids) => ids,
+                        None => return Ok(Box::new(std::iter::empty())),
+                    };
+
+                    let iter: QueryIter<&Uuid> = if let Some(start_id) = range.start_id {
+                        Box::new(ids.range(start_id..))
+                    } else {
+                        Box::new(ids.iter())
+                    };
+
+                    let iter: QueryIter<(Uuid, Identifier)> =
+                        Box::new(iter.take(range.limit as usize).map(move |id| (*id, t.clone())));
+
+                    return Ok(iter);
+                }
+
+                let iter: QueryIter<(&Uuid, &Identifier)> = if let Some(start_id) = range.start_id {
                     Box::new(self.vertices.range(start_id..))
                 } else {
                     Box::new(self.vertices.iter())
                 };
 
-                if let Some(t) = range.t {
-                    iter = Box::new(iter.filter(move |(_, v)| v == &&t));
-                }
-
                 let iter: QueryIter<(Uuid, Identifier)> =
                     Box::new(iter.take(range.limit as usize).map(|(k, v)| (*k, v.clone())));
 
@@ -135,12 +557,60 @@ impl InternalMemoryDatastore {
 
                 Ok(iter)
             }
+            VertexQuery::Traversal(q) => {
+                let start_ids: Vec<Uuid> = self.get_vertex_values_by_query(*q.inner)?.map(|(id, _)| id).collect();
+                let t = q.t;
+                let direction = q.direction;
+                let ids = util::traverse(start_ids, q.min_depth, q.max_depth, q.limit, |id| {
+                    Ok::<_, Error>(self.get_adjacent_vertex_ids(id, t.as_ref(), direction))
+                })?;
+                Ok(iter_vertex_values!(self, ids.into_iter()))
+            }
+            VertexQuery::Union(q) => {
+                let iter = self
+                    .get_vertex_values_by_query(*q.inner)?
+                    .chain(self.get_vertex_values_by_query(*q.other)?);
+                let mut seen = HashSet::new();
+                Ok(Box::new(iter.filter(move |(id, _)| seen.insert(*id))))
+            }
+            VertexQuery::Intersect(q) => match (*q.inner, *q.other) {
+                (VertexQuery::PropertyValue(property_query), other_query)
+                | (other_query, VertexQuery::PropertyValue(property_query))
+                    if self
+                        .type_indexed_vertex_query_type(&other_query, &property_query.name)
+                        .is_some() =>
+                {
+                    let t = self
+                        .type_indexed_vertex_query_type(&other_query, &property_query.name)
+                        .unwrap();
+                    self.get_vertex_values_by_type_property_value(
+                        t,
+                        &property_query.name,
+                        property_query.value,
+                        other_query,
+                        true,
+                    )
+                }
+                (inner_query, other_query) => {
+                    let ids: HashSet<Uuid> = self
+                        .get_vertex_values_by_query(other_query)?
+                        .map(|(id, _)| id)
+                        .collect();
+                    let iter = self.get_vertex_values_by_query(inner_query)?;
+                    Ok(Box::new(iter.filter(move |(id, _)| ids.contains(id))))
+                }
+            },
+            VertexQuery::Except(q) => {
+                let ids: HashSet<Uuid> = self.get_vertex_values_by_query(*q.other)?.map(|(id, _)| id).collect();
+                let iter = self.get_vertex_values_by_query(*q.inner)?;
+                Ok(Box::new(iter.filter(move |(id, _)| !ids.contains(id))))
+            }
             VertexQuery::PropertyPresence(q) => {
                 let vertices = self.get_all_vertices_with_property(&q.name, true)?;
                 Ok(iter_vertex_values!(self, vertices.into_iter()))
             }
             VertexQuery::PropertyValue(q) => {
-                if let Some(container) = self.property_values.get(&q.name) {
+                if let Some(container) = self.get_property_index(&q.name)? {
                     let wrapped_value = Json::new(q.value);
                     if let Some(sub_container) = container.get(&wrapped_value) {
                         let iter = Box::new(sub_container.iter().filter_map(move |member| match member {
@@ -156,6 +626,22 @@ impl InternalMemoryDatastore {
                     Err(Error::NotIndexed)
                 }
             }
+            VertexQuery::PropertyValueRange(q) => {
+                if let Some(container) = self.get_property_index(&q.name)? {
+                    let iter =
+                        Box::new(iter_property_value_range(container, q.range).filter_map(
+                            move |member| match member {
+                                IndexedPropertyMember::Vertex(id) => {
+                                    self.vertices.get(id).map(|value| (*id, value.clone()))
+                                }
+                                _ => None,
+                            },
+                        ));
+                    Ok(iter)
+                } else {
+                    Err(Error::NotIndexed)
+                }
+            }
             VertexQuery::PipePropertyPresence(q) => {
                 let vertices_with_property = self.get_all_vertices_with_property(&q.name, false)?;
                 let vertex_values = self.get_vertex_values_by_query(*q.inner)?;
@@ -169,9 +655,21 @@ impl InternalMemoryDatastore {
                 Ok(iter)
             }
             VertexQuery::PipePropertyValue(q) => {
+                // A composite index covering several piped property values
+                // is used in preference to the others.
+                if let Some(iter) = self.get_vertex_values_by_composite_index(&q)? {
+                    return Ok(iter);
+                }
+
+                // An index scoped to the type of the piped vertices is used
+                // in preference to the property's index.
+                if let Some(t) = self.type_indexed_vertex_query_type(&q.inner, &q.name) {
+                    return self.get_vertex_values_by_type_property_value(t, &q.name, q.value, *q.inner, q.equal);
+                }
+
                 let vertex_values = self.get_vertex_values_by_query(*q.inner)?;
 
-                let ids: HashSet<Uuid> = if let Some(container) = self.property_values.get(&q.name) {
+                let ids: HashSet<Uuid> = if let Some(container) = self.get_property_index(&q.name)? {
                     let wrapped_value = Json::new(q.value.clone());
                     if let Some(members) = container.get(&wrapped_value) {
                         members
@@ -194,6 +692,28 @@ impl InternalMemoryDatastore {
                     Box::new(vertex_values.filter(move |(id, _)| !ids.contains(id)))
                 };
 
+                Ok(iter)
+            }
+            VertexQuery::PipePropertyValueRange(q) => {
+                let vertex_values = self.get_vertex_values_by_query(*q.inner)?;
+
+                let ids: HashSet<Uuid> = if let Some(container) = self.get_property_index(&q.name)? {
+                    iter_property_value_range(container, q.range)
+                        .filter_map(|member| match member {
+                            IndexedPropertyMember::Vertex(id) => Some(*id),
+                            _ => None,
+                        })
+                        .collect()
+                } else {
+                    HashSet::default()
+                };
+
+                let iter: QueryIter<(Uuid, Identifier)> = if q.within {
+                    Box::new(vertex_values.filter(move |(id, _)| ids.contains(id)))
+                } else {
+                    Box::new(vertex_values.filter(move |(id, _)| !ids.contains(id)))
+                };
+
                 Ok(iter)
             }
         }
@@ -246,12 +766,51 @@ impl InternalMemoryDatastore {
                 let iter = Box::new(iter);
                 Ok(iter)
             }
+            EdgeQuery::Union(q) => {
+                let iter = self
+                    .get_edge_values_by_query(*q.inner)?
+                    .chain(self.get_edge_values_by_query(*q.other)?);
+                let mut seen = HashSet::new();
+                Ok(Box::new(iter.filter(move |(key, _)| seen.insert(key.clone()))))
+            }
+            EdgeQuery::Intersect(q) => match (*q.inner, *q.other) {
+                (EdgeQuery::PropertyValue(property_query), other_query)
+                | (other_query, EdgeQuery::PropertyValue(property_query))
+                    if self
+                        .type_indexed_edge_query_type(&other_query, &property_query.name)
+                        .is_some() =>
+                {
+                    let t = self
+                        .type_indexed_edge_query_type(&other_query, &property_query.name)
+                        .unwrap();
+                    self.get_edge_values_by_type_property_value(
+                        t,
+                        &property_query.name,
+                        property_query.value,
+                        other_query,
+                        true,
+                    )
+                }
+                (inner_query, other_query) => {
+                    let keys: HashSet<EdgeKey> = self
+                        .get_edge_values_by_query(other_query)?
+                        .map(|(key, _)| key)
+                        .collect();
+                    let iter = self.get_edge_values_by_query(inner_query)?;
+                    Ok(Box::new(iter.filter(move |(key, _)| keys.contains(key))))
+                }
+            },
+            EdgeQuery::Except(q) => {
+                let keys: HashSet<EdgeKey> = self.get_edge_values_by_query(*q.other)?.map(|(key, _)| key).collect();
+                let iter = self.get_edge_values_by_query(*q.inner)?;
+                Ok(Box::new(iter.filter(move |(key, _)| !keys.contains(key))))
+            }
             EdgeQuery::PropertyPresence(q) => {
                 let edges = self.get_all_edges_with_property(&q.name, true)?;
                 Ok(iter_edge_values!(self, edges.into_iter()))
             }
             EdgeQuery::PropertyValue(q) => {
-                if let Some(container) = self.property_values.get(&q.name) {
+                if let Some(container) = self.get_property_index(&q.name)? {
                     let wrapped_value = Json::new(q.value);
                     if let Some(sub_container) = container.get(&wrapped_value) {
                         let iter = Box::new(sub_container.iter().filter_map(move |member| match member {
@@ -265,6 +824,22 @@ impl InternalMemoryDatastore {
                     Err(Error::NotIndexed)
                 }
             }
+            EdgeQuery::PropertyValueRange(q) => {
+                if let Some(container) = self.get_property_index(&q.name)? {
+                    let iter =
+                        Box::new(iter_property_value_range(container, q.range).filter_map(
+                            move |member| match member {
+                                IndexedPropertyMember::Edge(key) => {
+                                    self.edges.get(key).map(|value| (key.clone(), *value))
+                                }
+                                _ => None,
+                            },
+                        ));
+                    Ok(iter)
+                } else {
+                    Err(Error::NotIndexed)
+                }
+            }
             EdgeQuery::PipePropertyPresence(q) => {
                 let edges_with_property = self.get_all_edges_with_property(&q.name, false)?;
                 let edge_values = self.get_edge_values_by_query(*q.inner)?;
@@ -278,9 +853,21 @@ impl InternalMemoryDatastore {
                 Ok(iter)
             }
             EdgeQuery::PipePropertyValue(q) => {
+                // A composite index covering several piped property values
+                // is used in preference to the others.
+                if let Some(iter) = self.get_edge_values_by_composite_index(&q)? {
+                    return Ok(iter);
+                }
+
+                // An index scoped to the type of the piped edges is used in
+                // preference to the property's index.
+                if let Some(t) = self.type_indexed_edge_query_type(&q.inner, &q.name) {
+                    return self.get_edge_values_by_type_property_value(t, &q.name, q.value, *q.inner, q.equal);
+                }
+
                 let edge_values = self.get_edge_values_by_query(*q.inner)?;
 
-                let keys: HashSet<EdgeKey> = if let Some(container) = self.property_values.get(&q.name) {
+                let keys: HashSet<EdgeKey> = if let Some(container) = self.get_property_index(&q.name)? {
                     let wrapped_value = Json::new(q.value);
                     if let Some(members) = container.get(&wrapped_value) {
                         members
@@ -303,6 +890,28 @@ impl InternalMemoryDatastore {
                     Box::new(edge_values.filter(move |(key, _)| !keys.contains(key)))
                 };
 
+                Ok(iter)
+            }
+            EdgeQuery::PipePropertyValueRange(q) => {
+                let edge_values = self.get_edge_values_by_query(*q.inner)?;
+
+                let keys: HashSet<EdgeKey> = if let Some(container) = self.get_property_index(&q.name)? {
+                    iter_property_value_range(container, q.range)
+                        .filter_map(|member| match member {
+                            IndexedPropertyMember::Edge(key) => Some(key.clone()),
+                            _ => None,
+                        })
+                        .collect()
+                } else {
+                    HashSet::default()
+                };
+
+                let iter: QueryIter<(EdgeKey, DateTime<Utc>)> = if q.within {
+                    Box::new(edge_values.filter(move |(key, _)| keys.contains(key)))
+                } else {
+                    Box::new(edge_values.filter(move |(key, _)| !keys.contains(key)))
+                };
+
                 Ok(iter)
             }
         }
@@ -310,11 +919,11 @@ impl InternalMemoryDatastore {
 
     fn delete_vertices(&mut self, vertices: Vec<Uuid>) {
         for vertex_id in vertices {
-            self.vertices.remove(&vertex_id);
-
+            // Properties are deleted first, since removing them from indexes
+            // scoped to a type needs the vertex's type.
             let mut deletable_vertex_properties: Vec<(Uuid, Identifier)> = Vec::new();
             for (property_key, _) in self.vertex_properties.range((vertex_id, Identifier::default())..) {
-                let &(ref property_vertex_id, _) = property_key;
+                let (property_vertex_id, _) = property_key;
 
                 if &vertex_id != property_vertex_id {
                     break;
@@ -324,25 +933,46 @@ impl InternalMemoryDatastore {
             }
             self.delete_vertex_properties(deletable_vertex_properties);
 
-            let mut deletable_edges: Vec<EdgeKey> = Vec::new();
-            for edge_key in self.edges.keys() {
-                if edge_key.outbound_id == vertex_id || edge_key.inbound_id == vertex_id {
-                    deletable_edges.push(edge_key.clone());
+            if let Some(t) = self.vertices.remove(&vertex_id) {
+                if let Some(ids) = self.vertices_by_type.get_mut(&t) {
+                    ids.remove(&vertex_id);
+                    if ids.is_empty() {
+                        self.vertices_by_type.remove(&t);
+                    }
                 }
             }
+
+            let mut deletable_edges: Vec<EdgeKey> = self
+                .iter_adjacent_edges(vertex_id, None, EdgeDirection::Outbound)
+                .map(|(key, _)| key.clone())
+                .collect();
+            deletable_edges.extend(
+                self.iter_adjacent_edges(vertex_id, None, EdgeDirection::Inbound)
+                    .map(|(key, _)| key.reversed()),
+            );
             self.delete_edges(deletable_edges);
         }
     }
 
     fn delete_vertex_properties(&mut self, keys: Vec<(Uuid, Identifier)>) {
         for property_key in keys {
+            // Composite indexes need the property's value to find the
+            // vertex, so it's removed from them first.
+            let member = IndexedPropertyMember::Vertex(property_key.0);
+            self.remove_composite_property_values(&member, &property_key.1);
+
             if let Some(property_value) = self.vertex_properties.remove(&property_key) {
                 let (property_vertex_id, property_name) = property_key;
                 if let Some(property_container) = self.property_values.get_mut(&property_name) {
-                    debug_assert!(property_container
-                        .get_mut(&property_value)
-                        .unwrap()
-                        .remove(&IndexedPropertyMember::Vertex(property_vertex_id)));
+                    // An index that's being built may not have the value yet.
+                    let removed = match property_container.get_mut(&property_value) {
+                        Some(members) => members.remove(&member).is_some(),
+                        None => false,
+                    };
+                    debug_assert!(removed || self.index_builds.contains_key(&property_name));
+                }
+                if let Some(t) = self.vertices.get(&property_vertex_id) {
+                    self.remove_type_property_value(t.clone(), property_name, &property_value, &member);
                 }
             }
         }
@@ -355,7 +985,7 @@ impl InternalMemoryDatastore {
 
             let mut deletable_edge_properties: Vec<(EdgeKey, Identifier)> = Vec::new();
             for (property_key, _) in self.edge_properties.range((edge_key.clone(), Identifier::default())..) {
-                let &(ref property_edge_key, _) = property_key;
+                let (property_edge_key, _) = property_key;
 
                 if &edge_key != property_edge_key {
                     break;
@@ -369,127 +999,1485 @@ impl InternalMemoryDatastore {
 
     fn delete_edge_properties(&mut self, keys: Vec<(EdgeKey, Identifier)>) {
         for property_key in keys {
+            let member = IndexedPropertyMember::Edge(property_key.0.clone());
+            self.remove_composite_property_values(&member, &property_key.1);
+
             if let Some(property_value) = self.edge_properties.remove(&property_key) {
                 let (property_edge_key, property_name) = property_key;
+                let t = property_edge_key.t.clone();
                 if let Some(property_container) = self.property_values.get_mut(&property_name) {
-                    debug_assert!(property_container
-                        .get_mut(&property_value)
-                        .unwrap()
-                        .remove(&IndexedPropertyMember::Edge(property_edge_key)));
+                    // An index that's being built may not have the value yet.
+                    let removed = match property_container.get_mut(&property_value) {
+                        Some(members) => members.remove(&member).is_some(),
+                        None => false,
+                    };
+                    debug_assert!(removed || self.index_builds.contains_key(&property_name));
                 }
+                self.remove_type_property_value(t, property_name, &property_value, &member);
             }
         }
     }
-}
 
-/// An in-memory datastore.
-#[derive(Debug, Clone)]
-pub struct MemoryDatastore {
-    datastore: Arc<RwLock<InternalMemoryDatastore>>,
-    path: Option<PathBuf>,
-}
-
-impl Default for MemoryDatastore {
-    fn default() -> MemoryDatastore {
-        Self {
-            datastore: Arc::new(RwLock::new(InternalMemoryDatastore::default())),
-            path: None,
+    // Removes a vertex or edge from a property's index scoped to its type,
+    // if there is one.
+    fn remove_type_property_value(
+        &mut self,
+        t: Identifier,
+        name: Identifier,
+        value: &Json,
+        member: &IndexedPropertyMember,
+    ) {
+        if let Some(property_container) = self.type_property_values.get_mut(&(t, name)) {
+            if let Some(members) = property_container.get_mut(value) {
+                members.remove(member);
+            }
         }
     }
-}
 
-impl MemoryDatastore {
-    /// Reads a persisted image from disk. Calls to sync will overwrite the
-    /// file at the specified path.
-    ///
-    /// # Arguments
-    /// * `path`: The path to the persisted image.
-    pub fn read<P: Into<PathBuf>>(path: P) -> StdResult<MemoryDatastore, BincodeError> {
-        let path = path.into();
-        let buf = BufReader::new(File::open(&path)?);
-        let datastore = bincode::deserialize_from(buf)?;
-        Ok(MemoryDatastore {
-            datastore: Arc::new(RwLock::new(datastore)),
-            path: Some(path),
-        })
+    // Gets the values of a vertex's or edge's properties, in order, or `None`
+    // if it doesn't have all of them.
+    fn get_composite_property_values(&self, member: &IndexedPropertyMember, names: &[Identifier]) -> Option<Vec<Json>> {
+        names
+            .iter()
+            .map(|name| match member {
+                IndexedPropertyMember::Vertex(id) => self.vertex_properties.get(&(*id, name.clone())).cloned(),
+                IndexedPropertyMember::Edge(key) => self.edge_properties.get(&(key.clone(), name.clone())).cloned(),
+            })
+            .collect()
     }
 
-    /// Creates a new datastore. Calls to sync will overwrite the file at the
-    /// specified path, but as opposed to `read`, this will not read the file
-    /// first.
-    ///
-    /// # Arguments
-    /// * `path`: The path to the persisted image.
-    pub fn create<P: Into<PathBuf>>(path: P) -> StdResult<MemoryDatastore, BincodeError> {
-        Ok(MemoryDatastore {
-            datastore: Arc::new(RwLock::new(InternalMemoryDatastore::default())),
-            path: Some(path.into()),
-        })
+    // Gets the values of a vertex's or edge's properties for each composite
+    // index covering a property, if it has all of the index's properties.
+    fn get_covering_composite_property_values(
+        &self,
+        member: &IndexedPropertyMember,
+        name: &Identifier,
+    ) -> Vec<(Vec<Identifier>, Vec<Json>)> {
+        self.composite_property_values
+            .keys()
+            .filter(|names| names.contains(name))
+            .filter_map(|names| {
+                self.get_composite_property_values(member, names)
+                    .map(|values| (names.clone(), values))
+            })
+            .collect()
     }
-}
 
-impl Datastore for MemoryDatastore {
-    fn sync(&self) -> Result<()> {
-        if let Some(ref persist_path) = self.path {
-            let temp_path = NamedTempFile::new().map_err(|err| Error::Datastore(Box::new(err)))?;
-            let buf = BufWriter::new(temp_path.as_file());
-            let datastore = self.datastore.read().unwrap();
-            bincode::serialize_into(buf, &*datastore)?;
-            temp_path
-                .persist(persist_path)
-                .map_err(|err| Error::Datastore(Box::new(err)))?;
+    // Adds a vertex or edge to the composite indexes covering a property
+    // that was just set.
+    fn add_composite_property_values(&mut self, member: &IndexedPropertyMember, name: &Identifier) {
+        for (names, values) in self.get_covering_composite_property_values(member, name) {
+            if let Some(property_container) = self.composite_property_values.get_mut(&names) {
+                property_container.entry(values).or_default().insert(member.clone());
+            }
         }
-        Ok(())
     }
 
-    fn create_vertex(&self, vertex: &Vertex) -> Result<bool> {
-        let mut datastore = self.datastore.write().unwrap();
-        let mut inserted = false;
+    // Removes a vertex or edge from the composite indexes covering a
+    // property that's about to be deleted or changed.
+    fn remove_composite_property_values(&mut self, member: &IndexedPropertyMember, name: &Identifier) {
+        for (names, values) in self.get_covering_composite_property_values(member, name) {
+            if let Some(members) = self
+                .composite_property_values
+                .get_mut(&names)
+                .and_then(|property_container| property_container.get_mut(&values))
+            {
+                members.remove(member);
+            }
+        }
+    }
 
-        datastore.vertices.entry(vertex.id).or_insert_with(|| {
-            inserted = true;
-            vertex.t.clone()
-        });
+    // Checks that property changes won't violate a unique constraint. Each
+    // change sets a property of a vertex or edge to a value. Vertices and
+    // edges that are created alongside the changes are in
+    // `created_vertices` and `created_edges`; changes to others that don't
+    // exist are ignored, like they are when applied.
+    fn check_unique_properties(
+        &self,
+        changes: &HashMap<(IndexedPropertyMember, Identifier), Json>,
+        created_vertices: &HashMap<Uuid, Identifier>,
+        created_edges: &HashSet<EdgeKey>,
+    ) -> Result<()> {
+        if self.unique_properties.is_empty() {
+            return Ok(());
+        }
 
-        Ok(inserted)
-    }
+        let mut seen = HashSet::new();
+        for ((member, name), value) in changes {
+            let scopes = match self.unique_properties.get(name) {
+                Some(scopes) => scopes,
+                None => continue,
+            };
+            let t = match member {
+                IndexedPropertyMember::Vertex(id) => self.vertices.get(id).or_else(|| created_vertices.get(id)),
+                IndexedPropertyMember::Edge(key) if created_edges.contains(key) || self.edges.contains_key(key) => {
+                    Some(&key.t)
+                }
+                IndexedPropertyMember::Edge(_) => None,
+            };
+            let t = match t {
+                Some(t) => t,
+                None => continue,
+            };
+            let is_vertex = matches!(member, IndexedPropertyMember::Vertex(_));
+
+            for scope in scopes.iter().filter(|scope| is_in_unique_scope(scope, Some(t))) {
+                if !seen.insert((name, scope, is_vertex, value)) {
+                    return Err(Error::ConstraintViolation);
+                }
 
-    fn get_vertices(&self, q: VertexQuery) -> Result<Vec<Vertex>> {
-        let datastore = self.datastore.read().unwrap();
-        let iter = datastore.get_vertex_values_by_query(q)?;
-        let iter = iter.map(|(uuid, t)| Vertex::with_id(uuid, t));
-        Ok(iter.collect())
-    }
+                let existing = self
+                    .property_values
+                    .get(name)
+                    .and_then(|property_container| property_container.get(value));
+                for other in existing.into_iter().flatten() {
+                    let other_t = match other {
+                        IndexedPropertyMember::Vertex(id) if is_vertex => self.vertices.get(id),
+                        IndexedPropertyMember::Edge(key) if !is_vertex => Some(&key.t),
+                        _ => continue,
+                    };
+                    if is_in_unique_scope(scope, other_t) && !changes.contains_key(&(other.clone(), name.clone())) {
+                        return Err(Error::ConstraintViolation);
+                    }
+                }
+            }
+        }
 
-    fn delete_vertices(&self, q: VertexQuery) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
-        let deletable_vertices = datastore.get_vertex_values_by_query(q)?.map(|(k, _)| k).collect();
-        datastore.delete_vertices(deletable_vertices);
         Ok(())
     }
 
-    fn get_vertex_count(&self) -> Result<u64> {
-        let datastore = self.datastore.read().unwrap();
-        Ok(datastore.vertices.len() as u64)
-    }
-
-    fn create_edge(&self, key: &EdgeKey) -> Result<bool> {
-        let mut datastore = self.datastore.write().unwrap();
+    // Checks that mutations, applied together, won't violate a unique
+    // constraint.
+    fn check_unique_mutations(&self, mutations: &[Mutation]) -> Result<()> {
+        if self.unique_properties.is_empty() {
+            return Ok(());
+        }
 
-        if !datastore.vertices.contains_key(&key.outbound_id) || !datastore.vertices.contains_key(&key.inbound_id) {
-            return Ok(false);
+        let mut changes = HashMap::new();
+        let mut created_vertices = HashMap::new();
+        let mut created_edges = HashSet::new();
+        for mutation in mutations {
+            match mutation {
+                Mutation::CreateVertex(id, t) => {
+                    created_vertices.insert(*id, t.clone());
+                }
+                Mutation::CreateEdge(key, _) => {
+                    created_edges.insert(key.clone());
+                }
+                Mutation::SetVertexProperties(vertices, name, value) if self.unique_properties.contains_key(name) => {
+                    for id in vertices {
+                        changes.insert((IndexedPropertyMember::Vertex(*id), name.clone()), value.clone());
+                    }
+                }
+                Mutation::SetEdgeProperties(edges, name, value) if self.unique_properties.contains_key(name) => {
+                    for key in edges {
+                        changes.insert((IndexedPropertyMember::Edge(key.clone()), name.clone()), value.clone());
+                    }
+                }
+                _ => {}
+            }
         }
+        self.check_unique_properties(&changes, &created_vertices, &created_edges)
+    }
 
-        datastore.edges.insert(key.clone(), Utc::now());
-        datastore.reversed_edges.insert(key.reversed(), Utc::now());
-        Ok(true)
+    // Checks that a property's existing values don't violate a unique
+    // constraint with the given scope.
+    fn check_existing_unique_property(&self, name: &Identifier, scope: &Option<Identifier>) -> Result<()> {
+        for members in self
+            .property_values
+            .get(name)
+            .into_iter()
+            .flat_map(|container| container.values())
+        {
+            let mut vertex_count = 0;
+            let mut edge_count = 0;
+            for member in members {
+                match member {
+                    IndexedPropertyMember::Vertex(id) if is_in_unique_scope(scope, self.vertices.get(id)) => {
+                        vertex_count += 1
+                    }
+                    IndexedPropertyMember::Edge(key) if is_in_unique_scope(scope, Some(&key.t)) => edge_count += 1,
+                    _ => {}
+                }
+            }
+            if vertex_count > 1 || edge_count > 1 {
+                return Err(Error::ConstraintViolation);
+            }
+        }
+        Ok(())
     }
 
-    fn get_edges(&self, q: EdgeQuery) -> Result<Vec<Edge>> {
-        let edge_values: Vec<(EdgeKey, DateTime<Utc>)> = {
-            let datastore = self.datastore.read().unwrap();
-            let iter = datastore.get_edge_values_by_query(q)?;
-            iter.collect()
+    // Checks that the vertices and edges created by mutations, if they still
+    // exist, have all of the properties that the schema requires.
+    fn check_required_properties(&self, mutations: &[Mutation]) -> Result<()> {
+        let schema = match self.schema {
+            Some(ref schema) => schema,
+            None => return Ok(()),
+        };
+
+        for mutation in mutations {
+            match mutation {
+                Mutation::CreateVertex(id, _) => {
+                    if let Some(t) = self.vertices.get(id) {
+                        let properties = self.get_all_properties_of_vertex(*id);
+                        let names = properties.iter().map(|property| &property.name).collect();
+                        schema.check_vertex(t, &names)?;
+                    }
+                }
+                Mutation::CreateEdge(key, _) if self.edges.contains_key(key) => {
+                    let properties = self.get_all_properties_of_edge(key);
+                    let names = properties.iter().map(|property| &property.name).collect();
+                    let outbound_t = self.vertices.get(&key.outbound_id);
+                    let inbound_t = self.vertices.get(&key.inbound_id);
+                    schema.check_edge(&key.t, outbound_t, inbound_t, &names)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn create_vertex(&mut self, vertex: Vertex) {
+        if let Entry::Vacant(entry) = self.vertices.entry(vertex.id) {
+            self.vertices_by_type
+                .entry(vertex.t.clone())
+                .or_default()
+                .insert(vertex.id);
+            entry.insert(vertex.t);
+        }
+    }
+
+    fn create_edge(&mut self, key: EdgeKey, update_datetime: DateTime<Utc>) {
+        // The vertices are checked again here because a transaction may be
+        // committed after one of them has been deleted.
+        if !self.vertices.contains_key(&key.outbound_id) || !self.vertices.contains_key(&key.inbound_id) {
+            return;
+        }
+
+        self.reversed_edges.insert(key.reversed(), update_datetime);
+        self.edges.insert(key, update_datetime);
+    }
+
+    fn set_vertex_properties(&mut self, vertices: Vec<Uuid>, name: Identifier, value: Json) {
+        let vertices: Vec<Uuid> = vertices
+            .into_iter()
+            .filter(|id| self.vertices.contains_key(id))
+            .collect();
+
+        let mut deletable_vertex_properties = Vec::<(Uuid, Identifier)>::new();
+        for id in &vertices {
+            deletable_vertex_properties.push((*id, name.clone()));
+        }
+        self.delete_vertex_properties(deletable_vertex_properties);
+
+        for id in &vertices {
+            self.vertex_properties.insert((*id, name.clone()), value.clone());
+            self.add_composite_property_values(&IndexedPropertyMember::Vertex(*id), &name);
+        }
+
+        for id in &vertices {
+            let t = self.vertices[id].clone();
+            if let Some(property_container) = self.type_property_values.get_mut(&(t, name.clone())) {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(IndexedPropertyMember::Vertex(*id));
+            }
+        }
+
+        if let Some(property_container) = self.property_values.get_mut(&name) {
+            let property_container = property_container.entry(value).or_default();
+            for id in vertices.into_iter() {
+                property_container.insert(IndexedPropertyMember::Vertex(id));
+            }
+        }
+    }
+
+    fn set_edge_properties(&mut self, edges: Vec<EdgeKey>, name: Identifier, value: Json) {
+        let edges: Vec<EdgeKey> = edges.into_iter().filter(|key| self.edges.contains_key(key)).collect();
+
+        let mut deletable_edge_properties = Vec::<(EdgeKey, Identifier)>::new();
+        for key in &edges {
+            deletable_edge_properties.push((key.clone(), name.clone()));
+        }
+        self.delete_edge_properties(deletable_edge_properties);
+
+        for key in &edges {
+            self.edge_properties.insert((key.clone(), name.clone()), value.clone());
+            self.add_composite_property_values(&IndexedPropertyMember::Edge(key.clone()), &name);
+        }
+
+        for key in &edges {
+            if let Some(property_container) = self.type_property_values.get_mut(&(key.t.clone(), name.clone())) {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(IndexedPropertyMember::Edge(key.clone()));
+            }
+        }
+
+        if let Some(property_container) = self.property_values.get_mut(&name) {
+            let property_container = property_container.entry(value).or_default();
+            for key in edges.into_iter() {
+                property_container.insert(IndexedPropertyMember::Edge(key));
+            }
+        }
+    }
+
+    fn index_property(&mut self, name: Identifier) {
+        let property_container = self.build_property_index(&name);
+        self.index_builds.remove(&name);
+        self.install_property_index(name, property_container);
+    }
+
+    fn drop_index(&mut self, name: &Identifier) {
+        self.property_values.remove(name);
+        self.index_builds.remove(name);
+        // Unique constraints are enforced through the index, so they're
+        // dropped along with it.
+        self.unique_properties.remove(name);
+    }
+
+    fn index_type_property(&mut self, t: Identifier, name: Identifier) {
+        let mut property_container: OrdMap<Json, OrdSet<IndexedPropertyMember>> = OrdMap::new();
+        for id in self.vertices_by_type.get(&t).into_iter().flatten() {
+            if let Some(value) = self.vertex_properties.get(&(*id, name.clone())) {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(IndexedPropertyMember::Vertex(*id));
+            }
+        }
+        for key in self.edges.keys().filter(|key| key.t == t) {
+            if let Some(value) = self.edge_properties.get(&(key.clone(), name.clone())) {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(IndexedPropertyMember::Edge(key.clone()));
+            }
+        }
+
+        self.type_property_values.insert((t, name), property_container);
+    }
+
+    fn drop_type_index(&mut self, t: Identifier, name: Identifier) {
+        self.type_property_values.remove(&(t, name));
+    }
+
+    fn index_composite_properties(&mut self, names: Vec<Identifier>) {
+        let mut property_container: OrdMap<Vec<Json>, OrdSet<IndexedPropertyMember>> = OrdMap::new();
+        let members = self
+            .vertices
+            .keys()
+            .map(|id| IndexedPropertyMember::Vertex(*id))
+            .chain(self.edges.keys().map(|key| IndexedPropertyMember::Edge(key.clone())));
+        for member in members {
+            if let Some(values) = self.get_composite_property_values(&member, &names) {
+                property_container.entry(values).or_default().insert(member);
+            }
+        }
+
+        self.composite_property_values.insert(names, property_container);
+    }
+
+    fn drop_composite_index(&mut self, names: &[Identifier]) {
+        self.composite_property_values.remove(names);
+    }
+
+    fn add_unique_constraint(&mut self, name: Identifier, t: Option<Identifier>) {
+        self.unique_properties.entry(name).or_default().insert(t);
+    }
+
+    fn drop_unique_constraint(&mut self, name: &Identifier, t: &Option<Identifier>) {
+        if let Some(scopes) = self.unique_properties.get_mut(name) {
+            scopes.remove(t);
+            if scopes.is_empty() {
+                self.unique_properties.remove(name);
+            }
+        }
+    }
+
+    // Gets the index of a property's values.
+    fn build_property_index(&self, name: &Identifier) -> OrdMap<Json, OrdSet<IndexedPropertyMember>> {
+        let mut property_container: OrdMap<Json, OrdSet<IndexedPropertyMember>> = OrdMap::new();
+        for id in self.vertices.keys() {
+            if let Some(value) = self.vertex_properties.get(&(*id, name.clone())) {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(IndexedPropertyMember::Vertex(*id));
+            }
+        }
+        for key in self.edges.keys() {
+            if let Some(value) = self.edge_properties.get(&(key.clone(), name.clone())) {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(IndexedPropertyMember::Edge(key.clone()));
+            }
+        }
+
+        property_container
+    }
+
+    fn install_property_index(
+        &mut self,
+        name: Identifier,
+        property_container: OrdMap<Json, OrdSet<IndexedPropertyMember>>,
+    ) {
+        let existing_property_container = self.property_values.entry(name).or_default();
+        for (value, members) in property_container.into_iter() {
+            let existing_members = existing_property_container.entry(value).or_default();
+            for member in members {
+                existing_members.insert(member);
+            }
+        }
+    }
+
+    // Adds the next batch of vertices and edges after `cursor` to a
+    // property's index, and moves the cursor to the last of them. Vertices
+    // are indexed before edges. Returns the number of vertices and edges in
+    // the batch, which is zero once all of them have been indexed.
+    fn index_batch(&mut self, name: &Identifier, cursor: &mut Option<IndexedPropertyMember>) -> usize {
+        let mut members = Vec::with_capacity(INDEX_BUILD_BATCH_SIZE);
+
+        if !matches!(cursor, Some(IndexedPropertyMember::Edge(_))) {
+            let start = match cursor {
+                Some(IndexedPropertyMember::Vertex(id)) => Bound::Excluded(*id),
+                _ => Bound::Unbounded,
+            };
+            members.extend(
+                self.vertices
+                    .range((start, Bound::Unbounded))
+                    .take(INDEX_BUILD_BATCH_SIZE)
+                    .map(|(id, _)| IndexedPropertyMember::Vertex(*id)),
+            );
+        }
+
+        if members.len() < INDEX_BUILD_BATCH_SIZE {
+            let start = match cursor {
+                Some(IndexedPropertyMember::Edge(key)) => Bound::Excluded(key.clone()),
+                _ => Bound::Unbounded,
+            };
+            members.extend(
+                self.edges
+                    .range((start, Bound::Unbounded))
+                    .take(INDEX_BUILD_BATCH_SIZE - members.len())
+                    .map(|(key, _)| IndexedPropertyMember::Edge(key.clone())),
+            );
+        }
+
+        let property_container = self.property_values.entry(name.clone()).or_default();
+        for member in &members {
+            let value = match member {
+                IndexedPropertyMember::Vertex(id) => self.vertex_properties.get(&(*id, name.clone())),
+                IndexedPropertyMember::Edge(key) => self.edge_properties.get(&(key.clone(), name.clone())),
+            };
+            if let Some(value) = value {
+                property_container
+                    .entry(value.clone())
+                    .or_default()
+                    .insert(member.clone());
+            }
+        }
+
+        let count = members.len();
+        if let Some(member) = members.pop() {
+            *cursor = Some(member);
+        }
+        count
+    }
+
+    // Starts a build of a property's index, replacing any earlier one, and
+    // returns its ID. The index is empty until it's filled in by
+    // `MemoryDatastore::run_index_build`.
+    fn add_index_build(&mut self, name: Identifier) -> u64 {
+        self.index_build_count += 1;
+        let id = self.index_build_count;
+        let total = (self.vertices.len() + self.edges.len()) as u64;
+        self.property_values.entry(name.clone()).or_default();
+        self.index_builds.insert(
+            name,
+            IndexBuild {
+                id,
+                status: IndexStatus::Building { processed: 0, total },
+            },
+        );
+        id
+    }
+
+    // Serializes the datastore into the contents of an image. The contents
+    // of indexes that aren't ready are left out, since they're incomplete;
+    // only their names are kept, so that they're built again when the image
+    // is read.
+    fn serialize(&self) -> Result<Vec<u8>> {
+        if self.index_builds.is_empty() {
+            return Ok(bincode::serialize(self)?);
+        }
+
+        let mut datastore = self.clone();
+        for name in self.index_builds.keys() {
+            datastore.property_values.remove(name);
+        }
+        Ok(bincode::serialize(&datastore)?)
+    }
+
+    fn apply(&mut self, mutation: Mutation) {
+        match mutation {
+            Mutation::CreateVertex(id, t) => self.create_vertex(Vertex::with_id(id, t)),
+            Mutation::CreateEdge(key, update_datetime) => self.create_edge(key, update_datetime),
+            Mutation::DeleteVertices(vertices) => self.delete_vertices(vertices),
+            Mutation::DeleteEdges(edges) => self.delete_edges(edges),
+            Mutation::SetVertexProperties(vertices, name, value) => self.set_vertex_properties(vertices, name, value),
+            Mutation::DeleteVertexProperties(keys) => self.delete_vertex_properties(keys),
+            Mutation::SetEdgeProperties(edges, name, value) => self.set_edge_properties(edges, name, value),
+            Mutation::DeleteEdgeProperties(keys) => self.delete_edge_properties(keys),
+            Mutation::IndexProperty(name) => self.index_property(name),
+            Mutation::DropIndex(name) => self.drop_index(&name),
+            Mutation::IndexTypeProperty(t, name) => self.index_type_property(t, name),
+            Mutation::DropTypeIndex(t, name) => self.drop_type_index(t, name),
+            Mutation::IndexCompositeProperties(names) => self.index_composite_properties(names),
+            Mutation::DropCompositeIndex(names) => self.drop_composite_index(&names),
+            Mutation::AddUniqueConstraint(name, t) => self.add_unique_constraint(name, t),
+            Mutation::DropUniqueConstraint(name, t) => self.drop_unique_constraint(&name, &t),
+            Mutation::SetSchema(schema) => self.schema = schema,
+        }
+    }
+}
+
+impl CursorSource for InternalMemoryDatastore {
+    fn range_start(&self, start_id: Option<Uuid>) -> Bound<Uuid> {
+        match start_id {
+            Some(start_id) => Bound::Included(start_id),
+            None => Bound::Unbounded,
+        }
+    }
+
+    fn vertex_range(&self, t: Option<&Identifier>, from: Bound<Uuid>, limit: usize) -> Result<Vec<(Uuid, Identifier)>> {
+        let vertices = match t {
+            Some(t) => match self.vertices_by_type.get(t) {
+                Some(ids) => ids
+                    .range((from, Bound::Unbounded))
+                    .take(limit)
+                    .map(|id| (*id, t.clone()))
+                    .collect(),
+                None => Vec::new(),
+            },
+            None => self
+                .vertices
+                .range((from, Bound::Unbounded))
+                .take(limit)
+                .map(|(id, t)| (*id, t.clone()))
+                .collect(),
+        };
+        Ok(vertices)
+    }
+
+    fn vertex(&self, id: Uuid) -> Result<Option<Identifier>> {
+        Ok(self.vertices.get(&id).cloned())
+    }
+
+    fn adjacent_edges(
+        &self,
+        id: Uuid,
+        t: Option<&Identifier>,
+        direction: EdgeDirection,
+        high: Option<DateTime<Utc>>,
+        low: Option<DateTime<Utc>>,
+        after: Option<&EdgeResume>,
+        limit: usize,
+    ) -> Result<Vec<(EdgeKey, DateTime<Utc>)>> {
+        // Edges are sorted by type and then the adjacent vertex's ID, so the
+        // update datetime of the last edge isn't needed to resume.
+        let lower_bound = match after {
+            Some((after_t, _, other_id)) => Bound::Excluded(EdgeKey::new(id, after_t.clone(), *other_id)),
+            None => Bound::Included(EdgeKey::new(id, t.cloned().unwrap_or_default(), Uuid::default())),
+        };
+        let range = match direction {
+            EdgeDirection::Outbound => self.edges.range((lower_bound, Bound::Unbounded)),
+            EdgeDirection::Inbound => self.reversed_edges.range((lower_bound, Bound::Unbounded)),
+        };
+
+        let edges = range
+            .take_while(|(key, _)| key.outbound_id == id && t.is_none_or(|t| &key.t == t))
+            .filter(|(_, update_datetime)| {
+                high.is_none_or(|high| **update_datetime <= high) && low.is_none_or(|low| **update_datetime >= low)
+            })
+            .take(limit)
+            .map(|(key, update_datetime)| match direction {
+                EdgeDirection::Outbound => (key.clone(), *update_datetime),
+                EdgeDirection::Inbound => (key.reversed(), *update_datetime),
+            })
+            .collect();
+        Ok(edges)
+    }
+
+    fn edge(&self, key: &EdgeKey) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.edges.get(key).copied())
+    }
+
+    fn is_indexed(&self, name: &Identifier) -> bool {
+        matches!(self.get_property_index(name), Ok(Some(_)))
+    }
+
+    fn vertex_property_values(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<&(Json, Uuid)>,
+        limit: usize,
+    ) -> Result<Vec<(Json, Uuid)>> {
+        let after = after.map(|(value, id)| (value, IndexedPropertyMember::Vertex(*id)));
+        self.property_index_members(name, filter, after, limit, |member| match member {
+            IndexedPropertyMember::Vertex(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    fn edge_property_values(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<&(Json, EdgeKey)>,
+        limit: usize,
+    ) -> Result<Vec<(Json, EdgeKey)>> {
+        let after = after.map(|(value, key)| (value, IndexedPropertyMember::Edge(key.clone())));
+        self.property_index_members(name, filter, after, limit, |member| match member {
+            IndexedPropertyMember::Edge(key) => Some(key.clone()),
+            _ => None,
+        })
+    }
+
+    fn vertex_property(&self, id: Uuid, name: &Identifier) -> Result<Option<Json>> {
+        Ok(self.vertex_properties.get(&(id, name.clone())).cloned())
+    }
+
+    fn edge_property(&self, key: &EdgeKey, name: &Identifier) -> Result<Option<Json>> {
+        Ok(self.edge_properties.get(&(key.clone(), name.clone())).cloned())
+    }
+
+    fn vertices(&self, q: VertexQuery) -> Result<Vec<(Uuid, Identifier)>> {
+        Ok(self.get_vertex_values_by_query(q)?.collect())
+    }
+
+    fn edges(&self, q: EdgeQuery) -> Result<Vec<(EdgeKey, DateTime<Utc>)>> {
+        Ok(self.get_edge_values_by_query(q)?.collect())
+    }
+}
+
+// The format version of images written by `sync`. The versions are:
+// * 0: A bincode dump of `InternalMemoryDatastore`, without a header, as
+//   written before images were versioned. These are upgraded when they're
+//   read, as long as they don't have any property values.
+// * 1: The current layout, with a header. Indexes that weren't ready are
+//   listed, and rebuilt when the image is read.
+const IMAGE_FORMAT_VERSION: u32 = 1;
+
+// The contents of version 1 images, which is what `InternalMemoryDatastore`
+// serializes to. Images are read into standard collections, which are then
+// converted into the persistent ones that the datastore uses.
+#[derive(Default, Deserialize)]
+struct ImageV1 {
+    vertices: BTreeMap<Uuid, Identifier>,
+    edges: BTreeMap<EdgeKey, DateTime<Utc>>,
+    reversed_edges: BTreeMap<EdgeKey, DateTime<Utc>>,
+    vertex_properties: BTreeMap<(Uuid, Identifier), Json>,
+    edge_properties: BTreeMap<(EdgeKey, Identifier), Json>,
+    property_values: HashMap<Identifier, BTreeMap<Json, HashSet<IndexedPropertyMember>>>,
+    type_property_values: HashMap<(Identifier, Identifier), BTreeMap<Json, HashSet<IndexedPropertyMember>>>,
+    composite_property_values: HashMap<Vec<Identifier>, BTreeMap<Vec<Json>, HashSet<IndexedPropertyMember>>>,
+    unique_properties: HashMap<Identifier, HashSet<Option<Identifier>>>,
+    schema: Option<Schema>,
+    unready_indexes: HashSet<Identifier>,
+}
+
+// Converts the indexes read from an image into persistent collections.
+fn indexes_from_image<N: Ord + Clone, K: Ord + Clone>(
+    indexes: HashMap<N, BTreeMap<K, HashSet<IndexedPropertyMember>>>,
+) -> OrdMap<N, OrdMap<K, OrdSet<IndexedPropertyMember>>> {
+    indexes
+        .into_iter()
+        .map(|(name, index)| {
+            let index: OrdMap<K, OrdSet<IndexedPropertyMember>> = index
+                .into_iter()
+                .map(|(value, members)| (value, members.into_iter().collect::<OrdSet<_>>()))
+                .collect();
+            (name, index)
+        })
+        .collect()
+}
+
+impl From<ImageV1> for InternalMemoryDatastore {
+    fn from(image: ImageV1) -> Self {
+        let mut datastore = InternalMemoryDatastore {
+            vertices: image.vertices.into_iter().collect(),
+            edges: image.edges.into_iter().collect(),
+            reversed_edges: image.reversed_edges.into_iter().collect(),
+            vertex_properties: image.vertex_properties.into_iter().collect(),
+            edge_properties: image.edge_properties.into_iter().collect(),
+            property_values: indexes_from_image(image.property_values),
+            type_property_values: indexes_from_image(image.type_property_values),
+            composite_property_values: indexes_from_image(image.composite_property_values),
+            unique_properties: image.unique_properties,
+            schema: image.schema,
+            ..InternalMemoryDatastore::default()
+        };
+        datastore.rebuild_vertex_type_index();
+        // Indexes that weren't ready are left out of images, so they're
+        // built again from scratch.
+        for name in image.unready_indexes {
+            datastore.add_index_build(name);
+        }
+        datastore
+    }
+}
+
+// Reads a version 0 image. These encoded property values in a way that can't
+// be decoded reliably, so rather than risk reading the wrong values, images
+// that have any are rejected. The entries are read one at a time, so that
+// property values are detected before they're decoded.
+fn read_image_v0(contents: &[u8]) -> StdResult<ImageV1, ImageError> {
+    fn next<T: for<'de> Deserialize<'de>>(reader: &mut &[u8]) -> StdResult<T, ImageError> {
+        bincode::deserialize_from(reader).map_err(|_| ImageError::UnrecognizedFormat)
+    }
+
+    let mut reader = contents;
+    let mut image = ImageV1 {
+        vertices: next(&mut reader)?,
+        edges: next(&mut reader)?,
+        reversed_edges: next(&mut reader)?,
+        ..ImageV1::default()
+    };
+
+    // The vertex and edge properties, which have to be empty
+    for _ in 0..2 {
+        if next::<u64>(&mut reader)? != 0 {
+            return Err(ImageError::LegacyProperties);
+        }
+    }
+
+    // The names of the indexed properties, each of which has to have no
+    // values
+    let indexes_len: u64 = next(&mut reader)?;
+    for _ in 0..indexes_len {
+        let name: Identifier = next(&mut reader)?;
+        if next::<u64>(&mut reader)? != 0 {
+            return Err(ImageError::LegacyProperties);
+        }
+        image.property_values.insert(name, BTreeMap::default());
+    }
+
+    if !reader.is_empty() {
+        return Err(ImageError::UnrecognizedFormat);
+    }
+    Ok(image)
+}
+
+fn read_image(path: &Path) -> StdResult<InternalMemoryDatastore, ImageError> {
+    let (version, contents) = image::read(BufReader::new(File::open(path)?))?;
+
+    let image: ImageV1 = match version {
+        // Since there's no header, anything that isn't a versioned image
+        // looks like a version 0 one.
+        0 => read_image_v0(&contents)?,
+        1 => bincode::deserialize(&contents)?,
+        _ => return Err(ImageError::UnsupportedVersion(version)),
+    };
+
+    Ok(image.into())
+}
+
+// Atomically replaces the image at a path, by writing it to a temporary file
+// in the same directory and then moving that into place. The image is
+// flushed to disk first, since the write-ahead log is truncated once it's
+// written.
+fn write_image(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let temp_path = NamedTempFile::new_in(dir).map_err(|err| Error::Datastore(Box::new(err)))?;
+    image::write(BufWriter::new(temp_path.as_file()), IMAGE_FORMAT_VERSION, contents)?;
+    temp_path
+        .as_file()
+        .sync_all()
+        .map_err(|err| Error::Datastore(Box::new(err)))?;
+    temp_path.persist(path).map_err(|err| Error::Datastore(Box::new(err)))?;
+    Ok(())
+}
+
+// Gets the path of the write-ahead log that accompanies an image.
+fn wal_path(path: &Path) -> PathBuf {
+    let mut wal_path = path.as_os_str().to_owned();
+    wal_path.push(".wal");
+    PathBuf::from(wal_path)
+}
+
+// The format version of write-ahead log records.
+const WAL_RECORD_VERSION: u8 = 1;
+
+// The longest record that's written to, or read from, a write-ahead log. A
+// longer length can only come from a corrupted record at the end of the log.
+const MAX_WAL_RECORD_LEN: usize = 1 << 30;
+
+// Fills `buf` from the log, returning false if the log ends first.
+fn read_wal_part<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+// Replays the records of a write-ahead log onto a datastore, returning the
+// length of the log up to the last complete record. A record that was only
+// partially written, e.g. because of a crash, is ignored, as is one whose
+// length is too long to have been written.
+fn replay_wal<R: Read>(reader: R, datastore: &mut InternalMemoryDatastore) -> StdResult<u64, ImageError> {
+    let mut reader = BufReader::new(reader);
+    let mut valid_len = 0;
+
+    loop {
+        let mut header = [0; 5];
+        if !read_wal_part(&mut reader, &mut header)? {
+            break;
+        }
+        if header[0] != WAL_RECORD_VERSION {
+            return Err(ImageError::UnsupportedVersion(u32::from(header[0])));
+        }
+        let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if len > MAX_WAL_RECORD_LEN {
+            break;
+        }
+
+        // The record is read as it arrives rather than into a buffer of its
+        // full length, so that a corrupted length doesn't allocate more
+        // than the log holds.
+        let mut record = Vec::new();
+        (&mut reader).take(len as u64).read_to_end(&mut record)?;
+        if record.len() < len {
+            break;
+        }
+
+        let mutations: Vec<Mutation> = bincode::deserialize(&record)?;
+        for mutation in mutations {
+            datastore.apply(mutation);
+        }
+        valid_len += (header.len() + record.len()) as u64;
+    }
+
+    Ok(valid_len)
+}
+
+// An append-only log of the mutations made since the image was last synced.
+// Each record is a format version byte, a little-endian `u32` length, and a
+// list of bincode-serialized `Mutation`s. Replaying a mutation that's
+// already in the image is harmless, so the log only needs to be truncated
+// after the image has been persisted.
+//
+// Every record is flushed to disk before the mutations in it are applied,
+// so a change that's been made survives a crash, at the cost of a disk sync
+// per write.
+#[derive(Debug)]
+struct WriteAheadLog {
+    file: File,
+}
+
+impl WriteAheadLog {
+    // Opens the log, first replaying any existing records onto `datastore`.
+    fn open(path: &Path, datastore: &mut InternalMemoryDatastore) -> StdResult<Self, ImageError> {
+        let file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let valid_len = replay_wal(&file, datastore)?;
+        // Drop any partial record, so that new records aren't appended
+        // after it.
+        file.set_len(valid_len)?;
+        file.sync_data()?;
+        Ok(WriteAheadLog { file })
+    }
+
+    // Creates an empty log, discarding any existing one.
+    fn create(path: &Path) -> StdResult<Self, ImageError> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        file.set_len(0)?;
+        file.sync_data()?;
+        Ok(WriteAheadLog { file })
+    }
+
+    // Appends mutations to the log. They're written as a single record, so
+    // that a transaction's mutations are replayed all together or not at
+    // all.
+    fn append(&mut self, mutations: &[Mutation]) -> Result<()> {
+        let record = bincode::serialize(mutations)?;
+        if record.len() > MAX_WAL_RECORD_LEN {
+            return Err(Error::Datastore(
+                format!("write-ahead log record is too long: {} bytes", record.len()).into(),
+            ));
+        }
+        let mut buf = Vec::with_capacity(record.len() + 5);
+        buf.push(WAL_RECORD_VERSION);
+        buf.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&record);
+        self.file
+            .write_all(&buf)
+            .map_err(|err| Error::Datastore(Box::new(err)))?;
+        self.file.sync_data().map_err(|err| Error::Datastore(Box::new(err)))
+    }
+
+    fn truncate(&mut self) -> Result<()> {
+        self.file.set_len(0).map_err(|err| Error::Datastore(Box::new(err)))?;
+        self.file.sync_data().map_err(|err| Error::Datastore(Box::new(err)))
+    }
+}
+
+// The number of vertices and edges indexed under each write lock when
+// building an index.
+const INDEX_BUILD_BATCH_SIZE: usize = 1_000;
+
+// A datastore's data, along with a lock that's held by whoever is writing
+// to it. Since the data shares its structure when it's cloned, long writes
+// are made to a clone, while readers continue to read the data, and the
+// clone is swapped in once they're done. Readers are only blocked for as
+// long as the swap takes, and by short writes, which are made in place.
+#[derive(Debug, Default)]
+struct SharedDatastore {
+    writer: Mutex<()>,
+    data: RwLock<InternalMemoryDatastore>,
+}
+
+impl SharedDatastore {
+    fn new(data: InternalMemoryDatastore) -> Self {
+        Self {
+            writer: Mutex::new(()),
+            data: RwLock::new(data),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, InternalMemoryDatastore> {
+        self.data.read().unwrap()
+    }
+
+    // Reads the data while locking out writers, but not readers, until the
+    // guard is dropped. Writers log their mutations while they hold the
+    // writer lock, so nothing is logged in the meantime either.
+    fn freeze(&self) -> FrozenRead<'_> {
+        let writer = self.writer.lock().unwrap();
+        FrozenRead {
+            _writer: writer,
+            data: self.read(),
+        }
+    }
+
+    // Locks the data for a short write, which blocks readers until it's
+    // done.
+    fn write(&self) -> WriteGuard<'_> {
+        let writer = self.writer.lock().unwrap();
+        WriteGuard {
+            _writer: writer,
+            data: self.data.write().unwrap(),
+        }
+    }
+
+    // Starts a long write, which is made to a copy of the data, and only
+    // blocks other writers and `freeze`. Nothing is written unless it's
+    // committed. Mutations are logged before the copy is committed, so
+    // `freeze` has to wait for the commit, or it could see data that's
+    // missing mutations that are already logged.
+    fn stage(&self) -> StagedWrite<'_> {
+        let writer = self.writer.lock().unwrap();
+        StagedWrite {
+            shared: self,
+            _writer: writer,
+            data: self.read().clone(),
+        }
+    }
+}
+
+struct FrozenRead<'a> {
+    _writer: MutexGuard<'a, ()>,
+    data: RwLockReadGuard<'a, InternalMemoryDatastore>,
+}
+
+impl Deref for FrozenRead<'_> {
+    type Target = InternalMemoryDatastore;
+
+    fn deref(&self) -> &InternalMemoryDatastore {
+        &self.data
+    }
+}
+
+struct WriteGuard<'a> {
+    _writer: MutexGuard<'a, ()>,
+    data: RwLockWriteGuard<'a, InternalMemoryDatastore>,
+}
+
+impl Deref for WriteGuard<'_> {
+    type Target = InternalMemoryDatastore;
+
+    fn deref(&self) -> &InternalMemoryDatastore {
+        &self.data
+    }
+}
+
+impl DerefMut for WriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut InternalMemoryDatastore {
+        &mut self.data
+    }
+}
+
+struct StagedWrite<'a> {
+    shared: &'a SharedDatastore,
+    _writer: MutexGuard<'a, ()>,
+    data: InternalMemoryDatastore,
+}
+
+impl StagedWrite<'_> {
+    // Swaps in the written data.
+    fn commit(self) {
+        let old = std::mem::replace(&mut *self.shared.data.write().unwrap(), self.data);
+        // The old data is dropped after the lock is released, since freeing
+        // what the write replaced can take a while.
+        drop(old);
+    }
+}
+
+impl Deref for StagedWrite<'_> {
+    type Target = InternalMemoryDatastore;
+
+    fn deref(&self) -> &InternalMemoryDatastore {
+        &self.data
+    }
+}
+
+impl DerefMut for StagedWrite<'_> {
+    fn deref_mut(&mut self) -> &mut InternalMemoryDatastore {
+        &mut self.data
+    }
+}
+
+// State shared by all handles to a transaction. The handles' own
+// `datastore` field points to a staged copy of the parent's data, which
+// mutations are applied to immediately so that reads see them; the
+// mutations are also recorded here so they can be replayed on the parent.
+// The staged copy shares its structure with the parent's data, so it only
+// holds copies of what the transaction has changed.
+#[derive(Debug)]
+struct MemoryTransaction {
+    parent: Arc<SharedDatastore>,
+    mutations: Mutex<Vec<Mutation>>,
+}
+
+/// An in-memory datastore.
+#[derive(Debug, Clone)]
+pub struct MemoryDatastore {
+    datastore: Arc<SharedDatastore>,
+    path: Option<PathBuf>,
+    wal: Option<Arc<Mutex<WriteAheadLog>>>,
+    transaction: Option<Arc<MemoryTransaction>>,
+}
+
+impl Default for MemoryDatastore {
+    fn default() -> MemoryDatastore {
+        Self {
+            datastore: Arc::new(SharedDatastore::new(InternalMemoryDatastore::default())),
+            path: None,
+            wal: None,
+            transaction: None,
+        }
+    }
+}
+
+impl MemoryDatastore {
+    /// Reads a persisted image from disk. Calls to sync will overwrite the
+    /// file at the specified path. If a write-ahead log was left next to the
+    /// image, it's replayed, and removed on the next sync. Images written by
+    /// older versions are upgraded; if the file can't be read as an image,
+    /// the returned `ImageError` describes why.
+    ///
+    /// # Arguments
+    /// * `path`: The path to the persisted image.
+    pub fn read<P: Into<PathBuf>>(path: P) -> StdResult<MemoryDatastore, ImageError> {
+        let path = path.into();
+        let mut datastore = read_image(&path)?;
+
+        match File::open(wal_path(&path)) {
+            Ok(file) => {
+                replay_wal(file, &mut datastore)?;
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(datastore)),
+            path: Some(path),
+            wal: None,
+            transaction: None,
+        }
+        .resume_index_builds())
+    }
+
+    /// Creates a new datastore. Calls to sync will overwrite the file at the
+    /// specified path, but as opposed to `read`, this will not read the file
+    /// first.
+    ///
+    /// # Arguments
+    /// * `path`: The path to the persisted image.
+    pub fn create<P: Into<PathBuf>>(path: P) -> StdResult<MemoryDatastore, ImageError> {
+        Ok(MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(InternalMemoryDatastore::default())),
+            path: Some(path.into()),
+            wal: None,
+            transaction: None,
+        })
+    }
+
+    /// Reads a persisted image from disk like `read`, but also records every
+    /// change in a write-ahead log at `<path>.wal`, so that changes made
+    /// since the last sync survive a crash. Each change is flushed to disk
+    /// before it's made, so writes wait on a disk sync. The log is replayed
+    /// on top of the image, and syncing checkpoints the image and truncates
+    /// the log. If there's no image yet, the datastore starts out empty.
+    ///
+    /// # Arguments
+    /// * `path`: The path to the persisted image.
+    pub fn read_with_wal<P: Into<PathBuf>>(path: P) -> StdResult<MemoryDatastore, ImageError> {
+        let path = path.into();
+        let mut datastore = match read_image(&path) {
+            Ok(datastore) => datastore,
+            Err(ImageError::Io(ref err)) if err.kind() == io::ErrorKind::NotFound => InternalMemoryDatastore::default(),
+            Err(err) => return Err(err),
+        };
+        let wal = WriteAheadLog::open(&wal_path(&path), &mut datastore)?;
+
+        Ok(MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(datastore)),
+            path: Some(path),
+            wal: Some(Arc::new(Mutex::new(wal))),
+            transaction: None,
+        }
+        .resume_index_builds())
+    }
+
+    /// Creates a new datastore like `create`, but also records every change
+    /// in a write-ahead log at `<path>.wal`. Any existing log is discarded.
+    ///
+    /// # Arguments
+    /// * `path`: The path to the persisted image.
+    pub fn create_with_wal<P: Into<PathBuf>>(path: P) -> StdResult<MemoryDatastore, ImageError> {
+        let path = path.into();
+        let wal = WriteAheadLog::create(&wal_path(&path))?;
+
+        Ok(MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(InternalMemoryDatastore::default())),
+            path: Some(path),
+            wal: Some(Arc::new(Mutex::new(wal))),
+            transaction: None,
+        })
+    }
+
+    fn apply(&self, datastore: &mut InternalMemoryDatastore, mutation: Mutation) -> Result<()> {
+        self.record(std::slice::from_ref(&mutation))?;
+        datastore.apply(mutation);
+        Ok(())
+    }
+
+    // Starts building a property's index, unless it's already ready. If a
+    // build is already running, it's replaced when `replace` is set, and
+    // otherwise left to finish. Returns the ID of the new build, if one was
+    // started.
+    fn start_index_build(&self, name: &Identifier, replace: bool) -> Option<u64> {
+        let mut datastore = self.datastore.write();
+        match datastore.index_builds.get(name) {
+            None if datastore.property_values.contains_key(name) => return None,
+            Some(IndexBuild {
+                status: IndexStatus::Building { .. },
+                ..
+            }) if !replace => return None,
+            _ => {}
+        }
+
+        Some(datastore.add_index_build(name.clone()))
+    }
+
+    // Fills in a property's index one batch at a time, so that other writes
+    // can be made in between. Each batch is made to a copy of the
+    // datastore, so readers aren't blocked while it's indexed. This stops
+    // early if the build is replaced, or the index is dropped.
+    fn run_index_build(&self, name: &Identifier, id: u64) -> Result<()> {
+        let mut cursor = None;
+        loop {
+            let mut datastore = self.datastore.stage();
+            match datastore.index_builds.get(name) {
+                Some(build) if build.id == id => {}
+                _ => return Ok(()),
+            }
+
+            let count = datastore.index_batch(name, &mut cursor);
+            if count == 0 {
+                // The index is only recorded once it's ready. Writes made
+                // during the build are recorded before it, so replaying the
+                // log builds the index from their final state.
+                if let Err(err) = self.record(&[Mutation::IndexProperty(name.clone())]) {
+                    datastore.index_builds.get_mut(name).unwrap().status = IndexStatus::Failed(err.to_string());
+                    datastore.commit();
+                    return Err(err);
+                }
+                datastore.index_builds.remove(name);
+                datastore.commit();
+                return Ok(());
+            }
+
+            if let IndexStatus::Building { ref mut processed, .. } =
+                datastore.index_builds.get_mut(name).unwrap().status
+            {
+                *processed += count as u64;
+            }
+            datastore.commit();
+        }
+    }
+
+    // Runs a build of a property's index on another thread.
+    fn spawn_index_build(&self, name: Identifier, id: u64) {
+        let datastore = self.clone();
+        thread::spawn(move || {
+            // Failures are reported through the index's status.
+            let _ = datastore.run_index_build(&name, id);
+        });
+    }
+
+    // Resumes the builds of indexes that weren't ready when the image was
+    // written, unless the write-ahead log finished or dropped them.
+    fn resume_index_builds(self) -> Self {
+        let builds: Vec<(Identifier, u64)> = self
+            .datastore
+            .read()
+            .index_builds
+            .iter()
+            .map(|(name, build)| (name.clone(), build.id))
+            .collect();
+        for (name, id) in builds {
+            self.spawn_index_build(name, id);
+        }
+        self
+    }
+
+    // Records mutations in the transaction, or the write-ahead log if there
+    // is one. This should be done before they're applied.
+    fn record(&self, mutations: &[Mutation]) -> Result<()> {
+        if let Some(ref transaction) = self.transaction {
+            // Transactions are logged when they're committed.
+            transaction.mutations.lock().unwrap().extend_from_slice(mutations);
+        } else if let Some(ref wal) = self.wal {
+            wal.lock().unwrap().append(mutations)?;
+        }
+        Ok(())
+    }
+}
+
+impl Datastore for MemoryDatastore {
+    fn sync(&self) -> Result<()> {
+        if let Some(ref persist_path) = self.path {
+            // Transactions persist their parent, not their staged changes.
+            let datastore = match self.transaction {
+                Some(ref transaction) => &transaction.parent,
+                None => &self.datastore,
+            };
+            // Writers are locked out until the log is truncated, so that no
+            // mutations are logged in between. This includes writes made to
+            // a copy of the data, which are logged before the copy is
+            // swapped in.
+            let datastore = datastore.freeze();
+            let contents = datastore.serialize()?;
+            write_image(persist_path, &contents)?;
+
+            // The image now has every logged mutation.
+            if let Some(ref wal) = self.wal {
+                wal.lock().unwrap().truncate()?;
+            } else {
+                match fs::remove_file(wal_path(persist_path)) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(Error::Datastore(Box::new(err))),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn backup(&self, path: &Path) -> Result<()> {
+        let datastore = match self.transaction {
+            Some(ref transaction) => &transaction.parent,
+            None => &self.datastore,
+        };
+        // Only serializing needs the lock, not writing the image out.
+        let contents = datastore.read().serialize()?;
+        write_image(path, &contents)
+    }
+
+    fn transaction(&self) -> Result<Self> {
+        // Nested transactions aren't supported
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let staged = self.datastore.read().clone();
+
+        Ok(MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(staged)),
+            path: self.path.clone(),
+            wal: self.wal.clone(),
+            transaction: Some(Arc::new(MemoryTransaction {
+                parent: self.datastore.clone(),
+                mutations: Mutex::new(Vec::new()),
+            })),
+        })
+    }
+
+    fn snapshot(&self) -> Result<Box<dyn Datastore + Send + Sync>> {
+        let datastore = self.datastore.read().clone();
+        Ok(Box::new(ReadOnlyDatastore::new(MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(datastore)),
+            path: None,
+            wal: None,
+            transaction: None,
+        })))
+    }
+
+    fn commit(&self) -> Result<()> {
+        let transaction = self.transaction.as_ref().ok_or(Error::Unsupported)?;
+        let mut staged = self.datastore.write();
+        let mut mutations = transaction.mutations.lock().unwrap();
+        // The changes are applied to a copy of the parent, so that readers
+        // aren't blocked while they're applied, and nothing is applied if
+        // they fail a check.
+        let mut parent = transaction.parent.stage();
+
+        // Unique constraints are checked again against the parent, since
+        // other changes may have been committed since the transaction's were
+        // made.
+        let constrained = mutations.iter().any(|mutation| match mutation {
+            Mutation::SetVertexProperties(_, name, _) | Mutation::SetEdgeProperties(_, name, _) => {
+                parent.unique_properties.contains_key(name)
+            }
+            _ => false,
+        });
+        for mutation in mutations.iter() {
+            if constrained {
+                parent.check_unique_mutations(std::slice::from_ref(mutation))?;
+            }
+            parent.apply(mutation.clone());
+        }
+        // Similarly, the vertices and edges that the transaction created
+        // must have their required properties once all of its changes are
+        // applied.
+        parent.check_required_properties(&mutations)?;
+
+        if let Some(ref wal) = self.wal {
+            wal.lock().unwrap().append(&mutations)?;
+        }
+        mutations.clear();
+
+        // Re-stage from the parent, so that changes committed by others
+        // are visible if the transaction continues to be used.
+        *staged = parent.clone();
+        parent.commit();
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<()> {
+        let transaction = self.transaction.as_ref().ok_or(Error::Unsupported)?;
+        let mut staged = self.datastore.write();
+        transaction.mutations.lock().unwrap().clear();
+        *staged = transaction.parent.read().clone();
+        Ok(())
+    }
+
+    fn create_vertex(&self, vertex: &Vertex) -> Result<bool> {
+        let mut datastore = self.datastore.write();
+
+        if datastore.vertices.contains_key(&vertex.id) {
+            return Ok(false);
+        }
+        if let Some(ref schema) = datastore.schema {
+            // A transaction's vertices can have their required properties
+            // set after they're created, so they're checked on commit.
+            if self.transaction.is_some() {
+                schema.check_vertex_type(&vertex.t)?;
+            } else {
+                schema.check_vertex(&vertex.t, &HashSet::new())?;
+            }
+        }
+
+        self.apply(&mut datastore, Mutation::CreateVertex(vertex.id, vertex.t.clone()))?;
+        Ok(true)
+    }
+
+    fn get_vertices(&self, q: VertexQuery) -> Result<Vec<Vertex>> {
+        let datastore = self.datastore.read();
+        let iter = datastore.get_vertex_values_by_query(q)?;
+        let iter = iter.map(|(uuid, t)| Vertex::with_id(uuid, t));
+        Ok(iter.collect())
+    }
+
+    fn get_vertices_iter(&self, q: VertexQuery) -> Result<DynIter<'_, Vertex>> {
+        // Results are read from a copy of the datastore, which is cheap to
+        // make since it shares its structure, so the lock isn't held while
+        // they're iterated over.
+        let datastore = Arc::new(self.datastore.read().clone());
+        let iter = cursor::iter_vertices(datastore, q)?;
+        Ok(Box::new(iter.map(|item| item.map(|(id, t)| Vertex::with_id(id, t)))))
+    }
+
+    fn get_vertices_page(&self, q: VertexQuery, limit: u32, token: Option<ContinuationToken>) -> Result<Page<Vertex>> {
+        let datastore = self.datastore.read();
+        cursor::page_vertices(&*datastore, fingerprint(&q), &q, limit, token, |(id, t)| {
+            Ok(Some(Vertex::with_id(id, t)))
+        })
+    }
+
+    fn delete_vertices(&self, q: VertexQuery) -> Result<()> {
+        let mut datastore = self.datastore.write();
+        let deletable_vertices = datastore.get_vertex_values_by_query(q)?.map(|(k, _)| k).collect();
+        self.apply(&mut datastore, Mutation::DeleteVertices(deletable_vertices))?;
+        Ok(())
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        let datastore = self.datastore.read();
+        Ok(datastore.vertices.len() as u64)
+    }
+
+    fn count_vertices(&self, q: VertexQuery) -> Result<u64> {
+        let datastore = self.datastore.read();
+        let count = datastore.get_vertex_values_by_query(q)?.count();
+        Ok(count as u64)
+    }
+
+    fn create_edge(&self, key: &EdgeKey) -> Result<bool> {
+        let mut datastore = self.datastore.write();
+
+        if !datastore.vertices.contains_key(&key.outbound_id) || !datastore.vertices.contains_key(&key.inbound_id) {
+            return Ok(false);
+        }
+        if let Some(ref schema) = datastore.schema {
+            let outbound_t = datastore.vertices.get(&key.outbound_id);
+            let inbound_t = datastore.vertices.get(&key.inbound_id);
+            if self.transaction.is_some() {
+                schema.check_edge_type(&key.t, outbound_t, inbound_t)?;
+            } else {
+                schema.check_edge(&key.t, outbound_t, inbound_t, &HashSet::new())?;
+            }
+        }
+
+        self.apply(&mut datastore, Mutation::CreateEdge(key.clone(), Utc::now()))?;
+        Ok(true)
+    }
+
+    fn get_edges(&self, q: EdgeQuery) -> Result<Vec<Edge>> {
+        let edge_values: Vec<(EdgeKey, DateTime<Utc>)> = {
+            let datastore = self.datastore.read();
+            let iter = datastore.get_edge_values_by_query(q)?;
+            iter.collect()
         };
 
         let iter = edge_values
@@ -498,15 +2486,48 @@ impl Datastore for MemoryDatastore {
         Ok(iter.collect())
     }
 
+    fn get_edges_iter(&self, q: EdgeQuery) -> Result<DynIter<'_, Edge>> {
+        let datastore = Arc::new(self.datastore.read().clone());
+        let iter = cursor::iter_edges(datastore, q)?;
+        Ok(Box::new(iter.map(|item| {
+            item.map(|(key, update_datetime)| Edge::new(key, update_datetime))
+        })))
+    }
+
+    fn get_edges_page(&self, q: EdgeQuery, limit: u32, token: Option<ContinuationToken>) -> Result<Page<Edge>> {
+        let datastore = self.datastore.read();
+        cursor::page_edges(
+            &*datastore,
+            fingerprint(&q),
+            &q,
+            limit,
+            token,
+            |(key, update_datetime)| Ok(Some(Edge::new(key, update_datetime))),
+        )
+    }
+
+    fn get_paths(&self, q: PathQuery) -> Result<Vec<Vec<Edge>>> {
+        let datastore = self.datastore.read();
+
+        if !datastore.vertices.contains_key(&q.start_id) {
+            return Ok(Vec::new());
+        }
+
+        util::shortest_paths(q.start_id, q.end_id, q.max_depth, q.limit, |id| {
+            let edges = datastore.iter_adjacent_edges(id, q.t.as_ref(), EdgeDirection::Outbound);
+            Ok::<_, Error>(edges.map(|(key, dt)| Edge::new(key.clone(), *dt)).collect())
+        })
+    }
+
     fn delete_edges(&self, q: EdgeQuery) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+        let mut datastore = self.datastore.write();
         let deletable_edges: Vec<EdgeKey> = datastore.get_edge_values_by_query(q)?.map(|(k, _)| k).collect();
-        datastore.delete_edges(deletable_edges);
+        self.apply(&mut datastore, Mutation::DeleteEdges(deletable_edges))?;
         Ok(())
     }
 
     fn get_edge_count(&self, id: Uuid, t: Option<&Identifier>, direction: EdgeDirection) -> Result<u64> {
-        let datastore = self.datastore.read().unwrap();
+        let datastore = self.datastore.read();
 
         let lower_bound = match t {
             Some(t) => EdgeKey::new(id, t.clone(), Uuid::default()),
@@ -530,9 +2551,15 @@ impl Datastore for MemoryDatastore {
         Ok(range.count() as u64)
     }
 
+    fn count_edges(&self, q: EdgeQuery) -> Result<u64> {
+        let datastore = self.datastore.read();
+        let count = datastore.get_edge_values_by_query(q)?.count();
+        Ok(count as u64)
+    }
+
     fn get_vertex_properties(&self, q: VertexPropertyQuery) -> Result<Vec<VertexProperty>> {
         let mut result = Vec::new();
-        let datastore = self.datastore.read().unwrap();
+        let datastore = self.datastore.read();
         let vertex_values = datastore.get_vertex_values_by_query(q.inner)?;
 
         for (id, _) in vertex_values {
@@ -546,68 +2573,91 @@ impl Datastore for MemoryDatastore {
         Ok(result)
     }
 
+    fn get_vertex_properties_iter(&self, q: VertexPropertyQuery) -> Result<DynIter<'_, VertexProperty>> {
+        let datastore = Arc::new(self.datastore.read().clone());
+        let vertices = cursor::iter_vertices(datastore.clone(), q.inner)?;
+        let name = q.name;
+        Ok(Box::new(vertices.filter_map(move |item| {
+            match item {
+                Ok((id, _)) => datastore
+                    .vertex_properties
+                    .get(&(id, name.clone()))
+                    .map(|value| Ok(VertexProperty::new(id, value.0.clone()))),
+                Err(err) => Some(Err(err)),
+            }
+        })))
+    }
+
+    fn get_vertex_properties_page(
+        &self,
+        q: VertexPropertyQuery,
+        limit: u32,
+        token: Option<ContinuationToken>,
+    ) -> Result<Page<VertexProperty>> {
+        let datastore = self.datastore.read();
+        cursor::page_vertices(&*datastore, fingerprint(&q), &q.inner, limit, token, |(id, _)| {
+            let value = datastore.vertex_properties.get(&(id, q.name.clone()));
+            Ok(value.map(|value| VertexProperty::new(id, value.0.clone())))
+        })
+    }
+
     fn get_all_vertex_properties(&self, q: VertexQuery) -> Result<Vec<VertexProperties>> {
-        let datastore = self.datastore.read().unwrap();
+        let datastore = self.datastore.read();
         let vertex_values = datastore.get_vertex_values_by_query(q)?;
 
         let mut result = Vec::new();
         for (id, t) in vertex_values {
-            let from = &(id, Identifier::default());
-            let to = &(util::next_uuid(id).unwrap(), Identifier::default());
-
-            let properties = datastore.vertex_properties.range(from..to);
-            result.push(VertexProperties::new(
-                Vertex::with_id(id, t),
-                properties
-                    .map(|(n, p)| NamedProperty::new(n.1.clone(), p.0.clone()))
-                    .collect(),
-            ));
+            let properties = datastore.get_all_properties_of_vertex(id);
+            result.push(VertexProperties::new(Vertex::with_id(id, t), properties));
         }
 
         Ok(result)
     }
 
-    fn set_vertex_properties(&self, q: VertexPropertyQuery, value: serde_json::Value) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
-
-        let vertex_values: Vec<(Uuid, Identifier)> = datastore.get_vertex_values_by_query(q.inner)?.collect();
-
-        let mut deletable_vertex_properties = Vec::<(Uuid, Identifier)>::new();
-        for (id, _) in &vertex_values {
-            deletable_vertex_properties.push((*id, q.name.clone()));
-        }
-        datastore.delete_vertex_properties(deletable_vertex_properties);
-
-        let wrapped_value = Json::new(value);
-        for (id, _) in &vertex_values {
-            datastore
-                .vertex_properties
-                .insert((*id, q.name.clone()), wrapped_value.clone());
-        }
+    fn get_all_vertex_properties_iter(&self, q: VertexQuery) -> Result<DynIter<'_, VertexProperties>> {
+        let datastore = Arc::new(self.datastore.read().clone());
+        let vertices = cursor::iter_vertices(datastore.clone(), q)?;
+        Ok(Box::new(vertices.map(move |item| {
+            let (id, t) = item?;
+            let properties = datastore.get_all_properties_of_vertex(id);
+            Ok(VertexProperties::new(Vertex::with_id(id, t), properties))
+        })))
+    }
 
-        if let Some(property_container) = datastore.property_values.get_mut(&q.name) {
-            let property_container = property_container.entry(wrapped_value).or_insert_with(HashSet::new);
-            for (id, _) in vertex_values.into_iter() {
-                property_container.insert(IndexedPropertyMember::Vertex(id));
+    fn set_vertex_properties(&self, q: VertexPropertyQuery, value: serde_json::Value) -> Result<()> {
+        let mut datastore = self.datastore.write();
+        let vertices: Vec<(Uuid, Identifier)> = datastore.get_vertex_values_by_query(q.inner)?.collect();
+        if let Some(ref schema) = datastore.schema {
+            for (_, t) in &vertices {
+                schema.check_vertex_property(t, &q.name, Some(&value))?;
             }
         }
-
+        let vertices = vertices.into_iter().map(|(id, _)| id).collect();
+        let mutation = Mutation::SetVertexProperties(vertices, q.name, Json::new(value));
+        datastore.check_unique_mutations(std::slice::from_ref(&mutation))?;
+        self.apply(&mut datastore, mutation)?;
         Ok(())
     }
 
     fn delete_vertex_properties(&self, q: VertexPropertyQuery) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+        let mut datastore = self.datastore.write();
         let mut deletable_vertex_properties = Vec::<(Uuid, Identifier)>::new();
-        for (id, _) in datastore.get_vertex_values_by_query(q.inner)? {
+        for (id, t) in datastore.get_vertex_values_by_query(q.inner)? {
+            if let Some(ref schema) = datastore.schema {
+                schema.check_vertex_property(&t, &q.name, None)?;
+            }
             deletable_vertex_properties.push((id, q.name.clone()));
         }
-        datastore.delete_vertex_properties(deletable_vertex_properties);
+        self.apply(
+            &mut datastore,
+            Mutation::DeleteVertexProperties(deletable_vertex_properties),
+        )?;
         Ok(())
     }
 
     fn get_edge_properties(&self, q: EdgePropertyQuery) -> Result<Vec<EdgeProperty>> {
         let mut result = Vec::new();
-        let datastore = self.datastore.read().unwrap();
+        let datastore = self.datastore.read();
         let edge_values = datastore.get_edge_values_by_query(q.inner)?;
 
         for (key, _) in edge_values {
@@ -621,96 +2671,398 @@ impl Datastore for MemoryDatastore {
         Ok(result)
     }
 
+    fn get_edge_properties_iter(&self, q: EdgePropertyQuery) -> Result<DynIter<'_, EdgeProperty>> {
+        let datastore = Arc::new(self.datastore.read().clone());
+        let edges = cursor::iter_edges(datastore.clone(), q.inner)?;
+        let name = q.name;
+        Ok(Box::new(edges.filter_map(move |item| {
+            match item {
+                Ok((key, _)) => datastore
+                    .edge_properties
+                    .get(&(key.clone(), name.clone()))
+                    .map(|value| Ok(EdgeProperty::new(key, value.0.clone()))),
+                Err(err) => Some(Err(err)),
+            }
+        })))
+    }
+
+    fn get_edge_properties_page(
+        &self,
+        q: EdgePropertyQuery,
+        limit: u32,
+        token: Option<ContinuationToken>,
+    ) -> Result<Page<EdgeProperty>> {
+        let datastore = self.datastore.read();
+        cursor::page_edges(&*datastore, fingerprint(&q), &q.inner, limit, token, |(key, _)| {
+            let value = datastore.edge_properties.get(&(key.clone(), q.name.clone()));
+            Ok(value.map(|value| EdgeProperty::new(key, value.0.clone())))
+        })
+    }
+
     fn get_all_edge_properties(&self, q: EdgeQuery) -> Result<Vec<EdgeProperties>> {
-        let datastore = self.datastore.read().unwrap();
+        let datastore = self.datastore.read();
         let edge_values = datastore.get_edge_values_by_query(q)?;
 
         let mut result = Vec::new();
-        for (id, t) in edge_values {
-            let from = &(id.clone(), Identifier::default());
-
-            let properties = datastore
-                .edge_properties
-                .range(from..)
-                .take_while(|((key, _name), _value)| *key == id);
-            result.push(EdgeProperties::new(
-                Edge::new(id.clone(), t),
-                properties
-                    .map(|(n, p)| NamedProperty::new(n.1.clone(), p.0.clone()))
-                    .collect(),
-            ));
+        for (key, update_datetime) in edge_values {
+            let properties = datastore.get_all_properties_of_edge(&key);
+            result.push(EdgeProperties::new(Edge::new(key, update_datetime), properties));
         }
 
         Ok(result)
     }
 
-    fn set_edge_properties(&self, q: EdgePropertyQuery, value: serde_json::Value) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
-        let edge_values: Vec<(EdgeKey, DateTime<Utc>)> = datastore.get_edge_values_by_query(q.inner)?.collect();
-
-        let mut deletable_edge_properties = Vec::<(EdgeKey, Identifier)>::new();
-        for (key, _) in &edge_values {
-            deletable_edge_properties.push((key.clone(), q.name.clone()));
-        }
-        datastore.delete_edge_properties(deletable_edge_properties);
-
-        let wrapped_value = Json::new(value);
-        for (key, _) in &edge_values {
-            datastore
-                .edge_properties
-                .insert((key.clone(), q.name.clone()), wrapped_value.clone());
-        }
+    fn get_all_edge_properties_iter(&self, q: EdgeQuery) -> Result<DynIter<'_, EdgeProperties>> {
+        let datastore = Arc::new(self.datastore.read().clone());
+        let edges = cursor::iter_edges(datastore.clone(), q)?;
+        Ok(Box::new(edges.map(move |item| {
+            let (key, update_datetime) = item?;
+            let properties = datastore.get_all_properties_of_edge(&key);
+            Ok(EdgeProperties::new(Edge::new(key, update_datetime), properties))
+        })))
+    }
 
-        if let Some(property_container) = datastore.property_values.get_mut(&q.name) {
-            let property_container = property_container.entry(wrapped_value).or_insert_with(HashSet::new);
-            for (key, _) in edge_values.into_iter() {
-                property_container.insert(IndexedPropertyMember::Edge(key));
+    fn set_edge_properties(&self, q: EdgePropertyQuery, value: serde_json::Value) -> Result<()> {
+        let mut datastore = self.datastore.write();
+        let edges: Vec<EdgeKey> = datastore
+            .get_edge_values_by_query(q.inner)?
+            .map(|(key, _)| key)
+            .collect();
+        if let Some(ref schema) = datastore.schema {
+            for key in &edges {
+                schema.check_edge_property(&key.t, &q.name, Some(&value))?;
             }
         }
-
+        let mutation = Mutation::SetEdgeProperties(edges, q.name, Json::new(value));
+        datastore.check_unique_mutations(std::slice::from_ref(&mutation))?;
+        self.apply(&mut datastore, mutation)?;
         Ok(())
     }
 
     fn delete_edge_properties(&self, q: EdgePropertyQuery) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+        let mut datastore = self.datastore.write();
         let edge_values: Vec<(EdgeKey, DateTime<Utc>)> = datastore.get_edge_values_by_query(q.inner)?.collect();
         let mut deletable_edge_properties = Vec::<(EdgeKey, Identifier)>::new();
         for (key, _) in edge_values {
+            if let Some(ref schema) = datastore.schema {
+                schema.check_edge_property(&key.t, &q.name, None)?;
+            }
             deletable_edge_properties.push((key, q.name.clone()));
         }
-        datastore.delete_edge_properties(deletable_edge_properties);
+        self.apply(
+            &mut datastore,
+            Mutation::DeleteEdgeProperties(deletable_edge_properties),
+        )?;
+        Ok(())
+    }
+
+    fn bulk_insert(&self, items: Vec<BulkInsertItem>) -> Result<()> {
+        // The insert is made to a copy of the datastore, so that readers
+        // aren't blocked while it's applied. The schema and unique
+        // constraints are checked against the whole insert before any of it
+        // is logged or applied, so that a violation leaves nothing behind.
+        let mut datastore = self.datastore.stage();
+        if let Some(ref schema) = datastore.schema {
+            schema.check_bulk_insert(&items, |id| Ok(datastore.vertices.get(&id).cloned()))?;
+        }
+
+        let mutations: Vec<Mutation> = items
+            .into_iter()
+            .map(|item| match item {
+                BulkInsertItem::Vertex(vertex) => Mutation::CreateVertex(vertex.id, vertex.t),
+                BulkInsertItem::Edge(key) => Mutation::CreateEdge(key, Utc::now()),
+                BulkInsertItem::VertexProperty(id, name, value) => {
+                    Mutation::SetVertexProperties(vec![id], name, Json::new(value))
+                }
+                BulkInsertItem::EdgeProperty(key, name, value) => {
+                    Mutation::SetEdgeProperties(vec![key], name, Json::new(value))
+                }
+            })
+            .collect();
+
+        datastore.check_unique_mutations(&mutations)?;
+        self.record(&mutations)?;
+        for mutation in mutations {
+            datastore.apply(mutation);
+        }
+
+        datastore.commit();
         Ok(())
     }
 
     fn index_property(&self, name: Identifier) -> Result<()> {
-        let mut datastore = self.datastore.write().unwrap();
+        match self.start_index_build(&name, true) {
+            Some(id) => self.run_index_build(&name, id),
+            None => Ok(()),
+        }
+    }
 
-        let mut property_container: HashMap<Json, HashSet<IndexedPropertyMember>> = HashMap::new();
-        for id in datastore.vertices.keys() {
-            if let Some(value) = datastore.vertex_properties.get(&(*id, name.clone())) {
-                property_container
-                    .entry(value.clone())
-                    .or_insert_with(HashSet::new)
-                    .insert(IndexedPropertyMember::Vertex(*id));
-            }
+    fn index_property_in_background(&self, name: Identifier) -> Result<()> {
+        // A transaction's index would be lost if it was committed before the
+        // build finished, so transactions build it right away.
+        if self.transaction.is_some() {
+            return self.index_property(name);
         }
-        for key in datastore.edges.keys() {
-            if let Some(value) = datastore.edge_properties.get(&(key.clone(), name.clone())) {
-                property_container
-                    .entry(value.clone())
-                    .or_insert_with(HashSet::new)
-                    .insert(IndexedPropertyMember::Edge(key.clone()));
-            }
+
+        if let Some(id) = self.start_index_build(&name, false) {
+            self.spawn_index_build(name, id);
         }
+        Ok(())
+    }
 
-        let existing_property_container = datastore.property_values.entry(name).or_insert_with(HashMap::new);
-        for (value, members) in property_container.into_iter() {
-            let existing_members = existing_property_container.entry(value).or_insert_with(HashSet::new);
-            for member in members {
-                existing_members.insert(member);
-            }
+    fn drop_index(&self, name: Identifier) -> Result<()> {
+        let mut datastore = self.datastore.write();
+        if !datastore.property_values.contains_key(&name) {
+            return Ok(());
+        }
+        self.apply(&mut datastore, Mutation::DropIndex(name))
+    }
+
+    fn get_indexed_properties(&self) -> Result<Vec<Identifier>> {
+        let datastore = self.datastore.read();
+        let mut names: Vec<Identifier> = datastore.property_values.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn get_index_status(&self, name: Identifier) -> Result<Option<IndexStatus>> {
+        let datastore = self.datastore.read();
+        if let Some(build) = datastore.index_builds.get(&name) {
+            Ok(Some(build.status.clone()))
+        } else if datastore.property_values.contains_key(&name) {
+            Ok(Some(IndexStatus::Ready))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn index_type_property(&self, t: Identifier, name: Identifier) -> Result<()> {
+        // The index is built on a copy of the datastore, so that readers
+        // aren't blocked while it's built.
+        let mut datastore = self.datastore.stage();
+        if datastore.get_type_property_index(&t, &name).is_some() {
+            return Ok(());
+        }
+        self.apply(&mut datastore, Mutation::IndexTypeProperty(t, name))?;
+        datastore.commit();
+        Ok(())
+    }
+
+    fn drop_type_index(&self, t: Identifier, name: Identifier) -> Result<()> {
+        let mut datastore = self.datastore.write();
+        if datastore.get_type_property_index(&t, &name).is_none() {
+            return Ok(());
+        }
+        self.apply(&mut datastore, Mutation::DropTypeIndex(t, name))
+    }
+
+    fn get_indexed_type_properties(&self) -> Result<Vec<(Identifier, Identifier)>> {
+        let datastore = self.datastore.read();
+        let mut type_properties: Vec<(Identifier, Identifier)> =
+            datastore.type_property_values.keys().cloned().collect();
+        type_properties.sort();
+        Ok(type_properties)
+    }
+
+    fn index_composite_properties(&self, names: Vec<Identifier>) -> Result<()> {
+        if !util::is_valid_composite_index(&names) {
+            return Err(Error::Unsupported);
+        }
+
+        let mut datastore = self.datastore.stage();
+        if datastore.composite_property_values.contains_key(&names) {
+            return Ok(());
+        }
+        self.apply(&mut datastore, Mutation::IndexCompositeProperties(names))?;
+        datastore.commit();
+        Ok(())
+    }
+
+    fn drop_composite_index(&self, names: Vec<Identifier>) -> Result<()> {
+        let mut datastore = self.datastore.write();
+        if !datastore.composite_property_values.contains_key(&names) {
+            return Ok(());
+        }
+        self.apply(&mut datastore, Mutation::DropCompositeIndex(names))
+    }
+
+    fn get_indexed_composite_properties(&self) -> Result<Vec<Vec<Identifier>>> {
+        let datastore = self.datastore.read();
+        let mut composite_properties: Vec<Vec<Identifier>> =
+            datastore.composite_property_values.keys().cloned().collect();
+        composite_properties.sort();
+        Ok(composite_properties)
+    }
+
+    fn add_unique_constraint(&self, name: Identifier, t: Option<Identifier>) -> Result<()> {
+        // Constraints are checked when transactions are committed, so they
+        // can't be changed from within one.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
         }
 
+        // Checking the existing values can take a while, so readers can
+        // continue to read from the datastore until the constraint is added.
+        let mut datastore = self.datastore.stage();
+        if datastore.get_property_index(&name)?.is_none() {
+            return Err(Error::NotIndexed);
+        }
+        match datastore.unique_properties.get(&name) {
+            Some(scopes) if scopes.contains(&t) => return Ok(()),
+            _ => {}
+        }
+        datastore.check_existing_unique_property(&name, &t)?;
+        self.apply(&mut datastore, Mutation::AddUniqueConstraint(name, t))?;
+        datastore.commit();
         Ok(())
     }
+
+    fn drop_unique_constraint(&self, name: Identifier, t: Option<Identifier>) -> Result<()> {
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut datastore = self.datastore.write();
+        match datastore.unique_properties.get(&name) {
+            Some(scopes) if scopes.contains(&t) => {}
+            _ => return Ok(()),
+        }
+        self.apply(&mut datastore, Mutation::DropUniqueConstraint(name, t))
+    }
+
+    fn get_unique_constraints(&self) -> Result<Vec<(Identifier, Option<Identifier>)>> {
+        let datastore = self.datastore.read();
+        let mut constraints: Vec<(Identifier, Option<Identifier>)> = datastore
+            .unique_properties
+            .iter()
+            .flat_map(|(name, scopes)| scopes.iter().map(move |t| (name.clone(), t.clone())))
+            .collect();
+        constraints.sort();
+        Ok(constraints)
+    }
+
+    fn set_schema(&self, schema: Option<Schema>) -> Result<()> {
+        // Transactions' writes are checked against the schema when they're
+        // made, rather than when they're committed, so it can't be changed
+        // from within one.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut datastore = self.datastore.write();
+        self.apply(&mut datastore, Mutation::SetSchema(schema))
+    }
+
+    fn get_schema(&self) -> Result<Option<Schema>> {
+        let datastore = self.datastore.read();
+        Ok(datastore.schema.clone())
+    }
+
+    fn get_schema_summary(&self) -> Result<SchemaSummary> {
+        let datastore = self.datastore.read();
+        let mut summary = SchemaSummary::default();
+        for (t, ids) in &datastore.vertices_by_type {
+            summary.vertex_types.insert(t.clone(), ids.len() as u64);
+        }
+        for key in datastore.edges.keys() {
+            *summary.edge_types.entry(key.t.clone()).or_default() += 1;
+        }
+        for (id, name) in datastore.vertex_properties.keys() {
+            if let Some(t) = datastore.vertices.get(id) {
+                summary
+                    .vertex_properties
+                    .entry(t.clone())
+                    .or_default()
+                    .insert(name.clone());
+            }
+        }
+        for (key, name) in datastore.edge_properties.keys() {
+            summary
+                .edge_properties
+                .entry(key.t.clone())
+                .or_default()
+                .insert(name.clone());
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InternalMemoryDatastore, SharedDatastore};
+    use crate::{Identifier, Vertex};
+
+    #[test]
+    fn should_read_during_staged_write() {
+        let shared = SharedDatastore::new(InternalMemoryDatastore::default());
+        let mut staged = shared.stage();
+        staged.create_vertex(Vertex::new(Identifier::default()));
+
+        // Readers see the data from before the write until it's committed
+        assert!(shared.read().vertices.is_empty());
+        staged.commit();
+        assert_eq!(shared.read().vertices.len(), 1);
+
+        let mut staged = shared.stage();
+        staged.create_vertex(Vertex::new(Identifier::default()));
+        drop(staged);
+        assert_eq!(shared.read().vertices.len(), 1);
+    }
+
+    #[test]
+    fn should_freeze_after_staged_write() {
+        use std::sync::{mpsc, Arc};
+        use std::thread;
+        use std::time::Duration;
+
+        let shared = Arc::new(SharedDatastore::new(InternalMemoryDatastore::default()));
+        let mut staged = shared.stage();
+        let (sender, receiver) = mpsc::channel();
+        let freezer = {
+            let shared = shared.clone();
+            thread::spawn(move || sender.send(shared.freeze().vertices.len()).unwrap())
+        };
+
+        // Freezing waits for the staged write to be committed
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+        staged.create_vertex(Vertex::new(Identifier::default()));
+        staged.commit();
+        assert_eq!(receiver.recv().unwrap(), 1);
+        freezer.join().unwrap();
+    }
+
+    #[cfg(feature = "test-suite")]
+    #[test]
+    fn should_resume_unready_index_build() {
+        use super::{ImageV1, MemoryDatastore};
+        use crate::{Datastore, Json, PropertyValueVertexQuery};
+        use std::sync::Arc;
+
+        let name = Identifier::new("foo").unwrap();
+        let vertex = Vertex::new(Identifier::default());
+        let id = vertex.id;
+        let mut datastore = InternalMemoryDatastore::default();
+        datastore.create_vertex(vertex);
+        datastore.set_vertex_properties(vec![id], name.clone(), Json::new(serde_json::json!(1)));
+        datastore.add_index_build(name.clone());
+
+        // Only the name of the index is persisted, not its contents
+        let image: ImageV1 = bincode::deserialize(&datastore.serialize().unwrap()).unwrap();
+        assert!(image.property_values.is_empty());
+        assert_eq!(image.unready_indexes, vec![name.clone()].into_iter().collect());
+
+        let datastore = MemoryDatastore {
+            datastore: Arc::new(SharedDatastore::new(image.into())),
+            path: None,
+            wal: None,
+            transaction: None,
+        }
+        .resume_index_builds();
+        crate::tests::wait_for_index(&datastore, &name);
+        let vertices = datastore
+            .get_vertices(PropertyValueVertexQuery::new(name, serde_json::json!(1)).into())
+            .unwrap();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].id, id);
+    }
 }
@@ -0,0 +1,125 @@
+//! The framing of persisted memory datastore images. An image is:
+//!
+//! * An 8 byte magic header, `INDRADBM`.
+//! * The format version of the contents, as a little-endian `u32`.
+//! * A CRC-32 checksum of the contents, as a little-endian `u32`.
+//! * The contents, whose encoding depends on the format version.
+//!
+//! Images written before the format was versioned are a bare dump of the
+//! contents, and are reported as version 0.
+
+use std::io::{self, Read, Write};
+
+use crate::errors::ImageError;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+const MAGIC: &[u8; 8] = b"INDRADBM";
+
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+// Computes the CRC-32 (IEEE) checksum of some bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = CRC32_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Writes an image with the given format version and contents.
+pub(crate) fn write<W: Write>(mut writer: W, version: u32, contents: &[u8]) -> Result<(), ImageError> {
+    writer.write_all(MAGIC)?;
+    writer.write_u32::<LittleEndian>(version)?;
+    writer.write_u32::<LittleEndian>(crc32(contents))?;
+    writer.write_all(contents)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads an image, returning its format version and checksum-verified
+/// contents. The header is read separately from the contents, so that the
+/// contents are read straight into the returned buffer without a copy.
+pub(crate) fn read<R: Read>(mut reader: R) -> Result<(u32, Vec<u8>), ImageError> {
+    let mut buf = Vec::with_capacity(MAGIC.len());
+    (&mut reader).take(MAGIC.len() as u64).read_to_end(&mut buf)?;
+
+    if buf != MAGIC {
+        reader.read_to_end(&mut buf)?;
+        return Ok((0, buf));
+    }
+
+    let version = reader.read_u32::<LittleEndian>().map_err(truncated)?;
+    let checksum = reader.read_u32::<LittleEndian>().map_err(truncated)?;
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+
+    if crc32(&contents) != checksum {
+        return Err(ImageError::ChecksumMismatch);
+    }
+
+    Ok((version, contents))
+}
+
+// Reports an image that ends partway through its header as truncated.
+fn truncated(err: io::Error) -> ImageError {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        ImageError::Truncated
+    } else {
+        ImageError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32, read, write};
+    use crate::errors::ImageError;
+
+    #[test]
+    fn should_compute_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn should_read_images() {
+        let mut buf = Vec::new();
+        write(&mut buf, 3, b"contents").unwrap();
+        assert_eq!(read(&buf[..]).unwrap(), (3, b"contents".to_vec()));
+
+        // Images without a header are from before the format was versioned
+        assert_eq!(read(&b"contents"[..]).unwrap(), (0, b"contents".to_vec()));
+
+        match read(&buf[..12]) {
+            Err(ImageError::Truncated) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let last = buf.len() - 1;
+        buf[last] ^= 1;
+        match read(&buf[..]) {
+            Err(ImageError::ChecksumMismatch) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -4,20 +4,30 @@
 //! requested.
 
 mod datastore;
+mod image;
 
 pub use self::datastore::MemoryDatastore;
 
 #[cfg(feature = "bench-suite")]
 full_bench_impl!(MemoryDatastore::default());
 
+#[cfg(feature = "bench-suite")]
+concurrent_bench_impl!(MemoryDatastore::default());
+
 #[cfg(feature = "test-suite")]
 full_test_impl!(MemoryDatastore::default());
 
+#[cfg(feature = "test-suite")]
+transaction_test_impl!(MemoryDatastore::default());
+
+#[cfg(feature = "test-suite")]
+snapshot_test_impl!(MemoryDatastore::default());
+
 #[cfg(feature = "test-suite")]
 #[test]
 fn should_serialize() {
     use super::MemoryDatastore;
-    use crate::{Datastore, Identifier, SpecificVertexQuery};
+    use crate::{Datastore, Identifier, RangeVertexQuery, SpecificVertexQuery};
     use tempfile::NamedTempFile;
 
     let path = NamedTempFile::new().unwrap();
@@ -37,4 +47,214 @@ fn should_serialize() {
     assert_eq!(vertices.len(), 1);
     assert_eq!(vertices[0].id, id);
     assert_eq!(vertices[0].t, Identifier::default());
+
+    // The vertex type index isn't persisted, so it should be rebuilt on read
+    let vertices = datastore
+        .get_vertices(RangeVertexQuery::new().t(Identifier::default()).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, id);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_replay_wal() {
+    use super::MemoryDatastore;
+    use crate::{Datastore, Identifier, ImageError, SpecificVertexQuery, VertexQueryExt};
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("image");
+    let wal_path = dir.path().join("image.wal");
+    let t = Identifier::new("test_vertex_type").unwrap();
+    let name = Identifier::new("test_property").unwrap();
+
+    // Changes made without syncing are only in the log
+    let (id, committed_id) = {
+        let datastore = MemoryDatastore::create_with_wal(&path).unwrap();
+        let id = datastore.create_vertex_from_type(t.clone()).unwrap();
+        datastore
+            .set_vertex_properties(
+                SpecificVertexQuery::single(id).property(name.clone()),
+                serde_json::json!(1),
+            )
+            .unwrap();
+        let transaction = datastore.transaction().unwrap();
+        let committed_id = transaction.create_vertex_from_type(t.clone()).unwrap();
+        transaction.commit().unwrap();
+        // Rolled back changes aren't logged
+        transaction.create_vertex_from_type(t).unwrap();
+        transaction.rollback().unwrap();
+        (id, committed_id)
+    };
+
+    // A partially written record at the end of the log is ignored
+    let mut wal = OpenOptions::new().append(true).open(&wal_path).unwrap();
+    wal.write_all(&[0xFF, 0xFF]).unwrap();
+
+    let datastore = MemoryDatastore::read_with_wal(&path).unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 2);
+    let vertices = datastore
+        .get_vertices(SpecificVertexQuery::new(vec![id, committed_id]).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 2);
+    let properties = datastore
+        .get_vertex_properties(SpecificVertexQuery::single(id).property(name))
+        .unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].value, serde_json::json!(1));
+
+    // Records appended after the partial one are still replayed
+    datastore
+        .delete_vertices(SpecificVertexQuery::single(id).into())
+        .unwrap();
+    drop(datastore);
+
+    // So is a record whose length is too long to have been written
+    let mut wal = OpenOptions::new().append(true).open(&wal_path).unwrap();
+    wal.write_all(&[1, 0xFF, 0xFF, 0xFF, 0xFF, 0]).unwrap();
+
+    let datastore = MemoryDatastore::read_with_wal(&path).unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 1);
+
+    // Syncing checkpoints the image and truncates the log
+    datastore.sync().unwrap();
+    assert_eq!(fs::metadata(&wal_path).unwrap().len(), 0);
+    let datastore = MemoryDatastore::read(&path).unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 1);
+
+    // Records in an unknown format aren't silently dropped
+    let mut wal = OpenOptions::new().append(true).open(&wal_path).unwrap();
+    wal.write_all(&[9, 0, 0, 0, 0]).unwrap();
+    match MemoryDatastore::read_with_wal(&path) {
+        Err(ImageError::UnsupportedVersion(9)) => (),
+        result => panic!("unexpected result: {:?}", result.map(|_| ())),
+    }
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_sync_during_transaction_commits() {
+    use super::MemoryDatastore;
+    use crate::{Datastore, Identifier};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("image");
+    let datastore = MemoryDatastore::create_with_wal(&path).unwrap();
+    let done = Arc::new(AtomicBool::new(false));
+
+    // Syncs made while transactions are being committed mustn't truncate
+    // records from the log that aren't in the image yet
+    let syncer = {
+        let datastore = datastore.clone();
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                datastore.sync().unwrap();
+            }
+        })
+    };
+    for _ in 0..200 {
+        let transaction = datastore.transaction().unwrap();
+        transaction.create_vertex_from_type(Identifier::default()).unwrap();
+        transaction.commit().unwrap();
+    }
+    done.store(true, Ordering::SeqCst);
+    syncer.join().unwrap();
+    drop(datastore);
+
+    let datastore = MemoryDatastore::read_with_wal(&path).unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 200);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_read_versioned_images() {
+    use super::MemoryDatastore;
+    use crate::{Datastore, EdgeDirection, Identifier, ImageError};
+    use std::fs;
+    use uuid::Uuid;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("image");
+
+    let datastore = MemoryDatastore::create(&path).unwrap();
+    datastore.create_vertex_from_type(Identifier::default()).unwrap();
+    datastore.sync().unwrap();
+    let image = fs::read(&path).unwrap();
+
+    // Images from before the format was versioned have no header. These
+    // fixtures were written by the last release before that, with two
+    // vertices, an edge between them and an index on `name`, and in the
+    // second one, a value for `name`.
+    fs::write(&path, include_bytes!("fixtures/v0.image")).unwrap();
+    let datastore = MemoryDatastore::read(&path).unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 2);
+    assert_eq!(
+        datastore
+            .get_edge_count(Uuid::from_u128(1), None, EdgeDirection::Outbound)
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        datastore.get_indexed_properties().unwrap(),
+        vec![Identifier::new("name").unwrap()]
+    );
+
+    fs::write(&path, include_bytes!("fixtures/v0_properties.image")).unwrap();
+    match MemoryDatastore::read(&path) {
+        Err(ImageError::LegacyProperties) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    let mut corrupted = image.clone();
+    corrupted[20] ^= 1;
+    fs::write(&path, &corrupted).unwrap();
+    match MemoryDatastore::read(&path) {
+        Err(ImageError::ChecksumMismatch) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    let mut future = image;
+    future[8] = 2;
+    fs::write(&path, &future).unwrap();
+    match MemoryDatastore::read(&path) {
+        Err(ImageError::UnsupportedVersion(2)) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    fs::write(&path, b"not an image").unwrap();
+    match MemoryDatastore::read(&path) {
+        Err(ImageError::UnrecognizedFormat) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_backup_committed_changes() {
+    use super::MemoryDatastore;
+    use crate::{Datastore, Identifier};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("backup");
+
+    let datastore = MemoryDatastore::default();
+    let id = datastore.create_vertex_from_type(Identifier::default()).unwrap();
+
+    // Changes staged by a transaction aren't backed up
+    let transaction = datastore.transaction().unwrap();
+    transaction.create_vertex_from_type(Identifier::default()).unwrap();
+    transaction.backup(&path).unwrap();
+
+    let backup = MemoryDatastore::read(&path).unwrap();
+    assert_eq!(backup.get_vertex_count().unwrap(), 1);
+    let vertices = backup
+        .get_vertices(crate::SpecificVertexQuery::single(id).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
 }
@@ -1,4 +1,5 @@
 use std::convert::TryFrom;
+use std::fmt;
 use std::str::FromStr;
 
 use crate::errors::{ValidationError, ValidationResult};
@@ -57,7 +58,7 @@ impl Identifier {
 
 impl Default for Identifier {
     fn default() -> Self {
-        Self { 0: "".to_string() }
+        Self("".to_string())
     }
 }
 
@@ -77,9 +78,9 @@ impl TryFrom<String> for Identifier {
     }
 }
 
-impl ToString for Identifier {
-    fn to_string(&self) -> String {
-        self.0.clone()
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
@@ -0,0 +1,16 @@
+/// The status of a property's index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IndexStatus {
+    /// The index is being built in the background.
+    Building {
+        /// The number of vertices and edges that have been indexed so far.
+        processed: u64,
+        /// The number of vertices and edges to index. Some datastores only
+        /// estimate this, so `processed` may end up exceeding it.
+        total: u64,
+    },
+    /// The index is ready to be queried.
+    Ready,
+    /// Building the index failed, with the given error message.
+    Failed(String),
+}
@@ -1,131 +1,107 @@
 use std::cmp::Ordering;
-use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 
-use serde::{Deserialize, Serialize};
+use crate::util;
 
-fn hash<H: Hasher>(value: &serde_json::Value, state: &mut H) {
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Ranks the different kinds of JSON values. Values of different kinds are
+// ordered by their rank.
+pub(crate) fn kind(value: &serde_json::Value) -> u8 {
     match value {
-        serde_json::Value::Null => {
-            state.write_u8(0);
-        }
-        serde_json::Value::Bool(v) => {
-            state.write_u8(1);
-            v.hash(state);
-        }
-        serde_json::Value::Number(v) => {
-            state.write_u8(2);
-            if v.is_i64() {
-                v.as_i64().unwrap().hash(state);
-            } else if v.is_u64() {
-                v.as_u64().unwrap().hash(state);
-            } else {
-                // Note that there are 16m different NaN values, and they won't hash the same here
-                v.as_f64().unwrap().to_bits().hash(state);
-            }
-        }
-        serde_json::Value::String(v) => {
-            state.write_u8(3);
-            v.hash(state);
-        }
-        serde_json::Value::Array(v) => {
-            state.write_u8(4);
-            for sv in v {
-                hash(sv, state);
-            }
-        }
-        serde_json::Value::Object(v) => {
-            state.write_u8(5);
-            for (sk, sv) in v {
-                sk.hash(state);
-                hash(sv, state);
-            }
+        serde_json::Value::Null => 0,
+        serde_json::Value::Bool(_) => 1,
+        serde_json::Value::Number(_) => 2,
+        serde_json::Value::String(_) => 3,
+        serde_json::Value::Array(_) => 4,
+        serde_json::Value::Object(_) => 5,
+    }
+}
+
+// JSON numbers can't be NaN, so floats always have an ordering.
+fn cmp_f64(first: f64, second: f64) -> Ordering {
+    first.partial_cmp(&second).unwrap_or(Ordering::Equal)
+}
+
+fn as_i128(value: &serde_json::Number) -> Option<i128> {
+    match value.as_i64() {
+        Some(value) => Some(i128::from(value)),
+        None => value.as_u64().map(i128::from),
+    }
+}
+
+fn cmp_i128_f64(first: i128, second: f64) -> Ordering {
+    // 2^127, which is beyond the range of any JSON integer
+    const LIMIT: f64 = 170_141_183_460_469_231_731_687_303_715_884_105_728.0;
+
+    if second >= LIMIT {
+        Ordering::Less
+    } else if second < -LIMIT {
+        Ordering::Greater
+    } else {
+        let floor = second.floor();
+        match first.cmp(&(floor as i128)) {
+            Ordering::Equal if second > floor => Ordering::Less,
+            non_lt => non_lt,
         }
     }
 }
 
-fn partial_cmp(first: &serde_json::Value, second: &serde_json::Value) -> Option<Ordering> {
+// Compares numbers exactly. Converting integers to floats for comparison
+// would lose precision for integers larger than 2^53.
+fn cmp_numbers(first: &serde_json::Number, second: &serde_json::Number) -> Ordering {
+    match (as_i128(first), as_i128(second)) {
+        (Some(v1), Some(v2)) => v1.cmp(&v2),
+        (Some(v1), None) => cmp_i128_f64(v1, second.as_f64().unwrap()),
+        (None, Some(v2)) => cmp_i128_f64(v2, first.as_f64().unwrap()).reverse(),
+        (None, None) => cmp_f64(first.as_f64().unwrap(), second.as_f64().unwrap()),
+    }
+}
+
+pub(crate) fn cmp(first: &serde_json::Value, second: &serde_json::Value) -> Ordering {
     match (first, second) {
-        (serde_json::Value::Null, serde_json::Value::Null) => Some(Ordering::Equal),
-        (serde_json::Value::Bool(v1), serde_json::Value::Bool(v2)) => v1.partial_cmp(v2),
-        (serde_json::Value::Number(v1), serde_json::Value::Number(v2)) => {
-            if v1.is_i64() {
-                let v1 = v1.as_i64().unwrap();
-                if v2.is_i64() {
-                    v1.partial_cmp(&v2.as_i64().unwrap())
-                } else if v2.is_u64() {
-                    match i64::try_from(v2.as_u64().unwrap()) {
-                        Ok(v2) => v1.partial_cmp(&v2),
-                        Err(_) => Some(Ordering::Less),
-                    }
-                } else {
-                    (v1 as f64).partial_cmp(&v2.as_f64().unwrap())
-                }
-            } else if v1.is_u64() {
-                let v1 = v1.as_u64().unwrap();
-                if v2.is_i64() {
-                    match u64::try_from(v2.as_i64().unwrap()) {
-                        Ok(v2) => v1.partial_cmp(&v2),
-                        Err(_) => Some(Ordering::Greater),
-                    }
-                } else if v2.is_u64() {
-                    v1.partial_cmp(&v2.as_u64().unwrap())
-                } else {
-                    (v1 as f64).partial_cmp(&v2.as_f64().unwrap())
-                }
-            } else {
-                let v1 = v1.as_f64().unwrap();
-                if v2.is_i64() {
-                    v1.partial_cmp(&(v2.as_i64().unwrap() as f64))
-                } else if v2.is_u64() {
-                    v1.partial_cmp(&(v2.as_u64().unwrap() as f64))
-                } else {
-                    v1.partial_cmp(&v2.as_f64().unwrap())
-                }
+        (serde_json::Value::Null, serde_json::Value::Null) => Ordering::Equal,
+        (serde_json::Value::Bool(v1), serde_json::Value::Bool(v2)) => v1.cmp(v2),
+        (serde_json::Value::Number(v1), serde_json::Value::Number(v2)) => cmp_numbers(v1, v2),
+        (serde_json::Value::String(v1), serde_json::Value::String(v2)) => v1.cmp(v2),
+        (serde_json::Value::Array(v1), serde_json::Value::Array(v2)) => cmp_by(v1.iter(), v2.iter(), cmp),
+        (serde_json::Value::Object(v1), serde_json::Value::Object(v2)) => cmp_by(v1.iter(), v2.iter(), |v1, v2| {
+            let (v1_key, v1_value) = v1;
+            let (v2_key, v2_value) = v2;
+            match v1_key.cmp(v2_key) {
+                Ordering::Equal => cmp(v1_value, v2_value),
+                non_eq => non_eq,
             }
-        }
-        (serde_json::Value::String(v1), serde_json::Value::String(v2)) => v1.partial_cmp(v2),
-        (serde_json::Value::Array(v1), serde_json::Value::Array(v2)) => {
-            partial_cmp_by(v1.iter(), v2.iter(), partial_cmp)
-        }
-        (serde_json::Value::Object(v1), serde_json::Value::Object(v2)) => {
-            partial_cmp_by(v1.iter(), v2.iter(), |v1, v2| {
-                let (v1_key, v1_value) = v1;
-                let (v2_key, v2_value) = v2;
-                match v1_key.partial_cmp(v2_key) {
-                    Some(Ordering::Equal) => partial_cmp(v1_value, v2_value),
-                    non_eq => non_eq,
-                }
-            })
-        }
-        _ => None,
+        }),
+        _ => kind(first).cmp(&kind(second)),
     }
 }
 
-fn partial_cmp_by<I, F>(mut first: I, mut second: I, mut f: F) -> Option<Ordering>
+fn cmp_by<I, F>(mut first: I, mut second: I, mut f: F) -> Ordering
 where
     I: Iterator,
-    F: FnMut(I::Item, I::Item) -> Option<Ordering>,
+    F: FnMut(I::Item, I::Item) -> Ordering,
 {
     loop {
         let x = match first.next() {
             None => {
                 if second.next().is_none() {
-                    return Some(Ordering::Equal);
+                    return Ordering::Equal;
                 } else {
-                    return Some(Ordering::Less);
+                    return Ordering::Less;
                 }
             }
             Some(val) => val,
         };
 
         let y = match second.next() {
-            None => return Some(Ordering::Greater),
+            None => return Ordering::Greater,
             Some(val) => val,
         };
 
         match f(x, y) {
-            Some(Ordering::Equal) => (),
+            Ordering::Equal => (),
             non_eq => return non_eq,
         }
     }
@@ -135,35 +111,98 @@ where
 /// that extends `serde_json::Value` with extra traits useful for datastore
 /// storage and querying. Publicly facing APIs do not use these values, so
 /// it's generally only useful for datastore authors.
-#[derive(Clone, Eq, Debug, Serialize, Deserialize)]
+#[derive(Clone, Eq, Debug)]
 pub struct Json(pub serde_json::Value);
 
 impl Json {
     pub fn new(value: serde_json::Value) -> Self {
-        Self { 0: value }
+        Self(value)
     }
 }
 
 impl From<serde_json::Value> for Json {
     fn from(value: serde_json::Value) -> Self {
-        Json { 0: value }
+        Json(value)
+    }
+}
+
+// `serde_json::Value` can only be deserialized from self-describing formats,
+// so for binary formats like bincode (used by memory datastore images), the
+// value is encoded as a JSON string instead.
+impl Serialize for Json {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.0.serialize(serializer)
+        } else {
+            serializer.serialize_str(&self.0.to_string())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Json {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            Ok(Json(serde_json::Value::deserialize(deserializer)?))
+        } else {
+            let value = String::deserialize(deserializer)?;
+            Ok(Json(serde_json::from_str(&value).map_err(D::Error::custom)?))
+        }
+    }
+}
+
+// Hashes the value structurally, consistently with `cmp`: numbers are hashed
+// by their split float and difference, so equal numbers (e.g. `1` and `1.0`)
+// hash the same.
+fn hash_value<H: Hasher>(value: &serde_json::Value, state: &mut H) {
+    kind(value).hash(state);
+    match value {
+        serde_json::Value::Null => {}
+        serde_json::Value::Bool(v) => v.hash(state),
+        serde_json::Value::Number(v) => {
+            let (f, diff) = util::split_number(v);
+            util::f64_to_ordered_u64(f).hash(state);
+            diff.hash(state);
+        }
+        serde_json::Value::String(v) => v.hash(state),
+        serde_json::Value::Array(v) => {
+            v.len().hash(state);
+            for value in v {
+                hash_value(value, state);
+            }
+        }
+        serde_json::Value::Object(v) => {
+            v.len().hash(state);
+            for (key, value) in v {
+                key.hash(state);
+                hash_value(value, state);
+            }
+        }
     }
 }
 
 impl Hash for Json {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        hash(&self.0, state);
+        hash_value(&self.0, state);
     }
 }
 
 impl PartialEq for Json {
     fn eq(&self, other: &Self) -> bool {
-        self.partial_cmp(other) == Some(Ordering::Equal)
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl PartialOrd for Json {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        partial_cmp(&self.0, &other.0)
+        Some(self.cmp(other))
+    }
+}
+
+/// Values of the same kind are ordered naturally. Values of different kinds
+/// are ordered by kind: null, booleans, numbers, strings, arrays, and then
+/// objects.
+impl Ord for Json {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp(&self.0, &other.0)
     }
 }
@@ -1,15 +1,22 @@
 mod bulk_insert;
 mod edges;
 mod identifiers;
+mod indexes;
 mod json;
+mod pagination;
 mod properties;
 mod queries;
+mod schema;
 mod vertices;
 
 pub use self::bulk_insert::BulkInsertItem;
 pub use self::edges::{Edge, EdgeKey};
 pub use self::identifiers::Identifier;
+pub use self::indexes::IndexStatus;
 pub use self::json::Json;
+pub(crate) use self::pagination::fingerprint;
+pub use self::pagination::{ContinuationToken, Page};
 pub use self::properties::{EdgeProperties, EdgeProperty, NamedProperty, VertexProperties, VertexProperty};
 pub use self::queries::*;
+pub use self::schema::{EdgeSchema, JsonType, PropertySchema, Schema, SchemaSummary, VertexSchema};
 pub use self::vertices::Vertex;
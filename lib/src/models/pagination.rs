@@ -0,0 +1,136 @@
+use std::convert::TryInto;
+use std::fmt::Debug;
+
+use crate::errors::{Error, Result};
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const TOKEN_VERSION: u8 = 2;
+
+// The most bytes of state that a token can hold. Tokens come from clients,
+// so this bounds how much is allocated to decode them.
+const MAX_TOKEN_STATE_LEN: u64 = 1 << 20;
+
+/// An opaque token that marks where a page of query results left off. Pass it
+/// back with the same query to get the next page.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ContinuationToken(Vec<u8>);
+
+impl ContinuationToken {
+    /// Creates a token from its serialized form, e.g. as received from a
+    /// client.
+    ///
+    /// # Arguments
+    /// * `bytes`: The serialized token.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Gets the serialized form of the token.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consumes the token, returning its serialized form.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    // Creates a token for resuming the query with the given fingerprint from
+    // `state`, which records where the page left off.
+    pub(crate) fn new<T: Serialize>(fingerprint: u64, state: &T) -> Result<Self> {
+        let mut bytes = vec![TOKEN_VERSION];
+        bytes.extend_from_slice(&fingerprint.to_be_bytes());
+        bincode::options()
+            .with_limit(MAX_TOKEN_STATE_LEN)
+            .serialize_into(&mut bytes, state)?;
+        Ok(Self(bytes))
+    }
+
+    // The inverse of `new`. Tokens that were created for a different query
+    // are rejected.
+    pub(crate) fn decode<T: DeserializeOwned>(&self, fingerprint: u64) -> Result<T> {
+        let bytes = &self.0;
+        if bytes.len() < 9 || bytes[0] != TOKEN_VERSION {
+            return Err(Error::InvalidContinuationToken);
+        }
+        if u64::from_be_bytes(bytes[1..9].try_into().unwrap()) != fingerprint {
+            return Err(Error::InvalidContinuationToken);
+        }
+        bincode::options()
+            .with_limit(MAX_TOKEN_STATE_LEN)
+            .deserialize(&bytes[9..])
+            .map_err(|_| Error::InvalidContinuationToken)
+    }
+}
+
+// Identifies a query, so that a token can only resume the query it was
+// created for. This is the FNV-1a hash of the query's debug representation,
+// which includes all of its parameters.
+pub(crate) fn fingerprint<Q: Debug>(q: &Q) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{:?}", q).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// A page of query results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    /// The results.
+    pub results: Vec<T>,
+
+    /// A token for getting the next page of results, or `None` if this is
+    /// the last page.
+    pub token: Option<ContinuationToken>,
+}
+
+impl<T> Page<T> {
+    /// Creates a new page.
+    ///
+    /// # Arguments
+    /// * `results`: The results.
+    /// * `token`: A token for getting the next page of results.
+    pub fn new(results: Vec<T>, token: Option<ContinuationToken>) -> Self {
+        Self { results, token }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fingerprint, ContinuationToken};
+    use crate::errors::Error;
+    use crate::{RangeVertexQuery, VertexQuery};
+    use uuid::Uuid;
+
+    #[test]
+    fn should_decode_continuation_tokens() {
+        let id = Uuid::from_u128(0x1234);
+        let q: VertexQuery = RangeVertexQuery::new().into();
+        let token = ContinuationToken::new(fingerprint(&q), &(7u64, Some(id))).unwrap();
+        assert_eq!(
+            token.decode::<(u64, Option<Uuid>)>(fingerprint(&q)).unwrap(),
+            (7, Some(id))
+        );
+
+        // Tokens can only be used with the query they were created for
+        let other: VertexQuery = RangeVertexQuery::new().limit(5).into();
+        match token.decode::<(u64, Option<Uuid>)>(fingerprint(&other)) {
+            Err(Error::InvalidContinuationToken) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let mut truncated = token.into_bytes();
+        truncated.pop();
+        for bytes in [vec![], vec![2, 0, 0], vec![1; 12], truncated] {
+            match ContinuationToken::from_bytes(bytes).decode::<(u64, Option<Uuid>)>(fingerprint(&q)) {
+                Err(Error::InvalidContinuationToken) => (),
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+}
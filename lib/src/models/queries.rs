@@ -1,6 +1,8 @@
+use std::cmp::Ordering;
+use std::f64;
 use std::str::FromStr;
-use std::u32;
 
+use super::json;
 use crate::{errors, EdgeKey, Identifier};
 
 use chrono::offset::Utc;
@@ -70,6 +72,92 @@ impl From<EdgeDirection> for String {
     }
 }
 
+/// A range of property values to query for.
+///
+/// Comparisons only match values of the same kind as the bound(s) - e.g.
+/// `GreaterThan(json!(30))` matches numbers greater than 30, but no strings.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum PropertyValueRange {
+    /// Matches values greater than the given value.
+    GreaterThan(serde_json::Value),
+    /// Matches values less than the given value.
+    LessThan(serde_json::Value),
+    /// Matches values between the given low and high values, inclusive. If
+    /// the values are of different kinds, nothing is matched.
+    Between(serde_json::Value, serde_json::Value),
+    /// Matches strings that start with the given prefix.
+    Prefix(String),
+}
+
+impl PropertyValueRange {
+    /// Gets the lowest value that could be in the range. Datastores that keep
+    /// property values sorted can start scanning from here.
+    pub fn start(&self) -> serde_json::Value {
+        match self {
+            PropertyValueRange::GreaterThan(bound) => bound.clone(),
+            PropertyValueRange::LessThan(bound) => match bound {
+                serde_json::Value::Null => serde_json::Value::Null,
+                serde_json::Value::Bool(_) => serde_json::Value::Bool(false),
+                serde_json::Value::Number(_) => {
+                    serde_json::Value::Number(serde_json::Number::from_f64(f64::MIN).unwrap())
+                }
+                serde_json::Value::String(_) => serde_json::Value::String(String::new()),
+                serde_json::Value::Array(_) => serde_json::Value::Array(Vec::new()),
+                serde_json::Value::Object(_) => serde_json::Value::Object(serde_json::Map::new()),
+            },
+            PropertyValueRange::Between(low, _) => low.clone(),
+            PropertyValueRange::Prefix(prefix) => serde_json::Value::String(prefix.clone()),
+        }
+    }
+
+    /// Determines where a value lies relative to the range, using the same
+    /// ordering as `Json`: `Less` if it sorts before the range, `Equal` if
+    /// it's in the range, and `Greater` if it sorts after the range. Because
+    /// ranges are contiguous, a sorted scan can stop at the first `Greater`
+    /// value.
+    ///
+    /// # Arguments
+    /// * `value`: The value to locate.
+    pub fn locate(&self, value: &serde_json::Value) -> Ordering {
+        match self {
+            PropertyValueRange::GreaterThan(bound) => match json::cmp(value, bound) {
+                Ordering::Greater if json::kind(value) == json::kind(bound) => Ordering::Equal,
+                Ordering::Greater => Ordering::Greater,
+                _ => Ordering::Less,
+            },
+            PropertyValueRange::LessThan(bound) => match json::cmp(value, bound) {
+                Ordering::Less if json::kind(value) == json::kind(bound) => Ordering::Equal,
+                Ordering::Less => Ordering::Less,
+                _ => Ordering::Greater,
+            },
+            PropertyValueRange::Between(low, high) => {
+                if json::kind(low) != json::kind(high) {
+                    Ordering::Greater
+                } else if json::cmp(value, low) == Ordering::Less {
+                    Ordering::Less
+                } else if json::cmp(value, high) == Ordering::Greater {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            }
+            PropertyValueRange::Prefix(prefix) => match value {
+                serde_json::Value::String(s) if s.starts_with(prefix.as_str()) => Ordering::Equal,
+                serde_json::Value::String(s) => s.as_str().cmp(prefix.as_str()),
+                _ => json::kind(value).cmp(&json::kind(&serde_json::Value::String(String::new()))),
+            },
+        }
+    }
+
+    /// Checks whether a value is in the range.
+    ///
+    /// # Arguments
+    /// * `value`: The value to check.
+    pub fn contains(&self, value: &serde_json::Value) -> bool {
+        self.locate(value) == Ordering::Equal
+    }
+}
+
 /// A query for vertices.
 ///
 /// Generally you shouldn't need to instantiate a `VertexQuery` directly, but
@@ -80,12 +168,19 @@ pub enum VertexQuery {
     Range(RangeVertexQuery),
     Specific(SpecificVertexQuery),
     Pipe(PipeVertexQuery),
+    Traversal(TraversalVertexQuery),
+
+    Union(UnionVertexQuery),
+    Intersect(IntersectVertexQuery),
+    Except(ExceptVertexQuery),
 
     PropertyPresence(PropertyPresenceVertexQuery),
     PropertyValue(PropertyValueVertexQuery),
+    PropertyValueRange(PropertyValueRangeVertexQuery),
 
     PipePropertyPresence(PipePropertyPresenceVertexQuery),
     PipePropertyValue(PipePropertyValueVertexQuery),
+    PipePropertyValueRange(PipePropertyValueRangeVertexQuery),
 }
 
 /// Extension trait with methods available in all vertex queries.
@@ -100,6 +195,40 @@ pub trait VertexQueryExt: Into<VertexQuery> {
         PipeEdgeQuery::new(Box::new(self.into()), EdgeDirection::Inbound)
     }
 
+    /// Gets the vertices reachable from the vertices by following edges in
+    /// a given direction. By default, this walks a single hop.
+    ///
+    /// # Arguments
+    /// * `direction`: Whether to follow outbound or inbound edges.
+    fn traverse(self, direction: EdgeDirection) -> TraversalVertexQuery {
+        TraversalVertexQuery::new(Box::new(self.into()), direction)
+    }
+
+    /// Gets the vertices returned by either these vertices or another
+    /// vertex query. Each vertex is returned at most once.
+    ///
+    /// # Arguments
+    /// * `other`: The other vertex query.
+    fn union<Q: Into<VertexQuery>>(self, other: Q) -> UnionVertexQuery {
+        UnionVertexQuery::new(Box::new(self.into()), Box::new(other.into()))
+    }
+
+    /// Gets the vertices that are also returned by another vertex query.
+    ///
+    /// # Arguments
+    /// * `other`: The other vertex query.
+    fn intersect<Q: Into<VertexQuery>>(self, other: Q) -> IntersectVertexQuery {
+        IntersectVertexQuery::new(Box::new(self.into()), Box::new(other.into()))
+    }
+
+    /// Gets the vertices that are not returned by another vertex query.
+    ///
+    /// # Arguments
+    /// * `other`: The other vertex query.
+    fn except<Q: Into<VertexQuery>>(self, other: Q) -> ExceptVertexQuery {
+        ExceptVertexQuery::new(Box::new(self.into()), Box::new(other.into()))
+    }
+
     /// Gets a property associated with the vertices.
     ///
     /// # Arguments
@@ -149,6 +278,33 @@ pub trait VertexQueryExt: Into<VertexQuery> {
     ) -> PipePropertyValueVertexQuery {
         PipePropertyValueVertexQuery::new(Box::new(self.into()), name, value, false)
     }
+
+    /// Gets vertices with a property value in a given range.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    fn with_property_in_range<T: Into<Identifier>>(
+        self,
+        name: T,
+        range: PropertyValueRange,
+    ) -> PipePropertyValueRangeVertexQuery {
+        PipePropertyValueRangeVertexQuery::new(Box::new(self.into()), name, range, true)
+    }
+
+    /// Gets vertices without a property value in a given range. This includes
+    /// vertices that do not have the property at all.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    fn with_property_not_in_range<T: Into<Identifier>>(
+        self,
+        name: T,
+        range: PropertyValueRange,
+    ) -> PipePropertyValueRangeVertexQuery {
+        PipePropertyValueRangeVertexQuery::new(Box::new(self.into()), name, range, false)
+    }
 }
 
 /// Gets vertices with a property.
@@ -196,6 +352,32 @@ impl PropertyValueVertexQuery {
     }
 }
 
+/// Gets vertices with a property value in a given range.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct PropertyValueRangeVertexQuery {
+    /// The name of the property.
+    pub name: Identifier,
+    /// The range of values.
+    pub range: PropertyValueRange,
+}
+
+vertex_query_type!(PropertyValueRangeVertexQuery, PropertyValueRange);
+
+impl PropertyValueRangeVertexQuery {
+    /// Creates a new vertex query for getting vertices with a property value
+    /// in a given range.
+    ///
+    /// Arguments
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    pub fn new<T: Into<Identifier>>(name: T, range: PropertyValueRange) -> Self {
+        Self {
+            name: name.into(),
+            range,
+        }
+    }
+}
+
 /// Gets vertices with a property.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct PipePropertyPresenceVertexQuery {
@@ -259,6 +441,41 @@ impl PipePropertyValueVertexQuery {
     }
 }
 
+/// Gets vertices with a property value in a given range.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct PipePropertyValueRangeVertexQuery {
+    /// The query to filter.
+    pub inner: Box<VertexQuery>,
+    /// The name of the property.
+    pub name: Identifier,
+    /// The range of values.
+    pub range: PropertyValueRange,
+    /// Whether we should look for values in the range or outside of it.
+    pub within: bool,
+}
+
+vertex_query_type!(PipePropertyValueRangeVertexQuery, PipePropertyValueRange);
+
+impl PipePropertyValueRangeVertexQuery {
+    /// Creates a new vertex query for getting vertices with a property value
+    /// in a given range.
+    ///
+    /// Arguments
+    /// * `inner`: The query to filter.
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    /// * `within`: Whether we should look for values in the range or outside
+    ///   of it.
+    pub fn new<T: Into<Identifier>>(inner: Box<VertexQuery>, name: T, range: PropertyValueRange, within: bool) -> Self {
+        Self {
+            inner,
+            name: name.into(),
+            range,
+            within,
+        }
+    }
+}
+
 /// Gets a range of vertices.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct RangeVertexQuery {
@@ -284,7 +501,7 @@ impl RangeVertexQuery {
     /// Creates a new vertex range query.
     pub fn new() -> Self {
         Self {
-            limit: u32::max_value(),
+            limit: u32::MAX,
             t: None,
             start_id: None,
         }
@@ -387,7 +604,7 @@ impl PipeVertexQuery {
         Self {
             inner,
             direction,
-            limit: u32::max_value(),
+            limit: u32::MAX,
             t: None,
         }
     }
@@ -419,6 +636,172 @@ impl PipeVertexQuery {
     }
 }
 
+/// Gets the vertices reachable from a set of starting vertices, by
+/// following edges in one direction for a bounded number of hops.
+///
+/// The traversal is breadth-first. Each vertex is visited at most once, at
+/// the depth of the shortest path to it, and is returned if that depth is
+/// between `min_depth` and `max_depth`, inclusive. The starting vertices are
+/// at depth 0.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.traverse()` on a vertex query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct TraversalVertexQuery {
+    /// The vertex query to start from.
+    pub inner: Box<VertexQuery>,
+
+    /// Whether to follow outbound or inbound edges.
+    pub direction: EdgeDirection,
+
+    /// Only follows edges of this type.
+    pub t: Option<Identifier>,
+
+    /// The minimum depth of vertices to return.
+    pub min_depth: u32,
+
+    /// The maximum depth to traverse to.
+    pub max_depth: u32,
+
+    /// Limits the number of vertices to get.
+    pub limit: u32,
+}
+
+vertex_query_type!(TraversalVertexQuery, Traversal);
+
+impl TraversalVertexQuery {
+    /// Creates a new traversal vertex query, which walks a single hop.
+    ///
+    /// Arguments
+    /// * `inner`: The vertex query to start from.
+    /// * `direction`: Whether to follow outbound or inbound edges.
+    pub fn new(inner: Box<VertexQuery>, direction: EdgeDirection) -> Self {
+        Self {
+            inner,
+            direction,
+            t: None,
+            min_depth: 1,
+            max_depth: 1,
+            limit: u32::MAX,
+        }
+    }
+
+    /// Only follow edges of a given type.
+    ///
+    /// # Arguments
+    /// * `t`: Sets the edge type filter.
+    pub fn t(self, t: Identifier) -> Self {
+        Self { t: Some(t), ..self }
+    }
+
+    /// Sets the minimum depth of vertices to return. Set this to 0 to include
+    /// the starting vertices.
+    ///
+    /// # Arguments
+    /// * `min_depth`: The minimum depth.
+    pub fn min_depth(self, min_depth: u32) -> Self {
+        Self { min_depth, ..self }
+    }
+
+    /// Sets the maximum depth to traverse to.
+    ///
+    /// # Arguments
+    /// * `max_depth`: The maximum depth.
+    pub fn max_depth(self, max_depth: u32) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Sets the limit.
+    ///
+    /// # Arguments
+    /// * `limit`: Limits the number of returned results.
+    pub fn limit(self, limit: u32) -> Self {
+        Self { limit, ..self }
+    }
+}
+
+/// Gets the vertices returned by either of two vertex queries. The results of
+/// `inner` come first, followed by the results of `other`. Each vertex is
+/// returned at most once.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.union()` on a vertex query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct UnionVertexQuery {
+    /// The vertex query to build off of.
+    pub inner: Box<VertexQuery>,
+
+    /// The vertex query to combine with.
+    pub other: Box<VertexQuery>,
+}
+
+vertex_query_type!(UnionVertexQuery, Union);
+
+impl UnionVertexQuery {
+    /// Creates a new union vertex query.
+    ///
+    /// Arguments
+    /// * `inner`: The vertex query to build off of.
+    /// * `other`: The vertex query to combine with.
+    pub fn new(inner: Box<VertexQuery>, other: Box<VertexQuery>) -> Self {
+        Self { inner, other }
+    }
+}
+
+/// Gets the vertices returned by a vertex query that are also returned by
+/// another. Results are in the order of `inner`.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.intersect()` on a vertex query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct IntersectVertexQuery {
+    /// The vertex query to build off of.
+    pub inner: Box<VertexQuery>,
+
+    /// The vertex query to combine with.
+    pub other: Box<VertexQuery>,
+}
+
+vertex_query_type!(IntersectVertexQuery, Intersect);
+
+impl IntersectVertexQuery {
+    /// Creates a new intersect vertex query.
+    ///
+    /// Arguments
+    /// * `inner`: The vertex query to build off of.
+    /// * `other`: The vertex query to combine with.
+    pub fn new(inner: Box<VertexQuery>, other: Box<VertexQuery>) -> Self {
+        Self { inner, other }
+    }
+}
+
+/// Gets the vertices returned by a vertex query that are not returned by
+/// another. Results are in the order of `inner`.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.except()` on a vertex query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct ExceptVertexQuery {
+    /// The vertex query to build off of.
+    pub inner: Box<VertexQuery>,
+
+    /// The vertex query to combine with.
+    pub other: Box<VertexQuery>,
+}
+
+vertex_query_type!(ExceptVertexQuery, Except);
+
+impl ExceptVertexQuery {
+    /// Creates a new except vertex query.
+    ///
+    /// Arguments
+    /// * `inner`: The vertex query to build off of.
+    /// * `other`: The vertex query to combine with.
+    pub fn new(inner: Box<VertexQuery>, other: Box<VertexQuery>) -> Self {
+        Self { inner, other }
+    }
+}
+
 /// Gets property values associated with vertices.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct VertexPropertyQuery {
@@ -453,11 +836,17 @@ pub enum EdgeQuery {
     Specific(SpecificEdgeQuery),
     Pipe(PipeEdgeQuery),
 
+    Union(UnionEdgeQuery),
+    Intersect(IntersectEdgeQuery),
+    Except(ExceptEdgeQuery),
+
     PropertyPresence(PropertyPresenceEdgeQuery),
     PropertyValue(PropertyValueEdgeQuery),
+    PropertyValueRange(PropertyValueRangeEdgeQuery),
 
     PipePropertyPresence(PipePropertyPresenceEdgeQuery),
     PipePropertyValue(PipePropertyValueEdgeQuery),
+    PipePropertyValueRange(PipePropertyValueRangeEdgeQuery),
 }
 
 /// Extension trait that specifies methods exposed by all edge queries.
@@ -472,6 +861,31 @@ pub trait EdgeQueryExt: Into<EdgeQuery> {
         PipeVertexQuery::new(Box::new(self.into()), EdgeDirection::Inbound)
     }
 
+    /// Gets the edges returned by either these edges or another
+    /// edge query. Each edge is returned at most once.
+    ///
+    /// # Arguments
+    /// * `other`: The other edge query.
+    fn union<Q: Into<EdgeQuery>>(self, other: Q) -> UnionEdgeQuery {
+        UnionEdgeQuery::new(Box::new(self.into()), Box::new(other.into()))
+    }
+
+    /// Gets the edges that are also returned by another edge query.
+    ///
+    /// # Arguments
+    /// * `other`: The other edge query.
+    fn intersect<Q: Into<EdgeQuery>>(self, other: Q) -> IntersectEdgeQuery {
+        IntersectEdgeQuery::new(Box::new(self.into()), Box::new(other.into()))
+    }
+
+    /// Gets the edges that are not returned by another edge query.
+    ///
+    /// # Arguments
+    /// * `other`: The other edge query.
+    fn except<Q: Into<EdgeQuery>>(self, other: Q) -> ExceptEdgeQuery {
+        ExceptEdgeQuery::new(Box::new(self.into()), Box::new(other.into()))
+    }
+
     /// Gets a property associated with the edges.
     ///
     /// # Arguments
@@ -521,6 +935,33 @@ pub trait EdgeQueryExt: Into<EdgeQuery> {
     ) -> PipePropertyValueEdgeQuery {
         PipePropertyValueEdgeQuery::new(Box::new(self.into()), name, value, false)
     }
+
+    /// Gets edges with a property value in a given range.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    fn with_property_in_range<T: Into<Identifier>>(
+        self,
+        name: T,
+        range: PropertyValueRange,
+    ) -> PipePropertyValueRangeEdgeQuery {
+        PipePropertyValueRangeEdgeQuery::new(Box::new(self.into()), name, range, true)
+    }
+
+    /// Gets edges without a property value in a given range. This includes
+    /// edges that do not have the property at all.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    fn with_property_not_in_range<T: Into<Identifier>>(
+        self,
+        name: T,
+        range: PropertyValueRange,
+    ) -> PipePropertyValueRangeEdgeQuery {
+        PipePropertyValueRangeEdgeQuery::new(Box::new(self.into()), name, range, false)
+    }
 }
 
 /// Gets edges with a property.
@@ -568,6 +1009,32 @@ impl PropertyValueEdgeQuery {
     }
 }
 
+/// Gets edges with a property value in a given range.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct PropertyValueRangeEdgeQuery {
+    /// The name of the property.
+    pub name: Identifier,
+    /// The range of values.
+    pub range: PropertyValueRange,
+}
+
+edge_query_type!(PropertyValueRangeEdgeQuery, PropertyValueRange);
+
+impl PropertyValueRangeEdgeQuery {
+    /// Creates a new edge query for getting edges with a property value
+    /// in a given range.
+    ///
+    /// Arguments
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    pub fn new<T: Into<Identifier>>(name: T, range: PropertyValueRange) -> Self {
+        Self {
+            name: name.into(),
+            range,
+        }
+    }
+}
+
 /// Gets edges with a property.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct PipePropertyPresenceEdgeQuery {
@@ -631,6 +1098,41 @@ impl PipePropertyValueEdgeQuery {
     }
 }
 
+/// Gets edges with a property value in a given range.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct PipePropertyValueRangeEdgeQuery {
+    /// The query to filter.
+    pub inner: Box<EdgeQuery>,
+    /// The name of the property.
+    pub name: Identifier,
+    /// The range of values.
+    pub range: PropertyValueRange,
+    /// Whether we should look for values in the range or outside of it.
+    pub within: bool,
+}
+
+edge_query_type!(PipePropertyValueRangeEdgeQuery, PipePropertyValueRange);
+
+impl PipePropertyValueRangeEdgeQuery {
+    /// Creates a new edge query for getting edges with a property value
+    /// in a given range.
+    ///
+    /// Arguments
+    /// * `inner`: The query to filter.
+    /// * `name`: The name of the property.
+    /// * `range`: The range of values.
+    /// * `within`: Whether we should look for values in the range or outside
+    ///   of it.
+    pub fn new<T: Into<Identifier>>(inner: Box<EdgeQuery>, name: T, range: PropertyValueRange, within: bool) -> Self {
+        Self {
+            inner,
+            name: name.into(),
+            range,
+            within,
+        }
+    }
+}
+
 /// Gets a specific set of edges.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct SpecificEdgeQuery {
@@ -698,7 +1200,7 @@ impl PipeEdgeQuery {
         Self {
             inner,
             direction,
-            limit: u32::max_value(),
+            limit: u32::MAX,
             t: None,
             high: None,
             low: None,
@@ -766,6 +1268,88 @@ impl PipeEdgeQuery {
     }
 }
 
+/// Gets the edges returned by either of two edge queries. The results of
+/// `inner` come first, followed by the results of `other`. Each edge is
+/// returned at most once.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.union()` on an edge query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct UnionEdgeQuery {
+    /// The edge query to build off of.
+    pub inner: Box<EdgeQuery>,
+
+    /// The edge query to combine with.
+    pub other: Box<EdgeQuery>,
+}
+
+edge_query_type!(UnionEdgeQuery, Union);
+
+impl UnionEdgeQuery {
+    /// Creates a new union edge query.
+    ///
+    /// Arguments
+    /// * `inner`: The edge query to build off of.
+    /// * `other`: The edge query to combine with.
+    pub fn new(inner: Box<EdgeQuery>, other: Box<EdgeQuery>) -> Self {
+        Self { inner, other }
+    }
+}
+
+/// Gets the edges returned by a edge query that are also returned by
+/// another. Results are in the order of `inner`.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.intersect()` on an edge query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct IntersectEdgeQuery {
+    /// The edge query to build off of.
+    pub inner: Box<EdgeQuery>,
+
+    /// The edge query to combine with.
+    pub other: Box<EdgeQuery>,
+}
+
+edge_query_type!(IntersectEdgeQuery, Intersect);
+
+impl IntersectEdgeQuery {
+    /// Creates a new intersect edge query.
+    ///
+    /// Arguments
+    /// * `inner`: The edge query to build off of.
+    /// * `other`: The edge query to combine with.
+    pub fn new(inner: Box<EdgeQuery>, other: Box<EdgeQuery>) -> Self {
+        Self { inner, other }
+    }
+}
+
+/// Gets the edges returned by a edge query that are not returned by
+/// another. Results are in the order of `inner`.
+///
+/// Generally, you shouldn't need to construct this directly, but rather call
+/// `.except()` on an edge query.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct ExceptEdgeQuery {
+    /// The edge query to build off of.
+    pub inner: Box<EdgeQuery>,
+
+    /// The edge query to combine with.
+    pub other: Box<EdgeQuery>,
+}
+
+edge_query_type!(ExceptEdgeQuery, Except);
+
+impl ExceptEdgeQuery {
+    /// Creates a new except edge query.
+    ///
+    /// Arguments
+    /// * `inner`: The edge query to build off of.
+    /// * `other`: The edge query to combine with.
+    pub fn new(inner: Box<EdgeQuery>, other: Box<EdgeQuery>) -> Self {
+        Self { inner, other }
+    }
+}
+
 /// Gets property values associated with edges.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct EdgePropertyQuery {
@@ -790,9 +1374,73 @@ impl EdgePropertyQuery {
     }
 }
 
+/// Gets the shortest paths between two vertices, following outbound edges.
+/// Each path is an ordered list of edges, and never visits a vertex more than
+/// once. Paths are returned in order of length.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct PathQuery {
+    /// The id of the vertex to start from.
+    pub start_id: Uuid,
+
+    /// The id of the vertex to end at.
+    pub end_id: Uuid,
+
+    /// Only follows edges of this type.
+    pub t: Option<Identifier>,
+
+    /// The maximum number of edges in a path.
+    pub max_depth: u32,
+
+    /// The maximum number of paths to get.
+    pub limit: u32,
+}
+
+impl PathQuery {
+    /// Creates a new path query, which gets the single shortest path.
+    ///
+    /// Arguments
+    /// * `start_id`: The id of the vertex to start from.
+    /// * `end_id`: The id of the vertex to end at.
+    pub fn new(start_id: Uuid, end_id: Uuid) -> Self {
+        Self {
+            start_id,
+            end_id,
+            t: None,
+            max_depth: u32::MAX,
+            limit: 1,
+        }
+    }
+
+    /// Only follow edges of a given type.
+    ///
+    /// # Arguments
+    /// * `t`: Sets the edge type filter.
+    pub fn t(self, t: Identifier) -> Self {
+        Self { t: Some(t), ..self }
+    }
+
+    /// Sets the maximum number of edges in a path.
+    ///
+    /// # Arguments
+    /// * `max_depth`: The maximum depth.
+    pub fn max_depth(self, max_depth: u32) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Sets the maximum number of paths to get, e.g. to get the k shortest
+    /// paths.
+    ///
+    /// # Arguments
+    /// * `limit`: Limits the number of returned paths.
+    pub fn limit(self, limit: u32) -> Self {
+        Self { limit, ..self }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::EdgeDirection;
+    use super::{EdgeDirection, PropertyValueRange};
+    use std::cmp::Ordering;
     use std::str::FromStr;
 
     #[test]
@@ -809,4 +1457,31 @@ mod tests {
         let s: String = EdgeDirection::Inbound.into();
         assert_eq!(s, "inbound".to_string());
     }
+
+    #[test]
+    fn should_locate_values_in_property_value_range() {
+        let range = PropertyValueRange::GreaterThan(serde_json::json!(30));
+        assert_eq!(range.locate(&serde_json::json!(true)), Ordering::Less);
+        assert_eq!(range.locate(&serde_json::json!(30)), Ordering::Less);
+        assert_eq!(range.locate(&serde_json::json!(30.5)), Ordering::Equal);
+        assert_eq!(range.locate(&serde_json::json!("40")), Ordering::Greater);
+
+        let range = PropertyValueRange::LessThan(serde_json::json!(30));
+        assert_eq!(range.locate(&serde_json::json!(null)), Ordering::Less);
+        assert_eq!(range.locate(&serde_json::json!(-1)), Ordering::Equal);
+        assert_eq!(range.locate(&serde_json::json!(30.0)), Ordering::Greater);
+
+        let range = PropertyValueRange::Between(serde_json::json!(1), serde_json::json!(2));
+        assert_eq!(range.locate(&serde_json::json!(0)), Ordering::Less);
+        assert_eq!(range.locate(&serde_json::json!(1)), Ordering::Equal);
+        assert_eq!(range.locate(&serde_json::json!(2.0)), Ordering::Equal);
+        assert_eq!(range.locate(&serde_json::json!(3)), Ordering::Greater);
+
+        let range = PropertyValueRange::Prefix("ab".to_string());
+        assert_eq!(range.locate(&serde_json::json!(1)), Ordering::Less);
+        assert_eq!(range.locate(&serde_json::json!("a")), Ordering::Less);
+        assert_eq!(range.locate(&serde_json::json!("abc")), Ordering::Equal);
+        assert_eq!(range.locate(&serde_json::json!("ac")), Ordering::Greater);
+        assert_eq!(range.locate(&serde_json::json!([])), Ordering::Greater);
+    }
 }
@@ -0,0 +1,390 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::errors::{Error, Result};
+use crate::{BulkInsertItem, EdgeKey, Identifier};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A graph schema, which restricts the vertices, edges and properties that
+/// can be written to a datastore.
+///
+/// Only vertices and edges of the types in the schema can be created.
+/// Properties that the schema declares must have values of the declared
+/// type, and required properties must be set when their vertex or edge is
+/// created. This can be done with a bulk insert, or by creating it and
+/// setting its required properties in a transaction, in which case they're
+/// checked when the transaction is committed. Properties that the schema
+/// doesn't declare are allowed.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schema {
+    /// The allowed vertex types.
+    pub vertex_types: HashMap<Identifier, VertexSchema>,
+    /// The allowed edge types.
+    pub edge_types: HashMap<Identifier, EdgeSchema>,
+}
+
+/// The schema of a vertex type.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VertexSchema {
+    /// The declared properties, keyed by name.
+    pub properties: HashMap<Identifier, PropertySchema>,
+}
+
+/// The schema of an edge type.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeSchema {
+    /// The allowed types of outbound vertices. If empty, vertices of any
+    /// type are allowed.
+    pub outbound_types: HashSet<Identifier>,
+    /// The allowed types of inbound vertices. If empty, vertices of any type
+    /// are allowed.
+    pub inbound_types: HashSet<Identifier>,
+    /// The declared properties, keyed by name.
+    pub properties: HashMap<Identifier, PropertySchema>,
+}
+
+/// The schema of a property.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PropertySchema {
+    /// The type of the property's values.
+    #[serde(default)]
+    pub t: JsonType,
+    /// Whether the property is required. Required properties can't be
+    /// deleted.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl PropertySchema {
+    /// Creates a new property schema.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the property's values.
+    /// * `required`: Whether the property is required.
+    pub fn new(t: JsonType, required: bool) -> Self {
+        Self { t, required }
+    }
+}
+
+/// A type of JSON value, as used by JSON schema.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonType {
+    /// Any value.
+    #[default]
+    Any,
+    Null,
+    Boolean,
+    /// A number without a fractional part.
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    /// Checks whether a value is of this type.
+    ///
+    /// # Arguments
+    /// * `value`: The value to check.
+    pub fn matches(&self, value: &serde_json::Value) -> bool {
+        match (self, value) {
+            (JsonType::Any, _) => true,
+            (JsonType::Null, serde_json::Value::Null) => true,
+            (JsonType::Boolean, serde_json::Value::Bool(_)) => true,
+            (JsonType::Integer, serde_json::Value::Number(n)) => {
+                n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|n| n.fract() == 0.0)
+            }
+            (JsonType::Number, serde_json::Value::Number(_)) => true,
+            (JsonType::String, serde_json::Value::String(_)) => true,
+            (JsonType::Array, serde_json::Value::Array(_)) => true,
+            (JsonType::Object, serde_json::Value::Object(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            JsonType::Any => "any",
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Integer => "integer",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A summary of the types of vertices and edges in a datastore, and of the
+/// properties that they have. Unlike a `Schema`, this describes the data
+/// that's actually there.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaSummary {
+    /// The number of vertices of each type.
+    pub vertex_types: HashMap<Identifier, u64>,
+    /// The number of edges of each type.
+    pub edge_types: HashMap<Identifier, u64>,
+    /// The names of the properties that vertices of each type have, keyed
+    /// by vertex type.
+    pub vertex_properties: HashMap<Identifier, HashSet<Identifier>>,
+    /// The names of the properties that edges of each type have, keyed by
+    /// edge type.
+    pub edge_properties: HashMap<Identifier, HashSet<Identifier>>,
+}
+
+fn violation(message: String) -> Error {
+    Error::SchemaViolation(message)
+}
+
+// Checks the value of a declared property, or whether it can be deleted if
+// `value` is `None`.
+fn check_property(
+    properties: &HashMap<Identifier, PropertySchema>,
+    owner: &str,
+    name: &Identifier,
+    value: Option<&serde_json::Value>,
+) -> Result<()> {
+    match (properties.get(name), value) {
+        (Some(property), Some(value)) if !property.t.matches(value) => Err(violation(format!(
+            "property `{}` of {} must be of type {}",
+            name.as_str(),
+            owner,
+            property.t
+        ))),
+        (Some(property), None) if property.required => Err(violation(format!(
+            "property `{}` of {} is required",
+            name.as_str(),
+            owner
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Checks that all of the required properties are among `properties`.
+fn check_required_properties(
+    properties: &HashMap<Identifier, PropertySchema>,
+    owner: &str,
+    set: &HashSet<&Identifier>,
+) -> Result<()> {
+    let mut missing: Vec<&Identifier> = properties
+        .iter()
+        .filter(|(name, property)| property.required && !set.contains(name))
+        .map(|(name, _)| name)
+        .collect();
+    missing.sort();
+    match missing.first() {
+        Some(name) => Err(violation(format!(
+            "property `{}` of {} is required",
+            name.as_str(),
+            owner
+        ))),
+        None => Ok(()),
+    }
+}
+
+impl Schema {
+    fn vertex_schema(&self, t: &Identifier) -> Result<&VertexSchema> {
+        self.vertex_types
+            .get(t)
+            .ok_or_else(|| violation(format!("vertex type `{}` isn't in the schema", t.as_str())))
+    }
+
+    fn edge_schema(&self, t: &Identifier) -> Result<&EdgeSchema> {
+        self.edge_types
+            .get(t)
+            .ok_or_else(|| violation(format!("edge type `{}` isn't in the schema", t.as_str())))
+    }
+
+    /// Checks that a vertex can be created with the given properties.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex.
+    /// * `properties`: The names of the properties it's created with.
+    ///
+    /// # Errors
+    /// Returns `Error::SchemaViolation` if the vertex type isn't in the
+    /// schema, or a required property is missing.
+    pub fn check_vertex(&self, t: &Identifier, properties: &HashSet<&Identifier>) -> Result<()> {
+        let schema = self.vertex_schema(t)?;
+        check_required_properties(&schema.properties, &format!("vertex type `{}`", t.as_str()), properties)
+    }
+
+    // Checks that a vertex type is in the schema, without checking its
+    // required properties. This is used for vertices created in a
+    // transaction, whose required properties are checked when it's committed.
+    pub(crate) fn check_vertex_type(&self, t: &Identifier) -> Result<()> {
+        self.vertex_schema(t).map(|_| ())
+    }
+
+    /// Checks that an edge can be created with the given properties.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the edge.
+    /// * `outbound_t`: The type of the outbound vertex, if it's known.
+    /// * `inbound_t`: The type of the inbound vertex, if it's known.
+    /// * `properties`: The names of the properties it's created with.
+    ///
+    /// # Errors
+    /// Returns `Error::SchemaViolation` if the edge type isn't in the schema,
+    /// it doesn't allow the type of one of the vertices, or a required
+    /// property is missing.
+    pub fn check_edge(
+        &self,
+        t: &Identifier,
+        outbound_t: Option<&Identifier>,
+        inbound_t: Option<&Identifier>,
+        properties: &HashSet<&Identifier>,
+    ) -> Result<()> {
+        self.check_edge_type(t, outbound_t, inbound_t)?;
+        let schema = self.edge_schema(t)?;
+        check_required_properties(&schema.properties, &format!("edge type `{}`", t.as_str()), properties)
+    }
+
+    // Checks that an edge type is in the schema, and that it allows the
+    // types of its vertices, without checking its required properties.
+    pub(crate) fn check_edge_type(
+        &self,
+        t: &Identifier,
+        outbound_t: Option<&Identifier>,
+        inbound_t: Option<&Identifier>,
+    ) -> Result<()> {
+        let schema = self.edge_schema(t)?;
+        let endpoints = [
+            ("outbound", outbound_t, &schema.outbound_types),
+            ("inbound", inbound_t, &schema.inbound_types),
+        ];
+        for (direction, vertex_t, allowed) in endpoints {
+            if let Some(vertex_t) = vertex_t {
+                if !allowed.is_empty() && !allowed.contains(vertex_t) {
+                    return Err(violation(format!(
+                        "edge type `{}` doesn't allow {} vertices of type `{}`",
+                        t.as_str(),
+                        direction,
+                        vertex_t.as_str()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that a vertex property can be set to a value, or deleted if
+    /// `value` is `None`.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertex.
+    /// * `name`: The name of the property.
+    /// * `value`: The value of the property.
+    ///
+    /// # Errors
+    /// Returns `Error::SchemaViolation` if the vertex type isn't in the
+    /// schema, the value isn't of the declared type, or a required property
+    /// would be deleted.
+    pub fn check_vertex_property(
+        &self,
+        t: &Identifier,
+        name: &Identifier,
+        value: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        let schema = self.vertex_schema(t)?;
+        check_property(
+            &schema.properties,
+            &format!("vertex type `{}`", t.as_str()),
+            name,
+            value,
+        )
+    }
+
+    /// Checks that an edge property can be set to a value, or deleted if
+    /// `value` is `None`.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the edge.
+    /// * `name`: The name of the property.
+    /// * `value`: The value of the property.
+    ///
+    /// # Errors
+    /// Returns `Error::SchemaViolation` if the edge type isn't in the
+    /// schema, the value isn't of the declared type, or a required property
+    /// would be deleted.
+    pub fn check_edge_property(
+        &self,
+        t: &Identifier,
+        name: &Identifier,
+        value: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        let schema = self.edge_schema(t)?;
+        check_property(&schema.properties, &format!("edge type `{}`", t.as_str()), name, value)
+    }
+
+    // Checks the items of a bulk insert. The vertices and edges it creates
+    // must have all of their required properties among its items. The types
+    // of vertices that aren't created by the insert are looked up with
+    // `vertex_type`; properties of vertices that don't exist are ignored.
+    pub(crate) fn check_bulk_insert<F>(&self, items: &[BulkInsertItem], mut vertex_type: F) -> Result<()>
+    where
+        F: FnMut(Uuid) -> Result<Option<Identifier>>,
+    {
+        let mut vertex_types = HashMap::new();
+        for item in items {
+            if let BulkInsertItem::Vertex(vertex) = item {
+                vertex_types.insert(vertex.id, vertex.t.clone());
+            }
+        }
+        let mut get_vertex_type = |id: Uuid| -> Result<Option<Identifier>> {
+            match vertex_types.get(&id) {
+                Some(t) => Ok(Some(t.clone())),
+                None => vertex_type(id),
+            }
+        };
+
+        let mut vertex_properties: HashMap<Uuid, HashSet<&Identifier>> = HashMap::new();
+        let mut edge_properties: HashMap<&EdgeKey, HashSet<&Identifier>> = HashMap::new();
+        for item in items {
+            match item {
+                BulkInsertItem::VertexProperty(id, name, value) => {
+                    if let Some(t) = get_vertex_type(*id)? {
+                        self.check_vertex_property(&t, name, Some(value))?;
+                    }
+                    vertex_properties.entry(*id).or_default().insert(name);
+                }
+                BulkInsertItem::EdgeProperty(key, name, value) => {
+                    self.check_edge_property(&key.t, name, Some(value))?;
+                    edge_properties.entry(key).or_default().insert(name);
+                }
+                _ => {}
+            }
+        }
+
+        let no_properties = HashSet::new();
+        for item in items {
+            match item {
+                BulkInsertItem::Vertex(vertex) => {
+                    let properties = vertex_properties.get(&vertex.id).unwrap_or(&no_properties);
+                    self.check_vertex(&vertex.t, properties)?;
+                }
+                BulkInsertItem::Edge(key) => {
+                    let outbound_t = get_vertex_type(key.outbound_id)?;
+                    let inbound_t = get_vertex_type(key.inbound_id)?;
+                    let properties = edge_properties.get(key).unwrap_or(&no_properties);
+                    self.check_edge(&key.t, outbound_t.as_ref(), inbound_t.as_ref(), properties)?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
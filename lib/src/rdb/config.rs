@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use super::managers::{merge_counts, KeyPrefix, KeyPrefixes, TYPE_COUNTS_CF};
+use crate::errors::{Error, Result};
+
+use rocksdb::{BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType, Options};
+use serde::{Deserialize, Serialize};
+
+/// A compression algorithm for rocksdb data files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RocksdbCompression {
+    None,
+    Snappy,
+    Zlib,
+    Bz2,
+    Lz4,
+    Lz4hc,
+    Zstd,
+}
+
+impl From<RocksdbCompression> for DBCompressionType {
+    fn from(compression: RocksdbCompression) -> Self {
+        match compression {
+            RocksdbCompression::None => DBCompressionType::None,
+            RocksdbCompression::Snappy => DBCompressionType::Snappy,
+            RocksdbCompression::Zlib => DBCompressionType::Zlib,
+            RocksdbCompression::Bz2 => DBCompressionType::Bz2,
+            RocksdbCompression::Lz4 => DBCompressionType::Lz4,
+            RocksdbCompression::Lz4hc => DBCompressionType::Lz4hc,
+            RocksdbCompression::Zstd => DBCompressionType::Zstd,
+        }
+    }
+}
+
+/// Tuning options for the rocksdb datastore.
+///
+/// The defaults are based off of the total ordered, flash storage example on
+/// https://github.com/facebook/rocksdb/wiki/RocksDB-Tuning-Guide. The config
+/// can be deserialized, e.g. from a JSON file, in which case any missing
+/// fields take their default values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RocksdbConfig {
+    /// The maximum number of open files to have. If `None`, the rocksdb
+    /// default will be used.
+    pub max_open_files: Option<i32>,
+    /// The size of each memtable, in bytes.
+    pub write_buffer_size: usize,
+    /// The maximum number of memtables to keep in memory, per column family.
+    pub max_write_buffer_number: i32,
+    /// The target size of level 1 files, in bytes.
+    pub target_file_size_base: u64,
+    /// The number of level 0 files that triggers a compaction.
+    pub level_zero_file_num_compaction_trigger: i32,
+    /// The number of level 0 files at which writes are slowed down.
+    pub level_zero_slowdown_writes_trigger: i32,
+    /// The number of level 0 files at which writes are stopped.
+    pub level_zero_stop_writes_trigger: i32,
+    /// The number of levels.
+    pub num_levels: i32,
+    /// The maximum total size of level 1, in bytes.
+    pub max_bytes_for_level_base: u64,
+    /// How much larger each level is than the one before it.
+    pub max_bytes_for_level_multiplier: f64,
+    /// The size of the block cache, in bytes, which is shared by all column
+    /// families. If `None`, the rocksdb default will be used.
+    pub block_cache_size: Option<usize>,
+    /// The number of bits per key to use for bloom filters. Column families
+    /// with a prefix extractor use prefix bloom filters, which let lookups by
+    /// key prefix skip data files that can't contain it. If `None`, bloom
+    /// filters are disabled.
+    pub bloom_filter_bits_per_key: Option<i32>,
+    /// The compression algorithm for each level, starting from level 0. If
+    /// empty, the rocksdb default will be used.
+    pub compression_per_level: Vec<RocksdbCompression>,
+    /// The length of the fixed-size key prefix to extract in each column
+    /// family, keyed by column family name. This overrides the column
+    /// family's default prefix extractor, which splits keys by their leading
+    /// UUID and/or identifier.
+    pub prefix_extractors: HashMap<String, usize>,
+}
+
+impl Default for RocksdbConfig {
+    fn default() -> Self {
+        Self {
+            max_open_files: None,
+            write_buffer_size: 67_108_864, // 64mb
+            max_write_buffer_number: 3,
+            target_file_size_base: 67_108_864, // 64mb
+            level_zero_file_num_compaction_trigger: 8,
+            level_zero_slowdown_writes_trigger: 17,
+            level_zero_stop_writes_trigger: 24,
+            num_levels: 4,
+            max_bytes_for_level_base: 536_870_912, // 512mb
+            max_bytes_for_level_multiplier: 8.0,
+            block_cache_size: None,
+            bloom_filter_bits_per_key: Some(10),
+            compression_per_level: Vec::new(),
+            prefix_extractors: HashMap::new(),
+        }
+    }
+}
+
+impl RocksdbConfig {
+    /// Gets the options for opening the database, along with descriptors
+    /// and key prefixes for the given column families.
+    pub(crate) fn options(
+        &self,
+        cf_names: &[&'static str],
+    ) -> Result<(Options, Vec<ColumnFamilyDescriptor>, KeyPrefixes)> {
+        if let Some(name) = self
+            .prefix_extractors
+            .keys()
+            .find(|name| !cf_names.contains(&name.as_str()))
+        {
+            return Err(Error::Datastore(format!("unknown column family: {}", name).into()));
+        }
+
+        let cache = match self.block_cache_size {
+            Some(block_cache_size) => Some(Cache::new_lru_cache(block_cache_size)?),
+            None => None,
+        };
+
+        let key_prefixes: KeyPrefixes = cf_names
+            .iter()
+            .filter_map(|name| {
+                let key_prefix = match self.prefix_extractors.get(*name) {
+                    Some(len) => Some(KeyPrefix::Fixed(*len)),
+                    None => KeyPrefix::for_cf(name),
+                };
+                key_prefix.map(|key_prefix| (*name, key_prefix))
+            })
+            .collect();
+
+        let opts = self.cf_options(cache.as_ref(), None);
+        let descriptors = cf_names
+            .iter()
+            .map(|name| {
+                let key_prefix = key_prefixes.get(name).copied();
+                let mut cf_opts = self.cf_options(cache.as_ref(), key_prefix);
+                if *name == TYPE_COUNTS_CF {
+                    cf_opts.set_merge_operator_associative("merge_counts", merge_counts);
+                }
+                ColumnFamilyDescriptor::new(*name, cf_opts)
+            })
+            .collect();
+        Ok((opts, descriptors, key_prefixes))
+    }
+
+    fn cf_options(&self, cache: Option<&Cache>, key_prefix: Option<KeyPrefix>) -> Options {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        opts.set_compaction_style(DBCompactionStyle::Level);
+        opts.set_write_buffer_size(self.write_buffer_size);
+        opts.set_max_write_buffer_number(self.max_write_buffer_number);
+        opts.set_target_file_size_base(self.target_file_size_base);
+        opts.set_level_zero_file_num_compaction_trigger(self.level_zero_file_num_compaction_trigger);
+        opts.set_level_zero_slowdown_writes_trigger(self.level_zero_slowdown_writes_trigger);
+        opts.set_level_zero_stop_writes_trigger(self.level_zero_stop_writes_trigger);
+        opts.set_num_levels(self.num_levels);
+        opts.set_max_bytes_for_level_base(self.max_bytes_for_level_base);
+        opts.set_max_bytes_for_level_multiplier(self.max_bytes_for_level_multiplier);
+
+        if let Some(max_open_files) = self.max_open_files {
+            opts.set_max_open_files(max_open_files);
+        }
+
+        if !self.compression_per_level.is_empty() {
+            let compression_per_level: Vec<DBCompressionType> =
+                self.compression_per_level.iter().map(|c| (*c).into()).collect();
+            opts.set_compression_per_level(&compression_per_level);
+        }
+
+        if let Some(key_prefix) = key_prefix {
+            opts.set_prefix_extractor(key_prefix.slice_transform());
+            if self.bloom_filter_bits_per_key.is_some() {
+                opts.set_memtable_prefix_bloom_ratio(0.1);
+            }
+        }
+
+        let mut block_opts = BlockBasedOptions::default();
+        if let Some(cache) = cache {
+            block_opts.set_block_cache(cache);
+        }
+        if let Some(bits_per_key) = self.bloom_filter_bits_per_key {
+            block_opts.set_bloom_filter(bits_per_key, false);
+        }
+        opts.set_block_based_table_factory(&block_opts);
+
+        opts
+    }
+}
@@ -1,27 +1,36 @@
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
-use std::i32;
+use std::ops::Bound;
 use std::path::Path;
-use std::sync::{Arc, RwLock};
-use std::u64;
-use std::usize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::thread::{self, JoinHandle};
 
+use super::config::RocksdbConfig;
 use super::managers::*;
+use super::verify::{verify, verify_type_counts, Inconsistency, Repairs};
+use crate::cursor::{self, CursorSource, EdgeResume, PropertyFilter};
 use crate::errors::{Error, Result};
-use crate::util::next_uuid;
+use crate::util::{self, next_uuid};
 use crate::{
-    BulkInsertItem, Datastore, Edge, EdgeDirection, EdgeKey, EdgeProperties, EdgeProperty, EdgePropertyQuery,
-    EdgeQuery, Identifier, Json, NamedProperty, PropertyPresenceEdgeQuery, PropertyPresenceVertexQuery,
-    PropertyValueEdgeQuery, PropertyValueVertexQuery, Vertex, VertexProperties, VertexProperty, VertexPropertyQuery,
-    VertexQuery,
+    fingerprint, BulkInsertItem, ContinuationToken, Datastore, DynIter, Edge, EdgeDirection, EdgeKey, EdgeProperties,
+    EdgeProperty, EdgePropertyQuery, EdgeQuery, Identifier, IndexStatus, Json, NamedProperty, Page, PathQuery,
+    PipePropertyValueEdgeQuery, PipePropertyValueVertexQuery, PropertyPresenceEdgeQuery, PropertyPresenceVertexQuery,
+    PropertyValueEdgeQuery, PropertyValueRange, PropertyValueRangeEdgeQuery, PropertyValueRangeVertexQuery,
+    PropertyValueVertexQuery, ReadOnlyDatastore, Schema, SchemaSummary, SpecificVertexQuery, Vertex, VertexProperties,
+    VertexProperty, VertexPropertyQuery, VertexQuery,
 };
 
 use chrono::offset::Utc;
 use chrono::DateTime;
-use rocksdb::{DBCompactionStyle, Options, WriteBatch, DB};
+use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{Snapshot, WriteBatch, DB};
 use uuid::Uuid;
 
-const CF_NAMES: [&str; 9] = [
+const CF_NAMES: [&str; 15] = [
     "vertices:v1",
+    "vertex_types:v1",
     "edges:v1",
     "edge_ranges:v1",
     "reversed_edge_ranges:v1",
@@ -29,49 +38,293 @@ const CF_NAMES: [&str; 9] = [
     "edge_properties:v1",
     "vertex_property_values:v1",
     "edge_property_values:v1",
+    "vertex_type_property_values:v1",
+    "edge_type_property_values:v1",
+    "vertex_composite_property_values:v1",
+    "edge_composite_property_values:v1",
     "metadata:v1",
+    TYPE_COUNTS_CF,
 ];
 
-fn get_options(max_open_files: Option<i32>) -> Options {
-    // Current tuning based off of the total ordered example, flash
-    // storage example on
-    // https://github.com/facebook/rocksdb/wiki/RocksDB-Tuning-Guide
-    let mut opts = Options::default();
-    opts.create_if_missing(true);
-    opts.set_compaction_style(DBCompactionStyle::Level);
-    opts.set_write_buffer_size(67_108_864); // 64mb
-    opts.set_max_write_buffer_number(3);
-    opts.set_target_file_size_base(67_108_864); // 64mb
-    opts.set_level_zero_file_num_compaction_trigger(8);
-    opts.set_level_zero_slowdown_writes_trigger(17);
-    opts.set_level_zero_stop_writes_trigger(24);
-    opts.set_num_levels(4);
-    opts.set_max_bytes_for_level_base(536_870_912); // 512mb
-    opts.set_max_bytes_for_level_multiplier(8.0);
+// The version of the encoding used for keys in the property value column
+// families. If a datastore was created with an older version, its property
+// value indexes are rebuilt when it's opened.
+const PROPERTY_VALUE_ENCODING_VERSION: u32 = 1;
 
-    if let Some(max_open_files) = max_open_files {
-        opts.set_max_open_files(max_open_files);
-    }
+// The number of vertices and edges indexed under each write lock when
+// building an index.
+const INDEX_BUILD_BATCH_SIZE: usize = 1_000;
+
+// The number of index builds that have been started, which is used to assign
+// build IDs.
+static INDEX_BUILD_COUNT: AtomicU64 = AtomicU64::new(0);
 
-    opts
+fn next_index_build_id() -> u64 {
+    INDEX_BUILD_COUNT.fetch_add(1, AtomicOrdering::Relaxed)
 }
 
 fn guard_indexed_property(db_ref: DBRef<'_>, property: &Identifier) -> Result<()> {
-    if !db_ref.indexed_properties.contains(property) {
-        Err(Error::NotIndexed)
-    } else {
-        Ok(())
+    match db_ref.indexes.properties.get(property) {
+        Some(state) if state.status == IndexStatus::Ready => Ok(()),
+        Some(_) => Err(Error::IndexNotReady),
+        None => Err(Error::NotIndexed),
+    }
+}
+
+// Narrows an iterator over property values, which are sorted, down to the
+// values in a given range.
+// Errors are passed through, so that they're surfaced.
+fn take_property_value_range<'a, T: 'a>(
+    iter: impl Iterator<Item = Result<(Identifier, Json, T)>> + 'a,
+    range: PropertyValueRange,
+) -> impl Iterator<Item = Result<(Identifier, Json, T)>> + 'a {
+    iter.map(move |item| {
+        let location = match item {
+            Ok((_, ref value, _)) => range.locate(&value.0),
+            Err(_) => Ordering::Equal,
+        };
+        (location, item)
+    })
+    .skip_while(|(location, _)| location == &Ordering::Less)
+    .take_while(|(location, _)| location == &Ordering::Equal)
+    .map(|(_, item)| item)
+}
+
+// Adds a property's values to the property value indexes.
+fn build_property_value_index(db_ref: DBRef<'_>, batch: &mut Batch, name: &Identifier) -> Result<()> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let edge_range_manager = EdgeRangeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+    let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+
+    for item in vertex_manager.iterate_for_range(Uuid::default()) {
+        let (vertex_id, _) = item?;
+        if let Some(property_value) = vertex_property_manager.get(vertex_id, name)? {
+            vertex_property_value_manager.set(batch, vertex_id, name, &property_value);
+        }
+    }
+
+    for item in edge_range_manager.iterate_for_all() {
+        let (out_id, t, _, in_id) = item?;
+        if let Some(property_value) = edge_property_manager.get(out_id, &t, in_id, name)? {
+            edge_property_value_manager.set(batch, out_id, &t, in_id, name, &property_value);
+        }
+    }
+
+    Ok(())
+}
+
+// Adds the values of a property of vertices and edges of a given type to the
+// property value indexes scoped to that type.
+fn build_type_property_value_index(
+    db_ref: DBRef<'_>,
+    batch: &mut Batch,
+    t: &Identifier,
+    name: &Identifier,
+) -> Result<()> {
+    let vertex_type_manager = VertexTypeManager::new(db_ref);
+    let edge_range_manager = EdgeRangeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    let vertex_type_property_value_manager = VertexTypePropertyValueManager::new(db_ref);
+    let edge_type_property_value_manager = EdgeTypePropertyValueManager::new(db_ref);
+
+    for vertex_id in vertex_type_manager.iterate_for_range(t, Uuid::default()) {
+        if let Some(property_value) = vertex_property_manager.get(vertex_id, name)? {
+            vertex_type_property_value_manager.set(batch, t, vertex_id, name, &property_value);
+        }
+    }
+
+    for item in edge_range_manager.iterate_for_all() {
+        let (out_id, edge_t, _, in_id) = item?;
+        if &edge_t != t {
+            continue;
+        }
+        if let Some(property_value) = edge_property_manager.get(out_id, t, in_id, name)? {
+            edge_type_property_value_manager.set(batch, out_id, t, in_id, name, &property_value);
+        }
+    }
+
+    Ok(())
+}
+
+// Adds the values of a combination of properties of vertices and edges to a
+// composite index. Only vertices and edges that have all of the properties
+// are indexed.
+fn build_composite_property_value_index(db_ref: DBRef<'_>, batch: &mut Batch, names: &[Identifier]) -> Result<()> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let edge_manager = EdgeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    let vertex_composite_property_value_manager = VertexCompositePropertyValueManager::new(db_ref);
+    let edge_composite_property_value_manager = EdgeCompositePropertyValueManager::new(db_ref);
+
+    for item in vertex_manager.iterate_for_range(Uuid::default()) {
+        let (id, _) = item?;
+        let values: Result<Option<Vec<Json>>> = names
+            .iter()
+            .map(|name| vertex_property_manager.get(id, name))
+            .collect::<Result<Vec<Option<Json>>>>()
+            .map(|values| values.into_iter().collect());
+        if let Some(values) = values? {
+            vertex_composite_property_value_manager.set(batch, names, &values, id);
+        }
+    }
+
+    for (out_id, t, _, in_id) in edge_manager.iterate_for_all() {
+        let values: Result<Option<Vec<Json>>> = names
+            .iter()
+            .map(|name| edge_property_manager.get(out_id, &t, in_id, name))
+            .collect::<Result<Vec<Option<Json>>>>()
+            .map(|values| values.into_iter().collect());
+        if let Some(values) = values? {
+            edge_composite_property_value_manager.set(batch, names, &values, out_id, &t, in_id);
+        }
+    }
+
+    Ok(())
+}
+
+// The last vertex or edge that a build of an index got up to.
+enum IndexBuildCursor {
+    Vertex(Uuid),
+    Edge(EdgeKey),
+}
+
+// Adds the next batch of vertices and edges after `cursor` to a property's
+// index, and moves the cursor to the last of them. Vertices are indexed
+// before edges. Returns the number of vertices and edges in the batch, which
+// is zero once all of them have been indexed.
+fn index_batch(
+    db_ref: DBRef<'_>,
+    batch: &mut Batch,
+    name: &Identifier,
+    cursor: &mut Option<IndexBuildCursor>,
+) -> Result<usize> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let edge_manager = EdgeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+    let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+    let mut count = 0;
+
+    if !matches!(cursor, Some(IndexBuildCursor::Edge(_))) {
+        let start = match cursor {
+            Some(IndexBuildCursor::Vertex(id)) => next_uuid(*id).ok(),
+            _ => Some(Uuid::default()),
+        };
+        if let Some(start) = start {
+            for item in vertex_manager.iterate_for_range(start).take(INDEX_BUILD_BATCH_SIZE) {
+                let (id, _) = item?;
+                if let Some(property_value) = vertex_property_manager.get(id, name)? {
+                    vertex_property_value_manager.set(batch, id, name, &property_value);
+                }
+                *cursor = Some(IndexBuildCursor::Vertex(id));
+                count += 1;
+            }
+        }
+    }
+
+    if count < INDEX_BUILD_BATCH_SIZE {
+        // The edge the cursor is on may have been deleted since, so it's
+        // skipped by comparison rather than position.
+        let start = match cursor {
+            Some(IndexBuildCursor::Edge(key)) => Some(key.clone()),
+            _ => None,
+        };
+        let keys = edge_manager
+            .iterate_from(start.as_ref())
+            .map(|(out_id, t, _, in_id)| EdgeKey::new(out_id, t, in_id))
+            .filter(|key| Some(key) != start.as_ref())
+            .take(INDEX_BUILD_BATCH_SIZE - count);
+        for key in keys {
+            if let Some(property_value) = edge_property_manager.get(key.outbound_id, &key.t, key.inbound_id, name)? {
+                edge_property_value_manager.set(batch, key.outbound_id, &key.t, key.inbound_id, name, &property_value);
+            }
+            *cursor = Some(IndexBuildCursor::Edge(key));
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+// Estimates the number of vertices and edges an index build will go through.
+fn estimate_index_build_total(db_ref: DBRef<'_>) -> Result<u64> {
+    Ok(VertexManager::new(db_ref).estimate_count()? + EdgeManager::new(db_ref).estimate_count()?)
+}
+
+// Rebuilds the property value indexes if they were written with an older
+// encoding.
+fn migrate_property_value_indexes(db: &DB, key_prefixes: &KeyPrefixes, indexes: &Indexes) -> Result<()> {
+    let metadata_manager = MetadataManager::new(db);
+    if metadata_manager.get_property_value_encoding_version()? >= PROPERTY_VALUE_ENCODING_VERSION {
+        return Ok(());
+    }
+
+    let db_ref = DBRef::new(db, key_prefixes, indexes, None, None);
+    let mut batch = db_ref.batch();
+    VertexPropertyValueManager::new(db_ref).delete_all(&mut batch);
+    EdgePropertyValueManager::new(db_ref).delete_all(&mut batch);
+    batch.write(db, None)?;
+
+    let mut batch = db_ref.batch();
+    for name in indexes.properties.keys() {
+        build_property_value_index(db_ref, &mut batch, name)?;
+    }
+    metadata_manager.set_property_value_encoding_version(&mut batch, PROPERTY_VALUE_ENCODING_VERSION)?;
+    batch.write(db, None)
+}
+
+// Builds the vertex type index for datastores created before it existed.
+fn migrate_vertex_type_index(db: &DB, key_prefixes: &KeyPrefixes, indexes: &Indexes) -> Result<()> {
+    let metadata_manager = MetadataManager::new(db);
+    if metadata_manager.get_vertex_type_index_built()? {
+        return Ok(());
+    }
+
+    let db_ref = DBRef::new(db, key_prefixes, indexes, None, None);
+    let vertex_manager = VertexManager::new(db_ref);
+    let vertex_type_manager = VertexTypeManager::new(db_ref);
+    let mut batch = db_ref.batch();
+    for item in vertex_manager.iterate_for_range(Uuid::default()) {
+        let (id, t) = item?;
+        vertex_type_manager.set(&mut batch, &t, id);
+    }
+    metadata_manager.set_vertex_type_index_built(&mut batch, true)?;
+    batch.write(db, None)
+}
+
+// Counts the vertices, edges and properties of each type for datastores
+// created before the counts were maintained.
+fn migrate_type_counts(db: &DB, key_prefixes: &KeyPrefixes, indexes: &Indexes) -> Result<()> {
+    let metadata_manager = MetadataManager::new(db);
+    if metadata_manager.get_type_counts_built()? {
+        return Ok(());
+    }
+
+    let db_ref = DBRef::new(db, key_prefixes, indexes, None, None);
+    let type_count_manager = TypeCountManager::new(db_ref);
+    let mut batch = db_ref.batch();
+    for (count, value) in type_count_manager.recount()? {
+        type_count_manager.set(&mut batch, &count, value);
     }
+    metadata_manager.set_type_counts_built(&mut batch, true)?;
+    batch.write(db, None)
 }
 
 fn vertices_from_property_value_iterator<'a>(
     db_ref: DBRef<'a>,
-    iter: impl Iterator<Item = VertexPropertyValueKey> + 'a,
+    iter: impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a,
 ) -> Result<Vec<VertexItem>> {
     let vertex_manager = VertexManager::new(db_ref);
 
     let mut vertices = Vec::new();
-    for (_, _, id) in iter {
+    for item in iter {
+        let (_, _, id) = item?;
         if let Some(t) = vertex_manager.get(id)? {
             vertices.push((id, t));
         }
@@ -113,12 +366,13 @@ fn vertices_from_piped_property_query(
 
 fn edges_from_property_value_iterator<'a>(
     db_ref: DBRef<'a>,
-    iter: impl Iterator<Item = EdgePropertyValueKey> + 'a,
+    iter: impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a,
 ) -> Result<Vec<EdgeRangeItem>> {
     let edge_manager = EdgeManager::new(db_ref);
 
     let mut edges = Vec::new();
-    for (_, _, (out_id, t, in_id)) in iter {
+    for item in iter {
+        let (_, _, (out_id, t, in_id)) = item?;
         if let Some(dt) = edge_manager.get(out_id, &t, in_id)? {
             edges.push((out_id, t, dt, in_id));
         }
@@ -166,37 +420,433 @@ fn edges_from_piped_property_query(
         .collect())
 }
 
-fn execute_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<Vec<VertexItem>> {
-    match q {
-        VertexQuery::Range(q) => {
+fn vertices_from_combined_query(
+    db_ref: DBRef<'_>,
+    inner_query: VertexQuery,
+    other_query: VertexQuery,
+    intersection: bool,
+) -> Result<Vec<VertexItem>> {
+    let other_vertices: HashSet<Uuid> = execute_vertex_query(db_ref, other_query)?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    let mut vertices = execute_vertex_query(db_ref, inner_query)?;
+    vertices.retain(|(id, _)| other_vertices.contains(id) == intersection);
+    Ok(vertices)
+}
+
+fn edges_from_combined_query(
+    db_ref: DBRef<'_>,
+    inner_query: EdgeQuery,
+    other_query: EdgeQuery,
+    intersection: bool,
+) -> Result<Vec<EdgeRangeItem>> {
+    let other_edges: HashSet<EdgeKey> = execute_edge_query(db_ref, other_query)?
+        .into_iter()
+        .map(|(out_id, t, _, in_id)| EdgeKey::new(out_id, t, in_id))
+        .collect();
+    let mut edges = execute_edge_query(db_ref, inner_query)?;
+    edges.retain(|(out_id, t, _, in_id)| {
+        other_edges.contains(&EdgeKey::new(*out_id, t.clone(), *in_id)) == intersection
+    });
+    Ok(edges)
+}
+
+// Gets the type that a query's vertices are restricted to, if a property is
+// indexed for that type.
+fn type_indexed_vertex_query_type(db_ref: DBRef<'_>, q: &VertexQuery, name: &Identifier) -> Option<Identifier> {
+    util::vertex_query_type(q)
+        .filter(|t| db_ref.indexes.is_type_indexed(t, name))
+        .cloned()
+}
+
+// Gets the type that a query's edges are restricted to, if a property is
+// indexed for that type.
+fn type_indexed_edge_query_type(db_ref: DBRef<'_>, q: &EdgeQuery, name: &Identifier) -> Option<Identifier> {
+    util::edge_query_type(q)
+        .filter(|t| db_ref.indexes.is_type_indexed(t, name))
+        .cloned()
+}
+
+// Gets the vertices of `other_query`, which are all of type `t`, whose
+// property value equals `value` (or doesn't, if `equal` isn't set), using
+// the property's index scoped to `t`. If `other_query` returns every vertex
+// of the type, the index is enough on its own.
+fn vertices_from_type_property_value_query(
+    db_ref: DBRef<'_>,
+    t: &Identifier,
+    name: &Identifier,
+    value: serde_json::Value,
+    other_query: VertexQuery,
+    equal: bool,
+) -> Result<Vec<VertexItem>> {
+    let vertex_type_property_value_manager = VertexTypePropertyValueManager::new(db_ref);
+    let value = Json::new(value);
+    if equal && util::is_full_vertex_type_query(&other_query) {
+        let iter = vertex_type_property_value_manager.iterate_for_value(t, name, &value);
+        return vertices_from_property_value_iterator(db_ref, iter);
+    }
+
+    let property_vertices: HashSet<Uuid> = vertex_type_property_value_manager
+        .iterate_for_value(t, name, &value)
+        .map(|item| item.map(|(_, _, id)| id))
+        .collect::<Result<_>>()?;
+    let mut vertices = execute_vertex_query(db_ref, other_query)?;
+    vertices.retain(|(id, _)| property_vertices.contains(id) == equal);
+    Ok(vertices)
+}
+
+// Gets the edges of `other_query`, which are all of type `t`, whose property
+// value equals `value` (or doesn't, if `equal` isn't set), using the
+// property's index scoped to `t`.
+fn edges_from_type_property_value_query(
+    db_ref: DBRef<'_>,
+    t: &Identifier,
+    name: &Identifier,
+    value: serde_json::Value,
+    other_query: EdgeQuery,
+    equal: bool,
+) -> Result<Vec<EdgeRangeItem>> {
+    let edge_type_property_value_manager = EdgeTypePropertyValueManager::new(db_ref);
+    let property_edges: HashSet<(Uuid, Uuid)> = edge_type_property_value_manager
+        .iterate_for_value(t, name, &Json::new(value))
+        .map(|item| item.map(|(_, _, (out_id, _, in_id))| (out_id, in_id)))
+        .collect::<Result<_>>()?;
+    let mut edges = execute_edge_query(db_ref, other_query)?;
+    edges.retain(|(out_id, _, _, in_id)| property_edges.contains(&(*out_id, *in_id)) == equal);
+    Ok(edges)
+}
+
+// Gets the vertices of a chain of piped property value queries from a
+// composite index, if one covers at least two of the chain's properties.
+// Returns `None` if there isn't such an index.
+fn vertices_from_composite_index(
+    db_ref: DBRef<'_>,
+    q: &PipePropertyValueVertexQuery,
+) -> Result<Option<Vec<VertexItem>>> {
+    if db_ref.indexes.composite_properties.is_empty() {
+        return Ok(None);
+    }
+    let filters = match util::vertex_property_value_filters(q) {
+        Some(filters) => filters,
+        None => return Ok(None),
+    };
+    let lookup = match filters.composite_lookup(&db_ref.indexes.composite_properties) {
+        Some(lookup) => lookup,
+        None => return Ok(None),
+    };
+
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let vertex_composite_property_value_manager = VertexCompositePropertyValueManager::new(db_ref);
+    let mut ids = Vec::new();
+    'candidates: for item in vertex_composite_property_value_manager.iterate_for_values(&lookup.names, &lookup.values) {
+        let id = item?;
+        for (name, value) in &lookup.remaining {
+            match vertex_property_manager.get(id, name)? {
+                Some(ref property_value) if &property_value.0 == value => {}
+                _ => continue 'candidates,
+            }
+        }
+        ids.push(id);
+    }
+
+    let vertices = match filters.inner {
+        Some(inner) => {
+            let ids: HashSet<Uuid> = ids.into_iter().collect();
+            let mut vertices = execute_vertex_query(db_ref, inner.clone())?;
+            vertices.retain(|(id, _)| ids.contains(id));
+            vertices
+        }
+        None => {
             let vertex_manager = VertexManager::new(db_ref);
+            let mut vertices = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(t) = vertex_manager.get(id)? {
+                    vertices.push((id, t));
+                }
+            }
+            vertices
+        }
+    };
+    Ok(Some(vertices))
+}
 
-            let next_uuid = match q.start_id {
-                Some(start_id) => {
-                    match next_uuid(start_id) {
-                        Ok(next_uuid) => next_uuid,
-                        // If we get an error back, it's because
-                        // `start_id` is the maximum possible value. We
-                        // know that no vertices exist whose ID is greater
-                        // than the maximum possible value, so just return
-                        // an empty list.
-                        Err(_) => return Ok(vec![]),
-                    }
+// Gets the edges of a chain of piped property value queries from a composite
+// index, if one covers at least two of the chain's properties. Returns `None`
+// if there isn't such an index.
+fn edges_from_composite_index(db_ref: DBRef<'_>, q: &PipePropertyValueEdgeQuery) -> Result<Option<Vec<EdgeRangeItem>>> {
+    if db_ref.indexes.composite_properties.is_empty() {
+        return Ok(None);
+    }
+    let filters = match util::edge_property_value_filters(q) {
+        Some(filters) => filters,
+        None => return Ok(None),
+    };
+    let lookup = match filters.composite_lookup(&db_ref.indexes.composite_properties) {
+        Some(lookup) => lookup,
+        None => return Ok(None),
+    };
+
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    let edge_composite_property_value_manager = EdgeCompositePropertyValueManager::new(db_ref);
+    let mut keys = Vec::new();
+    'candidates: for item in edge_composite_property_value_manager.iterate_for_values(&lookup.names, &lookup.values) {
+        let (out_id, t, in_id) = item?;
+        for (name, value) in &lookup.remaining {
+            match edge_property_manager.get(out_id, &t, in_id, name)? {
+                Some(ref property_value) if &property_value.0 == value => {}
+                _ => continue 'candidates,
+            }
+        }
+        keys.push(EdgeKey::new(out_id, t, in_id));
+    }
+
+    let edges = match filters.inner {
+        Some(inner) => {
+            let keys: HashSet<EdgeKey> = keys.into_iter().collect();
+            let mut edges = execute_edge_query(db_ref, inner.clone())?;
+            edges.retain(|(out_id, t, _, in_id)| keys.contains(&EdgeKey::new(*out_id, t.clone(), *in_id)));
+            edges
+        }
+        None => {
+            let edge_manager = EdgeManager::new(db_ref);
+            let mut edges = Vec::with_capacity(keys.len());
+            for key in keys {
+                if let Some(update_datetime) = edge_manager.get(key.outbound_id, &key.t, key.inbound_id)? {
+                    edges.push((key.outbound_id, key.t, update_datetime, key.inbound_id));
                 }
-                None => Uuid::default(),
-            };
+            }
+            edges
+        }
+    };
+    Ok(Some(edges))
+}
+
+// Gets whether a vertex or edge of type `t` is covered by a unique
+// constraint with the given scope. The type is `None` for a vertex that
+// doesn't exist, which is only covered by unscoped constraints.
+fn is_in_unique_scope(scope: &Option<Identifier>, t: Option<&Identifier>) -> bool {
+    match scope {
+        Some(scope) => t == Some(scope),
+        None => true,
+    }
+}
 
-            let mut iter: Box<dyn Iterator<Item = Result<VertexItem>>> =
-                Box::new(vertex_manager.iterate_for_range(next_uuid));
+// Checks that setting a vertex property to a value won't violate a unique
+// constraint. `setting` has the vertices that will have the value, along
+// with their types. `changing` has every vertex whose value of the property
+// is replaced by the write, so their current values are disregarded.
+fn check_unique_vertex_property(
+    db_ref: DBRef<'_>,
+    name: &Identifier,
+    value: &Json,
+    setting: &[(Uuid, Option<&Identifier>)],
+    changing: &HashSet<Uuid>,
+) -> Result<()> {
+    let scopes = match db_ref.indexes.unique_properties.get(name) {
+        Some(scopes) => scopes,
+        None => return Ok(()),
+    };
+    let vertex_manager = VertexManager::new(db_ref);
+    let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
 
-            if let Some(ref t) = q.t {
-                iter = Box::new(iter.filter(move |item| match item {
-                    Ok((_, v)) => v == t,
-                    Err(_) => true,
-                }));
+    for scope in scopes {
+        match setting.iter().filter(|(_, t)| is_in_unique_scope(scope, *t)).count() {
+            0 => continue,
+            1 => {}
+            _ => return Err(Error::ConstraintViolation),
+        }
+        for item in vertex_property_value_manager.iterate_for_value(name, value) {
+            let (_, _, id) = item?;
+            if !changing.contains(&id) && is_in_unique_scope(scope, vertex_manager.get(id)?.as_ref()) {
+                return Err(Error::ConstraintViolation);
             }
+        }
+    }
+    Ok(())
+}
 
-            let vertices: Result<Vec<VertexItem>> = iter.take(q.limit as usize).collect();
+// Checks that setting an edge property to a value won't violate a unique
+// constraint, like `check_unique_vertex_property`.
+fn check_unique_edge_property(
+    db_ref: DBRef<'_>,
+    name: &Identifier,
+    value: &Json,
+    setting: &[&EdgeKey],
+    changing: &HashSet<&EdgeKey>,
+) -> Result<()> {
+    let scopes = match db_ref.indexes.unique_properties.get(name) {
+        Some(scopes) => scopes,
+        None => return Ok(()),
+    };
+    let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+
+    for scope in scopes {
+        match setting
+            .iter()
+            .filter(|key| is_in_unique_scope(scope, Some(&key.t)))
+            .count()
+        {
+            0 => continue,
+            1 => {}
+            _ => return Err(Error::ConstraintViolation),
+        }
+        for item in edge_property_value_manager.iterate_for_value(name, value) {
+            let (_, _, (out_id, t, in_id)) = item?;
+            let key = EdgeKey::new(out_id, t, in_id);
+            if !changing.contains(&key) && is_in_unique_scope(scope, Some(&key.t)) {
+                return Err(Error::ConstraintViolation);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Checks that a property's existing values don't violate a unique
+// constraint with the given scope.
+fn check_existing_unique_property(db_ref: DBRef<'_>, name: &Identifier, scope: &Option<Identifier>) -> Result<()> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+    let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+
+    // Values are sorted, so duplicates are next to each other.
+    let mut last_value = None;
+    for item in vertex_property_value_manager.iterate_for_name(name) {
+        let (_, value, id) = item?;
+        if is_in_unique_scope(scope, vertex_manager.get(id)?.as_ref()) {
+            if last_value.as_ref() == Some(&value) {
+                return Err(Error::ConstraintViolation);
+            }
+            last_value = Some(value);
+        }
+    }
+
+    let mut last_value = None;
+    for item in edge_property_value_manager.iterate_for_name(name) {
+        let (_, value, (_, t, _)) = item?;
+        if is_in_unique_scope(scope, Some(&t)) {
+            if last_value.as_ref() == Some(&value) {
+                return Err(Error::ConstraintViolation);
+            }
+            last_value = Some(value);
+        }
+    }
+
+    Ok(())
+}
+
+// Checks that the property values staged by a transaction don't violate a
+// unique constraint, once they're combined with the latest state of the
+// database.
+fn check_staged_unique_properties(db_ref: DBRef<'_>) -> Result<()> {
+    if db_ref.indexes.unique_properties.is_empty() {
+        return Ok(());
+    }
+    let vertex_manager = VertexManager::new(db_ref);
+    let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+    let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+
+    for item in vertex_property_value_manager.iterate_for_staged() {
+        let (name, value, _) = item?;
+        for scope in db_ref.indexes.unique_properties.get(&name).into_iter().flatten() {
+            let mut count = 0;
+            for item in vertex_property_value_manager.iterate_for_value(&name, &value) {
+                let (_, _, id) = item?;
+                if is_in_unique_scope(scope, vertex_manager.get(id)?.as_ref()) {
+                    count += 1;
+                }
+            }
+            if count > 1 {
+                return Err(Error::ConstraintViolation);
+            }
+        }
+    }
+
+    for item in edge_property_value_manager.iterate_for_staged() {
+        let (name, value, _) = item?;
+        for scope in db_ref.indexes.unique_properties.get(&name).into_iter().flatten() {
+            let mut count = 0;
+            for item in edge_property_value_manager.iterate_for_value(&name, &value) {
+                let (_, _, (_, t, _)) = item?;
+                if is_in_unique_scope(scope, Some(&t)) {
+                    count += 1;
+                }
+            }
+            if count > 1 {
+                return Err(Error::ConstraintViolation);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Checks that the vertices and edges staged in a transaction have all of
+// the properties that the schema requires.
+fn check_staged_required_properties(schema: &Schema, db_ref: DBRef<'_>) -> Result<()> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let edge_manager = EdgeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+
+    for (id, t) in vertex_manager.iterate_for_staged() {
+        let mut names = Vec::new();
+        for item in vertex_property_manager.iterate_for_owner(id)? {
+            let ((_, name), _) = item?;
+            names.push(name);
+        }
+        schema.check_vertex(&t, &names.iter().collect())?;
+    }
+
+    for (out_id, t, _, in_id) in edge_manager.iterate_for_staged() {
+        let mut names = Vec::new();
+        for item in edge_property_manager.iterate_for_owner(out_id, &t, in_id)? {
+            let ((_, _, _, name), _) = item?;
+            names.push(name);
+        }
+        let outbound_t = vertex_manager.get(out_id)?;
+        let inbound_t = vertex_manager.get(in_id)?;
+        schema.check_edge(&t, outbound_t.as_ref(), inbound_t.as_ref(), &names.iter().collect())?;
+    }
+
+    Ok(())
+}
+
+// Gets the first vertex ID that a range query starting after `start_id`
+// could return.
+fn range_start_id(start_id: Option<Uuid>) -> Option<Uuid> {
+    match start_id {
+        // If we get an error back, it's because `start_id` is the maximum
+        // possible value. We know that no vertices exist whose ID is greater
+        // than the maximum possible value.
+        Some(start_id) => next_uuid(start_id).ok(),
+        None => Some(Uuid::default()),
+    }
+}
+
+fn execute_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<Vec<VertexItem>> {
+    match q {
+        VertexQuery::Range(q) => {
+            let next_uuid = match range_start_id(q.start_id) {
+                Some(next_uuid) => next_uuid,
+                None => return Ok(vec![]),
+            };
+
+            if let Some(t) = q.t {
+                let vertex_type_manager = VertexTypeManager::new(db_ref);
+                let vertices = vertex_type_manager
+                    .iterate_for_range(&t, next_uuid)
+                    .take(q.limit as usize)
+                    .map(|id| (id, t.clone()))
+                    .collect();
+                return Ok(vertices);
+            }
+
+            let vertex_manager = VertexManager::new(db_ref);
+            let vertices: Result<Vec<VertexItem>> = vertex_manager
+                .iterate_for_range(next_uuid)
+                .take(q.limit as usize)
+                .collect();
             vertices
         }
         VertexQuery::Specific(q) => {
@@ -216,10 +866,29 @@ fn execute_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<Vec<VertexI
             let vertices: Result<Vec<VertexItem>> = iter.collect();
             vertices
         }
+        VertexQuery::Traversal(q) => {
+            let start_ids = execute_vertex_query(db_ref, *q.inner)?.into_iter().map(|(id, _)| id);
+
+            let edge_range_manager = match q.direction {
+                EdgeDirection::Outbound => EdgeRangeManager::new(db_ref),
+                EdgeDirection::Inbound => EdgeRangeManager::new_reversed(db_ref),
+            };
+
+            let ids = util::traverse(start_ids, q.min_depth, q.max_depth, q.limit, |id| {
+                edge_range_manager
+                    .iterate_for_range(id, q.t.as_ref(), None)?
+                    .map(|item| item.map(|(_, _, _, adjacent_id)| adjacent_id))
+                    .collect()
+            })?;
+
+            execute_vertex_query(db_ref, SpecificVertexQuery::new(ids).into())
+        }
         VertexQuery::Pipe(q) => {
             let vertex_manager = VertexManager::new(db_ref);
+            let vertex_type_manager = VertexTypeManager::new(db_ref);
             let iter = execute_edge_query(db_ref, *q.inner)?.into_iter();
             let direction = q.direction;
+            let t = q.t;
 
             let iter = iter.map(move |(out_id, _, _, in_id)| {
                 let id = match direction {
@@ -227,6 +896,16 @@ fn execute_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<Vec<VertexI
                     EdgeDirection::Inbound => in_id,
                 };
 
+                // With a type filter, the type index answers whether the
+                // vertex matches without having to read it.
+                if let Some(ref t) = t {
+                    return if vertex_type_manager.exists(t, id)? {
+                        Ok(Some((id, t.clone())))
+                    } else {
+                        Ok(None)
+                    };
+                }
+
                 match vertex_manager.get(id)? {
                     Some(value) => Ok(Some((id, value))),
                     None => Ok(None),
@@ -239,18 +918,34 @@ fn execute_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<Vec<VertexI
                 _ => None,
             });
 
-            let mut iter: Box<dyn Iterator<Item = Result<VertexItem>>> = Box::new(iter);
-
-            if let Some(ref t) = q.t {
-                iter = Box::new(iter.filter(move |item| match item {
-                    Ok((_, v)) => v == t,
-                    Err(_) => true,
-                }));
-            }
-
             let vertices: Result<Vec<VertexItem>> = iter.take(q.limit as usize).collect();
             vertices
         }
+        VertexQuery::Union(q) => {
+            let mut vertices = execute_vertex_query(db_ref, *q.inner)?;
+            vertices.extend(execute_vertex_query(db_ref, *q.other)?);
+            let mut seen = HashSet::new();
+            vertices.retain(|(id, _)| seen.insert(*id));
+            Ok(vertices)
+        }
+        VertexQuery::Intersect(q) => match (*q.inner, *q.other) {
+            (VertexQuery::PropertyValue(property_query), other_query)
+            | (other_query, VertexQuery::PropertyValue(property_query))
+                if type_indexed_vertex_query_type(db_ref, &other_query, &property_query.name).is_some() =>
+            {
+                let t = type_indexed_vertex_query_type(db_ref, &other_query, &property_query.name).unwrap();
+                vertices_from_type_property_value_query(
+                    db_ref,
+                    &t,
+                    &property_query.name,
+                    property_query.value,
+                    other_query,
+                    true,
+                )
+            }
+            (inner_query, other_query) => vertices_from_combined_query(db_ref, inner_query, other_query, true),
+        },
+        VertexQuery::Except(q) => vertices_from_combined_query(db_ref, *q.inner, *q.other, false),
         VertexQuery::PropertyPresence(q) => {
             guard_indexed_property(db_ref, &q.name)?;
             let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
@@ -263,16 +958,39 @@ fn execute_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<Vec<VertexI
             let iter = vertex_property_value_manager.iterate_for_value(&q.name, &Json::new(q.value));
             vertices_from_property_value_iterator(db_ref, iter)
         }
+        VertexQuery::PropertyValueRange(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+            let iter = vertex_property_value_manager.iterate_from_value(&q.name, &Json::new(q.range.start()));
+            vertices_from_property_value_iterator(db_ref, take_property_value_range(iter, q.range))
+        }
         VertexQuery::PipePropertyPresence(q) => {
             guard_indexed_property(db_ref, &q.name)?;
             let property_query = PropertyPresenceVertexQuery::new(q.name).into();
             vertices_from_piped_property_query(db_ref, *q.inner, property_query, q.exists)
         }
         VertexQuery::PipePropertyValue(q) => {
+            // A composite index covering several piped property values is
+            // used in preference to the others.
+            if let Some(vertices) = vertices_from_composite_index(db_ref, &q)? {
+                return Ok(vertices);
+            }
+
+            // An index scoped to the type of the piped vertices is used in
+            // preference to the property's index.
+            if let Some(t) = type_indexed_vertex_query_type(db_ref, &q.inner, &q.name) {
+                return vertices_from_type_property_value_query(db_ref, &t, &q.name, q.value, *q.inner, q.equal);
+            }
+
             guard_indexed_property(db_ref, &q.name)?;
             let property_query = PropertyValueVertexQuery::new(q.name, q.value).into();
             vertices_from_piped_property_query(db_ref, *q.inner, property_query, q.equal)
         }
+        VertexQuery::PipePropertyValueRange(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let property_query = PropertyValueRangeVertexQuery::new(q.name, q.range).into();
+            vertices_from_piped_property_query(db_ref, *q.inner, property_query, q.within)
+        }
     }
 }
 
@@ -349,6 +1067,31 @@ fn execute_edge_query(db_ref: DBRef<'_>, q: EdgeQuery) -> Result<Vec<EdgeRangeIt
 
             Ok(edges)
         }
+        EdgeQuery::Union(q) => {
+            let mut edges = execute_edge_query(db_ref, *q.inner)?;
+            edges.extend(execute_edge_query(db_ref, *q.other)?);
+            let mut seen = HashSet::new();
+            edges.retain(|(out_id, t, _, in_id)| seen.insert((*out_id, t.clone(), *in_id)));
+            Ok(edges)
+        }
+        EdgeQuery::Intersect(q) => match (*q.inner, *q.other) {
+            (EdgeQuery::PropertyValue(property_query), other_query)
+            | (other_query, EdgeQuery::PropertyValue(property_query))
+                if type_indexed_edge_query_type(db_ref, &other_query, &property_query.name).is_some() =>
+            {
+                let t = type_indexed_edge_query_type(db_ref, &other_query, &property_query.name).unwrap();
+                edges_from_type_property_value_query(
+                    db_ref,
+                    &t,
+                    &property_query.name,
+                    property_query.value,
+                    other_query,
+                    true,
+                )
+            }
+            (inner_query, other_query) => edges_from_combined_query(db_ref, inner_query, other_query, true),
+        },
+        EdgeQuery::Except(q) => edges_from_combined_query(db_ref, *q.inner, *q.other, false),
         EdgeQuery::PropertyPresence(q) => {
             guard_indexed_property(db_ref, &q.name)?;
             let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
@@ -361,26 +1104,618 @@ fn execute_edge_query(db_ref: DBRef<'_>, q: EdgeQuery) -> Result<Vec<EdgeRangeIt
             let iter = edge_property_value_manager.iterate_for_value(&q.name, &Json::new(q.value));
             edges_from_property_value_iterator(db_ref, iter)
         }
+        EdgeQuery::PropertyValueRange(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+            let iter = edge_property_value_manager.iterate_from_value(&q.name, &Json::new(q.range.start()));
+            edges_from_property_value_iterator(db_ref, take_property_value_range(iter, q.range))
+        }
         EdgeQuery::PipePropertyPresence(q) => {
             guard_indexed_property(db_ref, &q.name)?;
             let property_query = PropertyPresenceEdgeQuery::new(q.name).into();
             edges_from_piped_property_query(db_ref, *q.inner, property_query, q.exists)
         }
         EdgeQuery::PipePropertyValue(q) => {
+            // A composite index covering several piped property values is
+            // used in preference to the others.
+            if let Some(edges) = edges_from_composite_index(db_ref, &q)? {
+                return Ok(edges);
+            }
+
+            // An index scoped to the type of the piped edges is used in
+            // preference to the property's index.
+            if let Some(t) = type_indexed_edge_query_type(db_ref, &q.inner, &q.name) {
+                return edges_from_type_property_value_query(db_ref, &t, &q.name, q.value, *q.inner, q.equal);
+            }
+
             guard_indexed_property(db_ref, &q.name)?;
             let property_query = PropertyValueEdgeQuery::new(q.name, q.value).into();
             edges_from_piped_property_query(db_ref, *q.inner, property_query, q.equal)
         }
+        EdgeQuery::PipePropertyValueRange(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let property_query = PropertyValueRangeEdgeQuery::new(q.name, q.range).into();
+            edges_from_piped_property_query(db_ref, *q.inner, property_query, q.within)
+        }
     }
 }
 
-/// A datastore that is backed by rocksdb.
-#[derive(Debug)]
-pub struct RocksdbDatastore {
-    db: Arc<DB>,
-    indexed_properties: Arc<RwLock<HashSet<Identifier>>>,
-}
-
+// Counts the vertices returned by a query. Where possible, this counts keys
+// in the vertex or property value index without loading the vertices.
+fn count_vertex_query(db_ref: DBRef<'_>, q: VertexQuery) -> Result<u64> {
+    match q {
+        VertexQuery::Range(q) => {
+            let count = match (range_start_id(q.start_id), q.t) {
+                (Some(next_uuid), Some(t)) => {
+                    VertexTypeManager::new(db_ref).count_for_range(&t, next_uuid, q.limit as usize)
+                }
+                (Some(next_uuid), None) => VertexManager::new(db_ref).count_for_range(next_uuid, q.limit as usize),
+                (None, _) => 0,
+            };
+            Ok(count as u64)
+        }
+        VertexQuery::PropertyPresence(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+            let iter = vertex_property_value_manager.iterate_for_name(&q.name);
+            count_vertices_from_property_value_iterator(db_ref, iter)
+        }
+        VertexQuery::PropertyValue(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+            let iter = vertex_property_value_manager.iterate_for_value(&q.name, &Json::new(q.value));
+            count_vertices_from_property_value_iterator(db_ref, iter)
+        }
+        q => Ok(execute_vertex_query(db_ref, q)?.len() as u64),
+    }
+}
+
+fn count_vertices_from_property_value_iterator(
+    db_ref: DBRef<'_>,
+    iter: impl Iterator<Item = Result<VertexPropertyValueKey>>,
+) -> Result<u64> {
+    let vertex_manager = VertexManager::new(db_ref);
+
+    let mut count = 0;
+    for item in iter {
+        let (_, _, id) = item?;
+        if vertex_manager.exists(id)? {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+// Counts the edges returned by a query. Where possible, this counts keys in
+// the edge range or property value index without loading the edges.
+fn count_edge_query(db_ref: DBRef<'_>, q: EdgeQuery) -> Result<u64> {
+    match q {
+        EdgeQuery::Pipe(q) if q.high.is_none() && q.low.is_none() => {
+            let vertices = execute_vertex_query(db_ref, *q.inner)?;
+
+            let edge_range_manager = match q.direction {
+                EdgeDirection::Outbound => EdgeRangeManager::new(db_ref),
+                EdgeDirection::Inbound => EdgeRangeManager::new_reversed(db_ref),
+            };
+
+            let limit = q.limit as usize;
+            let mut count = 0;
+            for (id, _) in vertices.into_iter() {
+                if count == limit {
+                    break;
+                }
+                count += edge_range_manager.count_for_range(id, q.t.as_ref(), limit - count);
+            }
+
+            Ok(count as u64)
+        }
+        EdgeQuery::PropertyPresence(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+            let iter = edge_property_value_manager.iterate_for_name(&q.name);
+            count_edges_from_property_value_iterator(db_ref, iter)
+        }
+        EdgeQuery::PropertyValue(q) => {
+            guard_indexed_property(db_ref, &q.name)?;
+            let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+            let iter = edge_property_value_manager.iterate_for_value(&q.name, &Json::new(q.value));
+            count_edges_from_property_value_iterator(db_ref, iter)
+        }
+        q => Ok(execute_edge_query(db_ref, q)?.len() as u64),
+    }
+}
+
+fn count_edges_from_property_value_iterator(
+    db_ref: DBRef<'_>,
+    iter: impl Iterator<Item = Result<EdgePropertyValueKey>>,
+) -> Result<u64> {
+    let edge_manager = EdgeManager::new(db_ref);
+
+    let mut count = 0;
+    for item in iter {
+        let (_, _, (out_id, t, in_id)) = item?;
+        if edge_manager.get(out_id, &t, in_id)?.is_some() {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+// A consistent view of the datastore that query results are read from in
+// chunks, for iterators that outlive the locks taken to create them.
+struct RocksdbSource {
+    db: Arc<DB>,
+    key_prefixes: Arc<KeyPrefixes>,
+    indexes: Indexes,
+    overlay: Option<Overlay>,
+    snapshot: Arc<OwnedSnapshot>,
+}
+
+impl RocksdbSource {
+    fn db_ref(&self) -> DBRef<'_> {
+        DBRef::new(
+            &self.db,
+            &self.key_prefixes,
+            &self.indexes,
+            self.overlay.as_ref(),
+            Some(self.snapshot.get()),
+        )
+    }
+
+    fn all_vertex_properties(&self, id: Uuid) -> Result<Vec<NamedProperty>> {
+        let manager = VertexPropertyManager::new(self.db_ref());
+        let mut properties = Vec::new();
+        for item in manager.iterate_for_owner(id)? {
+            let ((_, name), value) = item?;
+            properties.push(NamedProperty::new(name, value.0));
+        }
+        Ok(properties)
+    }
+
+    fn all_edge_properties(&self, key: &EdgeKey) -> Result<Vec<NamedProperty>> {
+        let manager = EdgePropertyManager::new(self.db_ref());
+        let mut properties = Vec::new();
+        for item in manager.iterate_for_owner(key.outbound_id, &key.t, key.inbound_id)? {
+            let ((_, _, _, name), value) = item?;
+            properties.push(NamedProperty::new(name, value.0));
+        }
+        Ok(properties)
+    }
+}
+
+impl CursorSource for RocksdbSource {
+    fn range_start(&self, start_id: Option<Uuid>) -> Bound<Uuid> {
+        match start_id {
+            Some(start_id) => Bound::Excluded(start_id),
+            None => Bound::Unbounded,
+        }
+    }
+
+    fn vertex_range(&self, t: Option<&Identifier>, from: Bound<Uuid>, limit: usize) -> Result<Vec<VertexItem>> {
+        let from = match from {
+            Bound::Included(id) => id,
+            Bound::Excluded(id) => match next_uuid(id) {
+                Ok(id) => id,
+                Err(_) => return Ok(Vec::new()),
+            },
+            Bound::Unbounded => Uuid::default(),
+        };
+
+        let db_ref = self.db_ref();
+        match t {
+            Some(t) => {
+                let vertex_type_manager = VertexTypeManager::new(db_ref);
+                let vertices = vertex_type_manager
+                    .iterate_for_range(t, from)
+                    .take(limit)
+                    .map(|id| (id, t.clone()))
+                    .collect();
+                Ok(vertices)
+            }
+            None => {
+                let vertex_manager = VertexManager::new(db_ref);
+                let vertices = vertex_manager.iterate_for_range(from).take(limit).collect();
+                vertices
+            }
+        }
+    }
+
+    fn vertex(&self, id: Uuid) -> Result<Option<Identifier>> {
+        VertexManager::new(self.db_ref()).get(id)
+    }
+
+    fn adjacent_edges(
+        &self,
+        id: Uuid,
+        t: Option<&Identifier>,
+        direction: EdgeDirection,
+        high: Option<DateTime<Utc>>,
+        low: Option<DateTime<Utc>>,
+        after: Option<&EdgeResume>,
+        limit: usize,
+    ) -> Result<Vec<(EdgeKey, DateTime<Utc>)>> {
+        let db_ref = self.db_ref();
+        let edge_range_manager = match direction {
+            EdgeDirection::Outbound => EdgeRangeManager::new(db_ref),
+            EdgeDirection::Inbound => EdgeRangeManager::new_reversed(db_ref),
+        };
+        let iter = match after {
+            Some(after) => Box::new(edge_range_manager.iterate_after(id, t, after)),
+            None => edge_range_manager.iterate_for_range(id, t, high)?,
+        };
+
+        let mut edges = Vec::new();
+        for item in iter {
+            let (first_id, t_, update_datetime, second_id) = item?;
+            if high.is_some_and(|high| update_datetime > high) {
+                continue;
+            }
+            if low.is_some_and(|low| update_datetime < low) {
+                // Edges of one type are sorted from newest to oldest, so
+                // there are no more to return of this type.
+                if t.is_some() {
+                    break;
+                }
+                continue;
+            }
+
+            let key = match direction {
+                EdgeDirection::Outbound => EdgeKey::new(first_id, t_, second_id),
+                EdgeDirection::Inbound => EdgeKey::new(second_id, t_, first_id),
+            };
+            edges.push((key, update_datetime));
+            if edges.len() == limit {
+                break;
+            }
+        }
+        Ok(edges)
+    }
+
+    fn edge(&self, key: &EdgeKey) -> Result<Option<DateTime<Utc>>> {
+        EdgeManager::new(self.db_ref()).get(key.outbound_id, &key.t, key.inbound_id)
+    }
+
+    fn is_indexed(&self, name: &Identifier) -> bool {
+        guard_indexed_property(self.db_ref(), name).is_ok()
+    }
+
+    fn vertex_property_values(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<&(Json, Uuid)>,
+        limit: usize,
+    ) -> Result<Vec<(Json, Uuid)>> {
+        let db_ref = self.db_ref();
+        guard_indexed_property(db_ref, name)?;
+        let manager = VertexPropertyValueManager::new(db_ref);
+        let iter: Box<dyn Iterator<Item = Result<VertexPropertyValueKey>>> = match (after, filter.start()) {
+            (Some((value, id)), _) => Box::new(manager.iterate_after(name, value, *id)),
+            (None, Some(start)) => Box::new(manager.iterate_from_value(name, &start)),
+            (None, None) => Box::new(manager.iterate_for_name(name)),
+        };
+
+        let mut members = Vec::new();
+        for item in iter {
+            let (_, value, id) = item?;
+            match filter.locate(&value) {
+                Ordering::Less => continue,
+                Ordering::Greater => break,
+                Ordering::Equal => members.push((value, id)),
+            }
+            if members.len() == limit {
+                break;
+            }
+        }
+        Ok(members)
+    }
+
+    fn edge_property_values(
+        &self,
+        name: &Identifier,
+        filter: &PropertyFilter<'_>,
+        after: Option<&(Json, EdgeKey)>,
+        limit: usize,
+    ) -> Result<Vec<(Json, EdgeKey)>> {
+        let db_ref = self.db_ref();
+        guard_indexed_property(db_ref, name)?;
+        let manager = EdgePropertyValueManager::new(db_ref);
+        let iter: Box<dyn Iterator<Item = Result<EdgePropertyValueKey>>> = match (after, filter.start()) {
+            (Some((value, key)), _) => Box::new(manager.iterate_after(name, value, key)),
+            (None, Some(start)) => Box::new(manager.iterate_from_value(name, &start)),
+            (None, None) => Box::new(manager.iterate_for_name(name)),
+        };
+
+        let mut members = Vec::new();
+        for item in iter {
+            let (_, value, (out_id, t, in_id)) = item?;
+            match filter.locate(&value) {
+                Ordering::Less => continue,
+                Ordering::Greater => break,
+                Ordering::Equal => members.push((value, EdgeKey::new(out_id, t, in_id))),
+            }
+            if members.len() == limit {
+                break;
+            }
+        }
+        Ok(members)
+    }
+
+    fn vertex_property(&self, id: Uuid, name: &Identifier) -> Result<Option<Json>> {
+        VertexPropertyManager::new(self.db_ref()).get(id, name)
+    }
+
+    fn edge_property(&self, key: &EdgeKey, name: &Identifier) -> Result<Option<Json>> {
+        EdgePropertyManager::new(self.db_ref()).get(key.outbound_id, &key.t, key.inbound_id, name)
+    }
+
+    fn vertices(&self, q: VertexQuery) -> Result<Vec<VertexItem>> {
+        execute_vertex_query(self.db_ref(), q)
+    }
+
+    fn edges(&self, q: EdgeQuery) -> Result<Vec<(EdgeKey, DateTime<Utc>)>> {
+        let edges = execute_edge_query(self.db_ref(), q)?;
+        Ok(edges
+            .into_iter()
+            .map(|(out_id, t, update_datetime, in_id)| (EdgeKey::new(out_id, t, in_id), update_datetime))
+            .collect())
+    }
+}
+
+// The index builds running in the background for a datastore. Once every
+// handle to the datastore has been dropped, they're stopped and waited on.
+// A stopped build is left unready, so it's resumed when the datastore is
+// next opened.
+#[derive(Debug, Default)]
+struct IndexBuilds {
+    stopped: Arc<AtomicBool>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Drop for IndexBuilds {
+    fn drop(&mut self) {
+        self.stopped.store(true, AtomicOrdering::SeqCst);
+        for handle in self.handles.get_mut().unwrap().drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+// A write made by a transaction, with the vertices, edges and properties
+// that it applies to resolved. When the transaction is committed, these are
+// replayed against the latest state of the database, rather than writing
+// the keys that they staged, which could be out of date by then: another
+// write may have changed the same property, or an index may have been added
+// or dropped.
+#[derive(Debug)]
+enum Mutation {
+    CreateVertex(Vertex),
+    CreateEdge(EdgeKey, DateTime<Utc>),
+    DeleteVertices(Vec<Uuid>),
+    DeleteEdges(Vec<EdgeKey>),
+    SetVertexProperties(Vec<Uuid>, Identifier, Json),
+    DeleteVertexProperties(Vec<Uuid>, Identifier),
+    SetEdgeProperties(Vec<EdgeKey>, Identifier, Json),
+    DeleteEdgeProperties(Vec<EdgeKey>, Identifier),
+    BulkInsert(Vec<BulkInsertItem>),
+}
+
+// Replays a transaction's write onto a batch. Vertices, edges and properties
+// that have since been deleted by other writes are skipped.
+fn replay_mutation(db_ref: DBRef<'_>, batch: &mut Batch, mutation: &Mutation) -> Result<()> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let edge_manager = EdgeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+
+    match mutation {
+        Mutation::CreateVertex(vertex) => {
+            if !vertex_manager.exists(vertex.id)? {
+                vertex_manager.create(batch, vertex)?;
+            }
+        }
+        Mutation::CreateEdge(key, update_datetime) => {
+            if vertex_manager.exists(key.outbound_id)? && vertex_manager.exists(key.inbound_id)? {
+                edge_manager.set(batch, key.outbound_id, &key.t, key.inbound_id, *update_datetime)?;
+            }
+        }
+        Mutation::DeleteVertices(ids) => {
+            for id in ids {
+                vertex_manager.delete(batch, *id)?;
+            }
+        }
+        Mutation::DeleteEdges(keys) => {
+            for key in keys {
+                if let Some(update_datetime) = edge_manager.get(key.outbound_id, &key.t, key.inbound_id)? {
+                    edge_manager.delete(batch, key.outbound_id, &key.t, key.inbound_id, update_datetime)?;
+                }
+            }
+        }
+        Mutation::SetVertexProperties(ids, name, value) => {
+            for id in ids {
+                if let Some(t) = vertex_manager.get(*id)? {
+                    vertex_property_manager.set(batch, *id, Some(&t), name, value)?;
+                }
+            }
+        }
+        Mutation::DeleteVertexProperties(ids, name) => {
+            for id in ids {
+                if let Some(t) = vertex_manager.get(*id)? {
+                    vertex_property_manager.delete(batch, *id, Some(&t), name)?;
+                }
+            }
+        }
+        Mutation::SetEdgeProperties(keys, name, value) => {
+            for key in keys {
+                if edge_manager.get(key.outbound_id, &key.t, key.inbound_id)?.is_some() {
+                    edge_property_manager.set(batch, key.outbound_id, &key.t, key.inbound_id, name, value)?;
+                }
+            }
+        }
+        Mutation::DeleteEdgeProperties(keys, name) => {
+            for key in keys {
+                if edge_manager.get(key.outbound_id, &key.t, key.inbound_id)?.is_some() {
+                    edge_property_manager.delete(batch, key.outbound_id, &key.t, key.inbound_id, name)?;
+                }
+            }
+        }
+        Mutation::BulkInsert(items) => write_bulk_insert(db_ref, batch, items)?,
+    }
+
+    Ok(())
+}
+
+// Writes the items of a bulk insert to a batch, without checking them
+// against the schema.
+fn write_bulk_insert(db_ref: DBRef<'_>, batch: &mut Batch, items: &[BulkInsertItem]) -> Result<()> {
+    let indexes = db_ref.indexes;
+    let vertex_manager = VertexManager::new(db_ref);
+    let edge_manager = EdgeManager::new(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    // The types of the vertices created by this insert, which can't be
+    // read back until the batch is written. Indexes scoped to a type
+    // and the type counts need them for the vertices' properties,
+    // including those inserted ahead of their vertices.
+    let vertex_types: HashMap<Uuid, Identifier> = items
+        .iter()
+        .filter_map(|item| match item {
+            BulkInsertItem::Vertex(vertex) => Some((vertex.id, vertex.t.clone())),
+            _ => None,
+        })
+        .collect();
+    // Where each of those vertices was last inserted. Only the last
+    // insert of a vertex is applied, since the others would be
+    // overwritten, and the index entries for their types couldn't be
+    // removed because the batch can't be read back.
+    let last_vertex_items: HashMap<Uuid, usize> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| match item {
+            BulkInsertItem::Vertex(vertex) => Some((vertex.id, i)),
+            _ => None,
+        })
+        .collect();
+    // Likewise, the properties set by this insert that are covered by
+    // composite indexes, which are updated once all of them are known.
+    let mut vertex_composite_properties: HashMap<Uuid, HashMap<Identifier, Json>> = HashMap::new();
+    let mut edge_composite_properties: HashMap<EdgeKey, HashMap<Identifier, Json>> = HashMap::new();
+    // And the properties set by this insert that have unique
+    // constraints, which are checked once all of them are known.
+    let mut vertex_unique_properties: HashMap<(Uuid, Identifier), Json> = HashMap::new();
+    let mut edge_unique_properties: HashMap<(EdgeKey, Identifier), Json> = HashMap::new();
+
+    for (i, item) in items.iter().enumerate() {
+        match *item {
+            BulkInsertItem::Vertex(ref vertex) => {
+                if last_vertex_items[&vertex.id] == i {
+                    vertex_manager.create(batch, vertex)?;
+                }
+            }
+            BulkInsertItem::Edge(ref key) => {
+                edge_manager.set(batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
+            }
+            BulkInsertItem::VertexProperty(id, ref name, ref value) => {
+                let t = match vertex_types.get(&id) {
+                    Some(t) => Some(t.clone()),
+                    None => vertex_manager.get(id)?,
+                };
+                let value = Json::new(value.clone());
+                vertex_property_manager.set_value(batch, id, t.as_ref(), name, &value)?;
+                if indexes.unique_properties.contains_key(name) {
+                    vertex_unique_properties.insert((id, name.clone()), value.clone());
+                }
+                if indexes.is_composite_indexed(name) {
+                    vertex_composite_properties
+                        .entry(id)
+                        .or_default()
+                        .insert(name.clone(), value);
+                }
+            }
+            BulkInsertItem::EdgeProperty(ref key, ref name, ref value) => {
+                let value = Json::new(value.clone());
+                edge_property_manager.set_value(batch, key.outbound_id, &key.t, key.inbound_id, name, &value)?;
+                if indexes.unique_properties.contains_key(name) {
+                    edge_unique_properties.insert((key.clone(), name.clone()), value.clone());
+                }
+                if indexes.is_composite_indexed(name) {
+                    edge_composite_properties
+                        .entry(key.clone())
+                        .or_default()
+                        .insert(name.clone(), value);
+                }
+            }
+        }
+    }
+
+    let mut vertex_unique_values = HashMap::<_, Vec<(Uuid, Option<Identifier>)>>::new();
+    let mut vertex_unique_changes: HashMap<&Identifier, HashSet<Uuid>> = HashMap::new();
+    for ((id, name), value) in &vertex_unique_properties {
+        let t = match vertex_types.get(id) {
+            Some(t) => Some(t.clone()),
+            None => vertex_manager.get(*id)?,
+        };
+        vertex_unique_values.entry((name, value)).or_default().push((*id, t));
+        vertex_unique_changes.entry(name).or_default().insert(*id);
+    }
+    for ((name, value), vertices) in &vertex_unique_values {
+        let setting: Vec<(Uuid, Option<&Identifier>)> = vertices.iter().map(|(id, t)| (*id, t.as_ref())).collect();
+        check_unique_vertex_property(db_ref, name, value, &setting, &vertex_unique_changes[name])?;
+    }
+
+    let mut edge_unique_values: HashMap<(&Identifier, &Json), Vec<&EdgeKey>> = HashMap::new();
+    let mut edge_unique_changes: HashMap<&Identifier, HashSet<&EdgeKey>> = HashMap::new();
+    for ((key, name), value) in &edge_unique_properties {
+        edge_unique_values.entry((name, value)).or_default().push(key);
+        edge_unique_changes.entry(name).or_default().insert(key);
+    }
+    for ((name, value), keys) in &edge_unique_values {
+        check_unique_edge_property(db_ref, name, value, keys, &edge_unique_changes[name])?;
+    }
+
+    for (id, properties) in &vertex_composite_properties {
+        let changes: Vec<(&Identifier, Option<&Json>)> =
+            properties.iter().map(|(name, value)| (name, Some(value))).collect();
+        vertex_property_manager.update_composite_indexes(batch, *id, &changes)?;
+    }
+    for (key, properties) in &edge_composite_properties {
+        let changes: Vec<(&Identifier, Option<&Json>)> =
+            properties.iter().map(|(name, value)| (name, Some(value))).collect();
+        edge_property_manager.update_composite_indexes(batch, key.outbound_id, &key.t, key.inbound_id, &changes)?;
+    }
+
+    Ok(())
+}
+
+// The state of a transaction: the changes it has staged, which its reads
+// see, and the writes that staged them, which are replayed when it's
+// committed. The overlay is locked before the writes.
+#[derive(Debug, Default)]
+struct TransactionState {
+    overlay: Mutex<Overlay>,
+    mutations: Mutex<Vec<Mutation>>,
+}
+
+/// A datastore that is backed by rocksdb.
+#[derive(Debug)]
+pub struct RocksdbDatastore {
+    db: Arc<DB>,
+    key_prefixes: Arc<KeyPrefixes>,
+    indexes: Arc<RwLock<Indexes>>,
+    // Held while checking and writing properties with unique constraints,
+    // so that concurrent writes can't both pass the check.
+    unique_lock: Arc<Mutex<()>>,
+    // The schema that writes are checked against, if any.
+    schema: Arc<RwLock<Option<Schema>>>,
+    transaction: Option<Arc<TransactionState>>,
+    snapshot: Option<Arc<OwnedSnapshot>>,
+    // Unset for the handles that background index builds run on, so that
+    // the builds don't keep themselves from being stopped.
+    index_builds: Option<Arc<IndexBuilds>>,
+}
+
 impl RocksdbDatastore {
     /// Creates a new rocksdb datastore.
     ///
@@ -389,29 +1724,69 @@ impl RocksdbDatastore {
     /// * `max_open_files`: The maximum number of open files to have. If
     ///   `None`, the default will be used.
     pub fn new<P: AsRef<Path>>(path: P, max_open_files: Option<i32>) -> Result<RocksdbDatastore> {
-        let opts = get_options(max_open_files);
-        let path = path.as_ref();
-
-        let db = match DB::open_cf(&opts, path, &CF_NAMES) {
-            Ok(db) => db,
-            Err(_) => {
-                let mut db = DB::open(&opts, path)?;
-
-                for cf_name in &CF_NAMES {
-                    db.create_cf(cf_name, &opts)?;
-                }
-
-                db
-            }
+        let config = RocksdbConfig {
+            max_open_files,
+            ..RocksdbConfig::default()
         };
+        Self::new_with_config(path, &config)
+    }
+
+    /// Creates a new rocksdb datastore with the given tuning options.
+    ///
+    /// # Arguments
+    /// * `path`: The file path to the rocksdb database.
+    /// * `config`: The tuning options.
+    pub fn new_with_config<P: AsRef<Path>>(path: P, config: &RocksdbConfig) -> Result<RocksdbDatastore> {
+        let (opts, descriptors, key_prefixes) = config.options(&CF_NAMES)?;
+        let db = DB::open_cf_descriptors(&opts, path, descriptors)?;
 
         let metadata_manager = MetadataManager::new(&db);
-        let indexed_properties = metadata_manager.get_indexed_properties()?;
+        let unready_indexed_properties = metadata_manager.get_unready_indexed_properties()?;
+        let schema = metadata_manager.get_schema()?;
+        let mut indexes = Indexes {
+            properties: metadata_manager
+                .get_indexed_properties()?
+                .into_iter()
+                .map(|name| {
+                    let state = IndexState {
+                        status: IndexStatus::Ready,
+                        build_id: next_index_build_id(),
+                    };
+                    (name, state)
+                })
+                .collect(),
+            type_properties: metadata_manager.get_indexed_type_properties()?,
+            composite_properties: metadata_manager.get_indexed_composite_properties()?,
+            unique_properties: metadata_manager.get_unique_properties()?,
+        };
+        migrate_property_value_indexes(&db, &key_prefixes, &indexes)?;
+        migrate_vertex_type_index(&db, &key_prefixes, &indexes)?;
+        migrate_type_counts(&db, &key_prefixes, &indexes)?;
+
+        // Builds that were interrupted, or that failed, are restarted.
+        let total = estimate_index_build_total(DBRef::new(&db, &key_prefixes, &indexes, None, None))?;
+        let mut builds = Vec::new();
+        for (name, state) in indexes.properties.iter_mut() {
+            if unready_indexed_properties.contains(name) {
+                state.status = IndexStatus::Building { processed: 0, total };
+                builds.push((name.clone(), state.build_id));
+            }
+        }
 
-        Ok(RocksdbDatastore {
+        let datastore = RocksdbDatastore {
             db: Arc::new(db),
-            indexed_properties: Arc::new(RwLock::new(indexed_properties)),
-        })
+            key_prefixes: Arc::new(key_prefixes),
+            indexes: Arc::new(RwLock::new(indexes)),
+            unique_lock: Arc::new(Mutex::new(())),
+            schema: Arc::new(RwLock::new(schema)),
+            transaction: None,
+            snapshot: None,
+            index_builds: Some(Arc::new(IndexBuilds::default())),
+        };
+        for (name, build_id) in builds {
+            datastore.spawn_index_build(name, build_id);
+        }
+        Ok(datastore)
     }
 
     /// Runs a repair operation on the rocksdb database.
@@ -421,18 +1796,265 @@ impl RocksdbDatastore {
     /// * `max_open_files`: The maximum number of open files to have. If
     ///   `None`, the default will be used.
     pub fn repair<P: AsRef<Path>>(path: P, max_open_files: Option<i32>) -> Result<()> {
-        let opts = get_options(max_open_files);
+        let config = RocksdbConfig {
+            max_open_files,
+            ..RocksdbConfig::default()
+        };
+        let (opts, _, _) = config.options(&CF_NAMES)?;
         DB::repair(&opts, path)?;
         Ok(())
     }
+
+    /// Creates a checkpoint of the rocksdb database: a consistent copy of it
+    /// that can be opened as a datastore of its own. Files are hard-linked
+    /// rather than copied where possible, so this is cheap if the checkpoint
+    /// is on the same filesystem. Changes staged by a transaction are not
+    /// included.
+    ///
+    /// # Arguments
+    /// * `path`: The file path to create the checkpoint at. This must not
+    ///   already exist.
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Checkpoint::new(&self.db)?.create_checkpoint(path)?;
+        Ok(())
+    }
+
+    /// Restores a rocksdb database from the latest backup made with
+    /// `Datastore::backup`. This must not be run while the database is open.
+    ///
+    /// # Arguments
+    /// * `backup_path`: The file path to the backups.
+    /// * `path`: The file path to restore the rocksdb database to. Any
+    ///   existing database at this path is replaced.
+    pub fn restore<P: AsRef<Path>, Q: AsRef<Path>>(backup_path: P, path: Q) -> Result<()> {
+        let mut engine = BackupEngine::open(&BackupEngineOptions::default(), backup_path)?;
+        engine.restore_from_latest_backup(&path, &path, &RestoreOptions::default())?;
+        Ok(())
+    }
+
+    /// Checks that the redundant structures of the database agree with each
+    /// other: that every edge has both of its edge ranges and that its
+    /// vertices exist, and that the vertex type and property value indexes
+    /// match the vertices and properties. The counts of the vertices, edges
+    /// and properties of each type are checked too. This scans every column
+    /// family.
+    ///
+    /// Without repairing, the check reads from a snapshot, so it can run
+    /// alongside other operations. When repairing, writes are blocked until
+    /// the check is done. Dangling edges and properties are deleted, index
+    /// entries are added or deleted to match, and counts are corrected. The
+    /// fixes are written in chunks as they're found, so a check that's
+    /// interrupted may leave some of them written. This can't be run on a
+    /// transaction.
+    ///
+    /// # Arguments
+    /// * `repair`: Whether to fix the inconsistencies that are found.
+    pub fn verify(&self, repair: bool) -> Result<Vec<Inconsistency>> {
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let db = self.db.clone();
+        if repair {
+            let indexes = self.indexes.write().unwrap();
+            // Fixes are written in chunks as they're found, so the check
+            // reads from a snapshot that doesn't see them.
+            let snapshot = db.snapshot();
+            let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, Some(&snapshot));
+            let mut repairs = Repairs::new(db_ref, true);
+            verify(db_ref, &mut repairs)?;
+            let mut inconsistencies = repairs.finish()?;
+            // The fixes change the type counts, so those are checked once
+            // they've been written.
+            let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
+            let mut repairs = Repairs::new(db_ref, true);
+            verify_type_counts(db_ref, &mut repairs)?;
+            inconsistencies.extend(repairs.finish()?);
+            Ok(inconsistencies)
+        } else {
+            let indexes = self.indexes.read().unwrap();
+            let snapshot = db.snapshot();
+            let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, Some(&snapshot));
+            let mut repairs = Repairs::new(db_ref, false);
+            verify(db_ref, &mut repairs)?;
+            verify_type_counts(db_ref, &mut repairs)?;
+            repairs.finish()
+        }
+    }
+
+    // Marks a property's index as being built, unless it's already ready. If
+    // a build is already running, it's replaced when `replace` is set, and
+    // otherwise left to finish. Returns the ID of the new build, if one was
+    // started.
+    fn start_index_build(&self, name: &Identifier, replace: bool) -> Result<Option<u64>> {
+        let mut indexes = self.indexes.write().unwrap();
+        match indexes.properties.get(name).map(|state| &state.status) {
+            Some(IndexStatus::Ready) => return Ok(None),
+            Some(IndexStatus::Building { .. }) if !replace => return Ok(None),
+            _ => {}
+        }
+
+        let db = self.db.clone();
+        let total = estimate_index_build_total(DBRef::new(&db, &self.key_prefixes, &indexes, None, None))?;
+        let build_id = next_index_build_id();
+        let state = IndexState {
+            status: IndexStatus::Building { processed: 0, total },
+            build_id,
+        };
+        let previous_state = indexes.properties.insert(name.clone(), state);
+
+        let mut batch = DBRef::new(&db, &self.key_prefixes, &indexes, None, None).batch();
+        MetadataManager::new(&db).set_indexed_properties(&mut batch, &indexes.properties)?;
+        if let Err(err) = batch.write(&db, None) {
+            match previous_state {
+                Some(previous_state) => indexes.properties.insert(name.clone(), previous_state),
+                None => indexes.properties.remove(name),
+            };
+            return Err(err);
+        }
+        Ok(Some(build_id))
+    }
+
+    // Fills in a property's index, marking it as failed if that doesn't
+    // work out. A build in the background is passed the flag that's set
+    // when it should stop.
+    fn run_index_build(&self, name: &Identifier, build_id: u64, stopped: Option<&AtomicBool>) -> Result<()> {
+        let result = self.fill_index(name, build_id, stopped);
+        if let Err(ref err) = result {
+            let mut indexes = self.indexes.write().unwrap();
+            if let Some(state) = indexes.properties.get_mut(name) {
+                if state.build_id == build_id {
+                    state.status = IndexStatus::Failed(err.to_string());
+                }
+            }
+        }
+        result
+    }
+
+    // Runs a build of a property's index on another thread.
+    fn spawn_index_build(&self, name: Identifier, build_id: u64) {
+        let index_builds = self
+            .index_builds
+            .as_ref()
+            .expect("expected a handle that runs index builds");
+        let stopped = index_builds.stopped.clone();
+        let datastore = RocksdbDatastore {
+            db: self.db.clone(),
+            key_prefixes: self.key_prefixes.clone(),
+            indexes: self.indexes.clone(),
+            unique_lock: self.unique_lock.clone(),
+            schema: self.schema.clone(),
+            transaction: None,
+            snapshot: None,
+            index_builds: None,
+        };
+        let handle = thread::spawn(move || {
+            // Failures are reported through the index's status.
+            let _ = datastore.run_index_build(&name, build_id, Some(&stopped));
+        });
+
+        let mut handles = index_builds.handles.lock().unwrap();
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+    }
+
+    // Fills in a property's index, only holding the write lock for one batch
+    // at a time, so that other operations can run in between. This stops
+    // early if the build is replaced, the index is dropped, or `stopped` is
+    // set.
+    fn fill_index(&self, name: &Identifier, build_id: u64, stopped: Option<&AtomicBool>) -> Result<()> {
+        let db = self.db.clone();
+        let mut cursor = None;
+        loop {
+            if stopped.is_some_and(|stopped| stopped.load(AtomicOrdering::SeqCst)) {
+                return Ok(());
+            }
+
+            let mut indexes = self.indexes.write().unwrap();
+            match indexes.properties.get(name) {
+                Some(state) if state.build_id == build_id => {}
+                _ => return Ok(()),
+            }
+
+            let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
+            let mut batch = db_ref.batch();
+            let count = index_batch(db_ref, &mut batch, name, &mut cursor)?;
+            if count == 0 {
+                let mut ready_indexes = indexes.clone();
+                ready_indexes.properties.get_mut(name).unwrap().status = IndexStatus::Ready;
+                MetadataManager::new(&db).set_indexed_properties(&mut batch, &ready_indexes.properties)?;
+                batch.write(&db, None)?;
+                *indexes = ready_indexes;
+                return Ok(());
+            }
+
+            batch.write(&db, None)?;
+            if let IndexStatus::Building { ref mut processed, .. } = indexes.properties.get_mut(name).unwrap().status {
+                *processed += count as u64;
+            }
+        }
+    }
+
+    // Locks out other writes of properties with unique constraints, if the
+    // write is `constrained`. Transactions don't need the lock until they're
+    // committed, since their writes aren't visible to others until then.
+    fn lock_unique_properties(&self, constrained: bool) -> Option<MutexGuard<'_, ()>> {
+        if self.transaction.is_none() && constrained {
+            Some(self.unique_lock.lock().unwrap())
+        } else {
+            None
+        }
+    }
+
+    // Locks the changes staged by this transaction, or returns `None` if
+    // this isn't a transaction.
+    fn overlay(&self) -> Option<MutexGuard<'_, Overlay>> {
+        self.transaction
+            .as_ref()
+            .map(|transaction| transaction.overlay.lock().unwrap())
+    }
+
+    // Records a write made by this transaction, to be replayed when it's
+    // committed. This is called while the write's overlay is still locked,
+    // so that writes are recorded in the order they were staged. Outside of
+    // a transaction, this does nothing.
+    fn record<F: FnOnce() -> Mutation>(&self, mutation: F) {
+        if let Some(ref transaction) = self.transaction {
+            transaction.mutations.lock().unwrap().push(mutation());
+        }
+    }
+
+    // Gets the snapshot to read from, or `None` to read the latest state.
+    fn db_snapshot(&self) -> Option<&Snapshot<'_>> {
+        self.snapshot.as_ref().map(|snapshot| snapshot.get())
+    }
+
+    // Gets a view of the datastore for iterators to read from, so that they
+    // don't hold its locks, and aren't affected by later writes.
+    fn cursor_source(&self) -> Arc<RocksdbSource> {
+        let indexes = self.indexes.read().unwrap().clone();
+        let overlay = self.overlay().map(|overlay| overlay.clone());
+        let snapshot = match self.snapshot {
+            Some(ref snapshot) => snapshot.clone(),
+            None => Arc::new(OwnedSnapshot::new(self.db.clone())),
+        };
+        Arc::new(RocksdbSource {
+            db: self.db.clone(),
+            key_prefixes: self.key_prefixes.clone(),
+            indexes,
+            overlay,
+            snapshot,
+        })
+    }
 }
 
 impl Datastore for RocksdbDatastore {
     fn sync(&self) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
         VertexManager::new(db_ref).compact();
+        VertexTypeManager::new(db_ref).compact();
         EdgeManager::new(db_ref).compact();
         EdgeRangeManager::new(db_ref).compact();
         EdgeRangeManager::new_reversed(db_ref).compact();
@@ -440,31 +2062,175 @@ impl Datastore for RocksdbDatastore {
         EdgePropertyManager::new(db_ref).compact();
         VertexPropertyValueManager::new(db_ref).compact();
         EdgePropertyValueManager::new(db_ref).compact();
+        VertexTypePropertyValueManager::new(db_ref).compact();
+        EdgeTypePropertyValueManager::new(db_ref).compact();
+        VertexCompositePropertyValueManager::new(db_ref).compact();
+        EdgeCompositePropertyValueManager::new(db_ref).compact();
+        TypeCountManager::new(db_ref).compact();
         MetadataManager::new(&db).compact();
         db.flush()?;
         Ok(())
     }
 
+    fn transaction(&self) -> Result<Self> {
+        // Nested transactions aren't supported
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        Ok(RocksdbDatastore {
+            db: self.db.clone(),
+            key_prefixes: self.key_prefixes.clone(),
+            indexes: self.indexes.clone(),
+            unique_lock: self.unique_lock.clone(),
+            schema: self.schema.clone(),
+            transaction: Some(Arc::new(TransactionState::default())),
+            snapshot: None,
+            index_builds: self.index_builds.clone(),
+        })
+    }
+
+    fn backup(&self, path: &Path) -> Result<()> {
+        // Backups to the same path are incremental: files that are already
+        // in an earlier backup are shared rather than copied again. The
+        // memtables are flushed first, so the write-ahead log isn't needed
+        // to restore.
+        let mut engine = BackupEngine::open(&BackupEngineOptions::default(), path)?;
+        engine.create_new_backup_flush(&self.db, true)?;
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Result<Box<dyn Datastore + Send + Sync>> {
+        let indexes = self.indexes.read().unwrap().clone();
+        let overlay = self.overlay().map(|overlay| {
+            Arc::new(TransactionState {
+                overlay: Mutex::new(overlay.clone()),
+                mutations: Mutex::default(),
+            })
+        });
+
+        Ok(Box::new(ReadOnlyDatastore::new(RocksdbDatastore {
+            db: self.db.clone(),
+            key_prefixes: self.key_prefixes.clone(),
+            indexes: Arc::new(RwLock::new(indexes)),
+            unique_lock: self.unique_lock.clone(),
+            schema: self.schema.clone(),
+            transaction: overlay,
+            snapshot: Some(Arc::new(OwnedSnapshot::new(self.db.clone()))),
+            index_builds: self.index_builds.clone(),
+        })))
+    }
+
+    fn commit(&self) -> Result<()> {
+        let indexes = self.indexes.read().unwrap();
+        let transaction = self.transaction.as_ref().ok_or(Error::Unsupported)?;
+        let mut overlay = transaction.overlay.lock().unwrap();
+        let mut mutations = transaction.mutations.lock().unwrap();
+
+        // The transaction's writes are replayed against the latest state of
+        // the database and its indexes, which may have changed since they
+        // were staged.
+        let mut replayed = Overlay::default();
+        for mutation in mutations.iter() {
+            let db_ref = DBRef::new(&self.db, &self.key_prefixes, &indexes, Some(&replayed), None);
+            let mut batch = db_ref.batch();
+            replay_mutation(db_ref, &mut batch, mutation)?;
+            batch.write(&self.db, Some(&mut replayed))?;
+        }
+
+        // Vertices and edges created in a transaction can have their
+        // required properties set after they're created, so they're only
+        // checked now.
+        if let Some(ref schema) = *self.schema.read().unwrap() {
+            let db_ref = DBRef::new(&self.db, &self.key_prefixes, &indexes, Some(&replayed), None);
+            check_staged_required_properties(schema, db_ref)?;
+        }
+
+        // Unique constraints are checked again against the latest state of
+        // the database, since other writes may have been made since the
+        // transaction's were.
+        let _unique_guard = if indexes.unique_properties.is_empty() {
+            None
+        } else {
+            let guard = self.unique_lock.lock().unwrap();
+            let db_ref = DBRef::new(&self.db, &self.key_prefixes, &indexes, Some(&replayed), None);
+            check_staged_unique_properties(db_ref)?;
+            Some(guard)
+        };
+
+        let mut batch = WriteBatch::default();
+
+        for (cf_name, staged) in replayed.iter() {
+            let cf = self.db.cf_handle(cf_name).unwrap();
+            for (key, value) in staged {
+                match value {
+                    // Staged counts are changes to be added to the counts.
+                    Some(value) if *cf_name == TYPE_COUNTS_CF => batch.merge_cf(cf, key, value),
+                    Some(value) => batch.put_cf(cf, key, value),
+                    None => batch.delete_cf(cf, key),
+                }
+            }
+        }
+
+        self.db.write(batch)?;
+        overlay.clear();
+        mutations.clear();
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<()> {
+        let transaction = self.transaction.as_ref().ok_or(Error::Unsupported)?;
+        let mut overlay = transaction.overlay.lock().unwrap();
+        overlay.clear();
+        transaction.mutations.lock().unwrap().clear();
+        Ok(())
+    }
+
     fn create_vertex(&self, vertex: &Vertex) -> Result<bool> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let vertex_manager = VertexManager::new(db_ref);
 
         if vertex_manager.exists(vertex.id)? {
             Ok(false)
         } else {
-            let mut batch = WriteBatch::default();
+            if let Some(ref schema) = *self.schema.read().unwrap() {
+                // A transaction's vertices can have their required
+                // properties set after they're created, so they're checked
+                // on commit.
+                if overlay.is_some() {
+                    schema.check_vertex_type(&vertex.t)?;
+                } else {
+                    schema.check_vertex(&vertex.t, &HashSet::new())?;
+                }
+            }
+            let mut batch = db_ref.batch();
             vertex_manager.create(&mut batch, vertex)?;
-            db.write(batch)?;
+            batch.write(&db, overlay.as_deref_mut())?;
+            self.record(|| Mutation::CreateVertex(vertex.clone()));
             Ok(true)
         }
     }
 
     fn get_vertices(&self, q: VertexQuery) -> Result<Vec<Vertex>> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let iter = execute_vertex_query(db_ref, q)?.into_iter();
 
         let iter = iter.map(move |(id, t)| {
@@ -475,52 +2241,117 @@ impl Datastore for RocksdbDatastore {
         iter.collect()
     }
 
+    fn get_vertices_iter(&self, q: VertexQuery) -> Result<DynIter<'_, Vertex>> {
+        let iter = cursor::iter_vertices(self.cursor_source(), q)?;
+        Ok(Box::new(iter.map(|item| item.map(|(id, t)| Vertex::with_id(id, t)))))
+    }
+
+    fn get_vertices_page(&self, q: VertexQuery, limit: u32, token: Option<ContinuationToken>) -> Result<Page<Vertex>> {
+        let source = self.cursor_source();
+        cursor::page_vertices(&*source, fingerprint(&q), &q, limit, token, |(id, t)| {
+            Ok(Some(Vertex::with_id(id, t)))
+        })
+    }
+
     fn delete_vertices(&self, q: VertexQuery) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
-        let iter = execute_vertex_query(db_ref, q)?.into_iter();
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
+        let ids: Vec<Uuid> = execute_vertex_query(db_ref, q)?.into_iter().map(|(id, _)| id).collect();
         let vertex_manager = VertexManager::new(db_ref);
-        let mut batch = WriteBatch::default();
+        let mut batch = db_ref.batch();
 
-        for (id, _) in iter {
-            vertex_manager.delete(&mut batch, id)?;
+        for id in &ids {
+            vertex_manager.delete(&mut batch, *id)?;
         }
 
-        db.write(batch)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| Mutation::DeleteVertices(ids));
         Ok(())
     }
 
     fn get_vertex_count(&self) -> Result<u64> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let vertex_manager = VertexManager::new(db_ref);
-        let iterator = vertex_manager.iterate_for_range(Uuid::default());
-        Ok(iterator.count() as u64)
+        Ok(vertex_manager.count_for_range(Uuid::default(), usize::MAX) as u64)
+    }
+
+    fn count_vertices(&self, q: VertexQuery) -> Result<u64> {
+        let db = self.db.clone();
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
+        count_vertex_query(db_ref, q)
     }
 
     fn create_edge(&self, key: &EdgeKey) -> Result<bool> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let vertex_manager = VertexManager::new(db_ref);
 
         if !vertex_manager.exists(key.outbound_id)? || !vertex_manager.exists(key.inbound_id)? {
             Ok(false)
         } else {
+            if let Some(ref schema) = *self.schema.read().unwrap() {
+                let outbound_t = vertex_manager.get(key.outbound_id)?;
+                let inbound_t = vertex_manager.get(key.inbound_id)?;
+                if overlay.is_some() {
+                    schema.check_edge_type(&key.t, outbound_t.as_ref(), inbound_t.as_ref())?;
+                } else {
+                    schema.check_edge(&key.t, outbound_t.as_ref(), inbound_t.as_ref(), &HashSet::new())?;
+                }
+            }
             let edge_manager = EdgeManager::new(db_ref);
-            let mut batch = WriteBatch::default();
-            edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
-            db.write(batch)?;
+            let mut batch = db_ref.batch();
+            let update_datetime = Utc::now();
+            edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, update_datetime)?;
+            batch.write(&db, overlay.as_deref_mut())?;
+            self.record(|| Mutation::CreateEdge(key.clone(), update_datetime));
             Ok(true)
         }
     }
 
     fn get_edges(&self, q: EdgeQuery) -> Result<Vec<Edge>> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let iter = execute_edge_query(db_ref, q)?.into_iter();
 
         let iter = iter.map(move |(out_id, t, update_datetime, in_id)| {
@@ -532,44 +2363,124 @@ impl Datastore for RocksdbDatastore {
         iter.collect()
     }
 
+    fn get_edges_iter(&self, q: EdgeQuery) -> Result<DynIter<'_, Edge>> {
+        let iter = cursor::iter_edges(self.cursor_source(), q)?;
+        Ok(Box::new(iter.map(|item| {
+            item.map(|(key, update_datetime)| Edge::new(key, update_datetime))
+        })))
+    }
+
+    fn get_edges_page(&self, q: EdgeQuery, limit: u32, token: Option<ContinuationToken>) -> Result<Page<Edge>> {
+        let source = self.cursor_source();
+        cursor::page_edges(&*source, fingerprint(&q), &q, limit, token, |(key, update_datetime)| {
+            Ok(Some(Edge::new(key, update_datetime)))
+        })
+    }
+
+    fn get_paths(&self, q: PathQuery) -> Result<Vec<Vec<Edge>>> {
+        let db = self.db.clone();
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
+
+        if !VertexManager::new(db_ref).exists(q.start_id)? {
+            return Ok(Vec::new());
+        }
+
+        let edge_range_manager = EdgeRangeManager::new(db_ref);
+        util::shortest_paths(q.start_id, q.end_id, q.max_depth, q.limit, |id| {
+            edge_range_manager
+                .iterate_for_range(id, q.t.as_ref(), None)?
+                .map(|item| {
+                    let (out_id, t, update_datetime, in_id) = item?;
+                    Ok(Edge::new(EdgeKey::new(out_id, t, in_id), update_datetime))
+                })
+                .collect()
+        })
+    }
+
     fn delete_edges(&self, q: EdgeQuery) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let edge_manager = EdgeManager::new(db_ref);
         let vertex_manager = VertexManager::new(db_ref);
         let iter = execute_edge_query(db_ref, q)?;
-        let mut batch = WriteBatch::default();
+        let mut batch = db_ref.batch();
+        let mut keys = Vec::new();
 
         for (out_id, t, update_datetime, in_id) in iter {
             if vertex_manager.get(out_id)?.is_some() {
                 edge_manager.delete(&mut batch, out_id, &t, in_id, update_datetime)?;
+                keys.push(EdgeKey::new(out_id, t, in_id));
             };
         }
 
-        db.write(batch)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| Mutation::DeleteEdges(keys));
         Ok(())
     }
 
     fn get_edge_count(&self, id: Uuid, t: Option<&Identifier>, direction: EdgeDirection) -> Result<u64> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
 
         let edge_range_manager = match direction {
             EdgeDirection::Outbound => EdgeRangeManager::new(db_ref),
             EdgeDirection::Inbound => EdgeRangeManager::new_reversed(db_ref),
         };
 
-        let count = edge_range_manager.iterate_for_range(id, t, None)?.count();
+        let count = edge_range_manager.count_for_range(id, t, usize::MAX);
 
         Ok(count as u64)
     }
 
+    fn count_edges(&self, q: EdgeQuery) -> Result<u64> {
+        let db = self.db.clone();
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
+        count_edge_query(db_ref, q)
+    }
+
     fn get_vertex_properties(&self, q: VertexPropertyQuery) -> Result<Vec<VertexProperty>> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let manager = VertexPropertyManager::new(db_ref);
         let mut properties = Vec::new();
 
@@ -584,10 +2495,44 @@ impl Datastore for RocksdbDatastore {
         Ok(properties)
     }
 
+    fn get_vertex_properties_iter(&self, q: VertexPropertyQuery) -> Result<DynIter<'_, VertexProperty>> {
+        let source = self.cursor_source();
+        let vertices = cursor::iter_vertices(source.clone(), q.inner)?;
+        let name = q.name;
+        Ok(Box::new(vertices.filter_map(move |item| {
+            let value = item.and_then(|(id, _)| Ok(source.vertex_property(id, &name)?.map(|value| (id, value))));
+            match value {
+                Ok(Some((id, value))) => Some(Ok(VertexProperty::new(id, value.0))),
+                Ok(None) => None,
+                Err(err) => Some(Err(err)),
+            }
+        })))
+    }
+
+    fn get_vertex_properties_page(
+        &self,
+        q: VertexPropertyQuery,
+        limit: u32,
+        token: Option<ContinuationToken>,
+    ) -> Result<Page<VertexProperty>> {
+        let source = self.cursor_source();
+        cursor::page_vertices(&*source, fingerprint(&q), &q.inner, limit, token, |(id, _)| {
+            let value = source.vertex_property(id, &q.name)?;
+            Ok(value.map(|value| VertexProperty::new(id, value.0)))
+        })
+    }
+
     fn get_all_vertex_properties(&self, q: VertexQuery) -> Result<Vec<VertexProperties>> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let iter = execute_vertex_query(db_ref, q)?.into_iter();
         let manager = VertexPropertyManager::new(db_ref);
 
@@ -607,41 +2552,95 @@ impl Datastore for RocksdbDatastore {
         iter.collect()
     }
 
+    fn get_all_vertex_properties_iter(&self, q: VertexQuery) -> Result<DynIter<'_, VertexProperties>> {
+        let source = self.cursor_source();
+        let vertices = cursor::iter_vertices(source.clone(), q)?;
+        Ok(Box::new(vertices.map(move |item| {
+            let (id, t) = item?;
+            let properties = source.all_vertex_properties(id)?;
+            Ok(VertexProperties::new(Vertex::with_id(id, t), properties))
+        })))
+    }
+
     fn set_vertex_properties(&self, q: VertexPropertyQuery, value: serde_json::Value) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let manager = VertexPropertyManager::new(db_ref);
-        let mut batch = WriteBatch::default();
+        let mut batch = db_ref.batch();
+        let _unique_guard = self.lock_unique_properties(indexes.unique_properties.contains_key(&q.name));
 
         let wrapped_value = Json::new(value);
-        for (id, _) in execute_vertex_query(db_ref, q.inner)?.into_iter() {
-            manager.set(&mut batch, id, &q.name, &wrapped_value)?;
+        let vertices = execute_vertex_query(db_ref, q.inner)?;
+        if let Some(ref schema) = *self.schema.read().unwrap() {
+            for (_, t) in &vertices {
+                schema.check_vertex_property(t, &q.name, Some(&wrapped_value.0))?;
+            }
+        }
+        if indexes.unique_properties.contains_key(&q.name) {
+            let setting: Vec<(Uuid, Option<&Identifier>)> = vertices.iter().map(|(id, t)| (*id, Some(t))).collect();
+            let changing: HashSet<Uuid> = vertices.iter().map(|(id, _)| *id).collect();
+            check_unique_vertex_property(db_ref, &q.name, &wrapped_value, &setting, &changing)?;
+        }
+        for (id, t) in &vertices {
+            manager.set(&mut batch, *id, Some(t), &q.name, &wrapped_value)?;
         }
 
-        db.write(batch)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| {
+            let ids = vertices.into_iter().map(|(id, _)| id).collect();
+            Mutation::SetVertexProperties(ids, q.name, wrapped_value)
+        });
         Ok(())
     }
 
     fn delete_vertex_properties(&self, q: VertexPropertyQuery) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let manager = VertexPropertyManager::new(db_ref);
-        let mut batch = WriteBatch::default();
+        let mut batch = db_ref.batch();
+        let schema = self.schema.read().unwrap();
+        let mut ids = Vec::new();
 
-        for (id, _) in execute_vertex_query(db_ref, q.inner)?.into_iter() {
-            manager.delete(&mut batch, id, &q.name)?;
+        for (id, t) in execute_vertex_query(db_ref, q.inner)?.into_iter() {
+            if let Some(ref schema) = *schema {
+                schema.check_vertex_property(&t, &q.name, None)?;
+            }
+            manager.delete(&mut batch, id, Some(&t), &q.name)?;
+            ids.push(id);
         }
 
-        db.write(batch)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| Mutation::DeleteVertexProperties(ids, q.name));
         Ok(())
     }
 
     fn get_edge_properties(&self, q: EdgePropertyQuery) -> Result<Vec<EdgeProperty>> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let manager = EdgePropertyManager::new(db_ref);
         let mut properties = Vec::new();
 
@@ -657,10 +2656,44 @@ impl Datastore for RocksdbDatastore {
         Ok(properties)
     }
 
+    fn get_edge_properties_iter(&self, q: EdgePropertyQuery) -> Result<DynIter<'_, EdgeProperty>> {
+        let source = self.cursor_source();
+        let edges = cursor::iter_edges(source.clone(), q.inner)?;
+        let name = q.name;
+        Ok(Box::new(edges.filter_map(move |item| {
+            let value = item.and_then(|(key, _)| Ok(source.edge_property(&key, &name)?.map(|value| (key, value))));
+            match value {
+                Ok(Some((key, value))) => Some(Ok(EdgeProperty::new(key, value.0))),
+                Ok(None) => None,
+                Err(err) => Some(Err(err)),
+            }
+        })))
+    }
+
+    fn get_edge_properties_page(
+        &self,
+        q: EdgePropertyQuery,
+        limit: u32,
+        token: Option<ContinuationToken>,
+    ) -> Result<Page<EdgeProperty>> {
+        let source = self.cursor_source();
+        cursor::page_edges(&*source, fingerprint(&q), &q.inner, limit, token, |(key, _)| {
+            let value = source.edge_property(&key, &q.name)?;
+            Ok(value.map(|value| EdgeProperty::new(key, value.0)))
+        })
+    }
+
     fn get_all_edge_properties(&self, q: EdgeQuery) -> Result<Vec<EdgeProperties>> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let iter = execute_edge_query(db_ref, q)?.into_iter();
         let manager = EdgePropertyManager::new(db_ref);
 
@@ -679,34 +2712,88 @@ impl Datastore for RocksdbDatastore {
         iter.collect()
     }
 
+    fn get_all_edge_properties_iter(&self, q: EdgeQuery) -> Result<DynIter<'_, EdgeProperties>> {
+        let source = self.cursor_source();
+        let edges = cursor::iter_edges(source.clone(), q)?;
+        Ok(Box::new(edges.map(move |item| {
+            let (key, update_datetime) = item?;
+            let properties = source.all_edge_properties(&key)?;
+            Ok(EdgeProperties::new(Edge::new(key, update_datetime), properties))
+        })))
+    }
+
     fn set_edge_properties(&self, q: EdgePropertyQuery, value: serde_json::Value) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let manager = EdgePropertyManager::new(db_ref);
-        let mut batch = WriteBatch::default();
+        let mut batch = db_ref.batch();
+        let _unique_guard = self.lock_unique_properties(indexes.unique_properties.contains_key(&q.name));
 
         let wrapped_value = Json::new(value);
-        for (out_id, t, _, in_id) in execute_edge_query(db_ref, q.inner)?.into_iter() {
-            manager.set(&mut batch, out_id, &t, in_id, &q.name, &wrapped_value)?;
+        let edges = execute_edge_query(db_ref, q.inner)?;
+        if let Some(ref schema) = *self.schema.read().unwrap() {
+            for (_, t, _, _) in &edges {
+                schema.check_edge_property(t, &q.name, Some(&wrapped_value.0))?;
+            }
+        }
+        if indexes.unique_properties.contains_key(&q.name) {
+            let keys: Vec<EdgeKey> = edges
+                .iter()
+                .map(|(out_id, t, _, in_id)| EdgeKey::new(*out_id, t.clone(), *in_id))
+                .collect();
+            let setting: Vec<&EdgeKey> = keys.iter().collect();
+            let changing: HashSet<&EdgeKey> = keys.iter().collect();
+            check_unique_edge_property(db_ref, &q.name, &wrapped_value, &setting, &changing)?;
+        }
+        for (out_id, t, _, in_id) in &edges {
+            manager.set(&mut batch, *out_id, t, *in_id, &q.name, &wrapped_value)?;
         }
 
-        db.write(batch)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| {
+            let keys = edges
+                .into_iter()
+                .map(|(out_id, t, _, in_id)| EdgeKey::new(out_id, t, in_id))
+                .collect();
+            Mutation::SetEdgeProperties(keys, q.name, wrapped_value)
+        });
         Ok(())
     }
 
     fn delete_edge_properties(&self, q: EdgePropertyQuery) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let manager = EdgePropertyManager::new(db_ref);
-        let mut batch = WriteBatch::default();
+        let mut batch = db_ref.batch();
+        let schema = self.schema.read().unwrap();
+        let mut keys = Vec::new();
 
         for (out_id, t, _, in_id) in execute_edge_query(db_ref, q.inner)?.into_iter() {
+            if let Some(ref schema) = *schema {
+                schema.check_edge_property(&t, &q.name, None)?;
+            }
             manager.delete(&mut batch, out_id, &t, in_id, &q.name)?;
+            keys.push(EdgeKey::new(out_id, t, in_id));
         }
 
-        db.write(batch)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| Mutation::DeleteEdgeProperties(keys, q.name));
         Ok(())
     }
 
@@ -714,75 +2801,342 @@ impl Datastore for RocksdbDatastore {
     // optimization can be done by using `WriteBatch`s.
     fn bulk_insert(&self, items: Vec<BulkInsertItem>) -> Result<()> {
         let db = self.db.clone();
-        let indexed_properties = self.indexed_properties.read().unwrap();
-        let db_ref = DBRef::new(&db, &indexed_properties);
+        let indexes = self.indexes.read().unwrap();
+        let mut overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
         let vertex_manager = VertexManager::new(db_ref);
-        let edge_manager = EdgeManager::new(db_ref);
-        let vertex_property_manager = VertexPropertyManager::new(db_ref);
-        let edge_property_manager = EdgePropertyManager::new(db_ref);
-        let mut batch = WriteBatch::default();
-
-        for item in items {
-            match item {
-                BulkInsertItem::Vertex(ref vertex) => {
-                    vertex_manager.create(&mut batch, vertex)?;
-                }
-                BulkInsertItem::Edge(ref key) => {
-                    edge_manager.set(&mut batch, key.outbound_id, &key.t, key.inbound_id, Utc::now())?;
-                }
-                BulkInsertItem::VertexProperty(id, ref name, ref value) => {
-                    vertex_property_manager.set(&mut batch, id, name, &Json::new(value.clone()))?;
-                }
-                BulkInsertItem::EdgeProperty(ref key, ref name, ref value) => {
-                    edge_property_manager.set(
-                        &mut batch,
-                        key.outbound_id,
-                        &key.t,
-                        key.inbound_id,
-                        name,
-                        &Json::new(value.clone()),
-                    )?;
-                }
-            }
+        let mut batch = db_ref.batch();
+        let _unique_guard = self.lock_unique_properties(!indexes.unique_properties.is_empty());
+
+        // The schema is held until the insert is written, so that it can't
+        // be changed after the insert is checked against it.
+        let schema = self.schema.read().unwrap();
+        if let Some(ref schema) = *schema {
+            schema.check_bulk_insert(&items, |id| vertex_manager.get(id))?;
         }
 
-        self.db.write(batch)?;
+        write_bulk_insert(db_ref, &mut batch, &items)?;
+        batch.write(&db, overlay.as_deref_mut())?;
+        self.record(|| Mutation::BulkInsert(items));
         Ok(())
     }
 
     fn index_property(&self, name: Identifier) -> Result<()> {
-        let mut indexed_properties = self.indexed_properties.write().unwrap();
-        if !indexed_properties.insert(name.clone()) {
+        // Indexes are shared by every handle to the datastore, so they can't
+        // be changed from within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        match self.start_index_build(&name, true)? {
+            Some(build_id) => self.run_index_build(&name, build_id, None),
+            None => Ok(()),
+        }
+    }
+
+    fn index_property_in_background(&self, name: Identifier) -> Result<()> {
+        // Like building an index in the foreground, this can't be done from
+        // within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        if let Some(build_id) = self.start_index_build(&name, false)? {
+            self.spawn_index_build(name, build_id);
+        }
+        Ok(())
+    }
+
+    fn drop_index(&self, name: Identifier) -> Result<()> {
+        // Like adding an index, this can't be done from within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut indexes = self.indexes.write().unwrap();
+        if indexes.properties.remove(&name).is_none() {
             return Ok(());
         }
 
+        // Unique constraints are enforced through the index, so they're
+        // dropped along with it.
+        let had_unique_constraints = indexes.unique_properties.remove(&name).is_some();
+
         let db = self.db.clone();
-        let db_ref = DBRef::new(&db, &indexed_properties);
-        let mut batch = WriteBatch::default();
-        let vertex_manager = VertexManager::new(db_ref);
-        let edge_range_manager = EdgeRangeManager::new(db_ref);
-        let vertex_property_manager = VertexPropertyManager::new(db_ref);
-        let edge_property_manager = EdgePropertyManager::new(db_ref);
-        let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
-        let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
+        let mut batch = db_ref.batch();
         let metadata_manager = MetadataManager::new(&db);
-        metadata_manager.set_indexed_properties(&mut batch, &indexed_properties)?;
+        metadata_manager.set_indexed_properties(&mut batch, &indexes.properties)?;
+        if had_unique_constraints {
+            metadata_manager.set_unique_properties(&mut batch, &indexes.unique_properties)?;
+        }
+        VertexPropertyValueManager::new(db_ref).delete_for_name(&mut batch, &name);
+        EdgePropertyValueManager::new(db_ref).delete_for_name(&mut batch, &name);
+        batch.write(&db, None)?;
+        Ok(())
+    }
 
-        for item in vertex_manager.iterate_for_range(Uuid::default()) {
-            let (vertex_id, _) = item?;
-            if let Some(property_value) = vertex_property_manager.get(vertex_id, &name)? {
-                vertex_property_value_manager.set(&mut batch, vertex_id, &name, &property_value);
-            }
+    fn get_indexed_properties(&self) -> Result<Vec<Identifier>> {
+        let mut names: Vec<Identifier> = self.indexes.read().unwrap().properties.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn get_index_status(&self, name: Identifier) -> Result<Option<IndexStatus>> {
+        let indexes = self.indexes.read().unwrap();
+        Ok(indexes.properties.get(&name).map(|state| state.status.clone()))
+    }
+
+    fn index_type_property(&self, t: Identifier, name: Identifier) -> Result<()> {
+        // Like other indexes, these can't be added from within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
         }
 
-        for item in edge_range_manager.iterate_for_all() {
-            let (out_id, t, _, in_id) = item?;
-            if let Some(property_value) = edge_property_manager.get(out_id, &t, in_id, &name)? {
-                edge_property_value_manager.set(&mut batch, out_id, &t, in_id, &name, &property_value);
-            }
+        let mut indexes = self.indexes.write().unwrap();
+        if indexes.is_type_indexed(&t, &name) {
+            return Ok(());
         }
 
-        db.write(batch)?;
+        // The index is filled in by the same batch that records it, so the
+        // write lock is held for the whole build.
+        let mut ready_indexes = indexes.clone();
+        ready_indexes
+            .type_properties
+            .entry(name.clone())
+            .or_default()
+            .insert(t.clone());
+        let db = self.db.clone();
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &ready_indexes, None, None);
+        let mut batch = db_ref.batch();
+        build_type_property_value_index(db_ref, &mut batch, &t, &name)?;
+        MetadataManager::new(&db).set_indexed_type_properties(&mut batch, &ready_indexes.type_properties)?;
+        batch.write(&db, None)?;
+        *indexes = ready_indexes;
         Ok(())
     }
+
+    fn drop_type_index(&self, t: Identifier, name: Identifier) -> Result<()> {
+        // Like adding an index, this can't be done from within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut indexes = self.indexes.write().unwrap();
+        let removed = match indexes.type_properties.get_mut(&name) {
+            Some(types) => types.remove(&t),
+            None => false,
+        };
+        if !removed {
+            return Ok(());
+        }
+        if indexes.type_properties[&name].is_empty() {
+            indexes.type_properties.remove(&name);
+        }
+
+        let db = self.db.clone();
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
+        let mut batch = db_ref.batch();
+        MetadataManager::new(&db).set_indexed_type_properties(&mut batch, &indexes.type_properties)?;
+        VertexTypePropertyValueManager::new(db_ref).delete_for_type_property(&mut batch, &t, &name);
+        EdgeTypePropertyValueManager::new(db_ref).delete_for_type_property(&mut batch, &t, &name);
+        batch.write(&db, None)?;
+        Ok(())
+    }
+
+    fn get_indexed_type_properties(&self) -> Result<Vec<(Identifier, Identifier)>> {
+        let indexes = self.indexes.read().unwrap();
+        let mut type_properties: Vec<(Identifier, Identifier)> = indexes
+            .type_properties
+            .iter()
+            .flat_map(|(name, types)| types.iter().map(move |t| (t.clone(), name.clone())))
+            .collect();
+        type_properties.sort();
+        Ok(type_properties)
+    }
+
+    fn index_composite_properties(&self, names: Vec<Identifier>) -> Result<()> {
+        if !util::is_valid_composite_index(&names) {
+            return Err(Error::Unsupported);
+        }
+
+        // Like other indexes, these can't be added from within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut indexes = self.indexes.write().unwrap();
+        if indexes.composite_properties.contains(&names) {
+            return Ok(());
+        }
+
+        // Like with indexes scoped to a type, the index is filled in by the
+        // same batch that records it.
+        let mut ready_indexes = indexes.clone();
+        ready_indexes.composite_properties.insert(names.clone());
+        let db = self.db.clone();
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &ready_indexes, None, None);
+        let mut batch = db_ref.batch();
+        build_composite_property_value_index(db_ref, &mut batch, &names)?;
+        MetadataManager::new(&db).set_indexed_composite_properties(&mut batch, &ready_indexes.composite_properties)?;
+        batch.write(&db, None)?;
+        *indexes = ready_indexes;
+        Ok(())
+    }
+
+    fn drop_composite_index(&self, names: Vec<Identifier>) -> Result<()> {
+        // Like adding an index, this can't be done from within a transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut indexes = self.indexes.write().unwrap();
+        if !indexes.composite_properties.remove(&names) {
+            return Ok(());
+        }
+
+        let db = self.db.clone();
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
+        let mut batch = db_ref.batch();
+        MetadataManager::new(&db).set_indexed_composite_properties(&mut batch, &indexes.composite_properties)?;
+        VertexCompositePropertyValueManager::new(db_ref).delete_for_names(&mut batch, &names);
+        EdgeCompositePropertyValueManager::new(db_ref).delete_for_names(&mut batch, &names);
+        batch.write(&db, None)?;
+        Ok(())
+    }
+
+    fn get_indexed_composite_properties(&self) -> Result<Vec<Vec<Identifier>>> {
+        let indexes = self.indexes.read().unwrap();
+        let mut composite_properties: Vec<Vec<Identifier>> = indexes.composite_properties.iter().cloned().collect();
+        composite_properties.sort();
+        Ok(composite_properties)
+    }
+
+    fn add_unique_constraint(&self, name: Identifier, t: Option<Identifier>) -> Result<()> {
+        // Like indexes, constraints can't be changed from within a
+        // transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut indexes = self.indexes.write().unwrap();
+        let db = self.db.clone();
+        let db_ref = DBRef::new(&db, &self.key_prefixes, &indexes, None, None);
+        guard_indexed_property(db_ref, &name)?;
+        match indexes.unique_properties.get(&name) {
+            Some(scopes) if scopes.contains(&t) => return Ok(()),
+            _ => {}
+        }
+
+        // The write lock keeps out other writes while the existing values
+        // are checked.
+        check_existing_unique_property(db_ref, &name, &t)?;
+        let mut unique_properties = indexes.unique_properties.clone();
+        unique_properties.entry(name).or_default().insert(t);
+        let mut batch = db_ref.batch();
+        MetadataManager::new(&db).set_unique_properties(&mut batch, &unique_properties)?;
+        batch.write(&db, None)?;
+        indexes.unique_properties = unique_properties;
+        Ok(())
+    }
+
+    fn drop_unique_constraint(&self, name: Identifier, t: Option<Identifier>) -> Result<()> {
+        // Like adding a constraint, this can't be done from within a
+        // transaction.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let mut indexes = self.indexes.write().unwrap();
+        let removed = match indexes.unique_properties.get_mut(&name) {
+            Some(scopes) => scopes.remove(&t),
+            None => false,
+        };
+        if !removed {
+            return Ok(());
+        }
+        if indexes.unique_properties[&name].is_empty() {
+            indexes.unique_properties.remove(&name);
+        }
+
+        let db = self.db.clone();
+        let mut batch = DBRef::new(&db, &self.key_prefixes, &indexes, None, None).batch();
+        MetadataManager::new(&db).set_unique_properties(&mut batch, &indexes.unique_properties)?;
+        batch.write(&db, None)?;
+        Ok(())
+    }
+
+    fn get_unique_constraints(&self) -> Result<Vec<(Identifier, Option<Identifier>)>> {
+        let indexes = self.indexes.read().unwrap();
+        let mut constraints: Vec<(Identifier, Option<Identifier>)> = indexes
+            .unique_properties
+            .iter()
+            .flat_map(|(name, scopes)| scopes.iter().map(move |t| (name.clone(), t.clone())))
+            .collect();
+        constraints.sort();
+        Ok(constraints)
+    }
+
+    fn set_schema(&self, schema: Option<Schema>) -> Result<()> {
+        // As with constraints, the schema can't be changed from within a
+        // transaction, since its writes are checked against it as they're
+        // made.
+        if self.transaction.is_some() {
+            return Err(Error::Unsupported);
+        }
+
+        let indexes = self.indexes.read().unwrap();
+        let mut current = self.schema.write().unwrap();
+        let db = self.db.clone();
+        let mut batch = DBRef::new(&db, &self.key_prefixes, &indexes, None, None).batch();
+        MetadataManager::new(&db).set_schema(&mut batch, &schema)?;
+        batch.write(&db, None)?;
+        *current = schema;
+        Ok(())
+    }
+
+    fn get_schema(&self) -> Result<Option<Schema>> {
+        Ok(self.schema.read().unwrap().clone())
+    }
+
+    fn get_schema_summary(&self) -> Result<SchemaSummary> {
+        let db = self.db.clone();
+        let indexes = self.indexes.read().unwrap();
+        let overlay = self.overlay();
+        let db_ref = DBRef::new(
+            &db,
+            &self.key_prefixes,
+            &indexes,
+            overlay.as_deref(),
+            self.db_snapshot(),
+        );
+
+        let mut summary = SchemaSummary::default();
+        for (count, value) in TypeCountManager::new(db_ref).get_all() {
+            if value <= 0 {
+                continue;
+            }
+            match count {
+                TypeCount::Vertices(t) => {
+                    summary.vertex_types.insert(t, value as u64);
+                }
+                TypeCount::Edges(t) => {
+                    summary.edge_types.insert(t, value as u64);
+                }
+                TypeCount::VertexProperties(t, name) => {
+                    summary.vertex_properties.entry(t).or_default().insert(name);
+                }
+                TypeCount::EdgeProperties(t, name) => {
+                    summary.edge_properties.entry(t).or_default().insert(name);
+                }
+            }
+        }
+        Ok(summary)
+    }
 }
@@ -1,7 +1,11 @@
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::btree_map::Range;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::io::Cursor;
+use std::iter::Peekable;
 use std::ops::Deref;
-use std::u8;
+use std::sync::Arc;
 
 use crate::errors::Result;
 use crate::models;
@@ -9,45 +13,557 @@ use crate::util;
 
 use chrono::offset::Utc;
 use chrono::DateTime;
-use rocksdb::{ColumnFamily, DBIterator, Direction, IteratorMode, WriteBatch, DB};
+use rocksdb::{
+    ColumnFamily, DBIterator, Direction, IteratorMode, MergeOperands, ReadOptions, SliceTransform, Snapshot,
+    WriteBatch, DB,
+};
 use uuid::Uuid;
 
 pub type OwnedPropertyItem = ((Uuid, models::Identifier), models::Json);
 pub type VertexItem = (Uuid, models::Identifier);
 pub type EdgeRangeItem = (Uuid, models::Identifier, DateTime<Utc>, Uuid);
 pub type EdgePropertyItem = ((Uuid, models::Identifier, Uuid, models::Identifier), models::Json);
-pub type VertexPropertyValueKey = (models::Identifier, u64, Uuid);
-pub type EdgePropertyValueKey = (models::Identifier, u64, (Uuid, models::Identifier, Uuid));
+pub type VertexPropertyValueKey = (models::Identifier, models::Json, Uuid);
+pub type EdgePropertyValueKey = (models::Identifier, models::Json, (Uuid, models::Identifier, Uuid));
+
+// The state of a property's index. Builds are numbered, so that a build can
+// tell whether the index was dropped and re-added, or rebuilt, while it was
+// running.
+#[derive(Clone, Debug)]
+pub(crate) struct IndexState {
+    pub status: models::IndexStatus,
+    pub build_id: u64,
+}
+
+// The indexed properties, including ones whose indexes aren't ready yet.
+// Writes keep all of their indexes up to date, but only ready ones can be
+// queried.
+pub(crate) type IndexedProperties = HashMap<models::Identifier, IndexState>;
+
+// The properties with indexes scoped to a type, keyed by property name, with
+// the types whose vertices and edges each one covers.
+pub(crate) type IndexedTypeProperties = HashMap<models::Identifier, HashSet<models::Identifier>>;
+
+// The property names of each composite index, in order.
+pub(crate) type IndexedCompositeProperties = HashSet<Vec<models::Identifier>>;
+
+// The scopes of the unique constraints on each property, where a scope of
+// `None` covers every vertex and edge, and otherwise covers those of one
+// type.
+pub(crate) type UniqueProperties = HashMap<models::Identifier, HashSet<Option<models::Identifier>>>;
+
+// The indexes of a datastore, along with the unique constraints that are
+// enforced through them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Indexes {
+    pub properties: IndexedProperties,
+    pub type_properties: IndexedTypeProperties,
+    pub composite_properties: IndexedCompositeProperties,
+    pub unique_properties: UniqueProperties,
+}
+
+impl Indexes {
+    // Gets whether a property of the vertices and edges of a type has an
+    // index scoped to the type.
+    pub fn is_type_indexed(&self, t: &models::Identifier, name: &models::Identifier) -> bool {
+        match self.type_properties.get(name) {
+            Some(types) => types.contains(t),
+            None => false,
+        }
+    }
+
+    // Gets whether a property is covered by any composite index.
+    pub fn is_composite_indexed(&self, name: &models::Identifier) -> bool {
+        self.composite_properties.iter().any(|names| names.contains(name))
+    }
+}
+
+// Encodes the property names of a composite index as the leading component
+// of its keys. Being a JSON array, it's terminated, so the names of one
+// index can't be a prefix of another's.
+fn composite_names_key(names: &[models::Identifier]) -> models::Json {
+    models::Json::new(serde_json::Value::Array(
+        names
+            .iter()
+            .map(|name| serde_json::Value::String(name.0.clone()))
+            .collect(),
+    ))
+}
+
+// Changes staged by a transaction, keyed by column family name and then by
+// key. A value of `None` marks a deleted key. The exception is the type
+// counts column family, whose staged values are changes to the counts
+// rather than the counts themselves.
+pub(crate) type Overlay = HashMap<&'static str, BTreeMap<Vec<u8>, StagedValue>>;
+
+// A value staged by a transaction, or `None` for a deleted key.
+pub(crate) type StagedValue = Option<Vec<u8>>;
+
+// The column family of the number of vertices, edges and properties of each
+// type. Counts are only ever changed by merging in deltas, which
+// `merge_counts` adds up, so that concurrent writes can't lose each other's
+// changes.
+pub(crate) const TYPE_COUNTS_CF: &str = "type_counts:v1";
+
+// What a count in the type counts column family is of.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum TypeCount {
+    // The vertices of a type.
+    Vertices(models::Identifier),
+    // The edges of a type.
+    Edges(models::Identifier),
+    // The properties with a name, of vertices of a type.
+    VertexProperties(models::Identifier, models::Identifier),
+    // The properties with a name, of edges of a type.
+    EdgeProperties(models::Identifier, models::Identifier),
+}
+
+impl TypeCount {
+    fn key(&self) -> Vec<u8> {
+        let (tag, components) = match self {
+            TypeCount::Vertices(t) => (0, vec![util::Component::Identifier(t)]),
+            TypeCount::Edges(t) => (1, vec![util::Component::Identifier(t)]),
+            TypeCount::VertexProperties(t, name) => (
+                2,
+                vec![util::Component::Identifier(t), util::Component::Identifier(name)],
+            ),
+            TypeCount::EdgeProperties(t, name) => (
+                3,
+                vec![util::Component::Identifier(t), util::Component::Identifier(name)],
+            ),
+        };
+        let mut key = vec![tag];
+        key.extend(util::build(&components));
+        key
+    }
+
+    fn from_key(key: &[u8]) -> Self {
+        let mut cursor = Cursor::new(&key[1..]);
+        match key[0] {
+            0 => TypeCount::Vertices(util::read_identifier(&mut cursor)),
+            1 => TypeCount::Edges(util::read_identifier(&mut cursor)),
+            2 => {
+                let t = util::read_identifier(&mut cursor);
+                TypeCount::VertexProperties(t, util::read_identifier(&mut cursor))
+            }
+            _ => {
+                let t = util::read_identifier(&mut cursor);
+                TypeCount::EdgeProperties(t, util::read_identifier(&mut cursor))
+            }
+        }
+    }
+}
+
+fn read_count(value: &[u8]) -> i64 {
+    value.try_into().map_or(0, i64::from_be_bytes)
+}
+
+// The merge operator of the type counts column family, which adds up the
+// deltas written to a count.
+pub(crate) fn merge_counts(_key: &[u8], existing: Option<&[u8]>, operands: &mut MergeOperands) -> Option<Vec<u8>> {
+    let count: i64 = existing.into_iter().chain(operands).map(read_count).sum();
+    Some(count.to_be_bytes().to_vec())
+}
+
+/// How the keys of a column family are split into prefixes, for prefix bloom
+/// filters and prefix seeks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyPrefix {
+    /// A fixed number of leading bytes.
+    Fixed(usize),
+    /// A leading UUID.
+    Uuid,
+    /// A leading identifier.
+    Identifier,
+    /// A leading UUID followed by an identifier.
+    UuidAndIdentifier,
+}
+
+impl KeyPrefix {
+    /// Gets the prefix of each key in a column family. Only the column
+    /// families of managers whose lookups are by a common key prefix have
+    /// one.
+    pub(crate) fn for_cf(cf_name: &str) -> Option<Self> {
+        match cf_name {
+            "vertex_types:v1"
+            | "vertex_property_values:v1"
+            | "edge_property_values:v1"
+            | "vertex_type_property_values:v1"
+            | "edge_type_property_values:v1" => Some(KeyPrefix::Identifier),
+            "edges:v1" | "edge_ranges:v1" | "reversed_edge_ranges:v1" | "vertex_properties:v1" => Some(KeyPrefix::Uuid),
+            "edge_properties:v1" => Some(KeyPrefix::UuidAndIdentifier),
+            _ => None,
+        }
+    }
+
+    // Gets the length of a key's prefix, or `None` if the key is too short to
+    // have one.
+    fn len(self, key: &[u8]) -> Option<usize> {
+        let len = match self {
+            KeyPrefix::Fixed(len) => len,
+            KeyPrefix::Uuid => 16,
+            KeyPrefix::Identifier => 1 + *key.first()? as usize,
+            KeyPrefix::UuidAndIdentifier => 17 + *key.get(16)? as usize,
+        };
+        if len <= key.len() {
+            Some(len)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn slice_transform(self) -> SliceTransform {
+        fn identifier_prefix(key: &[u8]) -> &[u8] {
+            &key[..KeyPrefix::Identifier.len(key).unwrap()]
+        }
+        fn has_identifier_prefix(key: &[u8]) -> bool {
+            KeyPrefix::Identifier.len(key).is_some()
+        }
+        fn uuid_and_identifier_prefix(key: &[u8]) -> &[u8] {
+            &key[..KeyPrefix::UuidAndIdentifier.len(key).unwrap()]
+        }
+        fn has_uuid_and_identifier_prefix(key: &[u8]) -> bool {
+            KeyPrefix::UuidAndIdentifier.len(key).is_some()
+        }
+
+        match self {
+            KeyPrefix::Fixed(len) => SliceTransform::create_fixed_prefix(len),
+            KeyPrefix::Uuid => SliceTransform::create_fixed_prefix(16),
+            KeyPrefix::Identifier => {
+                SliceTransform::create("indradb.identifier", identifier_prefix, Some(has_identifier_prefix))
+            }
+            KeyPrefix::UuidAndIdentifier => SliceTransform::create(
+                "indradb.uuid_and_identifier",
+                uuid_and_identifier_prefix,
+                Some(has_uuid_and_identifier_prefix),
+            ),
+        }
+    }
+}
+
+/// The key prefix of each column family that has one.
+pub(crate) type KeyPrefixes = HashMap<&'static str, KeyPrefix>;
 
-fn take_with_prefix(iterator: DBIterator<'_>, prefix: Vec<u8>) -> impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + '_ {
+fn take_with_prefix<'a>(
+    iterator: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a,
+    prefix: Vec<u8>,
+) -> impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a {
     iterator.take_while(move |item| -> bool {
         let (ref k, _) = *item;
         k.starts_with(&prefix)
     })
 }
 
+#[derive(Copy, Clone)]
+pub(crate) struct ColumnFamilyRef<'a> {
+    name: &'static str,
+    handle: &'a ColumnFamily,
+}
+
+impl<'a> ColumnFamilyRef<'a> {
+    fn new(db: &'a DB, name: &'static str) -> Self {
+        ColumnFamilyRef {
+            name,
+            handle: db.cf_handle(name).unwrap(),
+        }
+    }
+}
+
+// A write collected inside of a transaction: the column family name, the
+// key, and the value, or `None` for a delete.
+type StagedWrite = (&'static str, Vec<u8>, Option<Vec<u8>>);
+
+// The writes made by a single datastore operation. Outside of a transaction
+// these go to a `WriteBatch`; inside of one, they're collected so they can
+// be merged into the transaction's overlay.
+enum BatchWrites {
+    Direct(WriteBatch),
+    Staged(Vec<StagedWrite>),
+}
+
+// What a vertex, edge or property was counted as before a batch, and what
+// it's counted as after it.
+type CountChange = (Option<TypeCount>, Option<TypeCount>);
+
+// The writes made by a single datastore operation, along with the changes
+// they make to the counts of vertices, edges and properties of each type.
+pub(crate) struct Batch {
+    writes: BatchWrites,
+    // What each vertex, edge and property written by the batch was counted
+    // as before it, and what it's counted as after it, keyed by column
+    // family name and key. Reads don't see the batch's own writes, so a
+    // vertex, edge or property may be written more than once (e.g. an edge
+    // between two vertices that are both being deleted); only the first
+    // write knows what it was counted as beforehand.
+    counted: HashMap<(&'static str, Vec<u8>), CountChange>,
+}
+
+impl Batch {
+    fn new(writes: BatchWrites) -> Self {
+        Batch {
+            writes,
+            counted: HashMap::new(),
+        }
+    }
+
+    pub fn put_cf<K: AsRef<[u8]>, V: AsRef<[u8]>>(&mut self, cf: ColumnFamilyRef<'_>, key: K, value: V) {
+        match self.writes {
+            BatchWrites::Direct(ref mut batch) => batch.put_cf(cf.handle, key, value),
+            BatchWrites::Staged(ref mut writes) => {
+                writes.push((cf.name, key.as_ref().to_vec(), Some(value.as_ref().to_vec())))
+            }
+        }
+    }
+
+    pub fn delete_cf<K: AsRef<[u8]>>(&mut self, cf: ColumnFamilyRef<'_>, key: K) {
+        match self.writes {
+            BatchWrites::Direct(ref mut batch) => batch.delete_cf(cf.handle, key),
+            BatchWrites::Staged(ref mut writes) => writes.push((cf.name, key.as_ref().to_vec(), None)),
+        }
+    }
+
+    // Records what a vertex, edge or property that's being written was
+    // counted as before the write, and what it's counted as after it.
+    fn count(&mut self, cf: ColumnFamilyRef<'_>, key: &[u8], before: Option<TypeCount>, after: Option<TypeCount>) {
+        self.counted
+            .entry((cf.name, key.to_vec()))
+            .and_modify(|counted| counted.1 = after.clone())
+            .or_insert((before, after));
+    }
+
+    pub fn write(self, db: &DB, overlay: Option<&mut Overlay>) -> Result<()> {
+        let mut deltas: HashMap<TypeCount, i64> = HashMap::new();
+        for (before, after) in self.counted.into_values() {
+            if before != after {
+                if let Some(before) = before {
+                    *deltas.entry(before).or_default() -= 1;
+                }
+                if let Some(after) = after {
+                    *deltas.entry(after).or_default() += 1;
+                }
+            }
+        }
+        deltas.retain(|_, delta| *delta != 0);
+
+        match self.writes {
+            BatchWrites::Direct(mut batch) => {
+                let cf = db.cf_handle(TYPE_COUNTS_CF).unwrap();
+                for (count, delta) in deltas {
+                    batch.merge_cf(cf, count.key(), delta.to_be_bytes());
+                }
+                db.write(batch)?
+            }
+            BatchWrites::Staged(writes) => {
+                let overlay = overlay.expect("expected an overlay to write staged changes to");
+                for (cf_name, key, value) in writes {
+                    overlay.entry(cf_name).or_default().insert(key, value);
+                }
+                // The counts are staged as deltas, to be merged in when the
+                // transaction is committed.
+                let staged = overlay.entry(TYPE_COUNTS_CF).or_default();
+                for (count, delta) in deltas {
+                    let value = staged.entry(count.key()).or_insert(None);
+                    let delta = value.as_deref().map_or(0, read_count) + delta;
+                    *value = Some(delta.to_be_bytes().to_vec());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Iterates over a column family with a transaction's staged changes merged
+// in. Staged values take precedence over those in the database.
+pub(crate) struct OverlayIterator<'a> {
+    iterator: Peekable<DBIterator<'a>>,
+    staged: Option<Peekable<Range<'a, Vec<u8>, StagedValue>>>,
+}
+
+impl<'a> Iterator for OverlayIterator<'a> {
+    type Item = (Box<[u8]>, Box<[u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let staged = match self.staged {
+            Some(ref mut staged) => staged,
+            None => return self.iterator.next(),
+        };
+
+        loop {
+            let ordering = match (self.iterator.peek(), staged.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((k, _)), Some((staged_k, _))) => k.deref().cmp(staged_k.as_slice()),
+            };
+
+            if ordering == Ordering::Less {
+                return self.iterator.next();
+            } else if ordering == Ordering::Equal {
+                // Shadowed by the staged change
+                self.iterator.next();
+            }
+
+            let (k, v) = staged.next().unwrap();
+            if let Some(v) = v {
+                return Some((k.clone().into_boxed_slice(), v.clone().into_boxed_slice()));
+            }
+        }
+    }
+}
+
+// A snapshot of the database, which keeps the database open for as long as
+// the snapshot is alive.
+pub(crate) struct OwnedSnapshot {
+    // This is declared before `_db` so that it's dropped first.
+    snapshot: Snapshot<'static>,
+    _db: Arc<DB>,
+}
+
+impl OwnedSnapshot {
+    pub(crate) fn new(db: Arc<DB>) -> Self {
+        // The snapshot borrows the database, which is kept alive (and at
+        // the same address) by the `Arc` until after the snapshot has been
+        // dropped.
+        let snapshot = unsafe { std::mem::transmute::<Snapshot<'_>, Snapshot<'static>>(db.snapshot()) };
+        OwnedSnapshot { snapshot, _db: db }
+    }
+
+    pub(crate) fn get(&self) -> &Snapshot<'_> {
+        &self.snapshot
+    }
+}
+
+impl fmt::Debug for OwnedSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedSnapshot").finish_non_exhaustive()
+    }
+}
+
 #[derive(Copy, Clone)]
 pub(crate) struct DBRef<'a> {
     pub db: &'a DB,
-    pub indexed_properties: &'a HashSet<models::Identifier>,
+    pub key_prefixes: &'a KeyPrefixes,
+    pub indexes: &'a Indexes,
+    pub overlay: Option<&'a Overlay>,
+    // If set, reads come from this snapshot rather than the latest state of
+    // the database.
+    pub snapshot: Option<&'a Snapshot<'a>>,
 }
 
 impl<'a> DBRef<'a> {
-    pub(crate) fn new(db: &'a DB, indexed_properties: &'a HashSet<models::Identifier>) -> Self {
-        DBRef { db, indexed_properties }
+    pub(crate) fn new(
+        db: &'a DB,
+        key_prefixes: &'a KeyPrefixes,
+        indexes: &'a Indexes,
+        overlay: Option<&'a Overlay>,
+        snapshot: Option<&'a Snapshot<'a>>,
+    ) -> Self {
+        DBRef {
+            db,
+            key_prefixes,
+            indexes,
+            overlay,
+            snapshot,
+        }
+    }
+
+    pub(crate) fn batch(&self) -> Batch {
+        if self.overlay.is_some() {
+            Batch::new(BatchWrites::Staged(Vec::new()))
+        } else {
+            Batch::new(BatchWrites::Direct(WriteBatch::default()))
+        }
+    }
+
+    fn get_cf(&self, cf: ColumnFamilyRef<'a>, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        if let Some(overlay) = self.overlay {
+            if let Some(value) = overlay.get(cf.name).and_then(|staged| staged.get(key)) {
+                return Ok(value.clone());
+            }
+        }
+
+        match self.snapshot {
+            Some(snapshot) => Ok(snapshot.get_cf(cf.handle, key)?),
+            None => Ok(self.db.get_cf(cf.handle, key)?),
+        }
+    }
+
+    // Iterates over the keys staged by the overlay in a column family, other
+    // than those it deletes.
+    fn staged_iterator_cf(&self, cf: ColumnFamilyRef<'a>) -> impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a {
+        self.overlay
+            .and_then(|overlay| overlay.get(cf.name))
+            .into_iter()
+            .flat_map(|staged| staged.iter())
+            .filter_map(|(key, value)| {
+                value
+                    .as_ref()
+                    .map(|value| (key.clone().into_boxed_slice(), value.clone().into_boxed_slice()))
+            })
+    }
+
+    // Estimates the number of keys in a column family, without scanning it.
+    // This ignores the overlay and snapshot.
+    fn estimate_num_keys(&self, cf: ColumnFamilyRef<'a>) -> Result<u64> {
+        let count = self.db.property_int_value_cf(cf.handle, "rocksdb.estimate-num-keys")?;
+        Ok(count.unwrap_or(0))
+    }
+
+    fn iterator_cf(&self, cf: ColumnFamilyRef<'a>, from: &[u8]) -> OverlayIterator<'a> {
+        // Column families may have a prefix extractor, which shouldn't
+        // limit which keys are visited.
+        let mut opts = ReadOptions::default();
+        opts.set_total_order_seek(true);
+        self.iterator_cf_opt(cf, from, opts)
+    }
+
+    // Iterates over the keys that start with `prefix`, starting from `from`.
+    // When every such key has the same extracted prefix as `from`, the
+    // iterator is opened as a prefix seek, so that it can skip over files
+    // via prefix bloom filters.
+    fn prefix_iterator_cf(
+        &self,
+        cf: ColumnFamilyRef<'a>,
+        from: &[u8],
+        prefix: &[u8],
+    ) -> impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a {
+        debug_assert!(from.starts_with(prefix));
+        let mut opts = ReadOptions::default();
+        match self.key_prefixes.get(cf.name) {
+            Some(key_prefix) if key_prefix.len(prefix).is_some() => opts.set_prefix_same_as_start(true),
+            _ => opts.set_total_order_seek(true),
+        }
+        take_with_prefix(self.iterator_cf_opt(cf, from, opts), prefix.to_vec())
+    }
+
+    fn iterator_cf_opt(&self, cf: ColumnFamilyRef<'a>, from: &[u8], opts: ReadOptions) -> OverlayIterator<'a> {
+        let mode = IteratorMode::From(from, Direction::Forward);
+        let iterator = match self.snapshot {
+            Some(snapshot) => snapshot.iterator_cf_opt(cf.handle, opts, mode),
+            None => self.db.iterator_cf_opt(cf.handle, opts, mode),
+        };
+        let staged = self
+            .overlay
+            .and_then(|overlay| overlay.get(cf.name))
+            .map(|staged| staged.range(from.to_vec()..).peekable());
+
+        OverlayIterator {
+            iterator: iterator.peekable(),
+            staged,
+        }
     }
 }
 
 pub(crate) struct VertexManager<'a> {
     db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> VertexManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         VertexManager {
             db_ref,
-            cf: db_ref.db.cf_handle("vertices:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "vertices:v1"),
         }
     }
 
@@ -56,11 +572,11 @@ impl<'a> VertexManager<'a> {
     }
 
     pub fn exists(&self, id: Uuid) -> Result<bool> {
-        Ok(self.db_ref.db.get_cf(self.cf, &self.key(id))?.is_some())
+        Ok(self.db_ref.get_cf(self.cf, &self.key(id))?.is_some())
     }
 
     pub fn get(&self, id: Uuid) -> Result<Option<models::Identifier>> {
-        match self.db_ref.db.get_cf(self.cf, &self.key(id))? {
+        match self.db_ref.get_cf(self.cf, &self.key(id))? {
             Some(value_bytes) => {
                 let mut cursor = Cursor::new(value_bytes.deref());
                 Ok(Some(util::read_identifier(&mut cursor)))
@@ -69,12 +585,14 @@ impl<'a> VertexManager<'a> {
         }
     }
 
+    // Estimates the number of vertices, without scanning them.
+    pub fn estimate_count(&self) -> Result<u64> {
+        self.db_ref.estimate_num_keys(self.cf)
+    }
+
     pub fn iterate_for_range(&'a self, id: Uuid) -> impl Iterator<Item = Result<VertexItem>> + 'a {
         let low_key = util::build(&[util::Component::Uuid(id)]);
-        let iter = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&low_key, Direction::Forward));
+        let iter = self.db_ref.iterator_cf(self.cf, &low_key);
         iter.map(|item| -> Result<VertexItem> {
             let (k, v) = item;
 
@@ -90,19 +608,91 @@ impl<'a> VertexManager<'a> {
         })
     }
 
-    pub fn create(&self, batch: &mut WriteBatch, vertex: &models::Vertex) -> Result<()> {
+    // Iterates over the vertices that are created or changed in the overlay.
+    pub fn iterate_for_staged(&'a self) -> impl Iterator<Item = VertexItem> + 'a {
+        self.db_ref.staged_iterator_cf(self.cf).map(|(k, v)| {
+            let id = util::read_uuid(&mut Cursor::new(k));
+            let t = util::read_identifier(&mut Cursor::new(v));
+            (id, t)
+        })
+    }
+
+    // Counts the vertices from `id` onwards, up to `limit`, without reading
+    // their values.
+    pub fn count_for_range(&self, id: Uuid, limit: usize) -> usize {
+        let low_key = util::build(&[util::Component::Uuid(id)]);
+        self.db_ref.iterator_cf(self.cf, &low_key).take(limit).count()
+    }
+
+    pub fn create(&self, batch: &mut Batch, vertex: &models::Vertex) -> Result<()> {
+        let vertex_type_manager = VertexTypeManager::new(self.db_ref);
+        let old_t = self.get(vertex.id)?;
+        if let Some(ref t) = old_t {
+            vertex_type_manager.delete(batch, t, vertex.id);
+            if t != &vertex.t {
+                self.retype_properties(batch, vertex.id, Some(t), &vertex.t)?;
+            }
+        } else {
+            // Properties can be set on vertices that don't exist yet, in
+            // which case they're only counted once the vertex is created.
+            self.retype_properties(batch, vertex.id, None, &vertex.t)?;
+        }
+
         let key = self.key(vertex.id);
-        batch.put_cf(self.cf, &key, &util::build(&[util::Component::Identifier(&vertex.t)]));
+        batch.put_cf(self.cf, &key, util::build(&[util::Component::Identifier(&vertex.t)]));
+        batch.count(
+            self.cf,
+            &key,
+            old_t.map(TypeCount::Vertices),
+            Some(TypeCount::Vertices(vertex.t.clone())),
+        );
+        vertex_type_manager.set(batch, &vertex.t, vertex.id);
+        Ok(())
+    }
+
+    // Moves the properties of a vertex whose type is being changed from the
+    // counts and indexes of its old type to those of its new one.
+    fn retype_properties(
+        &self,
+        batch: &mut Batch,
+        id: Uuid,
+        old_t: Option<&models::Identifier>,
+        new_t: &models::Identifier,
+    ) -> Result<()> {
+        let indexes = self.db_ref.indexes;
+        let vertex_property_manager = VertexPropertyManager::new(self.db_ref);
+        let vertex_type_property_value_manager = VertexTypePropertyValueManager::new(self.db_ref);
+        for item in vertex_property_manager.iterate_for_owner(id)? {
+            let ((_, name), value) = item?;
+            batch.count(
+                vertex_property_manager.cf,
+                &vertex_property_manager.key(id, &name),
+                old_t.map(|old_t| TypeCount::VertexProperties(old_t.clone(), name.clone())),
+                Some(TypeCount::VertexProperties(new_t.clone(), name.clone())),
+            );
+            if let Some(old_t) = old_t.filter(|old_t| indexes.is_type_indexed(old_t, &name)) {
+                vertex_type_property_value_manager.delete(batch, old_t, id, &name, &value);
+            }
+            if indexes.is_type_indexed(new_t, &name) {
+                vertex_type_property_value_manager.set(batch, new_t, id, &name, &value);
+            }
+        }
         Ok(())
     }
 
-    pub fn delete(&self, batch: &mut WriteBatch, id: Uuid) -> Result<()> {
-        batch.delete_cf(self.cf, &self.key(id));
+    pub fn delete(&self, batch: &mut Batch, id: Uuid) -> Result<()> {
+        let t = self.get(id)?;
+        if let Some(ref t) = t {
+            VertexTypeManager::new(self.db_ref).delete(batch, t, id);
+        }
+        let key = self.key(id);
+        batch.delete_cf(self.cf, &key);
+        batch.count(self.cf, &key, t.clone().map(TypeCount::Vertices), None);
 
         let vertex_property_manager = VertexPropertyManager::new(self.db_ref);
         for item in vertex_property_manager.iterate_for_owner(id)? {
             let ((vertex_property_owner_id, vertex_property_name), _) = item?;
-            vertex_property_manager.delete(batch, vertex_property_owner_id, &vertex_property_name)?;
+            vertex_property_manager.delete(batch, vertex_property_owner_id, t.as_ref(), &vertex_property_name)?;
         }
 
         let edge_manager = EdgeManager::new(self.db_ref);
@@ -148,20 +738,87 @@ impl<'a> VertexManager<'a> {
     pub fn compact(&self) {
         self.db_ref
             .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+pub(crate) struct VertexTypeManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> VertexTypeManager<'a> {
+    pub fn new(db_ref: DBRef<'a>) -> Self {
+        VertexTypeManager {
+            db_ref,
+            cf: ColumnFamilyRef::new(db_ref.db, "vertex_types:v1"),
+        }
+    }
+
+    fn key(&self, t: &models::Identifier, id: Uuid) -> Vec<u8> {
+        util::build(&[util::Component::Identifier(t), util::Component::Uuid(id)])
+    }
+
+    pub fn exists(&self, t: &models::Identifier, id: Uuid) -> Result<bool> {
+        Ok(self.db_ref.get_cf(self.cf, &self.key(t, id))?.is_some())
+    }
+
+    // Iterates over the IDs of vertices of a given type, starting from `id`.
+    pub fn iterate_for_range(&'a self, t: &models::Identifier, id: Uuid) -> impl Iterator<Item = Uuid> + 'a {
+        let prefix = util::build(&[util::Component::Identifier(t)]);
+        let prefix_len = prefix.len();
+        let iterator = self.db_ref.prefix_iterator_cf(self.cf, &self.key(t, id), &prefix);
+
+        iterator.map(move |item| {
+            let (k, _) = item;
+            let mut cursor = Cursor::new(&k[prefix_len..]);
+            util::read_uuid(&mut cursor)
+        })
+    }
+
+    pub fn iterate_for_all(&'a self) -> impl Iterator<Item = (models::Identifier, Uuid)> + 'a {
+        self.db_ref.iterator_cf(self.cf, &[]).map(|item| {
+            let (k, _) = item;
+            let mut cursor = Cursor::new(k);
+            let t = util::read_identifier(&mut cursor);
+            let id = util::read_uuid(&mut cursor);
+            (t, id)
+        })
+    }
+
+    // Counts the vertices of a given type from `id` onwards, up to `limit`,
+    // without reading their IDs.
+    pub fn count_for_range(&self, t: &models::Identifier, id: Uuid, limit: usize) -> usize {
+        let prefix = util::build(&[util::Component::Identifier(t)]);
+        let iterator = self.db_ref.prefix_iterator_cf(self.cf, &self.key(t, id), &prefix);
+        iterator.take(limit).count()
+    }
+
+    pub fn set(&self, batch: &mut Batch, t: &models::Identifier, id: Uuid) {
+        batch.put_cf(self.cf, self.key(t, id), []);
+    }
+
+    pub fn delete(&self, batch: &mut Batch, t: &models::Identifier, id: Uuid) {
+        batch.delete_cf(self.cf, self.key(t, id));
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct EdgeManager<'a> {
     db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgeManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         EdgeManager {
             db_ref,
-            cf: db_ref.db.cf_handle("edges:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "edges:v1"),
         }
     }
 
@@ -174,7 +831,7 @@ impl<'a> EdgeManager<'a> {
     }
 
     pub fn get(&self, out_id: Uuid, t: &models::Identifier, in_id: Uuid) -> Result<Option<DateTime<Utc>>> {
-        match self.db_ref.db.get_cf(self.cf, &self.key(out_id, t, in_id))? {
+        match self.db_ref.get_cf(self.cf, &self.key(out_id, t, in_id))? {
             Some(value_bytes) => {
                 let mut cursor = Cursor::new(value_bytes.deref());
                 Ok(Some(util::read_datetime(&mut cursor)))
@@ -183,9 +840,42 @@ impl<'a> EdgeManager<'a> {
         }
     }
 
+    // Estimates the number of edges, without scanning them.
+    pub fn estimate_count(&self) -> Result<u64> {
+        self.db_ref.estimate_num_keys(self.cf)
+    }
+
+    pub fn iterate_for_all(&'a self) -> impl Iterator<Item = EdgeRangeItem> + 'a {
+        self.iterate_from(None)
+    }
+
+    // Iterates over the edges, starting from `start` if it's set.
+    pub fn iterate_from(&'a self, start: Option<&models::EdgeKey>) -> impl Iterator<Item = EdgeRangeItem> + 'a {
+        let low_key = match start {
+            Some(key) => self.key(key.outbound_id, &key.t, key.inbound_id),
+            None => Vec::new(),
+        };
+        self.db_ref.iterator_cf(self.cf, &low_key).map(Self::read_item)
+    }
+
+    // Iterates over the edges that are created or changed in the overlay.
+    pub fn iterate_for_staged(&'a self) -> impl Iterator<Item = EdgeRangeItem> + 'a {
+        self.db_ref.staged_iterator_cf(self.cf).map(Self::read_item)
+    }
+
+    fn read_item((k, v): (Box<[u8]>, Box<[u8]>)) -> EdgeRangeItem {
+        let mut cursor = Cursor::new(k);
+        let out_id = util::read_uuid(&mut cursor);
+        let t = util::read_identifier(&mut cursor);
+        let in_id = util::read_uuid(&mut cursor);
+        let mut cursor = Cursor::new(v);
+        let update_datetime = util::read_datetime(&mut cursor);
+        (out_id, t, update_datetime, in_id)
+    }
+
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         out_id: Uuid,
         t: &models::Identifier,
         in_id: Uuid,
@@ -194,17 +884,22 @@ impl<'a> EdgeManager<'a> {
         let edge_range_manager = EdgeRangeManager::new(self.db_ref);
         let reversed_edge_range_manager = EdgeRangeManager::new_reversed(self.db_ref);
 
-        if let Some(update_datetime) = self.get(out_id, t, in_id)? {
+        let existed = if let Some(update_datetime) = self.get(out_id, t, in_id)? {
             edge_range_manager.delete(batch, out_id, t, update_datetime, in_id)?;
             reversed_edge_range_manager.delete(batch, in_id, t, update_datetime, out_id)?;
-        }
+            true
+        } else {
+            false
+        };
 
         let key = self.key(out_id, t, in_id);
         batch.put_cf(
             self.cf,
             &key,
-            &util::build(&[util::Component::DateTime(new_update_datetime)]),
+            util::build(&[util::Component::DateTime(new_update_datetime)]),
         );
+        let count = TypeCount::Edges(t.clone());
+        batch.count(self.cf, &key, Some(count.clone()).filter(|_| existed), Some(count));
         edge_range_manager.set(batch, out_id, t, new_update_datetime, in_id)?;
         reversed_edge_range_manager.set(batch, in_id, t, new_update_datetime, out_id)?;
         Ok(())
@@ -212,13 +907,16 @@ impl<'a> EdgeManager<'a> {
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         out_id: Uuid,
         t: &models::Identifier,
         in_id: Uuid,
         update_datetime: DateTime<Utc>,
     ) -> Result<()> {
-        batch.delete_cf(self.cf, &self.key(out_id, t, in_id));
+        let key = self.key(out_id, t, in_id);
+        let count = self.get(out_id, t, in_id)?.map(|_| TypeCount::Edges(t.clone()));
+        batch.delete_cf(self.cf, &key);
+        batch.count(self.cf, &key, count, None);
 
         let edge_range_manager = EdgeRangeManager::new(self.db_ref);
         edge_range_manager.delete(batch, out_id, t, update_datetime, in_id)?;
@@ -244,27 +942,27 @@ impl<'a> EdgeManager<'a> {
     pub fn compact(&self) {
         self.db_ref
             .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct EdgeRangeManager<'a> {
     db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgeRangeManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         EdgeRangeManager {
             db_ref,
-            cf: db_ref.db.cf_handle("edge_ranges:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "edge_ranges:v1"),
         }
     }
 
     pub fn new_reversed(db_ref: DBRef<'a>) -> Self {
         EdgeRangeManager {
             db_ref,
-            cf: db_ref.db.cf_handle("reversed_edge_ranges:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "reversed_edge_ranges:v1"),
         }
     }
 
@@ -277,6 +975,17 @@ impl<'a> EdgeRangeManager<'a> {
         ])
     }
 
+    pub fn exists(
+        &self,
+        first_id: Uuid,
+        t: &models::Identifier,
+        update_datetime: DateTime<Utc>,
+        second_id: Uuid,
+    ) -> Result<bool> {
+        let key = self.key(first_id, t, update_datetime, second_id);
+        Ok(self.db_ref.get_cf(self.cf, &key)?.is_some())
+    }
+
     fn iterate<I>(&'a self, iterator: I) -> impl Iterator<Item = Result<EdgeRangeItem>> + 'a
     where
         I: Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a,
@@ -307,20 +1016,12 @@ impl<'a> EdgeRangeManager<'a> {
                     util::Component::Identifier(t),
                     util::Component::DateTime(high),
                 ]);
-                let iterator = self
-                    .db_ref
-                    .db
-                    .iterator_cf(self.cf, IteratorMode::From(&low_key, Direction::Forward));
-                let iterator = take_with_prefix(iterator, prefix);
+                let iterator = self.db_ref.prefix_iterator_cf(self.cf, &low_key, &prefix);
                 Ok(Box::new(self.iterate(iterator)))
             }
             None => {
                 let prefix = util::build(&[util::Component::Uuid(id)]);
-                let iterator = self
-                    .db_ref
-                    .db
-                    .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-                let iterator = take_with_prefix(iterator, prefix);
+                let iterator = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
                 let mapped = self.iterate(iterator);
 
                 if let Some(high) = high {
@@ -343,53 +1044,84 @@ impl<'a> EdgeRangeManager<'a> {
         }
     }
 
+    // Iterates over the edges of a vertex, optionally only of type `t`,
+    // starting after the edge with the given type, update datetime, and
+    // adjacent vertex ID.
+    pub fn iterate_after(
+        &'a self,
+        id: Uuid,
+        t: Option<&models::Identifier>,
+        after: &(models::Identifier, DateTime<Utc>, Uuid),
+    ) -> impl Iterator<Item = Result<EdgeRangeItem>> + 'a {
+        let prefix = match t {
+            Some(t) => util::build(&[util::Component::Uuid(id), util::Component::Identifier(t)]),
+            None => util::build(&[util::Component::Uuid(id)]),
+        };
+        // Appending a byte to a key gives the first key after it.
+        let mut from = self.key(id, &after.0, after.1, after.2);
+        from.push(0);
+        let iterator = self.db_ref.prefix_iterator_cf(self.cf, &from, &prefix);
+        self.iterate(iterator)
+    }
+
+    // Counts the edges of a vertex, up to `limit`, without deserializing
+    // their keys.
+    pub fn count_for_range(&self, id: Uuid, t: Option<&models::Identifier>, limit: usize) -> usize {
+        let prefix = match t {
+            Some(t) => util::build(&[util::Component::Uuid(id), util::Component::Identifier(t)]),
+            None => util::build(&[util::Component::Uuid(id)]),
+        };
+        let iterator = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        iterator.take(limit).count()
+    }
+
     pub fn iterate_for_all(&'a self) -> impl Iterator<Item = Result<EdgeRangeItem>> + 'a {
-        let iterator = self.db_ref.db.iterator_cf(self.cf, IteratorMode::Start);
+        let iterator = self.db_ref.iterator_cf(self.cf, &[]);
         self.iterate(iterator)
     }
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         first_id: Uuid,
         t: &models::Identifier,
         update_datetime: DateTime<Utc>,
         second_id: Uuid,
     ) -> Result<()> {
         let key = self.key(first_id, t, update_datetime, second_id);
-        batch.put_cf(self.cf, &key, &[]);
+        batch.put_cf(self.cf, &key, []);
         Ok(())
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         first_id: Uuid,
         t: &models::Identifier,
         update_datetime: DateTime<Utc>,
         second_id: Uuid,
     ) -> Result<()> {
-        batch.delete_cf(self.cf, &self.key(first_id, t, update_datetime, second_id));
+        batch.delete_cf(self.cf, self.key(first_id, t, update_datetime, second_id));
         Ok(())
     }
 
     pub fn compact(&self) {
         self.db_ref
             .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct VertexPropertyManager<'a> {
     db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> VertexPropertyManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         VertexPropertyManager {
             db_ref,
-            cf: db_ref.db.cf_handle("vertex_properties:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "vertex_properties:v1"),
         }
     }
 
@@ -406,12 +1138,7 @@ impl<'a> VertexPropertyManager<'a> {
     ) -> Result<impl Iterator<Item = Result<OwnedPropertyItem>> + 'a> {
         let prefix = util::build(&[util::Component::Uuid(vertex_id)]);
 
-        let iterator = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-
-        let filtered = take_with_prefix(iterator, prefix);
+        let filtered = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
 
         Ok(filtered.map(move |item| -> Result<OwnedPropertyItem> {
             let (k, v) = item;
@@ -425,64 +1152,201 @@ impl<'a> VertexPropertyManager<'a> {
         }))
     }
 
+    pub fn iterate_for_all(&'a self) -> impl Iterator<Item = Result<OwnedPropertyItem>> + 'a {
+        self.db_ref
+            .iterator_cf(self.cf, &[])
+            .map(|item| -> Result<OwnedPropertyItem> {
+                let (k, v) = item;
+                let mut cursor = Cursor::new(k);
+                let owner_id = util::read_uuid(&mut cursor);
+                let name_str = util::read_fixed_length_string(&mut cursor);
+                let name = unsafe { models::Identifier::new_unchecked(name_str) };
+                let value = serde_json::from_slice(&v)?;
+                Ok(((owner_id, name), value))
+            })
+    }
+
     pub fn get(&self, vertex_id: Uuid, name: &models::Identifier) -> Result<Option<models::Json>> {
         let key = self.key(vertex_id, name);
 
-        match self.db_ref.db.get_cf(self.cf, &key)? {
+        match self.db_ref.get_cf(self.cf, &key)? {
             Some(value_bytes) => Ok(Some(serde_json::from_slice(&value_bytes)?)),
             None => Ok(None),
         }
     }
 
+    // Sets a property of a vertex. The vertex's type is needed to keep the
+    // indexes scoped to it up to date; if it isn't known, e.g. because the
+    // vertex doesn't exist, they're left alone.
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         vertex_id: Uuid,
+        t: Option<&models::Identifier>,
+        name: &models::Identifier,
+        value: &models::Json,
+    ) -> Result<()> {
+        self.update_composite_indexes(batch, vertex_id, &[(name, Some(value))])?;
+        self.set_value(batch, vertex_id, t, name, value)
+    }
+
+    // Sets a property of a vertex, leaving the composite indexes that cover
+    // it alone. This is for when several properties are set by the same
+    // batch, in which case the composite indexes have to be updated for all
+    // of them at once.
+    pub fn set_value(
+        &self,
+        batch: &mut Batch,
+        vertex_id: Uuid,
+        t: Option<&models::Identifier>,
         name: &models::Identifier,
         value: &models::Json,
     ) -> Result<()> {
-        let is_indexed = self.db_ref.indexed_properties.contains(name);
+        let is_indexed = self.db_ref.indexes.properties.contains_key(name);
+        let indexed_type = t.filter(|t| self.db_ref.indexes.is_type_indexed(t, name));
         let key = self.key(vertex_id, name);
-        if is_indexed {
-            self.delete(batch, vertex_id, name)?;
+        let counted = self.counted_as(&key, vertex_id, name)?;
+        if is_indexed || indexed_type.is_some() {
+            self.delete_value(batch, vertex_id, t, name)?;
         }
         let value_json = serde_json::to_vec(value)?;
         batch.put_cf(self.cf, &key, &value_json);
+        batch.count(
+            self.cf,
+            &key,
+            counted,
+            t.map(|t| TypeCount::VertexProperties(t.clone(), name.clone())),
+        );
         if is_indexed {
             let vertex_property_value_manager = VertexPropertyValueManager::new(self.db_ref);
             vertex_property_value_manager.set(batch, vertex_id, name, value);
         }
+        if let Some(t) = indexed_type {
+            let vertex_type_property_value_manager = VertexTypePropertyValueManager::new(self.db_ref);
+            vertex_type_property_value_manager.set(batch, t, vertex_id, name, value);
+        }
         Ok(())
     }
 
-    pub fn delete(&self, batch: &mut WriteBatch, vertex_id: Uuid, name: &models::Identifier) -> Result<()> {
-        if self.db_ref.indexed_properties.contains(name) {
-            if let Some(value) = self.get(vertex_id, name)? {
-                let vertex_property_value_manager = VertexPropertyValueManager::new(self.db_ref);
-                vertex_property_value_manager.delete(batch, vertex_id, name, &value);
-            }
-        }
-        batch.delete_cf(self.cf, &self.key(vertex_id, name));
-        Ok(())
+    // Deletes a property of a vertex. Like with `set`, the vertex's type is
+    // needed to keep the indexes scoped to it up to date.
+    pub fn delete(
+        &self,
+        batch: &mut Batch,
+        vertex_id: Uuid,
+        t: Option<&models::Identifier>,
+        name: &models::Identifier,
+    ) -> Result<()> {
+        self.update_composite_indexes(batch, vertex_id, &[(name, None)])?;
+        let key = self.key(vertex_id, name);
+        let counted = self.counted_as(&key, vertex_id, name)?;
+        batch.count(self.cf, &key, counted, None);
+        self.delete_value(batch, vertex_id, t, name)
     }
 
-    pub fn compact(&self) {
-        self.db_ref
-            .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    // Gets what a property is counted as before the batch is written, which
+    // depends on the type its vertex has then, rather than the type it's
+    // being written with.
+    fn counted_as(&self, key: &[u8], vertex_id: Uuid, name: &models::Identifier) -> Result<Option<TypeCount>> {
+        if self.db_ref.get_cf(self.cf, key)?.is_none() {
+            return Ok(None);
+        }
+        let t = VertexManager::new(self.db_ref).get(vertex_id)?;
+        Ok(t.map(|t| TypeCount::VertexProperties(t, name.clone())))
     }
-}
 
-pub(crate) struct EdgePropertyManager<'a> {
-    db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    fn delete_value(
+        &self,
+        batch: &mut Batch,
+        vertex_id: Uuid,
+        t: Option<&models::Identifier>,
+        name: &models::Identifier,
+    ) -> Result<()> {
+        let is_indexed = self.db_ref.indexes.properties.contains_key(name);
+        let indexed_type = t.filter(|t| self.db_ref.indexes.is_type_indexed(t, name));
+        if is_indexed || indexed_type.is_some() {
+            if let Some(value) = self.get(vertex_id, name)? {
+                if is_indexed {
+                    let vertex_property_value_manager = VertexPropertyValueManager::new(self.db_ref);
+                    vertex_property_value_manager.delete(batch, vertex_id, name, &value);
+                }
+                if let Some(t) = indexed_type {
+                    let vertex_type_property_value_manager = VertexTypePropertyValueManager::new(self.db_ref);
+                    vertex_type_property_value_manager.delete(batch, t, vertex_id, name, &value);
+                }
+            }
+        }
+        batch.delete_cf(self.cf, self.key(vertex_id, name));
+        Ok(())
+    }
+
+    // Updates the composite indexes covering properties of a vertex that are
+    // being changed, given the new value of each of them, or `None` if it's
+    // being deleted. The vertex's other properties are read as they are
+    // before the batch is written.
+    pub fn update_composite_indexes(
+        &self,
+        batch: &mut Batch,
+        vertex_id: Uuid,
+        changes: &[(&models::Identifier, Option<&models::Json>)],
+    ) -> Result<()> {
+        let indexes = self.db_ref.indexes;
+        if indexes.composite_properties.is_empty() {
+            return Ok(());
+        }
+
+        let vertex_composite_property_value_manager = VertexCompositePropertyValueManager::new(self.db_ref);
+        for names in indexes
+            .composite_properties
+            .iter()
+            .filter(|names| changes.iter().any(|(name, _)| names.contains(name)))
+        {
+            let mut old_values = Vec::with_capacity(names.len());
+            let mut new_values = Vec::with_capacity(names.len());
+            for name in names {
+                let old_value = self.get(vertex_id, name)?;
+                let new_value = match changes.iter().find(|(changed_name, _)| changed_name == &name) {
+                    Some((_, value)) => value.cloned(),
+                    None => old_value.clone(),
+                };
+                old_values.push(old_value);
+                new_values.push(new_value);
+            }
+
+            // Vertices are only in a composite index if they have all of its
+            // properties.
+            let old_values: Option<Vec<models::Json>> = old_values.into_iter().collect();
+            let new_values: Option<Vec<models::Json>> = new_values.into_iter().collect();
+            if old_values == new_values {
+                continue;
+            }
+            if let Some(old_values) = old_values {
+                vertex_composite_property_value_manager.delete(batch, names, &old_values, vertex_id);
+            }
+            if let Some(new_values) = new_values {
+                vertex_composite_property_value_manager.set(batch, names, &new_values, vertex_id);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+pub(crate) struct EdgePropertyManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgePropertyManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         EdgePropertyManager {
             db_ref,
-            cf: db_ref.db.cf_handle("edge_properties:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "edge_properties:v1"),
         }
     }
 
@@ -507,12 +1371,7 @@ impl<'a> EdgePropertyManager<'a> {
             util::Component::Uuid(in_id),
         ]);
 
-        let iterator = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-
-        let filtered = take_with_prefix(iterator, prefix);
+        let filtered = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
 
         let mapped = filtered.map(move |item| -> Result<EdgePropertyItem> {
             let (k, v) = item;
@@ -545,6 +1404,22 @@ impl<'a> EdgePropertyManager<'a> {
         Ok(Box::new(mapped))
     }
 
+    pub fn iterate_for_all(&'a self) -> impl Iterator<Item = Result<EdgePropertyItem>> + 'a {
+        self.db_ref
+            .iterator_cf(self.cf, &[])
+            .map(|item| -> Result<EdgePropertyItem> {
+                let (k, v) = item;
+                let mut cursor = Cursor::new(k);
+                let out_id = util::read_uuid(&mut cursor);
+                let t = util::read_identifier(&mut cursor);
+                let in_id = util::read_uuid(&mut cursor);
+                let name_str = util::read_fixed_length_string(&mut cursor);
+                let name = unsafe { models::Identifier::new_unchecked(name_str) };
+                let value = serde_json::from_slice(&v)?;
+                Ok(((out_id, t, in_id, name), value))
+            })
+    }
+
     pub fn get(
         &self,
         out_id: Uuid,
@@ -554,7 +1429,7 @@ impl<'a> EdgePropertyManager<'a> {
     ) -> Result<Option<models::Json>> {
         let key = self.key(out_id, t, in_id, name);
 
-        match self.db_ref.db.get_cf(self.cf, &key)? {
+        match self.db_ref.get_cf(self.cf, &key)? {
             Some(value_bytes) => Ok(Some(serde_json::from_slice(&value_bytes)?)),
             None => Ok(None),
         }
@@ -562,62 +1437,164 @@ impl<'a> EdgePropertyManager<'a> {
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         out_id: Uuid,
         t: &models::Identifier,
         in_id: Uuid,
         name: &models::Identifier,
         value: &models::Json,
     ) -> Result<()> {
-        let is_indexed = self.db_ref.indexed_properties.contains(name);
+        self.update_composite_indexes(batch, out_id, t, in_id, &[(name, Some(value))])?;
+        self.set_value(batch, out_id, t, in_id, name, value)
+    }
+
+    // Sets a property of an edge, leaving the composite indexes that cover it
+    // alone. Like with `VertexPropertyManager::set_value`, this is for when
+    // several properties are set by the same batch.
+    pub fn set_value(
+        &self,
+        batch: &mut Batch,
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+        name: &models::Identifier,
+        value: &models::Json,
+    ) -> Result<()> {
+        let is_indexed = self.db_ref.indexes.properties.contains_key(name);
+        let is_type_indexed = self.db_ref.indexes.is_type_indexed(t, name);
         let key = self.key(out_id, t, in_id, name);
-        if is_indexed {
-            self.delete(batch, out_id, t, in_id, name)?;
+        let existed = self.db_ref.get_cf(self.cf, &key)?.is_some();
+        if is_indexed || is_type_indexed {
+            self.delete_value(batch, out_id, t, in_id, name)?;
         }
         let value_json = serde_json::to_vec(value)?;
         batch.put_cf(self.cf, &key, &value_json);
+        let count = TypeCount::EdgeProperties(t.clone(), name.clone());
+        batch.count(self.cf, &key, Some(count.clone()).filter(|_| existed), Some(count));
         if is_indexed {
             let edge_property_value_manager = EdgePropertyValueManager::new(self.db_ref);
             edge_property_value_manager.set(batch, out_id, t, in_id, name, value);
         }
+        if is_type_indexed {
+            let edge_type_property_value_manager = EdgeTypePropertyValueManager::new(self.db_ref);
+            edge_type_property_value_manager.set(batch, out_id, t, in_id, name, value);
+        }
         Ok(())
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+        name: &models::Identifier,
+    ) -> Result<()> {
+        self.update_composite_indexes(batch, out_id, t, in_id, &[(name, None)])?;
+        let key = self.key(out_id, t, in_id, name);
+        if self.db_ref.get_cf(self.cf, &key)?.is_some() {
+            batch.count(
+                self.cf,
+                &key,
+                Some(TypeCount::EdgeProperties(t.clone(), name.clone())),
+                None,
+            );
+        }
+        self.delete_value(batch, out_id, t, in_id, name)
+    }
+
+    fn delete_value(
+        &self,
+        batch: &mut Batch,
         out_id: Uuid,
         t: &models::Identifier,
         in_id: Uuid,
         name: &models::Identifier,
     ) -> Result<()> {
-        if self.db_ref.indexed_properties.contains(name) {
+        let is_indexed = self.db_ref.indexes.properties.contains_key(name);
+        let is_type_indexed = self.db_ref.indexes.is_type_indexed(t, name);
+        if is_indexed || is_type_indexed {
             if let Some(value) = self.get(out_id, t, in_id, name)? {
-                let edge_property_value_manager = EdgePropertyValueManager::new(self.db_ref);
-                edge_property_value_manager.delete(batch, out_id, t, in_id, name, &value);
+                if is_indexed {
+                    let edge_property_value_manager = EdgePropertyValueManager::new(self.db_ref);
+                    edge_property_value_manager.delete(batch, out_id, t, in_id, name, &value);
+                }
+                if is_type_indexed {
+                    let edge_type_property_value_manager = EdgeTypePropertyValueManager::new(self.db_ref);
+                    edge_type_property_value_manager.delete(batch, out_id, t, in_id, name, &value);
+                }
+            }
+        }
+        batch.delete_cf(self.cf, self.key(out_id, t, in_id, name));
+        Ok(())
+    }
+
+    // Updates the composite indexes covering properties of an edge that are
+    // being changed. Like with `VertexPropertyManager`, each change is the
+    // new value of a property, or `None` if it's being deleted.
+    pub fn update_composite_indexes(
+        &self,
+        batch: &mut Batch,
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+        changes: &[(&models::Identifier, Option<&models::Json>)],
+    ) -> Result<()> {
+        let indexes = self.db_ref.indexes;
+        if indexes.composite_properties.is_empty() {
+            return Ok(());
+        }
+
+        let edge_composite_property_value_manager = EdgeCompositePropertyValueManager::new(self.db_ref);
+        for names in indexes
+            .composite_properties
+            .iter()
+            .filter(|names| changes.iter().any(|(name, _)| names.contains(name)))
+        {
+            let mut old_values = Vec::with_capacity(names.len());
+            let mut new_values = Vec::with_capacity(names.len());
+            for name in names {
+                let old_value = self.get(out_id, t, in_id, name)?;
+                let new_value = match changes.iter().find(|(changed_name, _)| changed_name == &name) {
+                    Some((_, value)) => value.cloned(),
+                    None => old_value.clone(),
+                };
+                old_values.push(old_value);
+                new_values.push(new_value);
+            }
+
+            let old_values: Option<Vec<models::Json>> = old_values.into_iter().collect();
+            let new_values: Option<Vec<models::Json>> = new_values.into_iter().collect();
+            if old_values == new_values {
+                continue;
+            }
+            if let Some(old_values) = old_values {
+                edge_composite_property_value_manager.delete(batch, names, &old_values, out_id, t, in_id);
+            }
+            if let Some(new_values) = new_values {
+                edge_composite_property_value_manager.set(batch, names, &new_values, out_id, t, in_id);
             }
         }
-        batch.delete_cf(self.cf, &self.key(out_id, t, in_id, name));
         Ok(())
     }
 
     pub fn compact(&self) {
         self.db_ref
             .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct VertexPropertyValueManager<'a> {
     db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> VertexPropertyValueManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         VertexPropertyValueManager {
             db_ref,
-            cf: db_ref.db.cf_handle("vertex_property_values:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "vertex_property_values:v1"),
         }
     }
 
@@ -631,63 +1608,102 @@ impl<'a> VertexPropertyValueManager<'a> {
 
     fn iterate(
         &'a self,
-        iterator: DBIterator<'a>,
-        prefix: Vec<u8>,
-    ) -> impl Iterator<Item = VertexPropertyValueKey> + 'a {
-        let filtered = take_with_prefix(iterator, prefix);
-
-        filtered.map(move |item| -> VertexPropertyValueKey {
+        iterator: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a,
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        iterator.map(move |item| -> Result<VertexPropertyValueKey> {
             let (k, _) = item;
             let mut cursor = Cursor::new(k);
             let name = util::read_identifier(&mut cursor);
-            let value_hash = util::read_u64(&mut cursor);
+            let value = util::read_json(&mut cursor)?;
             let vertex_id = util::read_uuid(&mut cursor);
-            (name, value_hash, vertex_id)
+            Ok((name, value, vertex_id))
         })
     }
 
+    pub fn exists(
+        &self,
+        vertex_id: Uuid,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) -> Result<bool> {
+        let key = self.key(property_name, property_value, vertex_id);
+        Ok(self.db_ref.get_cf(self.cf, &key)?.is_some())
+    }
+
+    pub fn iterate_for_all(&'a self) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        self.iterate(self.db_ref.iterator_cf(self.cf, &[]))
+    }
+
+    pub fn iterate_for_staged(&'a self) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        self.iterate(self.db_ref.staged_iterator_cf(self.cf))
+    }
+
     pub fn iterate_for_name(
         &'a self,
         property_name: &models::Identifier,
-    ) -> impl Iterator<Item = VertexPropertyValueKey> + 'a {
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
         let prefix = util::build(&[util::Component::Identifier(property_name)]);
-        let iter = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-        self.iterate(iter, prefix)
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        self.iterate(iter)
     }
 
     pub fn iterate_for_value(
         &'a self,
         property_name: &models::Identifier,
         property_value: &models::Json,
-    ) -> impl Iterator<Item = VertexPropertyValueKey> + 'a {
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
         let prefix = util::build(&[
             util::Component::Identifier(property_name),
             util::Component::Json(property_value),
         ]);
-        let iter = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-        self.iterate(iter, prefix)
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        self.iterate(iter)
+    }
+
+    pub fn iterate_from_value(
+        &'a self,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        let prefix = util::build(&[util::Component::Identifier(property_name)]);
+        let start = util::build(&[
+            util::Component::Identifier(property_name),
+            util::Component::Json(property_value),
+        ]);
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &start, &prefix);
+        self.iterate(iter)
+    }
+
+    // Iterates over the values of a property, starting after the given
+    // value and vertex.
+    pub fn iterate_after(
+        &'a self,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+        vertex_id: Uuid,
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        let prefix = util::build(&[util::Component::Identifier(property_name)]);
+        // Appending a byte to a key gives the first key after it.
+        let mut start = self.key(property_name, property_value, vertex_id);
+        start.push(0);
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &start, &prefix);
+        self.iterate(iter)
     }
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         vertex_id: Uuid,
         property_name: &models::Identifier,
         property_value: &models::Json,
     ) {
         let key = self.key(property_name, property_value, vertex_id);
-        batch.put_cf(self.cf, key, &[]);
+        batch.put_cf(self.cf, key, []);
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         vertex_id: Uuid,
         property_name: &models::Identifier,
         property_value: &models::Json,
@@ -696,23 +1712,36 @@ impl<'a> VertexPropertyValueManager<'a> {
         batch.delete_cf(self.cf, key);
     }
 
+    pub fn delete_for_name(&self, batch: &mut Batch, property_name: &models::Identifier) {
+        let prefix = util::build(&[util::Component::Identifier(property_name)]);
+        for (key, _) in self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn delete_all(&self, batch: &mut Batch) {
+        for (key, _) in self.db_ref.iterator_cf(self.cf, &[]) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
     pub fn compact(&self) {
         self.db_ref
             .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct EdgePropertyValueManager<'a> {
     db_ref: DBRef<'a>,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> EdgePropertyValueManager<'a> {
     pub fn new(db_ref: DBRef<'a>) -> Self {
         EdgePropertyValueManager {
             db_ref,
-            cf: db_ref.db.cf_handle("edge_property_values:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db_ref.db, "edge_property_values:v1"),
         }
     }
 
@@ -733,52 +1762,97 @@ impl<'a> EdgePropertyValueManager<'a> {
         ])
     }
 
-    fn iterate(&'a self, iterator: DBIterator<'a>, prefix: Vec<u8>) -> impl Iterator<Item = EdgePropertyValueKey> + 'a {
-        let filtered = take_with_prefix(iterator, prefix);
-
-        filtered.map(move |item| -> EdgePropertyValueKey {
+    fn iterate(
+        &'a self,
+        iterator: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a,
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        iterator.map(move |item| -> Result<EdgePropertyValueKey> {
             let (k, _) = item;
             let mut cursor = Cursor::new(k);
             let name = util::read_identifier(&mut cursor);
-            let value_hash = util::read_u64(&mut cursor);
+            let value = util::read_json(&mut cursor)?;
             let out_id = util::read_uuid(&mut cursor);
             let t = util::read_identifier(&mut cursor);
             let in_id = util::read_uuid(&mut cursor);
-            (name, value_hash, (out_id, t, in_id))
+            Ok((name, value, (out_id, t, in_id)))
         })
     }
 
+    pub fn exists(
+        &self,
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) -> Result<bool> {
+        let key = self.key(property_name, property_value, out_id, t, in_id);
+        Ok(self.db_ref.get_cf(self.cf, &key)?.is_some())
+    }
+
+    pub fn iterate_for_all(&'a self) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        self.iterate(self.db_ref.iterator_cf(self.cf, &[]))
+    }
+
+    pub fn iterate_for_staged(&'a self) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        self.iterate(self.db_ref.staged_iterator_cf(self.cf))
+    }
+
     pub fn iterate_for_name(
         &'a self,
         property_name: &models::Identifier,
-    ) -> impl Iterator<Item = EdgePropertyValueKey> + 'a {
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
         let prefix = util::build(&[util::Component::Identifier(property_name)]);
-        let iter = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-        self.iterate(iter, prefix)
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        self.iterate(iter)
     }
 
     pub fn iterate_for_value(
         &'a self,
         property_name: &models::Identifier,
         property_value: &models::Json,
-    ) -> impl Iterator<Item = EdgePropertyValueKey> + 'a {
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
         let prefix = util::build(&[
             util::Component::Identifier(property_name),
             util::Component::Json(property_value),
         ]);
-        let iter = self
-            .db_ref
-            .db
-            .iterator_cf(self.cf, IteratorMode::From(&prefix, Direction::Forward));
-        self.iterate(iter, prefix)
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        self.iterate(iter)
+    }
+
+    pub fn iterate_from_value(
+        &'a self,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        let prefix = util::build(&[util::Component::Identifier(property_name)]);
+        let start = util::build(&[
+            util::Component::Identifier(property_name),
+            util::Component::Json(property_value),
+        ]);
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &start, &prefix);
+        self.iterate(iter)
+    }
+
+    // Iterates over the values of a property, starting after the given
+    // value and edge.
+    pub fn iterate_after(
+        &'a self,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+        key: &models::EdgeKey,
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        let prefix = util::build(&[util::Component::Identifier(property_name)]);
+        // Appending a byte to a key gives the first key after it.
+        let mut start = self.key(property_name, property_value, key.outbound_id, &key.t, key.inbound_id);
+        start.push(0);
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &start, &prefix);
+        self.iterate(iter)
     }
 
     pub fn set(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         out_id: Uuid,
         t: &models::Identifier,
         in_id: Uuid,
@@ -786,12 +1860,12 @@ impl<'a> EdgePropertyValueManager<'a> {
         property_value: &models::Json,
     ) {
         let key = self.key(property_name, property_value, out_id, t, in_id);
-        batch.put_cf(self.cf, key, &[]);
+        batch.put_cf(self.cf, key, []);
     }
 
     pub fn delete(
         &self,
-        batch: &mut WriteBatch,
+        batch: &mut Batch,
         out_id: Uuid,
         t: &models::Identifier,
         in_id: Uuid,
@@ -802,41 +1876,654 @@ impl<'a> EdgePropertyValueManager<'a> {
         batch.delete_cf(self.cf, key);
     }
 
+    pub fn delete_for_name(&self, batch: &mut Batch, property_name: &models::Identifier) {
+        let prefix = util::build(&[util::Component::Identifier(property_name)]);
+        for (key, _) in self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn delete_all(&self, batch: &mut Batch) {
+        for (key, _) in self.db_ref.iterator_cf(self.cf, &[]) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+// An index of the values of a property of vertices, scoped to the vertices
+// of one type.
+pub(crate) struct VertexTypePropertyValueManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> VertexTypePropertyValueManager<'a> {
+    pub fn new(db_ref: DBRef<'a>) -> Self {
+        VertexTypePropertyValueManager {
+            db_ref,
+            cf: ColumnFamilyRef::new(db_ref.db, "vertex_type_property_values:v1"),
+        }
+    }
+
+    fn key(
+        &self,
+        t: &models::Identifier,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+        vertex_id: Uuid,
+    ) -> Vec<u8> {
+        util::build(&[
+            util::Component::Identifier(t),
+            util::Component::Identifier(property_name),
+            util::Component::Json(property_value),
+            util::Component::Uuid(vertex_id),
+        ])
+    }
+
+    fn iterate(
+        &'a self,
+        iterator: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a,
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        iterator.map(move |item| -> Result<VertexPropertyValueKey> {
+            let (k, _) = item;
+            let mut cursor = Cursor::new(k);
+            let _ = util::read_identifier(&mut cursor);
+            let name = util::read_identifier(&mut cursor);
+            let value = util::read_json(&mut cursor)?;
+            let vertex_id = util::read_uuid(&mut cursor);
+            Ok((name, value, vertex_id))
+        })
+    }
+
+    pub fn iterate_for_value(
+        &'a self,
+        t: &models::Identifier,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) -> impl Iterator<Item = Result<VertexPropertyValueKey>> + 'a {
+        let prefix = util::build(&[
+            util::Component::Identifier(t),
+            util::Component::Identifier(property_name),
+            util::Component::Json(property_value),
+        ]);
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        self.iterate(iter)
+    }
+
+    pub fn set(
+        &self,
+        batch: &mut Batch,
+        t: &models::Identifier,
+        vertex_id: Uuid,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) {
+        let key = self.key(t, property_name, property_value, vertex_id);
+        batch.put_cf(self.cf, key, []);
+    }
+
+    pub fn delete(
+        &self,
+        batch: &mut Batch,
+        t: &models::Identifier,
+        vertex_id: Uuid,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) {
+        let key = self.key(t, property_name, property_value, vertex_id);
+        batch.delete_cf(self.cf, key);
+    }
+
+    pub fn delete_for_type_property(
+        &self,
+        batch: &mut Batch,
+        t: &models::Identifier,
+        property_name: &models::Identifier,
+    ) {
+        let prefix = util::build(&[
+            util::Component::Identifier(t),
+            util::Component::Identifier(property_name),
+        ]);
+        for (key, _) in self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+// An index of the values of a property of edges, scoped to the edges of one
+// type.
+pub(crate) struct EdgeTypePropertyValueManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> EdgeTypePropertyValueManager<'a> {
+    pub fn new(db_ref: DBRef<'a>) -> Self {
+        EdgeTypePropertyValueManager {
+            db_ref,
+            cf: ColumnFamilyRef::new(db_ref.db, "edge_type_property_values:v1"),
+        }
+    }
+
+    fn key(
+        &self,
+        t: &models::Identifier,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+        out_id: Uuid,
+        in_id: Uuid,
+    ) -> Vec<u8> {
+        util::build(&[
+            util::Component::Identifier(t),
+            util::Component::Identifier(property_name),
+            util::Component::Json(property_value),
+            util::Component::Uuid(out_id),
+            util::Component::Uuid(in_id),
+        ])
+    }
+
+    fn iterate(
+        &'a self,
+        iterator: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a,
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        iterator.map(move |item| -> Result<EdgePropertyValueKey> {
+            let (k, _) = item;
+            let mut cursor = Cursor::new(k);
+            let t = util::read_identifier(&mut cursor);
+            let name = util::read_identifier(&mut cursor);
+            let value = util::read_json(&mut cursor)?;
+            let out_id = util::read_uuid(&mut cursor);
+            let in_id = util::read_uuid(&mut cursor);
+            Ok((name, value, (out_id, t, in_id)))
+        })
+    }
+
+    pub fn iterate_for_value(
+        &'a self,
+        t: &models::Identifier,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) -> impl Iterator<Item = Result<EdgePropertyValueKey>> + 'a {
+        let prefix = util::build(&[
+            util::Component::Identifier(t),
+            util::Component::Identifier(property_name),
+            util::Component::Json(property_value),
+        ]);
+        let iter = self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix);
+        self.iterate(iter)
+    }
+
+    pub fn set(
+        &self,
+        batch: &mut Batch,
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) {
+        let key = self.key(t, property_name, property_value, out_id, in_id);
+        batch.put_cf(self.cf, key, []);
+    }
+
+    pub fn delete(
+        &self,
+        batch: &mut Batch,
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+        property_name: &models::Identifier,
+        property_value: &models::Json,
+    ) {
+        let key = self.key(t, property_name, property_value, out_id, in_id);
+        batch.delete_cf(self.cf, key);
+    }
+
+    pub fn delete_for_type_property(
+        &self,
+        batch: &mut Batch,
+        t: &models::Identifier,
+        property_name: &models::Identifier,
+    ) {
+        let prefix = util::build(&[
+            util::Component::Identifier(t),
+            util::Component::Identifier(property_name),
+        ]);
+        for (key, _) in self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+// An index of the values of a combination of properties of vertices, for
+// composite indexes. Keys are the index's property names, followed by the
+// values of the properties in the same order, so lookups can be by the values
+// of any number of leading properties.
+pub(crate) struct VertexCompositePropertyValueManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> VertexCompositePropertyValueManager<'a> {
+    pub fn new(db_ref: DBRef<'a>) -> Self {
+        VertexCompositePropertyValueManager {
+            db_ref,
+            cf: ColumnFamilyRef::new(db_ref.db, "vertex_composite_property_values:v1"),
+        }
+    }
+
+    fn key(&self, property_names: &[models::Identifier], property_values: &[models::Json], vertex_id: Uuid) -> Vec<u8> {
+        let names_key = composite_names_key(property_names);
+        let mut components = vec![util::Component::Json(&names_key)];
+        components.extend(property_values.iter().map(util::Component::Json));
+        components.push(util::Component::Uuid(vertex_id));
+        util::build(&components)
+    }
+
+    pub fn iterate_for_values(
+        &'a self,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+    ) -> impl Iterator<Item = Result<Uuid>> + 'a {
+        let names_key = composite_names_key(property_names);
+        let mut components = vec![util::Component::Json(&names_key)];
+        components.extend(property_values.iter().map(util::Component::Json));
+        let prefix = util::build(&components);
+        let len = property_names.len();
+        self.db_ref
+            .prefix_iterator_cf(self.cf, &prefix, &prefix)
+            .map(move |(k, _)| {
+                let mut cursor = Cursor::new(k);
+                for _ in 0..=len {
+                    util::read_json(&mut cursor)?;
+                }
+                Ok(util::read_uuid(&mut cursor))
+            })
+    }
+
+    pub fn set(
+        &self,
+        batch: &mut Batch,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+        vertex_id: Uuid,
+    ) {
+        let key = self.key(property_names, property_values, vertex_id);
+        batch.put_cf(self.cf, key, []);
+    }
+
+    pub fn delete(
+        &self,
+        batch: &mut Batch,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+        vertex_id: Uuid,
+    ) {
+        let key = self.key(property_names, property_values, vertex_id);
+        batch.delete_cf(self.cf, key);
+    }
+
+    pub fn delete_for_names(&self, batch: &mut Batch, property_names: &[models::Identifier]) {
+        let prefix = util::build(&[util::Component::Json(&composite_names_key(property_names))]);
+        for (key, _) in self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+// An index of the values of a combination of properties of edges, for
+// composite indexes. Keys are laid out like those of
+// `VertexCompositePropertyValueManager`, but end with the edge's key.
+pub(crate) struct EdgeCompositePropertyValueManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> EdgeCompositePropertyValueManager<'a> {
+    pub fn new(db_ref: DBRef<'a>) -> Self {
+        EdgeCompositePropertyValueManager {
+            db_ref,
+            cf: ColumnFamilyRef::new(db_ref.db, "edge_composite_property_values:v1"),
+        }
+    }
+
+    fn key(
+        &self,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+    ) -> Vec<u8> {
+        let names_key = composite_names_key(property_names);
+        let mut components = vec![util::Component::Json(&names_key)];
+        components.extend(property_values.iter().map(util::Component::Json));
+        components.push(util::Component::Uuid(out_id));
+        components.push(util::Component::Identifier(t));
+        components.push(util::Component::Uuid(in_id));
+        util::build(&components)
+    }
+
+    pub fn iterate_for_values(
+        &'a self,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+    ) -> impl Iterator<Item = Result<(Uuid, models::Identifier, Uuid)>> + 'a {
+        let names_key = composite_names_key(property_names);
+        let mut components = vec![util::Component::Json(&names_key)];
+        components.extend(property_values.iter().map(util::Component::Json));
+        let prefix = util::build(&components);
+        let len = property_names.len();
+        self.db_ref
+            .prefix_iterator_cf(self.cf, &prefix, &prefix)
+            .map(move |(k, _)| {
+                let mut cursor = Cursor::new(k);
+                for _ in 0..=len {
+                    util::read_json(&mut cursor)?;
+                }
+                let out_id = util::read_uuid(&mut cursor);
+                let t = util::read_identifier(&mut cursor);
+                let in_id = util::read_uuid(&mut cursor);
+                Ok((out_id, t, in_id))
+            })
+    }
+
+    pub fn set(
+        &self,
+        batch: &mut Batch,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+    ) {
+        let key = self.key(property_names, property_values, out_id, t, in_id);
+        batch.put_cf(self.cf, key, []);
+    }
+
+    pub fn delete(
+        &self,
+        batch: &mut Batch,
+        property_names: &[models::Identifier],
+        property_values: &[models::Json],
+        out_id: Uuid,
+        t: &models::Identifier,
+        in_id: Uuid,
+    ) {
+        let key = self.key(property_names, property_values, out_id, t, in_id);
+        batch.delete_cf(self.cf, key);
+    }
+
+    pub fn delete_for_names(&self, batch: &mut Batch, property_names: &[models::Identifier]) {
+        let prefix = util::build(&[util::Component::Json(&composite_names_key(property_names))]);
+        for (key, _) in self.db_ref.prefix_iterator_cf(self.cf, &prefix, &prefix) {
+            batch.delete_cf(self.cf, key);
+        }
+    }
+
+    pub fn compact(&self) {
+        self.db_ref
+            .db
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
+    }
+}
+
+pub(crate) struct TypeCountManager<'a> {
+    db_ref: DBRef<'a>,
+    cf: ColumnFamilyRef<'a>,
+}
+
+impl<'a> TypeCountManager<'a> {
+    pub fn new(db_ref: DBRef<'a>) -> Self {
+        TypeCountManager {
+            db_ref,
+            cf: ColumnFamilyRef::new(db_ref.db, TYPE_COUNTS_CF),
+        }
+    }
+
+    // Gets every count, including the changes staged by the overlay. Counts
+    // that have dropped to zero may be included.
+    pub fn get_all(&self) -> BTreeMap<TypeCount, i64> {
+        // The overlay's staged deltas would shadow the counts if it were
+        // iterated over along with the database, so they're added
+        // separately.
+        let db_ref = DBRef {
+            overlay: None,
+            ..self.db_ref
+        };
+        let mut counts: BTreeMap<TypeCount, i64> = db_ref
+            .iterator_cf(self.cf, &[])
+            .map(|(k, v)| (TypeCount::from_key(&k), read_count(&v)))
+            .collect();
+        for (k, v) in self.db_ref.staged_iterator_cf(self.cf) {
+            *counts.entry(TypeCount::from_key(&k)).or_default() += read_count(&v);
+        }
+        counts
+    }
+
+    // Overwrites a count. Unlike the changes made by other writes, this
+    // doesn't commute with concurrent changes to the count.
+    pub fn set(&self, batch: &mut Batch, count: &TypeCount, value: u64) {
+        batch.put_cf(self.cf, count.key(), (value as i64).to_be_bytes());
+    }
+
+    // Counts the vertices, edges and properties of each type by scanning
+    // them, rather than from the maintained counts.
+    pub fn recount(&self) -> Result<BTreeMap<TypeCount, u64>> {
+        let vertex_manager = VertexManager::new(self.db_ref);
+        let edge_manager = EdgeManager::new(self.db_ref);
+        let vertex_property_manager = VertexPropertyManager::new(self.db_ref);
+        let edge_property_manager = EdgePropertyManager::new(self.db_ref);
+        let mut counts = BTreeMap::new();
+
+        for item in vertex_manager.iterate_for_range(Uuid::default()) {
+            let (_, t) = item?;
+            *counts.entry(TypeCount::Vertices(t)).or_default() += 1;
+        }
+
+        for (_, t, _, _) in edge_manager.iterate_for_all() {
+            *counts.entry(TypeCount::Edges(t)).or_default() += 1;
+        }
+
+        // Properties are keyed by their vertex's ID, so each vertex's type
+        // only has to be looked up once. Properties of vertices that don't
+        // exist aren't counted.
+        let mut owner: Option<(Uuid, Option<models::Identifier>)> = None;
+        for item in vertex_property_manager.iterate_for_all() {
+            let ((id, name), _) = item?;
+            if owner.as_ref().map(|(owner_id, _)| *owner_id) != Some(id) {
+                owner = Some((id, vertex_manager.get(id)?));
+            }
+            if let Some((_, Some(t))) = &owner {
+                *counts.entry(TypeCount::VertexProperties(t.clone(), name)).or_default() += 1;
+            }
+        }
+
+        for item in edge_property_manager.iterate_for_all() {
+            let ((_, t, _, name), _) = item?;
+            *counts.entry(TypeCount::EdgeProperties(t, name)).or_default() += 1;
+        }
+
+        Ok(counts)
+    }
+
     pub fn compact(&self) {
         self.db_ref
             .db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
 
 pub(crate) struct MetadataManager<'a> {
     db: &'a DB,
-    cf: &'a ColumnFamily,
+    cf: ColumnFamilyRef<'a>,
 }
 
 impl<'a> MetadataManager<'a> {
     pub fn new(db: &'a DB) -> Self {
         MetadataManager {
             db,
-            cf: db.cf_handle("metadata:v1").unwrap(),
+            cf: ColumnFamilyRef::new(db, "metadata:v1"),
         }
     }
 
     pub fn get_indexed_properties(&self) -> Result<HashSet<models::Identifier>> {
-        match self.db.get_cf(self.cf, "indexed_properties")? {
+        match self.db.get_cf(self.cf.handle, "indexed_properties")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(HashSet::default()),
+        }
+    }
+
+    // Gets the indexed properties whose indexes weren't ready, because they
+    // were still being built or failed to build.
+    pub fn get_unready_indexed_properties(&self) -> Result<HashSet<models::Identifier>> {
+        match self.db.get_cf(self.cf.handle, "unready_indexed_properties")? {
             Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
             None => Ok(HashSet::default()),
         }
     }
 
-    pub fn set_indexed_properties(&self, batch: &mut WriteBatch, indices: &HashSet<models::Identifier>) -> Result<()> {
-        let value_bytes = bincode::serialize(&indices)?;
+    pub fn set_indexed_properties(&self, batch: &mut Batch, indices: &IndexedProperties) -> Result<()> {
+        let names: HashSet<&models::Identifier> = indices.keys().collect();
+        let value_bytes = bincode::serialize(&names)?;
         batch.put_cf(self.cf, "indexed_properties", &value_bytes);
+
+        let unready_names: HashSet<&models::Identifier> = indices
+            .iter()
+            .filter(|(_, state)| state.status != models::IndexStatus::Ready)
+            .map(|(name, _)| name)
+            .collect();
+        let value_bytes = bincode::serialize(&unready_names)?;
+        batch.put_cf(self.cf, "unready_indexed_properties", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_indexed_type_properties(&self) -> Result<IndexedTypeProperties> {
+        match self.db.get_cf(self.cf.handle, "indexed_type_properties")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(IndexedTypeProperties::default()),
+        }
+    }
+
+    pub fn set_indexed_type_properties(&self, batch: &mut Batch, indices: &IndexedTypeProperties) -> Result<()> {
+        let value_bytes = bincode::serialize(indices)?;
+        batch.put_cf(self.cf, "indexed_type_properties", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_indexed_composite_properties(&self) -> Result<IndexedCompositeProperties> {
+        match self.db.get_cf(self.cf.handle, "indexed_composite_properties")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(IndexedCompositeProperties::default()),
+        }
+    }
+
+    pub fn set_indexed_composite_properties(
+        &self,
+        batch: &mut Batch,
+        indices: &IndexedCompositeProperties,
+    ) -> Result<()> {
+        let value_bytes = bincode::serialize(indices)?;
+        batch.put_cf(self.cf, "indexed_composite_properties", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_unique_properties(&self) -> Result<UniqueProperties> {
+        match self.db.get_cf(self.cf.handle, "unique_properties")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(UniqueProperties::default()),
+        }
+    }
+
+    pub fn set_unique_properties(&self, batch: &mut Batch, constraints: &UniqueProperties) -> Result<()> {
+        let value_bytes = bincode::serialize(constraints)?;
+        batch.put_cf(self.cf, "unique_properties", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_schema(&self) -> Result<Option<models::Schema>> {
+        match self.db.get_cf(self.cf.handle, "schema")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_schema(&self, batch: &mut Batch, schema: &Option<models::Schema>) -> Result<()> {
+        let value_bytes = bincode::serialize(schema)?;
+        batch.put_cf(self.cf, "schema", &value_bytes);
+        Ok(())
+    }
+
+    // Gets the version of the encoding used for keys in the property value
+    // column families. Datastores created before it was tracked used version
+    // 0, which hashed values.
+    pub fn get_property_value_encoding_version(&self) -> Result<u32> {
+        match self.db.get_cf(self.cf.handle, "property_value_encoding_version")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(0),
+        }
+    }
+
+    pub fn set_property_value_encoding_version(&self, batch: &mut Batch, version: u32) -> Result<()> {
+        let value_bytes = bincode::serialize(&version)?;
+        batch.put_cf(self.cf, "property_value_encoding_version", &value_bytes);
+        Ok(())
+    }
+
+    // Gets whether the vertex type index has been built. Datastores created
+    // before the index existed need to build it from the vertices.
+    pub fn get_vertex_type_index_built(&self) -> Result<bool> {
+        match self.db.get_cf(self.cf.handle, "vertex_type_index_built")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(false),
+        }
+    }
+
+    pub fn set_vertex_type_index_built(&self, batch: &mut Batch, built: bool) -> Result<()> {
+        let value_bytes = bincode::serialize(&built)?;
+        batch.put_cf(self.cf, "vertex_type_index_built", &value_bytes);
+        Ok(())
+    }
+
+    pub fn get_type_counts_built(&self) -> Result<bool> {
+        match self.db.get_cf(self.cf.handle, "type_counts_built")? {
+            Some(value_bytes) => Ok(bincode::deserialize(&value_bytes)?),
+            None => Ok(false),
+        }
+    }
+
+    pub fn set_type_counts_built(&self, batch: &mut Batch, built: bool) -> Result<()> {
+        let value_bytes = bincode::serialize(&built)?;
+        batch.put_cf(self.cf, "type_counts_built", &value_bytes);
         Ok(())
     }
 
     pub fn compact(&self) {
         self.db
-            .compact_range_cf(self.cf, Option::<&[u8]>::None, Option::<&[u8]>::None);
+            .compact_range_cf(self.cf.handle, Option::<&[u8]>::None, Option::<&[u8]>::None);
     }
 }
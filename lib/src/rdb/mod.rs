@@ -1,9 +1,13 @@
 //! The rocksdb datastore implementation.
 
+mod config;
 mod datastore;
 mod managers;
+mod verify;
 
+pub use self::config::{RocksdbCompression, RocksdbConfig};
 pub use self::datastore::RocksdbDatastore;
+pub use self::verify::Inconsistency;
 
 #[cfg(feature = "bench-suite")]
 full_bench_impl!({
@@ -13,6 +17,14 @@ full_bench_impl!({
     RocksdbDatastore::new(path, Some(1)).unwrap()
 });
 
+#[cfg(feature = "bench-suite")]
+concurrent_bench_impl!({
+    use super::RocksdbDatastore;
+    use tempfile::tempdir;
+    let path = tempdir().unwrap().into_path();
+    RocksdbDatastore::new(path, Some(1)).unwrap()
+});
+
 #[cfg(feature = "test-suite")]
 full_test_impl!({
     use super::RocksdbDatastore;
@@ -21,6 +33,146 @@ full_test_impl!({
     RocksdbDatastore::new(path, Some(1)).unwrap()
 });
 
+#[cfg(feature = "test-suite")]
+transaction_test_impl!({
+    use super::RocksdbDatastore;
+    use tempfile::tempdir;
+    let path = tempdir().unwrap().into_path();
+    RocksdbDatastore::new(path, Some(1)).unwrap()
+});
+
+#[cfg(feature = "test-suite")]
+snapshot_test_impl!({
+    use super::RocksdbDatastore;
+    use tempfile::tempdir;
+    let path = tempdir().unwrap().into_path();
+    RocksdbDatastore::new(path, Some(1)).unwrap()
+});
+
+// Runs the test suite with every tuning option set, to make sure that none
+// of them change the results of queries.
+#[cfg(feature = "test-suite")]
+mod configured {
+    full_test_impl!({
+        use crate::{RocksdbCompression, RocksdbConfig, RocksdbDatastore};
+        use tempfile::tempdir;
+        let path = tempdir().unwrap().into_path();
+        let mut config = RocksdbConfig {
+            max_open_files: Some(16),
+            block_cache_size: Some(1_048_576),
+            bloom_filter_bits_per_key: Some(10),
+            compression_per_level: vec![
+                RocksdbCompression::None,
+                RocksdbCompression::None,
+                RocksdbCompression::Snappy,
+                RocksdbCompression::Snappy,
+            ],
+            ..RocksdbConfig::default()
+        };
+        for cf_name in &["vertices:v1", "edges:v1", "edge_ranges:v1", "reversed_edge_ranges:v1"] {
+            config.prefix_extractors.insert(cf_name.to_string(), 16);
+        }
+        RocksdbDatastore::new_with_config(path, &config).unwrap()
+    });
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_not_configure_unknown_column_families() {
+    use super::{RocksdbConfig, RocksdbDatastore};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let mut config = RocksdbConfig::default();
+    config.prefix_extractors.insert("foo:v1".to_string(), 16);
+    assert!(RocksdbDatastore::new_with_config(dir.path(), &config).is_err());
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_lookup_by_key_prefix_after_sync() {
+    use super::RocksdbDatastore;
+    use crate::{
+        Datastore, EdgeDirection, EdgeKey, EdgeQueryExt, Identifier, PropertyValueVertexQuery, RangeVertexQuery,
+        SpecificEdgeQuery, SpecificVertexQuery, VertexQueryExt,
+    };
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    let name = Identifier::new("a").unwrap();
+    datastore.index_property(name.clone()).unwrap();
+
+    // Identifiers where one is a prefix of the other must still have
+    // different key prefixes
+    let short_t = Identifier::new("a").unwrap();
+    let long_t = Identifier::new("ab").unwrap();
+    let short_id = datastore.create_vertex_from_type(short_t.clone()).unwrap();
+    let long_id = datastore.create_vertex_from_type(long_t.clone()).unwrap();
+    let short_key = EdgeKey::new(short_id, short_t.clone(), long_id);
+    let long_key = EdgeKey::new(short_id, long_t.clone(), long_id);
+    datastore.create_edge(&short_key).unwrap();
+    datastore.create_edge(&long_key).unwrap();
+    datastore
+        .set_vertex_properties(
+            SpecificVertexQuery::single(short_id).property(name.clone()),
+            serde_json::json!(1),
+        )
+        .unwrap();
+    datastore
+        .set_edge_properties(
+            SpecificEdgeQuery::single(short_key.clone()).property(name.clone()),
+            serde_json::json!(2),
+        )
+        .unwrap();
+
+    // Write everything out, so that lookups go through the prefix bloom
+    // filters of the data files rather than the memtables
+    datastore.sync().unwrap();
+
+    let vertices = datastore
+        .get_vertices(RangeVertexQuery::new().t(short_t.clone()).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, short_id);
+    assert_eq!(
+        datastore
+            .get_edge_count(short_id, None, EdgeDirection::Outbound)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        datastore
+            .get_edge_count(short_id, Some(&long_t), EdgeDirection::Outbound)
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        datastore.get_edge_count(long_id, None, EdgeDirection::Inbound).unwrap(),
+        2
+    );
+    assert_eq!(
+        datastore
+            .get_edge_count(long_id, None, EdgeDirection::Outbound)
+            .unwrap(),
+        0
+    );
+    let properties = datastore
+        .get_all_vertex_properties(SpecificVertexQuery::single(short_id).into())
+        .unwrap();
+    assert_eq!(properties[0].props.len(), 1);
+    let properties = datastore
+        .get_all_edge_properties(SpecificEdgeQuery::new(vec![short_key, long_key]).into())
+        .unwrap();
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties[0].props.len() + properties[1].props.len(), 1);
+    let vertices = datastore
+        .get_vertices(PropertyValueVertexQuery::new(name, serde_json::json!(1)).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, short_id);
+}
+
 #[cfg(feature = "test-suite")]
 #[test]
 fn should_repair() {
@@ -35,3 +187,501 @@ fn should_repair() {
     // Now try to repair
     RocksdbDatastore::repair(dir.path(), Some(1)).unwrap();
 }
+
+// Opens the database underlying a datastore directly, so tests can tamper
+// with it. The type counts column family needs its merge operator, or the
+// writes made to it can't be recovered from the log.
+#[cfg(all(test, feature = "test-suite"))]
+fn open_db(path: &std::path::Path) -> rocksdb::DB {
+    use self::managers::{merge_counts, TYPE_COUNTS_CF};
+    use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+
+    let cf_names = DB::list_cf(&Options::default(), path).unwrap();
+    let descriptors = cf_names.into_iter().map(|name| {
+        let mut opts = Options::default();
+        if name == TYPE_COUNTS_CF {
+            opts.set_merge_operator_associative("merge_counts", merge_counts);
+        }
+        ColumnFamilyDescriptor::new(name, opts)
+    });
+    DB::open_cf_descriptors(&Options::default(), path, descriptors).unwrap()
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_migrate_property_value_indexes() {
+    use super::RocksdbDatastore;
+    use crate::{Datastore, Identifier, PropertyValueVertexQuery, SpecificVertexQuery, VertexQueryExt};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let name = Identifier::new("foo").unwrap();
+    let value = serde_json::json!(1);
+
+    let id = {
+        let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+        datastore.index_property(name.clone()).unwrap();
+        let id = datastore
+            .create_vertex_from_type(Identifier::new("test_vertex_type").unwrap())
+            .unwrap();
+        datastore
+            .set_vertex_properties(SpecificVertexQuery::single(id).property(name.clone()), value.clone())
+            .unwrap();
+        id
+    };
+
+    // Replace the index with one in the old, hashed format
+    {
+        let db = open_db(dir.path());
+        let metadata_cf = db.cf_handle("metadata:v1").unwrap();
+        db.delete_cf(metadata_cf, "property_value_encoding_version").unwrap();
+        let values_cf = db.cf_handle("vertex_property_values:v1").unwrap();
+        let keys: Vec<Box<[u8]>> = db
+            .iterator_cf(values_cf, rocksdb::IteratorMode::Start)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys.len(), 1);
+        db.delete_cf(values_cf, &keys[0]).unwrap();
+        let mut legacy_key = vec![name.as_str().len() as u8];
+        legacy_key.extend_from_slice(name.as_str().as_bytes());
+        legacy_key.extend_from_slice(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0]);
+        legacy_key.extend_from_slice(id.as_bytes());
+        db.put_cf(values_cf, legacy_key, []).unwrap();
+    }
+
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    let result = datastore
+        .get_vertices(PropertyValueVertexQuery::new(name, value).into())
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, id);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_resume_interrupted_index_build() {
+    use super::RocksdbDatastore;
+    use crate::tests::wait_for_index;
+    use crate::{Datastore, Identifier, PropertyValueVertexQuery, SpecificVertexQuery, VertexQueryExt};
+    use std::collections::HashSet;
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let name = Identifier::new("foo").unwrap();
+    let value = serde_json::json!(1);
+
+    let id = {
+        let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+        datastore.index_property(name.clone()).unwrap();
+        let id = datastore
+            .create_vertex_from_type(Identifier::new("test_vertex_type").unwrap())
+            .unwrap();
+        datastore
+            .set_vertex_properties(SpecificVertexQuery::single(id).property(name.clone()), value.clone())
+            .unwrap();
+        id
+    };
+
+    // Mark the index as unready and clear it, as if the datastore was closed
+    // before the index was built
+    {
+        let db = open_db(dir.path());
+        let metadata_cf = db.cf_handle("metadata:v1").unwrap();
+        let unready_names: HashSet<&Identifier> = vec![&name].into_iter().collect();
+        db.put_cf(
+            metadata_cf,
+            "unready_indexed_properties",
+            bincode::serialize(&unready_names).unwrap(),
+        )
+        .unwrap();
+        let values_cf = db.cf_handle("vertex_property_values:v1").unwrap();
+        let keys: Vec<Box<[u8]>> = db
+            .iterator_cf(values_cf, rocksdb::IteratorMode::Start)
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys.len(), 1);
+        db.delete_cf(values_cf, &keys[0]).unwrap();
+    }
+
+    // Dropping the datastore stops the resumed build, and releases the
+    // database, so it can be opened again straight away
+    drop(RocksdbDatastore::new(dir.path(), Some(1)).unwrap());
+
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    wait_for_index(&datastore, &name);
+    let result = datastore
+        .get_vertices(PropertyValueVertexQuery::new(name, value).into())
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, id);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_migrate_type_counts() {
+    use super::RocksdbDatastore;
+    use crate::{Datastore, EdgeKey, Identifier, SpecificVertexQuery, VertexQueryExt};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let t = Identifier::new("test_type").unwrap();
+    let name = Identifier::new("foo").unwrap();
+
+    let summary = {
+        let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+        let outbound_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+        let inbound_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+        datastore
+            .create_edge(&EdgeKey::new(outbound_id, t.clone(), inbound_id))
+            .unwrap();
+        datastore
+            .set_vertex_properties(
+                SpecificVertexQuery::single(outbound_id).property(name.clone()),
+                serde_json::json!(1),
+            )
+            .unwrap();
+        datastore.get_schema_summary().unwrap()
+    };
+    assert_eq!(summary.vertex_types.get(&t), Some(&2));
+    assert_eq!(summary.edge_types.get(&t), Some(&1));
+
+    // The counts are kept when the datastore is reopened
+    assert_eq!(
+        RocksdbDatastore::new(dir.path(), Some(1))
+            .unwrap()
+            .get_schema_summary()
+            .unwrap(),
+        summary
+    );
+
+    // Clear the counts, as if the datastore was created before they existed
+    {
+        let mut db = open_db(dir.path());
+        let metadata_cf = db.cf_handle("metadata:v1").unwrap();
+        db.delete_cf(metadata_cf, "type_counts_built").unwrap();
+        db.drop_cf("type_counts:v1").unwrap();
+    }
+
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    assert_eq!(datastore.get_schema_summary().unwrap(), summary);
+    assert_eq!(datastore.verify(false).unwrap(), vec![]);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_count_properties_of_vertices_created_later() {
+    use super::RocksdbDatastore;
+    use crate::{BulkInsertItem, Datastore, Identifier, Vertex};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    let t = Identifier::new("test_type").unwrap();
+    let other_t = Identifier::new("other_type").unwrap();
+    let name = Identifier::new("foo").unwrap();
+    let first = Vertex::new(t.clone());
+    let second = Vertex::new(t.clone());
+
+    // Properties of vertices that don't exist aren't counted until the
+    // vertices are created, whether in the same insert or a later one
+    datastore
+        .bulk_insert(vec![
+            BulkInsertItem::VertexProperty(first.id, name.clone(), serde_json::json!(1)),
+            BulkInsertItem::Vertex(first.clone()),
+            BulkInsertItem::VertexProperty(second.id, name.clone(), serde_json::json!(2)),
+        ])
+        .unwrap();
+    let mut summary = datastore.get_schema_summary().unwrap();
+    assert_eq!(summary.vertex_types.get(&t), Some(&1));
+    assert!(summary.vertex_properties[&t].contains(&name));
+    datastore.create_vertex(&second).unwrap();
+    summary.vertex_types.insert(t.clone(), 2);
+    assert_eq!(datastore.get_schema_summary().unwrap(), summary);
+    assert_eq!(datastore.verify(false).unwrap(), vec![]);
+
+    // Changing the type of a vertex moves its properties to the new type
+    datastore
+        .bulk_insert(vec![
+            BulkInsertItem::VertexProperty(first.id, name.clone(), serde_json::json!(3)),
+            BulkInsertItem::Vertex(Vertex::with_id(first.id, other_t.clone())),
+        ])
+        .unwrap();
+    let summary = datastore.get_schema_summary().unwrap();
+    assert_eq!(summary.vertex_types.get(&t), Some(&1));
+    assert_eq!(summary.vertex_types.get(&other_t), Some(&1));
+    assert!(summary.vertex_properties[&t].contains(&name));
+    assert!(summary.vertex_properties[&other_t].contains(&name));
+    assert_eq!(datastore.verify(false).unwrap(), vec![]);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_verify_consistent_datastore() {
+    use super::RocksdbDatastore;
+    use crate::{Datastore, EdgeKey, Error, Identifier, SpecificVertexQuery, VertexQueryExt};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    let name = Identifier::new("foo").unwrap();
+    datastore.index_property(name.clone()).unwrap();
+    let t = Identifier::new("test_type").unwrap();
+    let outbound_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    let inbound_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    datastore
+        .create_edge(&EdgeKey::new(outbound_id, t.clone(), inbound_id))
+        .unwrap();
+    datastore
+        .set_vertex_properties(
+            SpecificVertexQuery::single(outbound_id).property(name),
+            serde_json::json!(1),
+        )
+        .unwrap();
+    datastore
+        .delete_vertices(SpecificVertexQuery::single(inbound_id).into())
+        .unwrap();
+
+    // Dropping an index deletes all of its entries
+    let dropped_name = Identifier::new("bar").unwrap();
+    datastore.index_property(dropped_name.clone()).unwrap();
+    datastore
+        .set_vertex_properties(
+            SpecificVertexQuery::single(outbound_id).property(dropped_name.clone()),
+            serde_json::json!(2),
+        )
+        .unwrap();
+    datastore.drop_index(dropped_name).unwrap();
+
+    assert_eq!(datastore.verify(false).unwrap(), vec![]);
+    assert_eq!(datastore.verify(true).unwrap(), vec![]);
+
+    match datastore.transaction().unwrap().verify(false) {
+        Err(Error::Unsupported) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_keep_indexes_consistent_with_transactions() {
+    use super::RocksdbDatastore;
+    use crate::{Datastore, Identifier, SpecificVertexQuery, VertexQueryExt};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    let name = Identifier::new("foo").unwrap();
+    let dropped_name = Identifier::new("bar").unwrap();
+    datastore.index_property(name.clone()).unwrap();
+    datastore.index_property(dropped_name.clone()).unwrap();
+    let id = datastore
+        .create_vertex_from_type(Identifier::new("test_type").unwrap())
+        .unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    trans
+        .set_vertex_properties(
+            SpecificVertexQuery::single(id).property(name.clone()),
+            serde_json::json!(1),
+        )
+        .unwrap();
+    trans
+        .set_vertex_properties(
+            SpecificVertexQuery::single(id).property(dropped_name.clone()),
+            serde_json::json!(1),
+        )
+        .unwrap();
+
+    // Neither the other write nor the dropped index should leave stale
+    // entries once the transaction is committed
+    datastore
+        .set_vertex_properties(SpecificVertexQuery::single(id).property(name), serde_json::json!(2))
+        .unwrap();
+    datastore.drop_index(dropped_name).unwrap();
+    trans.commit().unwrap();
+
+    assert_eq!(datastore.verify(false).unwrap(), vec![]);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_verify_and_repair() {
+    use super::{Inconsistency, RocksdbDatastore};
+    use crate::{
+        Datastore, EdgeDirection, EdgeKey, Identifier, PropertyValueVertexQuery, SpecificVertexQuery, VertexQueryExt,
+    };
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let name = Identifier::new("foo").unwrap();
+    let t = Identifier::new("test_type").unwrap();
+
+    let (first_id, second_id, third_id) = {
+        let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+        datastore.index_property(name.clone()).unwrap();
+        let ids: Vec<_> = (0..3)
+            .map(|i| {
+                let id = datastore.create_vertex_from_type(t.clone()).unwrap();
+                datastore
+                    .set_vertex_properties(
+                        SpecificVertexQuery::single(id).property(name.clone()),
+                        serde_json::json!(i),
+                    )
+                    .unwrap();
+                id
+            })
+            .collect();
+        datastore.create_edge(&EdgeKey::new(ids[0], t.clone(), ids[1])).unwrap();
+        datastore.create_edge(&EdgeKey::new(ids[1], t.clone(), ids[2])).unwrap();
+        (ids[0], ids[1], ids[2])
+    };
+
+    // Corrupt the datastore, as if some writes were partially applied
+    {
+        let db = open_db(dir.path());
+        let vertices_cf = db.cf_handle("vertices:v1").unwrap();
+        db.delete_cf(vertices_cf, third_id.as_bytes()).unwrap();
+        let reversed_edge_ranges_cf = db.cf_handle("reversed_edge_ranges:v1").unwrap();
+        let (range_key, _) = db
+            .iterator_cf(
+                reversed_edge_ranges_cf,
+                rocksdb::IteratorMode::From(second_id.as_bytes(), rocksdb::Direction::Forward),
+            )
+            .next()
+            .unwrap();
+        db.delete_cf(reversed_edge_ranges_cf, range_key).unwrap();
+        let vertex_properties_cf = db.cf_handle("vertex_properties:v1").unwrap();
+        let mut property_key = second_id.as_bytes().to_vec();
+        property_key.extend_from_slice(name.as_str().as_bytes());
+        db.delete_cf(vertex_properties_cf, property_key).unwrap();
+    }
+
+    let datastore = RocksdbDatastore::new(dir.path(), Some(1)).unwrap();
+    let expected = vec![
+        Inconsistency::StaleVertexType {
+            id: third_id,
+            t: t.clone(),
+        },
+        Inconsistency::MissingReversedEdgeRange(EdgeKey::new(first_id, t.clone(), second_id)),
+        Inconsistency::DanglingEdge(EdgeKey::new(second_id, t.clone(), third_id)),
+        Inconsistency::DanglingVertexProperty {
+            id: third_id,
+            name: name.clone(),
+        },
+        Inconsistency::StaleVertexPropertyValue {
+            id: second_id,
+            name: name.clone(),
+            value: serde_json::json!(1),
+        },
+        Inconsistency::StaleVertexCount {
+            t: t.clone(),
+            recorded: 3,
+            actual: 2,
+        },
+        Inconsistency::StaleVertexPropertyCount {
+            t: t.clone(),
+            name: name.clone(),
+            recorded: 3,
+            actual: 1,
+        },
+    ];
+    assert_eq!(
+        expected[5].to_string(),
+        "the count of test_type vertices is 3, but there are 2"
+    );
+    assert_eq!(datastore.verify(false).unwrap(), expected);
+    assert_eq!(datastore.verify(true).unwrap(), expected);
+    assert_eq!(datastore.verify(false).unwrap(), vec![]);
+
+    assert_eq!(
+        datastore
+            .get_edge_count(second_id, None, EdgeDirection::Inbound)
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        datastore
+            .get_edge_count(second_id, None, EdgeDirection::Outbound)
+            .unwrap(),
+        0
+    );
+    let vertices = datastore
+        .get_vertices(PropertyValueVertexQuery::new(name, serde_json::json!(1)).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 0);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_checkpoint() {
+    use super::RocksdbDatastore;
+    use crate::{Datastore, Identifier};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let checkpoint_path = dir.path().join("checkpoint");
+
+    let datastore = RocksdbDatastore::new(dir.path().join("db"), Some(1)).unwrap();
+    datastore.create_vertex_from_type(Identifier::default()).unwrap();
+    datastore.checkpoint(&checkpoint_path).unwrap();
+    datastore.create_vertex_from_type(Identifier::default()).unwrap();
+
+    let checkpoint = RocksdbDatastore::new(&checkpoint_path, Some(1)).unwrap();
+    assert_eq!(checkpoint.get_vertex_count().unwrap(), 1);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_backup_and_restore() {
+    use super::RocksdbDatastore;
+    use crate::{Datastore, Identifier, SpecificVertexQuery};
+    use tempfile::tempdir;
+
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("db");
+    let backup_path = dir.path().join("backup");
+    let restore_path = dir.path().join("restore");
+
+    let (first_id, second_id) = {
+        let datastore = RocksdbDatastore::new(&db_path, Some(1)).unwrap();
+        let first_id = datastore.create_vertex_from_type(Identifier::default()).unwrap();
+        datastore.backup(&backup_path).unwrap();
+        let second_id = datastore.create_vertex_from_type(Identifier::default()).unwrap();
+        // Later backups to the same path add to it
+        datastore.backup(&backup_path).unwrap();
+        datastore.create_vertex_from_type(Identifier::default()).unwrap();
+        (first_id, second_id)
+    };
+
+    // The latest backup is restored
+    RocksdbDatastore::restore(&backup_path, &restore_path).unwrap();
+    let datastore = RocksdbDatastore::new(&restore_path, Some(1)).unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 2);
+    let vertices = datastore
+        .get_vertices(SpecificVertexQuery::new(vec![first_id, second_id]).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 2);
+}
+
+#[cfg(feature = "test-suite")]
+#[test]
+fn should_deserialize_config() {
+    use super::{RocksdbCompression, RocksdbConfig};
+
+    // Missing options take their default values
+    let config: RocksdbConfig = serde_json::from_str(
+        r#"{"block_cache_size": 1048576, "compression_per_level": ["none", "lz4", "zstd"], "prefix_extractors": {"edges:v1": 16}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.block_cache_size, Some(1_048_576));
+    assert_eq!(
+        config.compression_per_level,
+        vec![
+            RocksdbCompression::None,
+            RocksdbCompression::Lz4,
+            RocksdbCompression::Zstd
+        ]
+    );
+    assert_eq!(config.prefix_extractors.get("edges:v1"), Some(&16));
+    assert_eq!(config.num_levels, RocksdbConfig::default().num_levels);
+}
@@ -0,0 +1,413 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::mem;
+
+use super::managers::*;
+use crate::errors::Result;
+use crate::{EdgeKey, Identifier};
+
+use uuid::Uuid;
+
+/// An inconsistency between the column families of a rocksdb datastore,
+/// found by `RocksdbDatastore::verify`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Inconsistency {
+    /// A vertex that is missing from the vertex type index.
+    MissingVertexType { id: Uuid, t: Identifier },
+    /// An entry in the vertex type index for a vertex that doesn't exist,
+    /// or that has a different type.
+    StaleVertexType { id: Uuid, t: Identifier },
+    /// An edge whose outbound or inbound vertex doesn't exist.
+    DanglingEdge(EdgeKey),
+    /// An edge that is missing from the edge ranges of its outbound vertex.
+    MissingEdgeRange(EdgeKey),
+    /// An edge that is missing from the reversed edge ranges of its inbound
+    /// vertex.
+    MissingReversedEdgeRange(EdgeKey),
+    /// An edge range for an edge that doesn't exist, or that was updated at
+    /// a different time.
+    StaleEdgeRange(EdgeKey),
+    /// A reversed edge range for an edge that doesn't exist, or that was
+    /// updated at a different time.
+    StaleReversedEdgeRange(EdgeKey),
+    /// A property of a vertex that doesn't exist.
+    DanglingVertexProperty { id: Uuid, name: Identifier },
+    /// A property of an edge that doesn't exist.
+    DanglingEdgeProperty { key: EdgeKey, name: Identifier },
+    /// An indexed vertex property that is missing from the property value
+    /// index.
+    MissingVertexPropertyValue { id: Uuid, name: Identifier },
+    /// An indexed edge property that is missing from the property value
+    /// index.
+    MissingEdgePropertyValue { key: EdgeKey, name: Identifier },
+    /// An entry in the vertex property value index that doesn't match a
+    /// property, or whose property isn't indexed.
+    StaleVertexPropertyValue {
+        id: Uuid,
+        name: Identifier,
+        value: serde_json::Value,
+    },
+    /// An entry in the edge property value index that doesn't match a
+    /// property, or whose property isn't indexed.
+    StaleEdgePropertyValue {
+        key: EdgeKey,
+        name: Identifier,
+        value: serde_json::Value,
+    },
+    /// A count of the vertices of a type that doesn't match the vertices.
+    StaleVertexCount { t: Identifier, recorded: i64, actual: u64 },
+    /// A count of the edges of a type that doesn't match the edges.
+    StaleEdgeCount { t: Identifier, recorded: i64, actual: u64 },
+    /// A count of the properties with a name, of vertices of a type, that
+    /// doesn't match the properties.
+    StaleVertexPropertyCount {
+        t: Identifier,
+        name: Identifier,
+        recorded: i64,
+        actual: u64,
+    },
+    /// A count of the properties with a name, of edges of a type, that
+    /// doesn't match the properties.
+    StaleEdgePropertyCount {
+        t: Identifier,
+        name: Identifier,
+        recorded: i64,
+        actual: u64,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inconsistency::MissingVertexType { id, t } => {
+                write!(f, "vertex {} is missing from the index of {} vertices", id, t.as_str())
+            }
+            Inconsistency::StaleVertexType { id, t } => write!(
+                f,
+                "vertex {} is in the index of {} vertices, but doesn't exist or has a different type",
+                id,
+                t.as_str()
+            ),
+            Inconsistency::DanglingEdge(key) => {
+                write!(f, "edge {} is missing its outbound or inbound vertex", edge(key))
+            }
+            Inconsistency::MissingEdgeRange(key) => write!(f, "edge {} is missing from its edge range", edge(key)),
+            Inconsistency::MissingReversedEdgeRange(key) => {
+                write!(f, "edge {} is missing from its reversed edge range", edge(key))
+            }
+            Inconsistency::StaleEdgeRange(key) => write!(
+                f,
+                "edge {} is in an edge range, but doesn't exist or was updated at a different time",
+                edge(key)
+            ),
+            Inconsistency::StaleReversedEdgeRange(key) => write!(
+                f,
+                "edge {} is in a reversed edge range, but doesn't exist or was updated at a different time",
+                edge(key)
+            ),
+            Inconsistency::DanglingVertexProperty { id, name } => write!(
+                f,
+                "property {} is set on vertex {}, which doesn't exist",
+                name.as_str(),
+                id
+            ),
+            Inconsistency::DanglingEdgeProperty { key, name } => write!(
+                f,
+                "property {} is set on edge {}, which doesn't exist",
+                name.as_str(),
+                edge(key)
+            ),
+            Inconsistency::MissingVertexPropertyValue { id, name } => write!(
+                f,
+                "property {} of vertex {} is missing from the property index",
+                name.as_str(),
+                id
+            ),
+            Inconsistency::MissingEdgePropertyValue { key, name } => write!(
+                f,
+                "property {} of edge {} is missing from the property index",
+                name.as_str(),
+                edge(key)
+            ),
+            Inconsistency::StaleVertexPropertyValue { id, name, value } => write!(
+                f,
+                "property {} of vertex {} is in the property index as {}, but isn't set to that or isn't indexed",
+                name.as_str(),
+                id,
+                value
+            ),
+            Inconsistency::StaleEdgePropertyValue { key, name, value } => write!(
+                f,
+                "property {} of edge {} is in the property index as {}, but isn't set to that or isn't indexed",
+                name.as_str(),
+                edge(key),
+                value
+            ),
+            Inconsistency::StaleVertexCount { t, recorded, actual } => write!(
+                f,
+                "the count of {} vertices is {}, but there are {}",
+                t.as_str(),
+                recorded,
+                actual
+            ),
+            Inconsistency::StaleEdgeCount { t, recorded, actual } => write!(
+                f,
+                "the count of {} edges is {}, but there are {}",
+                t.as_str(),
+                recorded,
+                actual
+            ),
+            Inconsistency::StaleVertexPropertyCount {
+                t,
+                name,
+                recorded,
+                actual,
+            } => write!(
+                f,
+                "the count of {} vertices with property {} is {}, but there are {}",
+                t.as_str(),
+                name.as_str(),
+                recorded,
+                actual
+            ),
+            Inconsistency::StaleEdgePropertyCount {
+                t,
+                name,
+                recorded,
+                actual,
+            } => write!(
+                f,
+                "the count of {} edges with property {} is {}, but there are {}",
+                t.as_str(),
+                name.as_str(),
+                recorded,
+                actual
+            ),
+        }
+    }
+}
+
+// Formats an edge key as `outbound_id -[t]-> inbound_id`.
+fn edge(key: &EdgeKey) -> String {
+    format!("{} -[{}]-> {}", key.outbound_id, key.t.as_str(), key.inbound_id)
+}
+
+// The number of fixes that are written at a time when repairing, so that
+// repairing a badly damaged database doesn't build one huge batch.
+const REPAIR_BATCH_SIZE: usize = 10_000;
+
+// The inconsistencies found by a check, along with their fixes. When
+// repairing, the fixes are written in chunks as they're queued, so the
+// check should read from a snapshot that doesn't see them. Otherwise, the
+// fixes are discarded.
+pub(crate) struct Repairs<'a> {
+    db_ref: DBRef<'a>,
+    repair: bool,
+    batch: Batch,
+    queued: usize,
+    inconsistencies: Vec<Inconsistency>,
+}
+
+impl<'a> Repairs<'a> {
+    pub fn new(db_ref: DBRef<'a>, repair: bool) -> Self {
+        Self {
+            db_ref,
+            repair,
+            batch: db_ref.batch(),
+            queued: 0,
+            inconsistencies: Vec::new(),
+        }
+    }
+
+    // Records an inconsistency, once its fix has been queued onto `batch`.
+    fn push(&mut self, inconsistency: Inconsistency) -> Result<()> {
+        self.inconsistencies.push(inconsistency);
+        self.queued += 1;
+        if self.queued >= REPAIR_BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let batch = mem::replace(&mut self.batch, self.db_ref.batch());
+        self.queued = 0;
+        if self.repair {
+            batch.write(self.db_ref.db, None)?;
+        }
+        Ok(())
+    }
+
+    // Writes any fixes that are still queued, and returns the
+    // inconsistencies that were found.
+    pub fn finish(mut self) -> Result<Vec<Inconsistency>> {
+        self.flush()?;
+        Ok(self.inconsistencies)
+    }
+}
+
+// Cross-checks the column families, recording each inconsistency that's
+// found in `repairs` along with its fix.
+pub(crate) fn verify(db_ref: DBRef<'_>, repairs: &mut Repairs<'_>) -> Result<()> {
+    let vertex_manager = VertexManager::new(db_ref);
+    let vertex_type_manager = VertexTypeManager::new(db_ref);
+    let edge_manager = EdgeManager::new(db_ref);
+    let edge_range_manager = EdgeRangeManager::new(db_ref);
+    let reversed_edge_range_manager = EdgeRangeManager::new_reversed(db_ref);
+    let vertex_property_manager = VertexPropertyManager::new(db_ref);
+    let edge_property_manager = EdgePropertyManager::new(db_ref);
+    let vertex_property_value_manager = VertexPropertyValueManager::new(db_ref);
+    let edge_property_value_manager = EdgePropertyValueManager::new(db_ref);
+
+    for item in vertex_manager.iterate_for_range(Uuid::default()) {
+        let (id, t) = item?;
+        if !vertex_type_manager.exists(&t, id)? {
+            vertex_type_manager.set(&mut repairs.batch, &t, id);
+            repairs.push(Inconsistency::MissingVertexType { id, t })?;
+        }
+    }
+
+    for (t, id) in vertex_type_manager.iterate_for_all() {
+        if vertex_manager.get(id)?.as_ref() != Some(&t) {
+            vertex_type_manager.delete(&mut repairs.batch, &t, id);
+            repairs.push(Inconsistency::StaleVertexType { id, t })?;
+        }
+    }
+
+    // Deleting a dangling edge also deletes its properties, so they're
+    // skipped when checking properties below, rather than being fixed twice.
+    let mut dangling_edges = HashSet::new();
+
+    for (out_id, t, update_datetime, in_id) in edge_manager.iterate_for_all() {
+        let key = EdgeKey::new(out_id, t, in_id);
+        if !vertex_manager.exists(out_id)? || !vertex_manager.exists(in_id)? {
+            edge_manager.delete(&mut repairs.batch, out_id, &key.t, in_id, update_datetime)?;
+            repairs.push(Inconsistency::DanglingEdge(key.clone()))?;
+            dangling_edges.insert(key);
+            continue;
+        }
+        if !edge_range_manager.exists(out_id, &key.t, update_datetime, in_id)? {
+            edge_range_manager.set(&mut repairs.batch, out_id, &key.t, update_datetime, in_id)?;
+            repairs.push(Inconsistency::MissingEdgeRange(key.clone()))?;
+        }
+        if !reversed_edge_range_manager.exists(in_id, &key.t, update_datetime, out_id)? {
+            reversed_edge_range_manager.set(&mut repairs.batch, in_id, &key.t, update_datetime, out_id)?;
+            repairs.push(Inconsistency::MissingReversedEdgeRange(key))?;
+        }
+    }
+
+    for item in edge_range_manager.iterate_for_all() {
+        let (out_id, t, update_datetime, in_id) = item?;
+        if edge_manager.get(out_id, &t, in_id)? != Some(update_datetime) {
+            edge_range_manager.delete(&mut repairs.batch, out_id, &t, update_datetime, in_id)?;
+            repairs.push(Inconsistency::StaleEdgeRange(EdgeKey::new(out_id, t, in_id)))?;
+        }
+    }
+
+    for item in reversed_edge_range_manager.iterate_for_all() {
+        let (in_id, t, update_datetime, out_id) = item?;
+        if edge_manager.get(out_id, &t, in_id)? != Some(update_datetime) {
+            reversed_edge_range_manager.delete(&mut repairs.batch, in_id, &t, update_datetime, out_id)?;
+            repairs.push(Inconsistency::StaleReversedEdgeRange(EdgeKey::new(out_id, t, in_id)))?;
+        }
+    }
+
+    for item in vertex_property_manager.iterate_for_all() {
+        let ((id, name), value) = item?;
+        if !vertex_manager.exists(id)? {
+            vertex_property_manager.delete(&mut repairs.batch, id, None, &name)?;
+            repairs.push(Inconsistency::DanglingVertexProperty { id, name })?;
+        } else if db_ref.indexes.properties.contains_key(&name)
+            && !vertex_property_value_manager.exists(id, &name, &value)?
+        {
+            vertex_property_value_manager.set(&mut repairs.batch, id, &name, &value);
+            repairs.push(Inconsistency::MissingVertexPropertyValue { id, name })?;
+        }
+    }
+
+    for item in edge_property_manager.iterate_for_all() {
+        let ((out_id, t, in_id, name), value) = item?;
+        let key = EdgeKey::new(out_id, t, in_id);
+        if dangling_edges.contains(&key) {
+            continue;
+        }
+        if edge_manager.get(out_id, &key.t, in_id)?.is_none() {
+            edge_property_manager.delete(&mut repairs.batch, out_id, &key.t, in_id, &name)?;
+            repairs.push(Inconsistency::DanglingEdgeProperty { key, name })?;
+        } else if db_ref.indexes.properties.contains_key(&name)
+            && !edge_property_value_manager.exists(out_id, &key.t, in_id, &name, &value)?
+        {
+            edge_property_value_manager.set(&mut repairs.batch, out_id, &key.t, in_id, &name, &value);
+            repairs.push(Inconsistency::MissingEdgePropertyValue { key, name })?;
+        }
+    }
+
+    for item in vertex_property_value_manager.iterate_for_all() {
+        let (name, value, id) = item?;
+        if !db_ref.indexes.properties.contains_key(&name)
+            || vertex_property_manager.get(id, &name)?.as_ref() != Some(&value)
+        {
+            vertex_property_value_manager.delete(&mut repairs.batch, id, &name, &value);
+            repairs.push(Inconsistency::StaleVertexPropertyValue {
+                id,
+                name,
+                value: value.0,
+            })?;
+        }
+    }
+
+    for item in edge_property_value_manager.iterate_for_all() {
+        let (name, value, (out_id, t, in_id)) = item?;
+        if !db_ref.indexes.properties.contains_key(&name)
+            || edge_property_manager.get(out_id, &t, in_id, &name)?.as_ref() != Some(&value)
+        {
+            edge_property_value_manager.delete(&mut repairs.batch, out_id, &t, in_id, &name, &value);
+            repairs.push(Inconsistency::StaleEdgePropertyValue {
+                key: EdgeKey::new(out_id, t, in_id),
+                name,
+                value: value.0,
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+// Checks the counts of the vertices, edges and properties of each type
+// against the data, recording each count that's off in `repairs` along with
+// its fix. This should be run after the fixes found by `verify` are
+// written, since they change the counts.
+pub(crate) fn verify_type_counts(db_ref: DBRef<'_>, repairs: &mut Repairs<'_>) -> Result<()> {
+    let type_count_manager = TypeCountManager::new(db_ref);
+    let recorded_counts = type_count_manager.get_all();
+    let actual_counts = type_count_manager.recount()?;
+
+    let counts: BTreeSet<&TypeCount> = recorded_counts.keys().chain(actual_counts.keys()).collect();
+    for count in counts {
+        let recorded = recorded_counts.get(count).copied().unwrap_or(0);
+        let actual = actual_counts.get(count).copied().unwrap_or(0);
+        if recorded == actual as i64 {
+            continue;
+        }
+        type_count_manager.set(&mut repairs.batch, count, actual);
+        repairs.push(match count.clone() {
+            TypeCount::Vertices(t) => Inconsistency::StaleVertexCount { t, recorded, actual },
+            TypeCount::Edges(t) => Inconsistency::StaleEdgeCount { t, recorded, actual },
+            TypeCount::VertexProperties(t, name) => Inconsistency::StaleVertexPropertyCount {
+                t,
+                name,
+                recorded,
+                actual,
+            },
+            TypeCount::EdgeProperties(t, name) => Inconsistency::StaleEdgePropertyCount {
+                t,
+                name,
+                recorded,
+                actual,
+            },
+        })?;
+    }
+
+    Ok(())
+}
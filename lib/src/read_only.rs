@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use crate::errors::{Error, Result};
+use crate::models;
+use crate::traits::{Datastore, DynIter};
+
+use uuid::Uuid;
+
+/// Wraps a datastore so that it can only be read. Reads are passed through
+/// to the wrapped datastore, while writes return `Error::Unsupported`.
+///
+/// Datastores use this to implement `Datastore::snapshot`, by wrapping a
+/// datastore that reads from a point-in-time view of their data.
+#[derive(Debug)]
+pub struct ReadOnlyDatastore<D: Datastore> {
+    datastore: Arc<D>,
+}
+
+impl<D: Datastore> ReadOnlyDatastore<D> {
+    /// Creates a new read-only datastore.
+    ///
+    /// # Arguments
+    /// * `datastore`: The datastore to read from.
+    pub fn new(datastore: D) -> Self {
+        Self {
+            datastore: Arc::new(datastore),
+        }
+    }
+}
+
+impl<D: Datastore> Clone for ReadOnlyDatastore<D> {
+    fn clone(&self) -> Self {
+        Self {
+            datastore: self.datastore.clone(),
+        }
+    }
+}
+
+impl<D: Datastore + Send + Sync + 'static> Datastore for ReadOnlyDatastore<D> {
+    fn snapshot(&self) -> Result<Box<dyn Datastore + Send + Sync>> {
+        // This is already a snapshot, so the copy reads from the same view.
+        Ok(Box::new(self.clone()))
+    }
+
+    fn create_vertex(&self, _vertex: &models::Vertex) -> Result<bool> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_vertices(&self, q: models::VertexQuery) -> Result<Vec<models::Vertex>> {
+        self.datastore.get_vertices(q)
+    }
+
+    fn get_vertices_iter(&self, q: models::VertexQuery) -> Result<DynIter<'_, models::Vertex>> {
+        self.datastore.get_vertices_iter(q)
+    }
+
+    fn get_vertices_page(
+        &self,
+        q: models::VertexQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::Vertex>> {
+        self.datastore.get_vertices_page(q, limit, token)
+    }
+
+    fn delete_vertices(&self, _q: models::VertexQuery) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_vertex_count(&self) -> Result<u64> {
+        self.datastore.get_vertex_count()
+    }
+
+    fn count_vertices(&self, q: models::VertexQuery) -> Result<u64> {
+        self.datastore.count_vertices(q)
+    }
+
+    fn create_edge(&self, _key: &models::EdgeKey) -> Result<bool> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_edges(&self, q: models::EdgeQuery) -> Result<Vec<models::Edge>> {
+        self.datastore.get_edges(q)
+    }
+
+    fn get_edges_iter(&self, q: models::EdgeQuery) -> Result<DynIter<'_, models::Edge>> {
+        self.datastore.get_edges_iter(q)
+    }
+
+    fn get_edges_page(
+        &self,
+        q: models::EdgeQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::Edge>> {
+        self.datastore.get_edges_page(q, limit, token)
+    }
+
+    fn get_paths(&self, q: models::PathQuery) -> Result<Vec<Vec<models::Edge>>> {
+        self.datastore.get_paths(q)
+    }
+
+    fn delete_edges(&self, _q: models::EdgeQuery) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_edge_count(
+        &self,
+        id: Uuid,
+        t: Option<&models::Identifier>,
+        direction: models::EdgeDirection,
+    ) -> Result<u64> {
+        self.datastore.get_edge_count(id, t, direction)
+    }
+
+    fn count_edges(&self, q: models::EdgeQuery) -> Result<u64> {
+        self.datastore.count_edges(q)
+    }
+
+    fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>> {
+        self.datastore.get_vertex_properties(q)
+    }
+
+    fn get_vertex_properties_iter(
+        &self,
+        q: models::VertexPropertyQuery,
+    ) -> Result<DynIter<'_, models::VertexProperty>> {
+        self.datastore.get_vertex_properties_iter(q)
+    }
+
+    fn get_vertex_properties_page(
+        &self,
+        q: models::VertexPropertyQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::VertexProperty>> {
+        self.datastore.get_vertex_properties_page(q, limit, token)
+    }
+
+    fn get_all_vertex_properties(&self, q: models::VertexQuery) -> Result<Vec<models::VertexProperties>> {
+        self.datastore.get_all_vertex_properties(q)
+    }
+
+    fn get_all_vertex_properties_iter(&self, q: models::VertexQuery) -> Result<DynIter<'_, models::VertexProperties>> {
+        self.datastore.get_all_vertex_properties_iter(q)
+    }
+
+    fn set_vertex_properties(&self, _q: models::VertexPropertyQuery, _value: serde_json::Value) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn delete_vertex_properties(&self, _q: models::VertexPropertyQuery) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>> {
+        self.datastore.get_edge_properties(q)
+    }
+
+    fn get_edge_properties_iter(&self, q: models::EdgePropertyQuery) -> Result<DynIter<'_, models::EdgeProperty>> {
+        self.datastore.get_edge_properties_iter(q)
+    }
+
+    fn get_edge_properties_page(
+        &self,
+        q: models::EdgePropertyQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::EdgeProperty>> {
+        self.datastore.get_edge_properties_page(q, limit, token)
+    }
+
+    fn get_all_edge_properties(&self, q: models::EdgeQuery) -> Result<Vec<models::EdgeProperties>> {
+        self.datastore.get_all_edge_properties(q)
+    }
+
+    fn get_all_edge_properties_iter(&self, q: models::EdgeQuery) -> Result<DynIter<'_, models::EdgeProperties>> {
+        self.datastore.get_all_edge_properties_iter(q)
+    }
+
+    fn set_edge_properties(&self, _q: models::EdgePropertyQuery, _value: serde_json::Value) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn delete_edge_properties(&self, _q: models::EdgePropertyQuery) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn bulk_insert(&self, _items: Vec<models::BulkInsertItem>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn index_property(&self, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn index_property_in_background(&self, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn drop_index(&self, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_indexed_properties(&self) -> Result<Vec<models::Identifier>> {
+        self.datastore.get_indexed_properties()
+    }
+
+    fn get_index_status(&self, name: models::Identifier) -> Result<Option<models::IndexStatus>> {
+        self.datastore.get_index_status(name)
+    }
+
+    fn index_type_property(&self, _t: models::Identifier, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn drop_type_index(&self, _t: models::Identifier, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_indexed_type_properties(&self) -> Result<Vec<(models::Identifier, models::Identifier)>> {
+        self.datastore.get_indexed_type_properties()
+    }
+
+    fn index_composite_properties(&self, _names: Vec<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn drop_composite_index(&self, _names: Vec<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_indexed_composite_properties(&self) -> Result<Vec<Vec<models::Identifier>>> {
+        self.datastore.get_indexed_composite_properties()
+    }
+
+    fn add_unique_constraint(&self, _name: models::Identifier, _t: Option<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn drop_unique_constraint(&self, _name: models::Identifier, _t: Option<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_unique_constraints(&self) -> Result<Vec<(models::Identifier, Option<models::Identifier>)>> {
+        self.datastore.get_unique_constraints()
+    }
+
+    fn set_schema(&self, _schema: Option<models::Schema>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_schema(&self) -> Result<Option<models::Schema>> {
+        self.datastore.get_schema()
+    }
+
+    fn get_schema_summary(&self) -> Result<models::SchemaSummary> {
+        self.datastore.get_schema_summary()
+    }
+}
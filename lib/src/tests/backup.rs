@@ -0,0 +1,15 @@
+use crate::{models, Datastore};
+
+use tempfile::tempdir;
+
+pub fn should_backup<D: Datastore>(datastore: &D) {
+    datastore
+        .create_vertex_from_type(models::Identifier::new("test_vertex_type").unwrap())
+        .unwrap();
+
+    // Backups are datastore-specific, so just make sure that one is written
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("backup");
+    datastore.backup(&path).unwrap();
+    assert!(path.exists());
+}
@@ -1,6 +1,6 @@
 use crate::{
-    BulkInsertItem, Datastore, EdgeKey, EdgeQueryExt, Identifier, SpecificEdgeQuery, SpecificVertexQuery, Vertex,
-    VertexQueryExt,
+    BulkInsertItem, Datastore, EdgeKey, EdgeQueryExt, Identifier, RangeVertexQuery, SpecificEdgeQuery,
+    SpecificVertexQuery, Vertex, VertexQueryExt,
 };
 
 use chrono::offset::Utc;
@@ -102,6 +102,31 @@ pub fn should_bulk_insert_a_redundant_vertex<D: Datastore>(datastore: &D) {
     assert!(datastore.bulk_insert(items).is_ok());
 }
 
+// When a vertex is inserted more than once with different types, it should
+// only be found under the type it ends up with
+pub fn should_bulk_insert_a_vertex_with_different_types<D: Datastore>(datastore: &D) {
+    let first_t = Identifier::new("bulk_insert_first_type").unwrap();
+    let second_t = Identifier::new("bulk_insert_second_type").unwrap();
+    let vertex = Vertex::new(first_t.clone());
+
+    let items = vec![
+        BulkInsertItem::Vertex(vertex.clone()),
+        BulkInsertItem::Vertex(Vertex::with_id(vertex.id, second_t.clone())),
+    ];
+    datastore.bulk_insert(items).unwrap();
+
+    let vertices = datastore
+        .get_vertices(SpecificVertexQuery::single(vertex.id).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    for t in [first_t, second_t] {
+        let count = datastore
+            .count_vertices(RangeVertexQuery::new().t(t.clone()).into())
+            .unwrap();
+        assert_eq!(count, if vertices[0].t == t { 1 } else { 0 });
+    }
+}
+
 // As an optimization, bulk insert does not verify that the vertices
 // associated with an inserted edge exist; this verifies that
 pub fn should_bulk_insert_an_invalid_edge<D: Datastore>(datastore: &D) {
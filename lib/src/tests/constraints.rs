@@ -0,0 +1,288 @@
+use crate::{models, Datastore, EdgeQueryExt, Error, VertexQueryExt};
+use uuid::Uuid;
+
+fn set_vertex_property<D: Datastore>(
+    datastore: &D,
+    id: Uuid,
+    name: &models::Identifier,
+    value: serde_json::Value,
+) -> Result<(), Error> {
+    datastore.set_vertex_properties(models::SpecificVertexQuery::single(id).property(name.clone()), value)
+}
+
+pub fn should_enforce_unique_vertex_property<D: Datastore>(datastore: &D) {
+    let email = models::Identifier::new("email").unwrap();
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let first_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    let second_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    datastore.index_property(email.clone()).unwrap();
+    datastore.add_unique_constraint(email.clone(), None).unwrap();
+
+    set_vertex_property(datastore, first_id, &email, serde_json::json!("a@example.com")).unwrap();
+    let result = set_vertex_property(datastore, second_id, &email, serde_json::json!("a@example.com"));
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let properties = datastore
+        .get_vertex_properties(models::SpecificVertexQuery::single(second_id).property(email.clone()))
+        .unwrap();
+    assert_eq!(properties.len(), 0);
+
+    // Setting a vertex's own value again is fine, as is giving the value to
+    // another vertex once the first one no longer has it
+    set_vertex_property(datastore, first_id, &email, serde_json::json!("a@example.com")).unwrap();
+    set_vertex_property(datastore, first_id, &email, serde_json::json!("b@example.com")).unwrap();
+    set_vertex_property(datastore, second_id, &email, serde_json::json!("a@example.com")).unwrap();
+    datastore
+        .delete_vertices(models::SpecificVertexQuery::single(first_id).into())
+        .unwrap();
+    let third_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    set_vertex_property(datastore, third_id, &email, serde_json::json!("b@example.com")).unwrap();
+
+    // A single write can't give several vertices the same value either
+    let fourth_id = datastore.create_vertex_from_type(t).unwrap();
+    let result = datastore.set_vertex_properties(
+        models::SpecificVertexQuery::new(vec![third_id, fourth_id]).property(email.clone()),
+        serde_json::json!("c@example.com"),
+    );
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // Other properties aren't affected
+    let other = models::Identifier::new("other").unwrap();
+    datastore
+        .set_vertex_properties(
+            models::SpecificVertexQuery::new(vec![third_id, fourth_id]).property(other),
+            serde_json::json!("c@example.com"),
+        )
+        .unwrap();
+}
+
+pub fn should_enforce_unique_edge_property<D: Datastore>(datastore: &D) {
+    let name = models::Identifier::new("name").unwrap();
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let edge_t = models::Identifier::new("test_edge_type").unwrap();
+    let outbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let mut keys = Vec::new();
+    for _ in 0..2 {
+        let inbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+        datastore.create_edge(&key).unwrap();
+        keys.push(key);
+    }
+    datastore.index_property(name.clone()).unwrap();
+    datastore.add_unique_constraint(name.clone(), None).unwrap();
+
+    datastore
+        .set_edge_properties(
+            models::SpecificEdgeQuery::single(keys[0].clone()).property(name.clone()),
+            serde_json::json!("foo"),
+        )
+        .unwrap();
+    let result = datastore.set_edge_properties(
+        models::SpecificEdgeQuery::single(keys[1].clone()).property(name.clone()),
+        serde_json::json!("foo"),
+    );
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.set_edge_properties(
+        models::SpecificVertexQuery::single(outbound_id)
+            .outbound()
+            .property(name.clone()),
+        serde_json::json!("bar"),
+    );
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // Vertices and edges can share a value
+    set_vertex_property(datastore, outbound_id, &name, serde_json::json!("foo")).unwrap();
+}
+
+pub fn should_enforce_type_scoped_unique_property<D: Datastore>(datastore: &D) {
+    let email = models::Identifier::new("email").unwrap();
+    let user_t = models::Identifier::new("user").unwrap();
+    let other_t = models::Identifier::new("other").unwrap();
+    let first_user_id = datastore.create_vertex_from_type(user_t.clone()).unwrap();
+    let second_user_id = datastore.create_vertex_from_type(user_t.clone()).unwrap();
+    let first_other_id = datastore.create_vertex_from_type(other_t.clone()).unwrap();
+    let second_other_id = datastore.create_vertex_from_type(other_t).unwrap();
+    datastore.index_property(email.clone()).unwrap();
+    datastore.add_unique_constraint(email.clone(), Some(user_t)).unwrap();
+
+    // Only vertices of the type are covered by the constraint
+    let value = serde_json::json!("a@example.com");
+    set_vertex_property(datastore, first_user_id, &email, value.clone()).unwrap();
+    set_vertex_property(datastore, first_other_id, &email, value.clone()).unwrap();
+    set_vertex_property(datastore, second_other_id, &email, value.clone()).unwrap();
+    let result = set_vertex_property(datastore, second_user_id, &email, value);
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_enforce_unique_property_in_bulk_insert<D: Datastore>(datastore: &D) {
+    let email = models::Identifier::new("email").unwrap();
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let existing_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    datastore.index_property(email.clone()).unwrap();
+    datastore.add_unique_constraint(email.clone(), None).unwrap();
+    set_vertex_property(datastore, existing_id, &email, serde_json::json!("a@example.com")).unwrap();
+
+    // Values can't be duplicated within an insert...
+    let first_v = models::Vertex::new(t.clone());
+    let second_v = models::Vertex::new(t.clone());
+    let result = datastore.bulk_insert(vec![
+        models::BulkInsertItem::Vertex(first_v.clone()),
+        models::BulkInsertItem::Vertex(second_v.clone()),
+        models::BulkInsertItem::VertexProperty(first_v.id, email.clone(), serde_json::json!("b@example.com")),
+        models::BulkInsertItem::VertexProperty(second_v.id, email.clone(), serde_json::json!("b@example.com")),
+    ]);
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // ...or duplicate existing values
+    let result = datastore.bulk_insert(vec![
+        models::BulkInsertItem::Vertex(first_v.clone()),
+        models::BulkInsertItem::VertexProperty(first_v.id, email.clone(), serde_json::json!("a@example.com")),
+    ]);
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(datastore.get_vertex_count().unwrap(), 1);
+
+    // Values that are replaced within the insert don't count
+    datastore
+        .bulk_insert(vec![
+            models::BulkInsertItem::Vertex(first_v.clone()),
+            models::BulkInsertItem::VertexProperty(existing_id, email.clone(), serde_json::json!("c@example.com")),
+            models::BulkInsertItem::VertexProperty(first_v.id, email.clone(), serde_json::json!("a@example.com")),
+        ])
+        .unwrap();
+    let q = models::PropertyValueVertexQuery::new(email, serde_json::json!("a@example.com"));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, first_v.id);
+}
+
+pub fn should_check_a_whole_bulk_insert_for_unique_violations<D: Datastore>(datastore: &D) {
+    let email = models::Identifier::new("email").unwrap();
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    datastore.index_property(email.clone()).unwrap();
+    datastore.add_unique_constraint(email.clone(), None).unwrap();
+
+    // The insert is large enough that datastores may apply it in batches,
+    // and the duplicate value is in the last item
+    let vertices: Vec<models::Vertex> = (0..2_500).map(|_| models::Vertex::new(t.clone())).collect();
+    let mut items = Vec::new();
+    for (i, vertex) in vertices.iter().enumerate() {
+        items.push(models::BulkInsertItem::Vertex(vertex.clone()));
+        items.push(models::BulkInsertItem::VertexProperty(
+            vertex.id,
+            email.clone(),
+            serde_json::json!(format!("{}@example.com", i)),
+        ));
+    }
+    items.push(models::BulkInsertItem::VertexProperty(
+        vertices[vertices.len() - 1].id,
+        email,
+        serde_json::json!("0@example.com"),
+    ));
+
+    let result = datastore.bulk_insert(items);
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(datastore.get_vertex_count().unwrap(), 0);
+}
+
+pub fn should_add_unique_constraints<D: Datastore>(datastore: &D) {
+    let email = models::Identifier::new("email").unwrap();
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let other_t = models::Identifier::new("other").unwrap();
+    assert_eq!(datastore.get_unique_constraints().unwrap(), vec![]);
+
+    // The property must be indexed
+    let result = datastore.add_unique_constraint(email.clone(), None);
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // Constraints that existing values violate can't be added
+    datastore.index_property(email.clone()).unwrap();
+    let first_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    let second_id = datastore.create_vertex_from_type(other_t.clone()).unwrap();
+    set_vertex_property(datastore, first_id, &email, serde_json::json!("a@example.com")).unwrap();
+    set_vertex_property(datastore, second_id, &email, serde_json::json!("a@example.com")).unwrap();
+    let result = datastore.add_unique_constraint(email.clone(), None);
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    datastore.add_unique_constraint(email.clone(), Some(t.clone())).unwrap();
+    datastore
+        .add_unique_constraint(email.clone(), Some(other_t.clone()))
+        .unwrap();
+    datastore.add_unique_constraint(email.clone(), Some(t.clone())).unwrap();
+    assert_eq!(
+        datastore.get_unique_constraints().unwrap(),
+        vec![(email.clone(), Some(other_t.clone())), (email.clone(), Some(t.clone()))]
+    );
+
+    datastore
+        .drop_unique_constraint(email.clone(), Some(t.clone()))
+        .unwrap();
+    datastore.drop_unique_constraint(email.clone(), Some(t)).unwrap();
+    datastore.drop_unique_constraint(email.clone(), None).unwrap();
+    assert_eq!(
+        datastore.get_unique_constraints().unwrap(),
+        vec![(email.clone(), Some(other_t))]
+    );
+
+    // Constraints are dropped along with the property's index
+    datastore.drop_index(email).unwrap();
+    assert_eq!(datastore.get_unique_constraints().unwrap(), vec![]);
+}
+
+pub fn should_check_unique_constraints_on_commit<D: Datastore>(datastore: &D) {
+    let email = models::Identifier::new("email").unwrap();
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let first_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+    let second_id = datastore.create_vertex_from_type(t).unwrap();
+    datastore.index_property(email.clone()).unwrap();
+    datastore.add_unique_constraint(email.clone(), None).unwrap();
+
+    // Constraints are checked against the transaction's own changes...
+    let trans = datastore.transaction().unwrap();
+    set_vertex_property(&trans, first_id, &email, serde_json::json!("a@example.com")).unwrap();
+    let result = set_vertex_property(&trans, second_id, &email, serde_json::json!("a@example.com"));
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // ...and against changes made outside of it, when it's committed
+    set_vertex_property(datastore, second_id, &email, serde_json::json!("a@example.com")).unwrap();
+    let result = trans.commit();
+    match result {
+        Err(Error::ConstraintViolation) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let properties = datastore
+        .get_vertex_properties(models::SpecificVertexQuery::single(first_id).property(email))
+        .unwrap();
+    assert_eq!(properties.len(), 0);
+}
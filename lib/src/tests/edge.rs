@@ -2,7 +2,8 @@ use std::collections::HashSet;
 
 use super::util::{create_edge_from, create_edges, create_time_range_queryable_edges};
 use crate::{
-    models, Datastore, EdgeDirection, EdgeKey, EdgeQueryExt, SpecificEdgeQuery, SpecificVertexQuery, VertexQueryExt,
+    models, Datastore, EdgeDirection, EdgeKey, EdgeQueryExt, PropertyPresenceEdgeQuery, PropertyValueEdgeQuery,
+    SpecificEdgeQuery, SpecificVertexQuery, VertexQueryExt,
 };
 
 use chrono::offset::Utc;
@@ -96,7 +97,7 @@ pub fn should_not_create_an_invalid_edge<D: Datastore>(datastore: &D) {
     let edge_t = models::Identifier::new("test_edge_type").unwrap();
     let key = models::EdgeKey::new(outbound_v.id, edge_t, Uuid::default());
     let result = datastore.create_edge(&key);
-    assert_eq!(result.unwrap(), false);
+    assert!(!result.unwrap());
 }
 
 pub fn should_delete_a_valid_edge<D: Datastore>(datastore: &D) {
@@ -166,6 +167,55 @@ pub fn should_get_an_inbound_edge_count<D: Datastore>(datastore: &D) {
     assert_eq!(count, 1);
 }
 
+pub fn should_count_edges<D: Datastore>(datastore: &D) {
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let edge_t = models::Identifier::new("test_edge_type").unwrap();
+
+    let count = |q: models::EdgeQuery| -> u64 {
+        let count = datastore.count_edges(q.clone()).unwrap();
+        assert_eq!(count, datastore.get_edges(q).unwrap().len() as u64);
+        count
+    };
+
+    let q = SpecificVertexQuery::single(outbound_id).outbound();
+    assert_eq!(count(q.clone().into()), 5);
+    assert_eq!(count(q.clone().limit(3).into()), 3);
+    assert_eq!(count(q.clone().t(edge_t.clone()).into()), 5);
+    assert_eq!(count(q.clone().t(models::Identifier::new("foo").unwrap()).into()), 0);
+    assert_eq!(count(SpecificVertexQuery::single(inbound_ids[0]).inbound().into()), 1);
+    assert_eq!(
+        count(SpecificVertexQuery::new(inbound_ids.to_vec()).inbound().into()),
+        5
+    );
+
+    let key = EdgeKey::new(outbound_id, edge_t.clone(), inbound_ids[0]);
+    let missing_key = EdgeKey::new(outbound_id, models::Identifier::new("foo").unwrap(), inbound_ids[0]);
+    assert_eq!(count(SpecificEdgeQuery::new(vec![key.clone(), missing_key]).into()), 1);
+
+    // Counts should use the property index when there is one
+    let name = models::Identifier::new("count_property").unwrap();
+    datastore.index_property(name.clone()).unwrap();
+    let other_key = EdgeKey::new(outbound_id, edge_t, inbound_ids[1]);
+    datastore
+        .set_edge_properties(
+            SpecificEdgeQuery::new(vec![key.clone(), other_key]).property(name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+    datastore
+        .set_edge_properties(
+            SpecificEdgeQuery::single(key).property(name.clone()),
+            serde_json::Value::Bool(false),
+        )
+        .unwrap();
+    assert_eq!(count(PropertyPresenceEdgeQuery::new(name.clone()).into()), 2);
+    assert_eq!(
+        count(PropertyValueEdgeQuery::new(name.clone(), serde_json::Value::Bool(false)).into()),
+        1
+    );
+    assert_eq!(count(q.with_property(name).into()), 2);
+}
+
 pub fn should_get_an_edge_range<D: Datastore>(datastore: &D) {
     let (outbound_id, start_time, end_time, _) = create_time_range_queryable_edges(datastore);
     let t = models::Identifier::new("test_edge_type").unwrap();
@@ -345,3 +395,101 @@ fn check_edge_range(range: &[models::Edge], expected_outbound_id: Uuid, expected
         covered_ids.insert(edge.key.inbound_id);
     }
 }
+
+pub fn should_combine_edge_queries<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let edges = datastore
+        .get_edges(SpecificVertexQuery::single(outbound_id).outbound().into())
+        .unwrap();
+    assert_eq!(edges.len(), 5);
+    let keys: Vec<EdgeKey> = edges.into_iter().map(|e| e.key).collect();
+    let get_keys =
+        |q: models::EdgeQuery| -> Vec<EdgeKey> { datastore.get_edges(q).unwrap().into_iter().map(|e| e.key).collect() };
+
+    let first = SpecificEdgeQuery::new(keys[..3].to_vec());
+    let second = SpecificEdgeQuery::new(keys[2..].to_vec());
+    assert_eq!(get_keys(first.clone().union(second.clone()).into()), keys);
+    assert_eq!(get_keys(first.clone().intersect(second.clone()).into()), keys[2..3]);
+    assert_eq!(get_keys(first.except(second).into()), keys[..2]);
+
+    // Combined queries can be piped from
+    let q = SpecificVertexQuery::single(outbound_id)
+        .outbound()
+        .except(SpecificEdgeQuery::single(keys[0].clone()))
+        .inbound();
+    let result: HashSet<Uuid> = datastore
+        .get_vertices(q.into())
+        .unwrap()
+        .into_iter()
+        .map(|v| v.id)
+        .collect();
+    assert_eq!(result, keys[1..].iter().map(|key| key.inbound_id).collect());
+}
+
+pub fn should_get_paths<D: Datastore>(datastore: &D) {
+    // Builds a -> d directly, a -> b -> d, and a -> c -> e -> d, where the
+    // direct edge has a different type
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let follows_t = models::Identifier::new("follows").unwrap();
+    let blocks_t = models::Identifier::new("blocks").unwrap();
+    let ids: Vec<Uuid> = (0..5)
+        .map(|_| datastore.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+    let (a, b, c, d, e) = (ids[0], ids[1], ids[2], ids[3], ids[4]);
+    let direct = models::EdgeKey::new(a, blocks_t, d);
+    assert!(datastore.create_edge(&direct).unwrap());
+    let follows = |outbound_id, inbound_id| models::EdgeKey::new(outbound_id, follows_t.clone(), inbound_id);
+    for key in [
+        follows(a, b),
+        follows(b, d),
+        follows(a, c),
+        follows(c, e),
+        follows(e, d),
+    ] {
+        assert!(datastore.create_edge(&key).unwrap());
+    }
+
+    let get_paths = |q: models::PathQuery| -> Vec<Vec<models::EdgeKey>> {
+        let paths = datastore.get_paths(q).unwrap();
+        paths
+            .into_iter()
+            .map(|path| path.into_iter().map(|edge| edge.key).collect())
+            .collect()
+    };
+
+    // The shortest path
+    let q = models::PathQuery::new(a, d);
+    assert_eq!(get_paths(q.clone()), vec![vec![direct.clone()]]);
+    assert_eq!(
+        get_paths(q.clone().t(follows_t.clone())),
+        vec![vec![follows(a, b), follows(b, d)]]
+    );
+
+    // The k shortest paths, in order of length
+    assert_eq!(
+        get_paths(q.clone().limit(10)),
+        vec![
+            vec![direct.clone()],
+            vec![follows(a, b), follows(b, d)],
+            vec![follows(a, c), follows(c, e), follows(e, d)],
+        ]
+    );
+    assert_eq!(
+        get_paths(q.clone().limit(10).max_depth(2)),
+        vec![vec![direct], vec![follows(a, b), follows(b, d)]]
+    );
+    assert_eq!(get_paths(q.limit(0)), Vec::<Vec<models::EdgeKey>>::new());
+
+    // Paths follow outbound edges only
+    assert_eq!(
+        get_paths(models::PathQuery::new(d, a)),
+        Vec::<Vec<models::EdgeKey>>::new()
+    );
+
+    // A vertex has an empty path to itself, if it exists
+    assert_eq!(get_paths(models::PathQuery::new(a, a)), vec![vec![]]);
+    assert_eq!(
+        get_paths(models::PathQuery::new(Uuid::default(), Uuid::default())),
+        Vec::<Vec<models::EdgeKey>>::new()
+    );
+}
@@ -1,3 +1,4 @@
+use super::util::wait_for_index;
 use crate::{models, Datastore, EdgeQueryExt, Error, VertexQueryExt};
 use uuid::Uuid;
 
@@ -37,7 +38,7 @@ pub fn should_not_query_unindexed_vertex_property<D: Datastore>(datastore: &D) {
         .get_vertices(models::PropertyPresenceVertexQuery::new(models::Identifier::new("foo").unwrap()).into());
     match result {
         Err(Error::NotIndexed) => (),
-        _ => assert!(false, "unexpected result: {:?}", result),
+        _ => panic!("unexpected result: {:?}", result),
     }
 }
 
@@ -46,7 +47,7 @@ pub fn should_not_query_unindexed_edge_property<D: Datastore>(datastore: &D) {
         datastore.get_edges(models::PropertyPresenceEdgeQuery::new(models::Identifier::new("foo").unwrap()).into());
     match result {
         Err(Error::NotIndexed) => (),
-        _ => assert!(false, "unexpected result: {:?}", result),
+        _ => panic!("unexpected result: {:?}", result),
     }
 }
 
@@ -264,3 +265,697 @@ pub fn should_query_indexed_edge_property_empty<D: Datastore>(datastore: &D) {
         .unwrap();
     assert_eq!(result.len(), 0);
 }
+
+pub fn should_query_vertex_property_range<D: Datastore>(datastore: &D) {
+    let property_name = models::Identifier::new("ranged-vertex-property").unwrap();
+    datastore.index_property(property_name.clone()).unwrap();
+
+    let values = vec![
+        serde_json::json!(-5),
+        serde_json::json!(25),
+        serde_json::json!(30),
+        serde_json::json!(30.5),
+        serde_json::json!(31),
+        serde_json::json!(45),
+        serde_json::json!("40"),
+        serde_json::json!("4x"),
+        serde_json::Value::Null,
+    ];
+    let mut ids = Vec::new();
+    for value in values {
+        let id = datastore
+            .create_vertex_from_type(models::Identifier::new("test_vertex_type").unwrap())
+            .unwrap();
+        let q = models::SpecificVertexQuery::single(id).property(property_name.clone());
+        datastore.set_vertex_properties(q, value).unwrap();
+        ids.push(id);
+    }
+
+    let get = |range: models::PropertyValueRange| -> Vec<Uuid> {
+        let q = models::PropertyValueRangeVertexQuery::new(property_name.clone(), range);
+        let mut result: Vec<Uuid> = datastore
+            .get_vertices(q.into())
+            .unwrap()
+            .into_iter()
+            .map(|v| v.id)
+            .collect();
+        result.sort();
+        result
+    };
+    let expect = |indices: &[usize]| -> Vec<Uuid> {
+        let mut expected: Vec<Uuid> = indices.iter().map(|i| ids[*i]).collect();
+        expected.sort();
+        expected
+    };
+
+    assert_eq!(
+        get(models::PropertyValueRange::GreaterThan(serde_json::json!(30))),
+        expect(&[3, 4, 5])
+    );
+    assert_eq!(
+        get(models::PropertyValueRange::LessThan(serde_json::json!(31))),
+        expect(&[0, 1, 2, 3])
+    );
+    assert_eq!(
+        get(models::PropertyValueRange::Between(
+            serde_json::json!(30),
+            serde_json::json!(31)
+        )),
+        expect(&[2, 3, 4])
+    );
+    assert_eq!(
+        get(models::PropertyValueRange::Between(
+            serde_json::json!(30),
+            serde_json::json!("40")
+        )),
+        expect(&[])
+    );
+    assert_eq!(
+        get(models::PropertyValueRange::Prefix("4".to_string())),
+        expect(&[6, 7])
+    );
+    assert_eq!(get(models::PropertyValueRange::Prefix("40".to_string())), expect(&[6]));
+
+    // Piped queries
+    let q = models::SpecificVertexQuery::new(vec![ids[0], ids[4], ids[6]]);
+    let result = datastore
+        .get_vertices(
+            q.clone()
+                .with_property_in_range(
+                    property_name.clone(),
+                    models::PropertyValueRange::GreaterThan(serde_json::json!(30)),
+                )
+                .into(),
+        )
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, ids[4]);
+    let mut result: Vec<Uuid> = datastore
+        .get_vertices(
+            q.with_property_not_in_range(
+                property_name,
+                models::PropertyValueRange::GreaterThan(serde_json::json!(30)),
+            )
+            .into(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|v| v.id)
+        .collect();
+    result.sort();
+    assert_eq!(result, expect(&[0, 6]));
+}
+
+pub fn should_query_edge_property_range<D: Datastore>(datastore: &D) {
+    let property_name = models::Identifier::new("ranged-edge-property").unwrap();
+    datastore.index_property(property_name.clone()).unwrap();
+
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let outbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let mut keys = Vec::new();
+    for weight in &[0.1, 0.5, 0.9] {
+        let inbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = models::EdgeKey::new(
+            outbound_id,
+            models::Identifier::new("test_edge_type").unwrap(),
+            inbound_id,
+        );
+        datastore.create_edge(&key).unwrap();
+        let q = models::SpecificEdgeQuery::single(key.clone()).property(property_name.clone());
+        datastore.set_edge_properties(q, serde_json::json!(weight)).unwrap();
+        keys.push(key);
+    }
+
+    let range = models::PropertyValueRange::Between(serde_json::json!(0.2), serde_json::json!(0.8));
+    let result = datastore
+        .get_edges(models::PropertyValueRangeEdgeQuery::new(property_name.clone(), range.clone()).into())
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[1]);
+
+    // Piped queries
+    let q = models::SpecificVertexQuery::single(outbound_id).outbound();
+    let result = datastore
+        .get_edges(
+            q.clone()
+                .with_property_in_range(property_name.clone(), range.clone())
+                .into(),
+        )
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[1]);
+    let result = datastore
+        .get_edges(q.with_property_not_in_range(property_name, range).into())
+        .unwrap();
+    assert_eq!(result.len(), 2);
+    assert!(result.iter().all(|edge| edge.key != keys[1]));
+}
+
+pub fn should_not_query_unindexed_property_range<D: Datastore>(datastore: &D) {
+    let range = models::PropertyValueRange::GreaterThan(serde_json::json!(0));
+    let result = datastore.get_vertices(
+        models::PropertyValueRangeVertexQuery::new(models::Identifier::new("foo").unwrap(), range.clone()).into(),
+    );
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore
+        .get_edges(models::PropertyValueRangeEdgeQuery::new(models::Identifier::new("foo").unwrap(), range).into());
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_get_indexed_properties<D: Datastore>(datastore: &D) {
+    assert_eq!(datastore.get_indexed_properties().unwrap(), vec![]);
+
+    let first_name = models::Identifier::new("a").unwrap();
+    let second_name = models::Identifier::new("b").unwrap();
+    datastore.index_property(second_name.clone()).unwrap();
+    datastore.index_property(first_name.clone()).unwrap();
+    datastore.index_property(first_name.clone()).unwrap();
+    assert_eq!(
+        datastore.get_indexed_properties().unwrap(),
+        vec![first_name.clone(), second_name]
+    );
+
+    datastore.drop_index(first_name).unwrap();
+    assert_eq!(
+        datastore.get_indexed_properties().unwrap(),
+        vec![models::Identifier::new("b").unwrap()]
+    );
+}
+
+pub fn should_drop_index_of_vertex_property<D: Datastore>(datastore: &D) {
+    let property_name = models::Identifier::new("dropped-vertex-property").unwrap();
+    let id = setup_vertex_with_indexed_property(datastore, &property_name);
+    datastore.drop_index(property_name.clone()).unwrap();
+
+    let result = datastore.get_vertices(models::PropertyPresenceVertexQuery::new(property_name.clone()).into());
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.get_vertices(
+        models::PropertyValueVertexQuery::new(property_name.clone(), serde_json::Value::Bool(true)).into(),
+    );
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // Changes made while the property isn't indexed are picked up when it's
+    // indexed again
+    datastore
+        .set_vertex_properties(
+            models::SpecificVertexQuery::single(id).property(property_name.clone()),
+            serde_json::Value::Bool(false),
+        )
+        .unwrap();
+    datastore.index_property(property_name.clone()).unwrap();
+    let result = datastore
+        .get_vertices(
+            models::PropertyValueVertexQuery::new(property_name.clone(), serde_json::Value::Bool(true)).into(),
+        )
+        .unwrap();
+    assert_eq!(result.len(), 0);
+    let result = datastore
+        .get_vertices(models::PropertyValueVertexQuery::new(property_name, serde_json::Value::Bool(false)).into())
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, id);
+}
+
+pub fn should_drop_index_of_edge_property<D: Datastore>(datastore: &D) {
+    let property_name = models::Identifier::new("dropped-edge-property").unwrap();
+    let key = setup_edge_with_indexed_property(datastore, &property_name);
+    datastore.drop_index(property_name.clone()).unwrap();
+
+    let result = datastore.get_edges(models::PropertyPresenceEdgeQuery::new(property_name.clone()).into());
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    datastore
+        .delete_edge_properties(models::SpecificEdgeQuery::single(key).property(property_name.clone()))
+        .unwrap();
+    datastore.index_property(property_name.clone()).unwrap();
+    let result = datastore
+        .get_edges(models::PropertyPresenceEdgeQuery::new(property_name).into())
+        .unwrap();
+    assert_eq!(result.len(), 0);
+}
+
+pub fn should_drop_unindexed_property<D: Datastore>(datastore: &D) {
+    datastore
+        .drop_index(models::Identifier::new("unindexed").unwrap())
+        .unwrap();
+    assert_eq!(datastore.get_indexed_properties().unwrap(), vec![]);
+}
+
+pub fn should_get_index_status<D: Datastore>(datastore: &D) {
+    let property_name = models::Identifier::new("status-property").unwrap();
+    assert_eq!(datastore.get_index_status(property_name.clone()).unwrap(), None);
+
+    datastore.index_property(property_name.clone()).unwrap();
+    assert_eq!(
+        datastore.get_index_status(property_name.clone()).unwrap(),
+        Some(models::IndexStatus::Ready)
+    );
+
+    datastore.drop_index(property_name.clone()).unwrap();
+    assert_eq!(datastore.get_index_status(property_name).unwrap(), None);
+}
+
+pub fn should_index_property_in_background<D: Datastore>(datastore: &D) {
+    // Setup, with enough vertices that the index is built in several batches
+    let property_name = models::Identifier::new("background-property").unwrap();
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let vertices: Vec<models::Vertex> = (0..2_500).map(|_| models::Vertex::new(vertex_t.clone())).collect();
+    let mut items: Vec<models::BulkInsertItem> = vertices
+        .iter()
+        .map(|v| models::BulkInsertItem::Vertex(v.clone()))
+        .collect();
+    items.extend(vertices.iter().enumerate().map(|(i, v)| {
+        models::BulkInsertItem::VertexProperty(v.id, property_name.clone(), serde_json::Value::Bool(i % 2 == 0))
+    }));
+    let key = models::EdgeKey::new(
+        vertices[0].id,
+        models::Identifier::new("test_edge_type").unwrap(),
+        vertices[1].id,
+    );
+    items.push(models::BulkInsertItem::Edge(key.clone()));
+    items.push(models::BulkInsertItem::EdgeProperty(
+        key.clone(),
+        property_name.clone(),
+        serde_json::Value::Bool(true),
+    ));
+    datastore.bulk_insert(items).unwrap();
+
+    datastore.index_property_in_background(property_name.clone()).unwrap();
+    assert_eq!(datastore.get_indexed_properties().unwrap(), vec![property_name.clone()]);
+
+    // Until the index is ready, queries on it fail rather than returning
+    // partial results
+    let q = models::PropertyValueVertexQuery::new(property_name.clone(), serde_json::Value::Bool(true));
+    match datastore.get_vertices(q.clone().into()) {
+        Err(Error::IndexNotReady) => (),
+        Ok(result) => assert_eq!(result.len(), 1_250),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    // Writes made during the build should be reflected in the index
+    datastore
+        .set_vertex_properties(
+            models::SpecificVertexQuery::single(vertices[1].id).property(property_name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+    datastore
+        .delete_vertices(models::SpecificVertexQuery::single(vertices[2].id).into())
+        .unwrap();
+
+    wait_for_index(datastore, &property_name);
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.len(), 1_250);
+    assert!(result.iter().any(|v| v.id == vertices[1].id));
+    assert!(!result.iter().any(|v| v.id == vertices[2].id));
+    let result = datastore
+        .get_vertices(
+            models::PropertyValueVertexQuery::new(property_name.clone(), serde_json::Value::Bool(false)).into(),
+        )
+        .unwrap();
+    assert_eq!(result.len(), 1_249);
+    let result = datastore
+        .get_edges(models::PropertyPresenceEdgeQuery::new(property_name).into())
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, key);
+}
+
+pub fn should_query_type_indexed_vertex_property<D: Datastore>(datastore: &D) {
+    // Setup, with the property set on vertices of two types
+    let property_name = models::Identifier::new("type-indexed-vertex-property").unwrap();
+    let indexed_t = models::Identifier::new("indexed_vertex_type").unwrap();
+    let other_t = models::Identifier::new("other_vertex_type").unwrap();
+    let mut ids = Vec::new();
+    for (i, t) in [&indexed_t, &indexed_t, &indexed_t, &other_t].iter().enumerate() {
+        let id = datastore.create_vertex_from_type((*t).clone()).unwrap();
+        datastore
+            .set_vertex_properties(
+                models::SpecificVertexQuery::single(id).property(property_name.clone()),
+                serde_json::Value::Bool(i != 1),
+            )
+            .unwrap();
+        ids.push(id);
+    }
+
+    // The property isn't indexed on its own, but filters on queries
+    // restricted to the type use the index scoped to it
+    datastore
+        .index_type_property(indexed_t.clone(), property_name.clone())
+        .unwrap();
+    let mut expected = vec![ids[0], ids[2]];
+    expected.sort();
+
+    let q = models::RangeVertexQuery::new()
+        .t(indexed_t.clone())
+        .with_property_equal_to(property_name.clone(), serde_json::Value::Bool(true));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+
+    let q = models::RangeVertexQuery::new()
+        .t(indexed_t.clone())
+        .with_property_not_equal_to(property_name.clone(), serde_json::Value::Bool(true));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, ids[1]);
+
+    let q = models::PropertyValueVertexQuery::new(property_name.clone(), serde_json::Value::Bool(true))
+        .intersect(models::RangeVertexQuery::new().t(indexed_t.clone()));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+
+    // Changes made after the index is built should be reflected in it
+    datastore
+        .set_vertex_properties(
+            models::SpecificVertexQuery::single(ids[0]).property(property_name.clone()),
+            serde_json::Value::Bool(false),
+        )
+        .unwrap();
+    datastore
+        .delete_vertices(models::SpecificVertexQuery::single(ids[2]).into())
+        .unwrap();
+    let new_v = models::Vertex::new(indexed_t.clone());
+    datastore
+        .bulk_insert(vec![
+            models::BulkInsertItem::Vertex(new_v.clone()),
+            models::BulkInsertItem::VertexProperty(new_v.id, property_name.clone(), serde_json::Value::Bool(true)),
+        ])
+        .unwrap();
+    let q = models::RangeVertexQuery::new()
+        .t(indexed_t)
+        .with_property_equal_to(property_name.clone(), serde_json::Value::Bool(true));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, new_v.id);
+
+    // Queries restricted to other types still need the property to be
+    // indexed
+    let result = datastore.get_vertices(
+        models::PropertyValueVertexQuery::new(property_name, serde_json::Value::Bool(true))
+            .intersect(models::RangeVertexQuery::new().t(other_t))
+            .into(),
+    );
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_query_type_indexed_edge_property<D: Datastore>(datastore: &D) {
+    // Setup, with the property set on edges of two types
+    let property_name = models::Identifier::new("type-indexed-edge-property").unwrap();
+    let indexed_t = models::Identifier::new("indexed_edge_type").unwrap();
+    let other_t = models::Identifier::new("other_edge_type").unwrap();
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let outbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let mut keys = Vec::new();
+    for t in [&indexed_t, &indexed_t, &other_t].iter() {
+        let inbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = models::EdgeKey::new(outbound_id, (*t).clone(), inbound_id);
+        datastore.create_edge(&key).unwrap();
+        keys.push(key);
+    }
+    for key in &keys[1..] {
+        datastore
+            .set_edge_properties(
+                models::SpecificEdgeQuery::single(key.clone()).property(property_name.clone()),
+                serde_json::Value::Bool(true),
+            )
+            .unwrap();
+    }
+    datastore
+        .index_type_property(indexed_t.clone(), property_name.clone())
+        .unwrap();
+
+    let q = models::SpecificVertexQuery::single(outbound_id)
+        .outbound()
+        .t(indexed_t.clone())
+        .with_property_equal_to(property_name.clone(), serde_json::Value::Bool(true));
+    let result = datastore.get_edges(q.clone().into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[1]);
+
+    let result = datastore
+        .get_edges(
+            models::PropertyValueEdgeQuery::new(property_name.clone(), serde_json::Value::Bool(true))
+                .intersect(models::SpecificVertexQuery::single(outbound_id).outbound().t(indexed_t))
+                .into(),
+        )
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[1]);
+
+    // Changes made after the index is built should be reflected in it
+    datastore
+        .set_edge_properties(
+            models::SpecificEdgeQuery::single(keys[0].clone()).property(property_name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+    datastore
+        .delete_edges(models::SpecificEdgeQuery::single(keys[1].clone()).into())
+        .unwrap();
+    let result = datastore.get_edges(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[0]);
+}
+
+pub fn should_get_indexed_type_properties<D: Datastore>(datastore: &D) {
+    assert_eq!(datastore.get_indexed_type_properties().unwrap(), vec![]);
+
+    let first_t = models::Identifier::new("a").unwrap();
+    let second_t = models::Identifier::new("b").unwrap();
+    let name = models::Identifier::new("foo").unwrap();
+    datastore.index_type_property(second_t.clone(), name.clone()).unwrap();
+    datastore.index_type_property(first_t.clone(), name.clone()).unwrap();
+    datastore.index_type_property(first_t.clone(), name.clone()).unwrap();
+    assert_eq!(
+        datastore.get_indexed_type_properties().unwrap(),
+        vec![(first_t.clone(), name.clone()), (second_t.clone(), name.clone())]
+    );
+
+    // Indexes scoped to a type are separate from the property's index
+    assert_eq!(datastore.get_indexed_properties().unwrap(), vec![]);
+
+    datastore.drop_type_index(first_t.clone(), name.clone()).unwrap();
+    datastore.drop_type_index(first_t, name.clone()).unwrap();
+    assert_eq!(datastore.get_indexed_type_properties().unwrap(), vec![(second_t, name)]);
+}
+
+pub fn should_query_composite_indexed_vertex_properties<D: Datastore>(datastore: &D) {
+    // Setup, with vertices in a few countries and cities
+    let country = models::Identifier::new("country").unwrap();
+    let city = models::Identifier::new("city").unwrap();
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let places = [("fr", "paris"), ("fr", "lyon"), ("us", "paris"), ("fr", "paris")];
+    let mut ids = Vec::new();
+    for (country_value, city_value) in places.iter() {
+        let id = datastore.create_vertex_from_type(t.clone()).unwrap();
+        let q = models::SpecificVertexQuery::single(id);
+        datastore
+            .set_vertex_properties(q.clone().property(country.clone()), serde_json::json!(country_value))
+            .unwrap();
+        datastore
+            .set_vertex_properties(q.property(city.clone()), serde_json::json!(city_value))
+            .unwrap();
+        ids.push(id);
+    }
+
+    // Neither property is indexed on its own, but piped filters on both use
+    // the composite index, in whichever order they're piped
+    datastore
+        .index_composite_properties(vec![country.clone(), city.clone()])
+        .unwrap();
+    let mut expected = vec![ids[0], ids[3]];
+    expected.sort();
+
+    let q = models::PropertyValueVertexQuery::new(country.clone(), serde_json::json!("fr"))
+        .with_property_equal_to(city.clone(), serde_json::json!("paris"));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+
+    let q = models::PropertyValueVertexQuery::new(city.clone(), serde_json::json!("paris"))
+        .with_property_equal_to(country.clone(), serde_json::json!("fr"));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+
+    // Filters piped from another query are checked against its results
+    let q = models::SpecificVertexQuery::new(vec![ids[0], ids[1], ids[2]])
+        .with_property_equal_to(country.clone(), serde_json::json!("fr"))
+        .with_property_equal_to(city.clone(), serde_json::json!("paris"));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, ids[0]);
+
+    // Filters on properties outside of the index are checked separately
+    let extra = models::Identifier::new("extra").unwrap();
+    datastore
+        .set_vertex_properties(
+            models::SpecificVertexQuery::single(ids[3]).property(extra.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+    let q = models::PropertyValueVertexQuery::new(country.clone(), serde_json::json!("fr"))
+        .with_property_equal_to(city.clone(), serde_json::json!("paris"))
+        .with_property_equal_to(extra, serde_json::Value::Bool(true));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, ids[3]);
+
+    // Changes made after the index is built should be reflected in it
+    datastore
+        .set_vertex_properties(
+            models::SpecificVertexQuery::single(ids[0]).property(city.clone()),
+            serde_json::json!("lyon"),
+        )
+        .unwrap();
+    datastore
+        .delete_vertex_properties(models::SpecificVertexQuery::single(ids[3]).property(country.clone()))
+        .unwrap();
+    datastore
+        .delete_vertices(models::SpecificVertexQuery::single(ids[1]).into())
+        .unwrap();
+    let new_v = models::Vertex::new(t);
+    datastore
+        .bulk_insert(vec![
+            models::BulkInsertItem::Vertex(new_v.clone()),
+            models::BulkInsertItem::VertexProperty(new_v.id, city.clone(), serde_json::json!("lyon")),
+            models::BulkInsertItem::VertexProperty(new_v.id, country.clone(), serde_json::json!("fr")),
+        ])
+        .unwrap();
+    let mut expected = vec![ids[0], new_v.id];
+    expected.sort();
+    let q = models::PropertyValueVertexQuery::new(country.clone(), serde_json::json!("fr"))
+        .with_property_equal_to(city.clone(), serde_json::json!("lyon"));
+    let result = datastore.get_vertices(q.into()).unwrap();
+    assert_eq!(result.iter().map(|v| v.id).collect::<Vec<Uuid>>(), expected);
+    let q = models::PropertyValueVertexQuery::new(country.clone(), serde_json::json!("fr"))
+        .with_property_equal_to(city.clone(), serde_json::json!("paris"));
+    assert_eq!(datastore.get_vertices(q.into()).unwrap().len(), 0);
+
+    // Once the index is dropped, the properties need to be indexed again
+    datastore
+        .drop_composite_index(vec![country.clone(), city.clone()])
+        .unwrap();
+    let q = models::PropertyValueVertexQuery::new(country, serde_json::json!("fr"))
+        .with_property_equal_to(city, serde_json::json!("lyon"));
+    let result = datastore.get_vertices(q.into());
+    match result {
+        Err(Error::NotIndexed) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_query_composite_indexed_edge_properties<D: Datastore>(datastore: &D) {
+    // Setup, with edges between a few vertices
+    let first = models::Identifier::new("first").unwrap();
+    let second = models::Identifier::new("second").unwrap();
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let edge_t = models::Identifier::new("test_edge_type").unwrap();
+    let outbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let mut keys = Vec::new();
+    for (first_value, second_value) in [(1, 1), (1, 2), (2, 1)].iter() {
+        let inbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+        let key = models::EdgeKey::new(outbound_id, edge_t.clone(), inbound_id);
+        datastore.create_edge(&key).unwrap();
+        let q = models::SpecificEdgeQuery::single(key.clone());
+        datastore
+            .set_edge_properties(q.clone().property(first.clone()), serde_json::json!(first_value))
+            .unwrap();
+        datastore
+            .set_edge_properties(q.property(second.clone()), serde_json::json!(second_value))
+            .unwrap();
+        keys.push(key);
+    }
+
+    datastore
+        .index_composite_properties(vec![first.clone(), second.clone()])
+        .unwrap();
+
+    let q = models::PropertyValueEdgeQuery::new(first.clone(), serde_json::json!(1))
+        .with_property_equal_to(second.clone(), serde_json::json!(2));
+    let result = datastore.get_edges(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[1]);
+
+    let q = models::SpecificVertexQuery::single(outbound_id)
+        .outbound()
+        .with_property_equal_to(second.clone(), serde_json::json!(1))
+        .with_property_equal_to(first.clone(), serde_json::json!(2));
+    let result = datastore.get_edges(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[2]);
+
+    // Changes made after the index is built should be reflected in it
+    datastore
+        .set_edge_properties(
+            models::SpecificEdgeQuery::single(keys[0].clone()).property(second.clone()),
+            serde_json::json!(2),
+        )
+        .unwrap();
+    datastore
+        .delete_edges(models::SpecificEdgeQuery::single(keys[1].clone()).into())
+        .unwrap();
+    let q = models::PropertyValueEdgeQuery::new(first, serde_json::json!(1))
+        .with_property_equal_to(second, serde_json::json!(2));
+    let result = datastore.get_edges(q.into()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].key, keys[0]);
+}
+
+pub fn should_get_indexed_composite_properties<D: Datastore>(datastore: &D) {
+    assert_eq!(
+        datastore.get_indexed_composite_properties().unwrap(),
+        Vec::<Vec<models::Identifier>>::new()
+    );
+
+    let a = models::Identifier::new("a").unwrap();
+    let b = models::Identifier::new("b").unwrap();
+    datastore
+        .index_composite_properties(vec![b.clone(), a.clone()])
+        .unwrap();
+    datastore
+        .index_composite_properties(vec![a.clone(), b.clone()])
+        .unwrap();
+    datastore
+        .index_composite_properties(vec![a.clone(), b.clone()])
+        .unwrap();
+    assert_eq!(
+        datastore.get_indexed_composite_properties().unwrap(),
+        vec![vec![a.clone(), b.clone()], vec![b.clone(), a.clone()]]
+    );
+
+    // Composite indexes are separate from the properties' own indexes
+    assert_eq!(datastore.get_indexed_properties().unwrap(), vec![]);
+
+    // Composite indexes need at least two distinct properties
+    for names in [vec![a.clone()], vec![a.clone(), a.clone()]] {
+        let result = datastore.index_composite_properties(names);
+        match result {
+            Err(Error::Unsupported) => (),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    datastore.drop_composite_index(vec![b.clone(), a.clone()]).unwrap();
+    datastore.drop_composite_index(vec![b, a.clone()]).unwrap();
+    assert_eq!(
+        datastore.get_indexed_composite_properties().unwrap(),
+        vec![vec![a, models::Identifier::new("b").unwrap()]]
+    );
+}
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use super::util::create_edges;
+use crate::{
+    models, BulkInsertItem, Datastore, EdgeQueryExt, RangeVertexQuery, Result, SpecificEdgeQuery, SpecificVertexQuery,
+    VertexQueryExt,
+};
+
+use uuid::Uuid;
+
+pub fn should_get_range_vertices_iter<D: Datastore>(datastore: &D) {
+    // Insert enough vertices that they'll be loaded in several batches
+    let t = models::Identifier::new("iter_vertex_type").unwrap();
+    let vertices: Vec<models::Vertex> = (0..2_500).map(|_| models::Vertex::new(t.clone())).collect();
+    datastore
+        .bulk_insert(vertices.iter().cloned().map(BulkInsertItem::Vertex).collect())
+        .unwrap();
+
+    let q = RangeVertexQuery::new().t(t);
+    let expected = datastore.get_vertices(q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 2_500);
+
+    let result: Result<Vec<models::Vertex>> = datastore.get_vertices_iter(q.clone().into()).unwrap().collect();
+    let result = result.unwrap();
+    assert_eq!(result, expected);
+    let ids: HashSet<Uuid> = result.into_iter().map(|v| v.id).collect();
+    assert_eq!(ids, vertices.into_iter().map(|v| v.id).collect());
+
+    // Limits should apply across batches
+    let result: Result<Vec<models::Vertex>> = datastore
+        .get_vertices_iter(q.clone().limit(1_500).into())
+        .unwrap()
+        .collect();
+    assert_eq!(result.unwrap(), expected[..1_500]);
+
+    // As should start IDs
+    let start_id = expected[100].id;
+    let expected_from_start = datastore.get_vertices(q.clone().start_id(start_id).into()).unwrap();
+    let result: Result<Vec<models::Vertex>> = datastore
+        .get_vertices_iter(q.start_id(start_id).into())
+        .unwrap()
+        .collect();
+    assert_eq!(result.unwrap(), expected_from_start);
+}
+
+pub fn should_get_vertex_properties_iter<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let name = models::Identifier::new("foo").unwrap();
+    let ids: Vec<Uuid> = (0..3)
+        .map(|_| datastore.create_vertex_from_type(t.clone()).unwrap())
+        .collect();
+    datastore
+        .set_vertex_properties(
+            SpecificVertexQuery::new(ids[..2].to_vec()).property(name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+
+    let q = SpecificVertexQuery::new(ids).property(name);
+    let expected = datastore.get_vertex_properties(q.clone()).unwrap();
+    assert_eq!(expected.len(), 2);
+    let result: Result<Vec<models::VertexProperty>> =
+        datastore.get_vertex_properties_iter(q.clone()).unwrap().collect();
+    assert_eq!(result.unwrap(), expected);
+
+    let expected = datastore.get_all_vertex_properties(q.inner.clone()).unwrap();
+    assert_eq!(expected.len(), 3);
+    let result: Result<Vec<models::VertexProperties>> =
+        datastore.get_all_vertex_properties_iter(q.inner).unwrap().collect();
+    assert_eq!(result.unwrap(), expected);
+}
+
+pub fn should_get_edge_properties_iter<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let name = models::Identifier::new("edge-property").unwrap();
+    let edges = datastore
+        .get_edges(SpecificVertexQuery::single(outbound_id).outbound().into())
+        .unwrap();
+    assert_eq!(edges.len(), 5);
+    datastore
+        .set_edge_properties(
+            SpecificEdgeQuery::single(edges[0].key.clone()).property(name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+
+    let q = SpecificVertexQuery::single(outbound_id).outbound();
+    let result: Result<Vec<models::Edge>> = datastore.get_edges_iter(q.clone().into()).unwrap().collect();
+    assert_eq!(result.unwrap(), edges);
+
+    let expected = datastore.get_edge_properties(q.clone().property(name.clone())).unwrap();
+    assert_eq!(expected.len(), 1);
+    let result: Result<Vec<models::EdgeProperty>> = datastore
+        .get_edge_properties_iter(q.clone().property(name))
+        .unwrap()
+        .collect();
+    assert_eq!(result.unwrap(), expected);
+
+    let expected = datastore.get_all_edge_properties(q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 5);
+    let result: Result<Vec<models::EdgeProperties>> =
+        datastore.get_all_edge_properties_iter(q.into()).unwrap().collect();
+    assert_eq!(result.unwrap(), expected);
+}
+
+pub fn should_stream_piped_queries<D: Datastore>(datastore: &D) {
+    // Link enough vertices to one vertex that the edges and vertices piped
+    // from it are loaded in several batches
+    let t = models::Identifier::new("iter_vertex_type").unwrap();
+    let edge_t = models::Identifier::new("iter_edge_type").unwrap();
+    let name = models::Identifier::new("iter_edge_property").unwrap();
+    datastore.index_property(name.clone()).unwrap();
+    let hub = models::Vertex::new(t.clone());
+    let vertices: Vec<models::Vertex> = (0..2_500).map(|_| models::Vertex::new(t.clone())).collect();
+    let mut items = vec![BulkInsertItem::Vertex(hub.clone())];
+    for vertex in &vertices {
+        let key = models::EdgeKey::new(hub.id, edge_t.clone(), vertex.id);
+        items.push(BulkInsertItem::Vertex(vertex.clone()));
+        items.push(BulkInsertItem::Edge(key.clone()));
+        items.push(BulkInsertItem::EdgeProperty(
+            key,
+            name.clone(),
+            serde_json::Value::Bool(true),
+        ));
+    }
+    datastore.bulk_insert(items).unwrap();
+
+    let edges_q = SpecificVertexQuery::single(hub.id).outbound();
+    let expected = datastore.get_edges(edges_q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 2_500);
+
+    // Writes made while iterating shouldn't block, or affect the results
+    let mut iter = datastore.get_edges_iter(edges_q.clone().into()).unwrap();
+    let mut result: Vec<models::Edge> = iter.by_ref().take(10).map(|edge| edge.unwrap()).collect();
+    let extra = datastore.create_vertex_from_type(t.clone()).unwrap();
+    assert!(datastore
+        .create_edge(&models::EdgeKey::new(hub.id, edge_t.clone(), extra))
+        .unwrap());
+    for edge in iter {
+        result.push(edge.unwrap());
+    }
+    assert_eq!(result, expected);
+
+    let vertices_q = edges_q.clone().limit(2_000).inbound();
+    let expected = datastore.get_vertices(vertices_q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 2_000);
+    let result: Result<Vec<models::Vertex>> = datastore.get_vertices_iter(vertices_q.into()).unwrap().collect();
+    assert_eq!(result.unwrap(), expected);
+
+    let property_q = models::PropertyPresenceEdgeQuery::new(name);
+    let expected: HashSet<models::EdgeKey> = datastore
+        .get_edges(property_q.clone().into())
+        .unwrap()
+        .into_iter()
+        .map(|edge| edge.key)
+        .collect();
+    assert_eq!(expected.len(), 2_500);
+    let result: Result<HashSet<models::EdgeKey>> = datastore
+        .get_edges_iter(property_q.into())
+        .unwrap()
+        .map(|edge| edge.map(|edge| edge.key))
+        .collect();
+    assert_eq!(result.unwrap(), expected);
+}
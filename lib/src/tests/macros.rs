@@ -17,9 +17,13 @@ macro_rules! full_test_impl {
         // Sync
         define_test!(should_sync, $code);
 
+        // Backup
+        define_test!(should_backup, $code);
+
         // Bulk insert
         define_test!(should_bulk_insert, $code);
         define_test!(should_bulk_insert_a_redundant_vertex, $code);
+        define_test!(should_bulk_insert_a_vertex_with_different_types, $code);
         define_test!(should_bulk_insert_an_invalid_edge, $code);
 
         // Vertices
@@ -28,11 +32,15 @@ macro_rules! full_test_impl {
         define_test!(should_get_no_vertices_with_zero_limit, $code);
         define_test!(should_get_range_vertices_out_of_range, $code);
         define_test!(should_get_no_vertices_with_type_filter, $code);
+        define_test!(should_get_range_vertices_with_type_filter, $code);
         define_test!(should_get_single_vertex, $code);
         define_test!(should_get_single_vertex_nonexisting, $code);
         define_test!(should_get_vertices, $code);
         define_test!(should_get_vertices_piped, $code);
+        define_test!(should_traverse_vertices, $code);
+        define_test!(should_combine_vertex_queries, $code);
         define_test!(should_get_a_vertex_count, $code);
+        define_test!(should_count_vertices, $code);
         define_test!(should_delete_a_valid_outbound_vertex, $code);
         define_test!(should_delete_a_valid_inbound_vertex, $code);
         define_test!(should_not_delete_an_invalid_vertex, $code);
@@ -48,6 +56,7 @@ macro_rules! full_test_impl {
         define_test!(should_get_an_edge_count_with_no_type, $code);
         define_test!(should_get_an_edge_count_for_an_invalid_edge, $code);
         define_test!(should_get_an_inbound_edge_count, $code);
+        define_test!(should_count_edges, $code);
         define_test!(should_get_an_edge_range, $code);
         define_test!(should_get_edges_with_no_type, $code);
         define_test!(should_get_no_edges_for_an_invalid_range, $code);
@@ -57,6 +66,8 @@ macro_rules! full_test_impl {
         define_test!(should_get_no_edges_for_reversed_time, $code);
         define_test!(should_get_edges, $code);
         define_test!(should_get_edges_piped, $code);
+        define_test!(should_combine_edge_queries, $code);
+        define_test!(should_get_paths, $code);
 
         // Indexing
         define_test!(should_not_query_unindexed_vertex_property, $code);
@@ -69,6 +80,37 @@ macro_rules! full_test_impl {
         define_test!(should_update_indexed_edge_property, $code);
         define_test!(should_query_indexed_vertex_property_empty, $code);
         define_test!(should_query_indexed_edge_property_empty, $code);
+        define_test!(should_query_vertex_property_range, $code);
+        define_test!(should_query_edge_property_range, $code);
+        define_test!(should_not_query_unindexed_property_range, $code);
+        define_test!(should_get_indexed_properties, $code);
+        define_test!(should_drop_index_of_vertex_property, $code);
+        define_test!(should_drop_index_of_edge_property, $code);
+        define_test!(should_drop_unindexed_property, $code);
+        define_test!(should_get_index_status, $code);
+        define_test!(should_index_property_in_background, $code);
+        define_test!(should_query_type_indexed_vertex_property, $code);
+        define_test!(should_query_type_indexed_edge_property, $code);
+        define_test!(should_get_indexed_type_properties, $code);
+        define_test!(should_query_composite_indexed_vertex_properties, $code);
+        define_test!(should_query_composite_indexed_edge_properties, $code);
+        define_test!(should_get_indexed_composite_properties, $code);
+
+        // Constraints
+        define_test!(should_enforce_unique_vertex_property, $code);
+        define_test!(should_enforce_unique_edge_property, $code);
+        define_test!(should_enforce_type_scoped_unique_property, $code);
+        define_test!(should_enforce_unique_property_in_bulk_insert, $code);
+        define_test!(should_check_a_whole_bulk_insert_for_unique_violations, $code);
+        define_test!(should_add_unique_constraints, $code);
+
+        // Schema
+        define_test!(should_get_schema, $code);
+        define_test!(should_enforce_schema_vertex_types, $code);
+        define_test!(should_enforce_schema_edge_types, $code);
+        define_test!(should_enforce_schema_property_types, $code);
+        define_test!(should_enforce_schema_required_properties, $code);
+        define_test!(should_get_schema_summary, $code);
 
         // Properties
         define_test!(should_handle_vertex_properties, $code);
@@ -79,5 +121,51 @@ macro_rules! full_test_impl {
         define_test!(should_not_set_invalid_edge_properties, $code);
         define_test!(should_not_delete_invalid_edge_properties, $code);
         define_test!(should_get_all_edge_properties, $code);
+
+        // Iterators
+        define_test!(should_get_range_vertices_iter, $code);
+        define_test!(should_get_vertex_properties_iter, $code);
+        define_test!(should_get_edge_properties_iter, $code);
+        define_test!(should_stream_piped_queries, $code);
+
+        // Pagination
+        define_test!(should_page_range_vertices, $code);
+        define_test!(should_page_piped_vertices, $code);
+        define_test!(should_page_edges, $code);
+        define_test!(should_not_page_with_an_invalid_token, $code);
+        define_test!(should_resume_pages_after_writes, $code);
+        define_test!(should_page_vertex_properties, $code);
+        define_test!(should_page_edge_properties, $code);
+        define_test!(should_not_page_with_a_zero_limit, $code);
+        define_test!(should_not_page_with_a_token_for_another_query, $code);
+    };
+}
+
+/// Use this macro to enable the transaction test suite, for datastores that
+/// support transactions.
+#[macro_export]
+macro_rules! transaction_test_impl {
+    ($code:expr) => {
+        define_test!(should_commit_transaction, $code);
+        define_test!(should_rollback_transaction, $code);
+        define_test!(should_delete_in_transaction, $code);
+        define_test!(should_index_properties_set_in_transaction, $code);
+        define_test!(should_index_properties_set_concurrently_with_transaction, $code);
+        define_test!(should_not_nest_transactions, $code);
+        define_test!(should_not_commit_outside_of_transaction, $code);
+        define_test!(should_check_unique_constraints_on_commit, $code);
+        define_test!(should_get_schema_summary_in_transaction, $code);
+        define_test!(should_check_required_properties_on_commit, $code);
+    };
+}
+
+/// Use this macro to enable the snapshot test suite, for datastores that
+/// support snapshots.
+#[macro_export]
+macro_rules! snapshot_test_impl {
+    ($code:expr) => {
+        define_test!(should_read_from_snapshot, $code);
+        define_test!(should_not_write_to_snapshot, $code);
+        define_test!(should_snapshot_transaction, $code);
     };
 }
@@ -4,21 +4,34 @@
 //! `indradb` crate can reuse them. Generally you can use the convenience macro
 //! `full_test_impl`.
 
+mod backup;
 mod bulk_insert;
+mod constraints;
 mod edge;
 mod indexing;
+mod iter;
 #[macro_use]
 mod macros;
+mod pagination;
 mod properties;
+mod schema;
+mod snapshot;
 mod sync;
+mod transaction;
 mod util;
 mod vertex;
 
+pub use self::backup::*;
 pub use self::bulk_insert::*;
+pub use self::constraints::*;
 pub use self::edge::*;
 pub use self::indexing::*;
-pub use self::macros::*;
+pub use self::iter::*;
+pub use self::pagination::*;
 pub use self::properties::*;
+pub use self::schema::*;
+pub use self::snapshot::*;
 pub use self::sync::*;
+pub use self::transaction::*;
 pub use self::util::*;
 pub use self::vertex::*;
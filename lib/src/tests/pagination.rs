@@ -0,0 +1,184 @@
+use super::util::create_edges;
+use crate::{
+    models, BulkInsertItem, ContinuationToken, Datastore, EdgeQueryExt, Error, RangeVertexQuery, SpecificEdgeQuery,
+    SpecificVertexQuery, VertexQueryExt,
+};
+
+// Gets all pages of a vertex query, returning the size of each page along
+// with all of the vertices.
+fn get_vertex_pages<D: Datastore>(
+    datastore: &D,
+    q: models::VertexQuery,
+    limit: u32,
+) -> (Vec<usize>, Vec<models::Vertex>) {
+    let mut page_sizes = Vec::new();
+    let mut vertices = Vec::new();
+    let mut token = None;
+
+    loop {
+        let page = datastore.get_vertices_page(q.clone(), limit, token).unwrap();
+        page_sizes.push(page.results.len());
+        vertices.extend(page.results);
+        token = page.token;
+        if token.is_none() {
+            return (page_sizes, vertices);
+        }
+    }
+}
+
+pub fn should_page_range_vertices<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("page_vertex_type").unwrap();
+    let items = (0..25)
+        .map(|_| BulkInsertItem::Vertex(models::Vertex::new(t.clone())))
+        .collect();
+    datastore.bulk_insert(items).unwrap();
+
+    let q = RangeVertexQuery::new().t(t);
+    let expected = datastore.get_vertices(q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 25);
+    let (page_sizes, vertices) = get_vertex_pages(datastore, q.clone().into(), 10);
+    assert_eq!(page_sizes, vec![10, 10, 5]);
+    assert_eq!(vertices, expected);
+
+    // The query's limit should apply across pages
+    let (page_sizes, vertices) = get_vertex_pages(datastore, q.limit(15).into(), 10);
+    assert_eq!(page_sizes, vec![10, 5]);
+    assert_eq!(vertices, expected[..15]);
+}
+
+pub fn should_page_piped_vertices<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let q = SpecificVertexQuery::single(outbound_id).outbound().inbound();
+    let expected = datastore.get_vertices(q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 5);
+    let (page_sizes, vertices) = get_vertex_pages(datastore, q.into(), 2);
+    assert_eq!(page_sizes, vec![2, 2, 1]);
+    assert_eq!(vertices, expected);
+}
+
+pub fn should_page_edges<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let q = SpecificVertexQuery::single(outbound_id).outbound();
+    let expected = datastore.get_edges(q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 5);
+
+    let first = datastore.get_edges_page(q.clone().into(), 3, None).unwrap();
+    assert_eq!(first.results, expected[..3]);
+    let second = datastore.get_edges_page(q.into(), 3, first.token).unwrap();
+    assert_eq!(second.results, expected[3..]);
+    assert_eq!(second.token, None);
+}
+
+pub fn should_not_page_with_an_invalid_token<D: Datastore>(datastore: &D) {
+    let token = ContinuationToken::from_bytes(b"foo".to_vec());
+    let result = datastore.get_vertices_page(RangeVertexQuery::new().into(), 10, Some(token));
+    match result {
+        Err(Error::InvalidContinuationToken) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_resume_pages_after_writes<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let q = SpecificVertexQuery::single(outbound_id).outbound();
+    let expected = datastore.get_edges(q.clone().into()).unwrap();
+    assert_eq!(expected.len(), 5);
+
+    // Deleting a result that was already returned shouldn't make the next
+    // page skip any
+    let first = datastore.get_edges_page(q.clone().into(), 2, None).unwrap();
+    assert_eq!(first.results, expected[..2]);
+    datastore
+        .delete_edges(SpecificEdgeQuery::single(expected[0].key.clone()).into())
+        .unwrap();
+    let second = datastore.get_edges_page(q.into(), 2, first.token).unwrap();
+    assert_eq!(second.results, expected[2..4]);
+}
+
+pub fn should_page_vertex_properties<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("page_vertex_property_type").unwrap();
+    let name = models::Identifier::new("page_vertex_property").unwrap();
+    let vertices: Vec<models::Vertex> = (0..25).map(|_| models::Vertex::new(t.clone())).collect();
+    let mut items: Vec<BulkInsertItem> = vertices.iter().cloned().map(BulkInsertItem::Vertex).collect();
+    for (i, vertex) in vertices.iter().enumerate().filter(|(i, _)| i % 3 != 0) {
+        items.push(BulkInsertItem::VertexProperty(
+            vertex.id,
+            name.clone(),
+            serde_json::Value::from(i),
+        ));
+    }
+    datastore.bulk_insert(items).unwrap();
+
+    let q = RangeVertexQuery::new().t(t).property(name);
+    let expected = datastore.get_vertex_properties(q.clone()).unwrap();
+    assert_eq!(expected.len(), 16);
+
+    // Vertices without the property don't count towards the page's limit
+    let mut page_sizes = Vec::new();
+    let mut properties = Vec::new();
+    let mut token = None;
+    loop {
+        let page = datastore.get_vertex_properties_page(q.clone(), 5, token).unwrap();
+        page_sizes.push(page.results.len());
+        properties.extend(page.results);
+        token = page.token;
+        if token.is_none() {
+            break;
+        }
+    }
+    assert_eq!(page_sizes, vec![5, 5, 5, 1]);
+    assert_eq!(properties, expected);
+}
+
+pub fn should_page_edge_properties<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let name = models::Identifier::new("page_edge_property").unwrap();
+    let edges = datastore
+        .get_edges(SpecificVertexQuery::single(outbound_id).outbound().into())
+        .unwrap();
+    for edge in &edges[1..] {
+        let q = SpecificEdgeQuery::single(edge.key.clone()).property(name.clone());
+        datastore.set_edge_properties(q, serde_json::Value::Bool(true)).unwrap();
+    }
+
+    let q = SpecificVertexQuery::single(outbound_id).outbound().property(name);
+    let expected = datastore.get_edge_properties(q.clone()).unwrap();
+    assert_eq!(expected.len(), 4);
+    let first = datastore.get_edge_properties_page(q.clone(), 3, None).unwrap();
+    assert_eq!(first.results, expected[..3]);
+    let second = datastore.get_edge_properties_page(q, 3, first.token).unwrap();
+    assert_eq!(second.results, expected[3..]);
+    assert_eq!(second.token, None);
+}
+
+pub fn should_not_page_with_a_zero_limit<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let q = SpecificVertexQuery::single(outbound_id);
+    let name = models::Identifier::new("foo").unwrap();
+
+    match datastore.get_vertices_page(q.clone().into(), 0, None) {
+        Err(Error::InvalidPageLimit) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match datastore.get_edges_page(q.clone().outbound().into(), 0, None) {
+        Err(Error::InvalidPageLimit) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match datastore.get_vertex_properties_page(q.property(name), 0, None) {
+        Err(Error::InvalidPageLimit) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_not_page_with_a_token_for_another_query<D: Datastore>(datastore: &D) {
+    let (outbound_id, _) = create_edges(datastore);
+    let q = SpecificVertexQuery::single(outbound_id).outbound();
+    let page = datastore.get_edges_page(q.clone().into(), 2, None).unwrap();
+    assert!(page.token.is_some());
+
+    let other = q.limit(4);
+    match datastore.get_edges_page(other.into(), 2, page.token) {
+        Err(Error::InvalidContinuationToken) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use crate::{models, Datastore, EdgeQueryExt, Error, VertexQueryExt};
+
+fn test_schema() -> models::Schema {
+    let user_t = models::Identifier::new("user").unwrap();
+    let post_t = models::Identifier::new("post").unwrap();
+    let mut user_properties = HashMap::new();
+    user_properties.insert(
+        models::Identifier::new("email").unwrap(),
+        models::PropertySchema::new(models::JsonType::String, true),
+    );
+    user_properties.insert(
+        models::Identifier::new("age").unwrap(),
+        models::PropertySchema::new(models::JsonType::Integer, false),
+    );
+    let mut vertex_types = HashMap::new();
+    vertex_types.insert(
+        user_t.clone(),
+        models::VertexSchema {
+            properties: user_properties,
+        },
+    );
+    vertex_types.insert(post_t.clone(), models::VertexSchema::default());
+
+    let mut wrote_properties = HashMap::new();
+    wrote_properties.insert(
+        models::Identifier::new("at").unwrap(),
+        models::PropertySchema::new(models::JsonType::Number, false),
+    );
+    let mut edge_types = HashMap::new();
+    edge_types.insert(
+        models::Identifier::new("wrote").unwrap(),
+        models::EdgeSchema {
+            outbound_types: vec![user_t].into_iter().collect(),
+            inbound_types: vec![post_t].into_iter().collect(),
+            properties: wrote_properties,
+        },
+    );
+    edge_types.insert(models::Identifier::new("links").unwrap(), models::EdgeSchema::default());
+
+    models::Schema {
+        vertex_types,
+        edge_types,
+    }
+}
+
+// Inserts a user vertex, along with its required properties.
+fn insert_user<D: Datastore>(datastore: &D) -> models::Vertex {
+    let user = models::Vertex::new(models::Identifier::new("user").unwrap());
+    datastore
+        .bulk_insert(vec![
+            models::BulkInsertItem::Vertex(user.clone()),
+            models::BulkInsertItem::VertexProperty(
+                user.id,
+                models::Identifier::new("email").unwrap(),
+                serde_json::json!("a@example.com"),
+            ),
+        ])
+        .unwrap();
+    user
+}
+
+pub fn should_get_schema<D: Datastore>(datastore: &D) {
+    assert_eq!(datastore.get_schema().unwrap(), None);
+    datastore.set_schema(Some(test_schema())).unwrap();
+    assert_eq!(datastore.get_schema().unwrap(), Some(test_schema()));
+    datastore.set_schema(None).unwrap();
+    assert_eq!(datastore.get_schema().unwrap(), None);
+}
+
+pub fn should_enforce_schema_vertex_types<D: Datastore>(datastore: &D) {
+    datastore.set_schema(Some(test_schema())).unwrap();
+    let post_t = models::Identifier::new("post").unwrap();
+    let other_t = models::Identifier::new("other").unwrap();
+
+    datastore.create_vertex_from_type(post_t).unwrap();
+    let result = datastore.create_vertex_from_type(other_t.clone());
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.bulk_insert(vec![models::BulkInsertItem::Vertex(models::Vertex::new(
+        other_t.clone(),
+    ))]);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(datastore.get_vertex_count().unwrap(), 1);
+
+    // Anything goes once the schema is removed
+    datastore.set_schema(None).unwrap();
+    datastore.create_vertex_from_type(other_t).unwrap();
+}
+
+pub fn should_enforce_schema_edge_types<D: Datastore>(datastore: &D) {
+    datastore.set_schema(Some(test_schema())).unwrap();
+    let user = insert_user(datastore);
+    let post_id = datastore
+        .create_vertex_from_type(models::Identifier::new("post").unwrap())
+        .unwrap();
+    let wrote_t = models::Identifier::new("wrote").unwrap();
+
+    assert!(datastore
+        .create_edge(&models::EdgeKey::new(user.id, wrote_t.clone(), post_id))
+        .unwrap());
+    assert!(datastore
+        .create_edge(&models::EdgeKey::new(
+            post_id,
+            models::Identifier::new("links").unwrap(),
+            user.id
+        ))
+        .unwrap());
+
+    // The edge type must be in the schema...
+    let key = models::EdgeKey::new(user.id, models::Identifier::new("other").unwrap(), post_id);
+    let result = datastore.create_edge(&key);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    // ...and allow the types of its vertices
+    let key = models::EdgeKey::new(post_id, wrote_t.clone(), user.id);
+    let result = datastore.create_edge(&key);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.bulk_insert(vec![models::BulkInsertItem::Edge(key)]);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.bulk_insert(vec![
+        models::BulkInsertItem::Vertex(models::Vertex::with_id(
+            post_id,
+            models::Identifier::new("post").unwrap(),
+        )),
+        models::BulkInsertItem::Edge(models::EdgeKey::new(post_id, wrote_t, post_id)),
+    ]);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    let count = datastore
+        .get_edge_count(user.id, None, models::EdgeDirection::Outbound)
+        .unwrap();
+    assert_eq!(count, 1);
+    let count = datastore
+        .get_edge_count(post_id, None, models::EdgeDirection::Outbound)
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+pub fn should_enforce_schema_property_types<D: Datastore>(datastore: &D) {
+    datastore.set_schema(Some(test_schema())).unwrap();
+    let user = insert_user(datastore);
+    let post_id = datastore
+        .create_vertex_from_type(models::Identifier::new("post").unwrap())
+        .unwrap();
+    let age = models::Identifier::new("age").unwrap();
+    let q = models::SpecificVertexQuery::single(user.id);
+
+    datastore
+        .set_vertex_properties(q.clone().property(age.clone()), serde_json::json!(30))
+        .unwrap();
+    datastore
+        .set_vertex_properties(q.clone().property(age.clone()), serde_json::json!(31.0))
+        .unwrap();
+    let result = datastore.set_vertex_properties(q.clone().property(age.clone()), serde_json::json!(31.5));
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.set_vertex_properties(q.clone().property(age.clone()), serde_json::json!("31"));
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.bulk_insert(vec![models::BulkInsertItem::VertexProperty(
+        user.id,
+        age.clone(),
+        serde_json::json!(null),
+    )]);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let properties = datastore.get_vertex_properties(q.clone().property(age)).unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].value, serde_json::json!(31.0));
+
+    // Properties that aren't declared can have any value
+    datastore
+        .set_vertex_properties(
+            q.property(models::Identifier::new("nickname").unwrap()),
+            serde_json::json!(["a", "b"]),
+        )
+        .unwrap();
+
+    let key = models::EdgeKey::new(user.id, models::Identifier::new("wrote").unwrap(), post_id);
+    datastore.create_edge(&key).unwrap();
+    let q = models::SpecificEdgeQuery::single(key);
+    let at = models::Identifier::new("at").unwrap();
+    datastore
+        .set_edge_properties(q.clone().property(at.clone()), serde_json::json!(1.5))
+        .unwrap();
+    let result = datastore.set_edge_properties(q.property(at), serde_json::json!(true));
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_enforce_schema_required_properties<D: Datastore>(datastore: &D) {
+    datastore.set_schema(Some(test_schema())).unwrap();
+    let user_t = models::Identifier::new("user").unwrap();
+    let email = models::Identifier::new("email").unwrap();
+
+    // Vertices with required properties can only be created along with them
+    let result = datastore.create_vertex_from_type(user_t.clone());
+    match result {
+        Err(Error::SchemaViolation(message)) => {
+            assert_eq!(message, "property `email` of vertex type `user` is required")
+        }
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    let result = datastore.bulk_insert(vec![models::BulkInsertItem::Vertex(models::Vertex::new(user_t))]);
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(datastore.get_vertex_count().unwrap(), 0);
+
+    // ...and the properties can't be deleted
+    let user = insert_user(datastore);
+    let q = models::SpecificVertexQuery::single(user.id).property(email);
+    let result = datastore.delete_vertex_properties(q.clone());
+    match result {
+        Err(Error::SchemaViolation(_)) => (),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(datastore.get_vertex_properties(q).unwrap().len(), 1);
+}
+
+pub fn should_check_required_properties_on_commit<D: Datastore>(datastore: &D) {
+    datastore.set_schema(Some(test_schema())).unwrap();
+    let user_t = models::Identifier::new("user").unwrap();
+    let email = models::Identifier::new("email").unwrap();
+
+    // Transactions can create vertices before setting their required
+    // properties...
+    let trans = datastore.transaction().unwrap();
+    let id = trans.create_vertex_from_type(user_t).unwrap();
+
+    // ...but can't be committed until they're set
+    let result = trans.commit();
+    match result {
+        Err(Error::SchemaViolation(message)) => {
+            assert_eq!(message, "property `email` of vertex type `user` is required")
+        }
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(datastore.get_vertex_count().unwrap(), 0);
+
+    let q = models::SpecificVertexQuery::single(id).property(email);
+    trans
+        .set_vertex_properties(q.clone(), serde_json::json!("a@example.com"))
+        .unwrap();
+    trans.commit().unwrap();
+    assert_eq!(datastore.get_vertex_count().unwrap(), 1);
+    assert_eq!(datastore.get_vertex_properties(q).unwrap().len(), 1);
+}
+
+pub fn should_get_schema_summary<D: Datastore>(datastore: &D) {
+    assert_eq!(
+        datastore.get_schema_summary().unwrap(),
+        models::SchemaSummary::default()
+    );
+
+    let user_t = models::Identifier::new("user").unwrap();
+    let post_t = models::Identifier::new("post").unwrap();
+    let wrote_t = models::Identifier::new("wrote").unwrap();
+    let email = models::Identifier::new("email").unwrap();
+    let age = models::Identifier::new("age").unwrap();
+    let title = models::Identifier::new("title").unwrap();
+    let at = models::Identifier::new("at").unwrap();
+    let user_1 = models::Vertex::new(user_t.clone());
+    let user_2 = models::Vertex::new(user_t.clone());
+    let post_1 = models::Vertex::new(post_t.clone());
+    let post_2 = models::Vertex::new(post_t.clone());
+    let edge_1 = models::EdgeKey::new(user_1.id, wrote_t.clone(), post_1.id);
+    let edge_2 = models::EdgeKey::new(user_2.id, wrote_t.clone(), post_1.id);
+
+    // Vertices and edges that are inserted more than once are only counted
+    // once
+    datastore
+        .bulk_insert(vec![
+            models::BulkInsertItem::Vertex(user_1.clone()),
+            models::BulkInsertItem::Vertex(user_2.clone()),
+            models::BulkInsertItem::Vertex(post_1.clone()),
+            models::BulkInsertItem::Vertex(user_1.clone()),
+            models::BulkInsertItem::Edge(edge_1.clone()),
+            models::BulkInsertItem::Edge(edge_2.clone()),
+            models::BulkInsertItem::Edge(edge_1.clone()),
+            models::BulkInsertItem::VertexProperty(user_1.id, email.clone(), serde_json::json!("a@example.com")),
+            models::BulkInsertItem::VertexProperty(user_1.id, age.clone(), serde_json::json!(30)),
+            models::BulkInsertItem::VertexProperty(user_2.id, email.clone(), serde_json::json!("b@example.com")),
+            models::BulkInsertItem::EdgeProperty(edge_1.clone(), at.clone(), serde_json::json!(1)),
+            models::BulkInsertItem::Vertex(post_2.clone()),
+            models::BulkInsertItem::VertexProperty(post_2.id, title.clone(), serde_json::json!("hello")),
+        ])
+        .unwrap();
+    let summary = datastore.get_schema_summary().unwrap();
+    let mut expected = models::SchemaSummary::default();
+    expected.vertex_types.insert(user_t.clone(), 2);
+    expected.vertex_types.insert(post_t.clone(), 2);
+    expected.edge_types.insert(wrote_t.clone(), 2);
+    expected
+        .vertex_properties
+        .insert(user_t.clone(), vec![email.clone(), age].into_iter().collect());
+    expected
+        .vertex_properties
+        .insert(post_t.clone(), vec![title.clone()].into_iter().collect());
+    expected
+        .edge_properties
+        .insert(wrote_t.clone(), vec![at].into_iter().collect());
+    assert_eq!(summary, expected);
+
+    // Types and properties that are left without any vertices or edges are
+    // dropped
+    datastore
+        .delete_vertices(models::SpecificVertexQuery::single(user_1.id).into())
+        .unwrap();
+    let summary = datastore.get_schema_summary().unwrap();
+    let mut expected = models::SchemaSummary::default();
+    expected.vertex_types.insert(user_t.clone(), 1);
+    expected.vertex_types.insert(post_t.clone(), 2);
+    expected.edge_types.insert(wrote_t, 1);
+    expected
+        .vertex_properties
+        .insert(user_t, vec![email].into_iter().collect());
+    expected
+        .vertex_properties
+        .insert(post_t, vec![title].into_iter().collect());
+    assert_eq!(summary, expected);
+
+    datastore
+        .delete_edges(models::SpecificEdgeQuery::single(edge_2).into())
+        .unwrap();
+    let summary = datastore.get_schema_summary().unwrap();
+    assert!(summary.edge_types.is_empty());
+    assert_eq!(summary.vertex_types.len(), 2);
+}
@@ -0,0 +1,89 @@
+use crate::{models, Datastore, Error, RangeVertexQuery, SpecificVertexQuery, VertexQueryExt};
+
+pub fn should_read_from_snapshot<D: Datastore>(datastore: &D) {
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let outbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let inbound_id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let key = models::EdgeKey::new(
+        outbound_id,
+        models::Identifier::new("test_edge_type").unwrap(),
+        inbound_id,
+    );
+    datastore.create_edge(&key).unwrap();
+    let property_name = models::Identifier::new("foo").unwrap();
+    let q = SpecificVertexQuery::single(outbound_id);
+    datastore
+        .set_vertex_properties(q.clone().property(property_name.clone()), serde_json::Value::Bool(true))
+        .unwrap();
+
+    let snapshot = datastore.snapshot().unwrap();
+
+    // Changes made after the snapshot was taken should not be visible in it
+    datastore.create_vertex_from_type(vertex_t).unwrap();
+    datastore.delete_edges(q.clone().outbound().into()).unwrap();
+    datastore
+        .set_vertex_properties(
+            q.clone().property(property_name.clone()),
+            serde_json::Value::Bool(false),
+        )
+        .unwrap();
+
+    assert_eq!(snapshot.get_vertex_count().unwrap(), 2);
+    assert_eq!(snapshot.get_vertices(RangeVertexQuery::new().into()).unwrap().len(), 2);
+    let edges = snapshot.get_edges(q.clone().outbound().into()).unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].key, key);
+    assert_eq!(snapshot.count_edges(q.clone().outbound().into()).unwrap(), 1);
+    let properties = snapshot
+        .get_vertex_properties(q.clone().property(property_name.clone()))
+        .unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].value, serde_json::Value::Bool(true));
+
+    // ...while the datastore itself should see them
+    assert_eq!(datastore.get_vertex_count().unwrap(), 3);
+    assert_eq!(datastore.get_edges(q.clone().outbound().into()).unwrap().len(), 0);
+    let properties = datastore.get_vertex_properties(q.property(property_name)).unwrap();
+    assert_eq!(properties[0].value, serde_json::Value::Bool(false));
+}
+
+pub fn should_not_write_to_snapshot<D: Datastore>(datastore: &D) {
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let id = datastore.create_vertex_from_type(vertex_t.clone()).unwrap();
+    let snapshot = datastore.snapshot().unwrap();
+
+    match snapshot.create_vertex_from_type(vertex_t) {
+        Err(Error::Unsupported) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match snapshot.delete_vertices(SpecificVertexQuery::single(id).into()) {
+        Err(Error::Unsupported) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match snapshot.index_property(models::Identifier::new("foo").unwrap()) {
+        Err(Error::Unsupported) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    assert_eq!(datastore.get_vertex_count().unwrap(), 1);
+}
+
+pub fn should_snapshot_transaction<D: Datastore>(datastore: &D) {
+    let trans = datastore.transaction().unwrap();
+    let id = trans
+        .create_vertex_from_type(models::Identifier::new("test_vertex_type").unwrap())
+        .unwrap();
+
+    // A snapshot of a transaction includes its staged changes
+    let snapshot = trans.snapshot().unwrap();
+    trans.rollback().unwrap();
+
+    assert_eq!(
+        snapshot
+            .get_vertices(SpecificVertexQuery::single(id).into())
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(datastore.get_vertex_count().unwrap(), 0);
+}
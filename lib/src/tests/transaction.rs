@@ -0,0 +1,237 @@
+use crate::{
+    models, Datastore, Error, PropertyValueVertexQuery, RangeVertexQuery, SpecificVertexQuery, VertexQueryExt,
+};
+
+pub fn should_commit_transaction<D: Datastore>(datastore: &D) {
+    let trans = datastore.transaction().unwrap();
+
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let outbound_v = models::Vertex::new(vertex_t.clone());
+    let inbound_v = models::Vertex::new(vertex_t);
+    trans.create_vertex(&outbound_v).unwrap();
+    trans.create_vertex(&inbound_v).unwrap();
+    let key = models::EdgeKey::new(
+        outbound_v.id,
+        models::Identifier::new("test_edge_type").unwrap(),
+        inbound_v.id,
+    );
+    assert!(trans.create_edge(&key).unwrap());
+    let property_name = models::Identifier::new("foo").unwrap();
+    let q = SpecificVertexQuery::single(outbound_v.id);
+    trans
+        .set_vertex_properties(q.clone().property(property_name.clone()), serde_json::Value::Bool(true))
+        .unwrap();
+
+    // The transaction should see its own changes
+    let edges = trans.get_edges(q.clone().outbound().into()).unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].key, key);
+    let properties = trans
+        .get_vertex_properties(q.clone().property(property_name.clone()))
+        .unwrap();
+    assert_eq!(properties.len(), 1);
+
+    // ...but nothing should be visible outside of it until it's committed
+    assert_eq!(datastore.get_vertices(q.clone().into()).unwrap().len(), 0);
+    assert_eq!(datastore.get_edges(q.clone().outbound().into()).unwrap().len(), 0);
+
+    trans.commit().unwrap();
+
+    assert_eq!(datastore.get_vertices(q.clone().into()).unwrap().len(), 1);
+    let edges = datastore.get_edges(q.clone().outbound().into()).unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].key, key);
+    let properties = datastore.get_vertex_properties(q.property(property_name)).unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].value, serde_json::Value::Bool(true));
+}
+
+pub fn should_rollback_transaction<D: Datastore>(datastore: &D) {
+    let trans = datastore.transaction().unwrap();
+    let id = trans
+        .create_vertex_from_type(models::Identifier::new("test_vertex_type").unwrap())
+        .unwrap();
+    assert_eq!(
+        trans
+            .get_vertices(SpecificVertexQuery::single(id).into())
+            .unwrap()
+            .len(),
+        1
+    );
+
+    trans.rollback().unwrap();
+
+    assert_eq!(
+        trans
+            .get_vertices(SpecificVertexQuery::single(id).into())
+            .unwrap()
+            .len(),
+        0
+    );
+    trans.commit().unwrap();
+    assert_eq!(
+        datastore
+            .get_vertices(SpecificVertexQuery::single(id).into())
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
+pub fn should_delete_in_transaction<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let id = datastore.create_vertex_from_type(t.clone()).unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    trans.delete_vertices(SpecificVertexQuery::single(id).into()).unwrap();
+
+    // Deletions should be reflected in range queries made in the
+    // transaction, but not in those made outside of it
+    let range = trans.get_vertices(RangeVertexQuery::new().t(t.clone()).into()).unwrap();
+    assert!(range.iter().all(|v| v.id != id));
+    let range = datastore.get_vertices(RangeVertexQuery::new().t(t).into()).unwrap();
+    assert!(range.iter().any(|v| v.id == id));
+
+    trans.commit().unwrap();
+    assert_eq!(
+        datastore
+            .get_vertices(SpecificVertexQuery::single(id).into())
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
+pub fn should_index_properties_set_in_transaction<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let property_name = models::Identifier::new("foo").unwrap();
+    let id = datastore.create_vertex_from_type(t.clone()).unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    trans
+        .set_vertex_properties(
+            SpecificVertexQuery::single(id).property(property_name.clone()),
+            serde_json::json!(1),
+        )
+        .unwrap();
+
+    // Indexes added while the transaction is open should include its values
+    // once it's committed
+    datastore.index_property(property_name.clone()).unwrap();
+    datastore.index_type_property(t.clone(), property_name.clone()).unwrap();
+    trans.commit().unwrap();
+
+    let vertices = datastore
+        .get_vertices(PropertyValueVertexQuery::new(property_name.clone(), serde_json::json!(1)).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, id);
+    let vertices = datastore
+        .get_vertices(
+            RangeVertexQuery::new()
+                .t(t)
+                .with_property_equal_to(property_name, serde_json::json!(1))
+                .into(),
+        )
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, id);
+}
+
+pub fn should_index_properties_set_concurrently_with_transaction<D: Datastore>(datastore: &D) {
+    let property_name = models::Identifier::new("foo").unwrap();
+    datastore.index_property(property_name.clone()).unwrap();
+    let id = datastore
+        .create_vertex_from_type(models::Identifier::new("test_vertex_type").unwrap())
+        .unwrap();
+    let q = SpecificVertexQuery::single(id).property(property_name.clone());
+
+    let trans = datastore.transaction().unwrap();
+    trans.set_vertex_properties(q.clone(), serde_json::json!(1)).unwrap();
+
+    // The property is set outside of the transaction before it's committed,
+    // so the value that's indexed outside of it should be replaced
+    datastore
+        .set_vertex_properties(q.clone(), serde_json::json!(2))
+        .unwrap();
+    trans.commit().unwrap();
+
+    let properties = datastore.get_vertex_properties(q).unwrap();
+    assert_eq!(properties.len(), 1);
+    assert_eq!(properties[0].value, serde_json::json!(1));
+    let vertices = datastore
+        .get_vertices(PropertyValueVertexQuery::new(property_name.clone(), serde_json::json!(1)).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 1);
+    assert_eq!(vertices[0].id, id);
+    let vertices = datastore
+        .get_vertices(PropertyValueVertexQuery::new(property_name, serde_json::json!(2)).into())
+        .unwrap();
+    assert_eq!(vertices.len(), 0);
+}
+
+pub fn should_not_nest_transactions<D: Datastore>(datastore: &D) {
+    let trans = datastore.transaction().unwrap();
+    let result = trans.transaction();
+    match result {
+        Err(Error::Unsupported) => (),
+        _ => panic!("unexpected result"),
+    }
+}
+
+pub fn should_not_commit_outside_of_transaction<D: Datastore>(datastore: &D) {
+    match datastore.commit() {
+        Err(Error::Unsupported) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match datastore.rollback() {
+        Err(Error::Unsupported) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+pub fn should_get_schema_summary_in_transaction<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let property_name = models::Identifier::new("foo").unwrap();
+    let existing_id = datastore.create_vertex_from_type(t.clone()).unwrap();
+
+    let trans = datastore.transaction().unwrap();
+    let id = trans.create_vertex_from_type(t.clone()).unwrap();
+    trans
+        .set_vertex_properties(
+            SpecificVertexQuery::single(id).property(property_name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+
+    // The transaction should count its own changes, but nothing else should
+    // until it's committed
+    let summary = trans.get_schema_summary().unwrap();
+    assert_eq!(summary.vertex_types.get(&t), Some(&2));
+    assert_eq!(
+        summary.vertex_properties.get(&t),
+        Some(&vec![property_name.clone()].into_iter().collect())
+    );
+    let summary = datastore.get_schema_summary().unwrap();
+    assert_eq!(summary.vertex_types.get(&t), Some(&1));
+    assert!(summary.vertex_properties.is_empty());
+
+    trans.commit().unwrap();
+    let summary = datastore.get_schema_summary().unwrap();
+    assert_eq!(summary.vertex_types.get(&t), Some(&2));
+    assert_eq!(
+        summary.vertex_properties.get(&t),
+        Some(&vec![property_name].into_iter().collect())
+    );
+
+    let trans = datastore.transaction().unwrap();
+    trans
+        .delete_vertices(SpecificVertexQuery::new(vec![existing_id, id]).into())
+        .unwrap();
+    assert!(trans.get_schema_summary().unwrap().vertex_types.is_empty());
+    trans.commit().unwrap();
+    assert_eq!(
+        datastore.get_schema_summary().unwrap(),
+        models::SchemaSummary::default()
+    );
+}
@@ -1,3 +1,6 @@
+use std::thread;
+use std::time::Duration;
+
 use crate::{models, Datastore};
 
 use chrono::offset::Utc;
@@ -62,3 +65,16 @@ pub fn create_time_range_queryable_edges<D: Datastore>(
 
     (outbound_v.id, start_time, end_time, inbound_ids)
 }
+
+// Waits for a property's index to be built in the background, panicking if
+// the build fails or doesn't finish in time.
+pub fn wait_for_index<D: Datastore>(datastore: &D, name: &models::Identifier) {
+    for _ in 0..3_000 {
+        match datastore.get_index_status(name.clone()).unwrap() {
+            Some(models::IndexStatus::Ready) => return,
+            Some(models::IndexStatus::Building { .. }) => thread::sleep(Duration::from_millis(10)),
+            status => panic!("unexpected status: {:?}", status),
+        }
+    }
+    panic!("index was not built in time");
+}
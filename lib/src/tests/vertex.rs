@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use super::util::{create_edge_from, create_edges};
-use crate::{models, Datastore, EdgeQueryExt, RangeVertexQuery, SpecificVertexQuery, VertexQueryExt};
+use crate::{
+    models, Datastore, EdgeQueryExt, PropertyPresenceVertexQuery, PropertyValueVertexQuery, RangeVertexQuery,
+    SpecificVertexQuery, VertexQueryExt,
+};
 
 use uuid::Uuid;
 
@@ -57,6 +60,39 @@ pub fn should_get_no_vertices_with_type_filter<D: Datastore>(datastore: &D) {
     assert_eq!(range.len(), 0);
 }
 
+pub fn should_get_range_vertices_with_type_filter<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("test_filtered_vertex_type").unwrap();
+    create_vertices(datastore);
+
+    let mut ids: Vec<Uuid> = (0..3)
+        .map(|_| datastore.create_vertex_from_type(t.clone()).unwrap())
+        .collect();
+    ids.sort();
+
+    let range = datastore
+        .get_vertices(RangeVertexQuery::new().t(t.clone()).into())
+        .unwrap();
+    assert_eq!(range.iter().map(|v| v.id).collect::<Vec<Uuid>>(), ids);
+    assert!(range.iter().all(|v| v.t == t));
+
+    let range = datastore
+        .get_vertices(RangeVertexQuery::new().t(t.clone()).limit(2).into())
+        .unwrap();
+    assert_eq!(range.iter().map(|v| v.id).collect::<Vec<Uuid>>(), &ids[..2]);
+
+    datastore
+        .delete_vertices(SpecificVertexQuery::single(ids[0]).into())
+        .unwrap();
+    let range = datastore
+        .get_vertices(RangeVertexQuery::new().t(t.clone()).into())
+        .unwrap();
+    assert_eq!(range.iter().map(|v| v.id).collect::<Vec<Uuid>>(), &ids[1..]);
+    assert_eq!(
+        datastore.count_vertices(RangeVertexQuery::new().t(t).into()).unwrap(),
+        2
+    );
+}
+
 pub fn should_get_single_vertex<D: Datastore>(datastore: &D) {
     let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
     let vertex = models::Vertex::new(vertex_t);
@@ -152,6 +188,88 @@ pub fn should_get_vertices_piped<D: Datastore>(datastore: &D) {
     assert_eq!(range[0], v);
 }
 
+pub fn should_traverse_vertices<D: Datastore>(datastore: &D) {
+    // Builds a -> b -> c -> d -> a, plus a -> x with a different edge type
+    let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
+    let follows_t = models::Identifier::new("follows").unwrap();
+    let blocks_t = models::Identifier::new("blocks").unwrap();
+    let ids: Vec<Uuid> = (0..5)
+        .map(|_| datastore.create_vertex_from_type(vertex_t.clone()).unwrap())
+        .collect();
+    let (a, b, c, d, x) = (ids[0], ids[1], ids[2], ids[3], ids[4]);
+    for (outbound_id, inbound_id) in [(a, b), (b, c), (c, d), (d, a)] {
+        let key = models::EdgeKey::new(outbound_id, follows_t.clone(), inbound_id);
+        assert!(datastore.create_edge(&key).unwrap());
+    }
+    assert!(datastore.create_edge(&models::EdgeKey::new(a, blocks_t, x)).unwrap());
+
+    let traverse = |q: models::TraversalVertexQuery| -> HashSet<Uuid> {
+        let vertices = datastore.get_vertices(q.into()).unwrap();
+        let ids: HashSet<Uuid> = vertices.iter().map(|v| v.id).collect();
+        assert_eq!(ids.len(), vertices.len());
+        ids
+    };
+
+    let start = SpecificVertexQuery::single(a);
+    let outbound = start.clone().traverse(models::EdgeDirection::Outbound);
+
+    // A single hop by default
+    assert_eq!(traverse(outbound.clone()), [b, x].into_iter().collect());
+
+    // Vertices are only visited once, so the cycle back to `a` is ignored
+    assert_eq!(
+        traverse(outbound.clone().max_depth(10)),
+        [b, c, d, x].into_iter().collect()
+    );
+    assert_eq!(
+        traverse(outbound.clone().max_depth(10).min_depth(0)),
+        [a, b, c, d, x].into_iter().collect()
+    );
+
+    // Edge type filters and depth bounds
+    assert_eq!(
+        traverse(outbound.clone().t(follows_t.clone()).max_depth(3)),
+        [b, c, d].into_iter().collect()
+    );
+    assert_eq!(
+        traverse(outbound.clone().min_depth(2).max_depth(3)),
+        [c, d].into_iter().collect()
+    );
+    assert_eq!(
+        traverse(outbound.clone().min_depth(0).max_depth(0)),
+        [a].into_iter().collect()
+    );
+    assert_eq!(traverse(outbound.clone().min_depth(2).max_depth(1)), HashSet::new());
+
+    // Inbound traversals walk edges backwards
+    assert_eq!(
+        traverse(
+            SpecificVertexQuery::single(d)
+                .traverse(models::EdgeDirection::Inbound)
+                .max_depth(2)
+        ),
+        [c, b].into_iter().collect()
+    );
+
+    // Limits apply to the total results, in order of depth
+    let vertices = datastore
+        .get_vertices(outbound.t(follows_t).max_depth(3).limit(2).into())
+        .unwrap();
+    assert_eq!(vertices.iter().map(|v| v.id).collect::<Vec<Uuid>>(), vec![b, c]);
+
+    // Traversals can be composed with other queries
+    let vertices = datastore
+        .get_vertices(
+            start
+                .traverse(models::EdgeDirection::Outbound)
+                .outbound()
+                .inbound()
+                .into(),
+        )
+        .unwrap();
+    assert_eq!(vertices.iter().map(|v| v.id).collect::<Vec<Uuid>>(), vec![c]);
+}
+
 pub fn should_delete_a_valid_outbound_vertex<D: Datastore>(datastore: &D) {
     let (outbound_id, _) = create_edges(datastore);
     let q = SpecificVertexQuery::single(outbound_id);
@@ -191,6 +309,35 @@ pub fn should_not_delete_an_invalid_vertex<D: Datastore>(datastore: &D) {
         .unwrap();
 }
 
+pub fn should_combine_vertex_queries<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("test_vertex_type").unwrap();
+    let ids: Vec<Uuid> = (0..4)
+        .map(|_| datastore.create_vertex_from_type(t.clone()).unwrap())
+        .collect();
+    let get_ids = |q: models::VertexQuery| -> Vec<Uuid> {
+        datastore.get_vertices(q).unwrap().into_iter().map(|v| v.id).collect()
+    };
+
+    let first = SpecificVertexQuery::new(ids[..3].to_vec());
+    let second = SpecificVertexQuery::new(vec![ids[1], ids[2], ids[3], Uuid::default()]);
+    assert_eq!(get_ids(first.clone().union(second.clone()).into()), ids);
+    assert_eq!(get_ids(first.clone().intersect(second.clone()).into()), ids[1..3]);
+    assert_eq!(get_ids(first.clone().except(second.clone()).into()), ids[..1]);
+
+    // Combined queries can themselves be combined
+    let q = first.except(second).union(SpecificVertexQuery::single(ids[3]));
+    assert_eq!(get_ids(q.into()), vec![ids[0], ids[3]]);
+
+    // And piped from
+    let (outbound_id, inbound_ids) = create_edges(datastore);
+    let q = SpecificVertexQuery::single(outbound_id)
+        .outbound()
+        .inbound()
+        .except(SpecificVertexQuery::new(inbound_ids[..2].to_vec()));
+    let result: HashSet<Uuid> = get_ids(q.into()).into_iter().collect();
+    assert_eq!(result, inbound_ids[2..].iter().cloned().collect());
+}
+
 pub fn should_get_a_vertex_count<D: Datastore>(datastore: &D) {
     let vertex_t = models::Identifier::new("test_vertex_type").unwrap();
     let v = models::Vertex::new(vertex_t);
@@ -199,6 +346,53 @@ pub fn should_get_a_vertex_count<D: Datastore>(datastore: &D) {
     assert!(count >= 1);
 }
 
+pub fn should_count_vertices<D: Datastore>(datastore: &D) {
+    let t = models::Identifier::new("count_vertex_type").unwrap();
+    let other_t = models::Identifier::new("other_count_vertex_type").unwrap();
+    let ids: Vec<Uuid> = (0..3)
+        .map(|_| datastore.create_vertex_from_type(t.clone()).unwrap())
+        .collect();
+    datastore.create_vertex_from_type(other_t.clone()).unwrap();
+    datastore.create_vertex_from_type(other_t).unwrap();
+
+    let count = |q: models::VertexQuery| -> u64 {
+        let count = datastore.count_vertices(q.clone()).unwrap();
+        assert_eq!(count, datastore.get_vertices(q).unwrap().len() as u64);
+        count
+    };
+
+    assert_eq!(count(RangeVertexQuery::new().into()), 5);
+    assert_eq!(count(RangeVertexQuery::new().limit(2).into()), 2);
+    assert_eq!(count(RangeVertexQuery::new().t(t.clone()).into()), 3);
+    assert_eq!(
+        count(RangeVertexQuery::new().start_id(Uuid::from_u128(u128::MAX)).into()),
+        0
+    );
+    assert_eq!(count(SpecificVertexQuery::new(vec![ids[0], Uuid::default()]).into()), 1);
+
+    // Counts should use the property index when there is one
+    let name = models::Identifier::new("count_property").unwrap();
+    datastore.index_property(name.clone()).unwrap();
+    datastore
+        .set_vertex_properties(
+            SpecificVertexQuery::new(ids[..2].to_vec()).property(name.clone()),
+            serde_json::Value::Bool(true),
+        )
+        .unwrap();
+    datastore
+        .set_vertex_properties(
+            SpecificVertexQuery::single(ids[0]).property(name.clone()),
+            serde_json::Value::Bool(false),
+        )
+        .unwrap();
+    assert_eq!(count(PropertyPresenceVertexQuery::new(name.clone()).into()), 2);
+    assert_eq!(
+        count(PropertyValueVertexQuery::new(name.clone(), serde_json::Value::Bool(true)).into()),
+        1
+    );
+    assert_eq!(count(RangeVertexQuery::new().t(t).without_property(name).into()), 1);
+}
+
 fn create_vertices<D: Datastore>(datastore: &D) -> Vec<Uuid> {
     let t = models::Identifier::new("test_vertex_type").unwrap();
 
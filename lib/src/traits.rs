@@ -1,9 +1,200 @@
 use crate::errors::{Error, Result};
 use crate::models;
 use crate::models::{EdgeQueryExt, VertexQueryExt};
+use std::cmp::min;
+use std::convert::TryInto;
+use std::path::Path;
 use std::vec::Vec;
 use uuid::Uuid;
 
+/// The number of results loaded at a time by the iterator-returning read
+/// methods.
+pub(crate) const ITER_BATCH_SIZE: u32 = 1_000;
+
+/// An iterator over the results of a query. Results are produced lazily, so
+/// an error may be returned partway through iteration.
+pub type DynIter<'a, T> = Box<dyn Iterator<Item = Result<T>> + 'a>;
+
+// Produces items that are loaded in batches. `load` is called whenever the
+// previous batch is exhausted, and returns `None` once there are no more
+// batches. Iteration stops after the first error.
+pub(crate) struct BatchIter<T, F> {
+    load: F,
+    batch: std::vec::IntoIter<T>,
+    done: bool,
+}
+
+impl<T, F: FnMut() -> Result<Option<Vec<T>>>> BatchIter<T, F> {
+    pub(crate) fn new(load: F) -> Self {
+        Self {
+            load,
+            batch: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+}
+
+impl<T, F: FnMut() -> Result<Option<Vec<T>>>> Iterator for BatchIter<T, F> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            if let Some(item) = self.batch.next() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            match (self.load)() {
+                Ok(Some(batch)) => self.batch = batch.into_iter(),
+                Ok(None) => self.done = true,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+// Groups the results of `iter` into batches, and maps each batch with
+// `load`.
+pub(crate) fn load_in_batches<'a, K: 'a, T: 'a, F>(mut iter: DynIter<'a, K>, mut load: F) -> DynIter<'a, T>
+where
+    F: FnMut(Vec<K>) -> Result<Vec<T>> + 'a,
+{
+    Box::new(BatchIter::new(move || {
+        let mut keys = Vec::new();
+        for key in iter.by_ref().take(ITER_BATCH_SIZE as usize) {
+            keys.push(key?);
+        }
+        if keys.is_empty() {
+            Ok(None)
+        } else {
+            load(keys).map(Some)
+        }
+    }))
+}
+
+// Lazily loads the results of a vertex range query in batches. If `last_id`
+// is set, only vertices after it are returned, and `q.start_id` is ignored.
+fn iter_range_vertices<'a, D: Datastore + ?Sized>(
+    datastore: &'a D,
+    q: models::RangeVertexQuery,
+    mut last_id: Option<Uuid>,
+) -> DynIter<'a, models::Vertex> {
+    let mut remaining = q.limit;
+
+    Box::new(BatchIter::new(move || {
+        if remaining == 0 {
+            return Ok(None);
+        }
+
+        let limit = min(remaining, ITER_BATCH_SIZE);
+        let vertices = match last_id {
+            None => datastore.get_vertices(
+                models::RangeVertexQuery {
+                    limit,
+                    t: q.t.clone(),
+                    start_id: q.start_id,
+                }
+                .into(),
+            )?,
+            Some(last_id) => {
+                // Datastores differ on whether `start_id` is inclusive, so
+                // ask for one extra vertex and skip any that were already
+                // returned.
+                let mut vertices = datastore.get_vertices(
+                    models::RangeVertexQuery {
+                        limit: limit.saturating_add(1),
+                        t: q.t.clone(),
+                        start_id: Some(last_id),
+                    }
+                    .into(),
+                )?;
+                vertices.retain(|v| v.id > last_id);
+                vertices.truncate(limit as usize);
+                vertices
+            }
+        };
+
+        if vertices.len() < limit as usize {
+            remaining = 0;
+        } else {
+            remaining -= limit;
+        }
+
+        match vertices.last() {
+            Some(last) => {
+                last_id = Some(last.id);
+                Ok(Some(vertices))
+            }
+            None => Ok(None),
+        }
+    }))
+}
+
+// Gets up to `limit` results from `iter` as a page, which started at
+// `offset` into the full results. The token for the next page holds the
+// offset and what `last_id` gets from the last result, and can only be used
+// with the query that `fingerprint` was computed from.
+fn collect_page<T, F>(
+    mut iter: DynIter<'_, T>,
+    fingerprint: u64,
+    limit: u32,
+    offset: u64,
+    last_id: F,
+) -> Result<models::Page<T>>
+where
+    F: Fn(&T) -> Option<Uuid>,
+{
+    let mut results = Vec::new();
+    for item in iter.by_ref().take(limit as usize) {
+        results.push(item?);
+    }
+
+    let token = match iter.next() {
+        Some(Err(err)) => return Err(err),
+        Some(Ok(_)) => {
+            let offset = offset + results.len() as u64;
+            let state = (offset, results.last().and_then(last_id));
+            Some(models::ContinuationToken::new(fingerprint, &state)?)
+        }
+        None => None,
+    };
+
+    Ok(models::Page::new(results, token))
+}
+
+// Decodes the offset and last ID from a token created by `collect_page`,
+// after checking the limit of the page being requested.
+fn decode_page_token(
+    fingerprint: u64,
+    limit: u32,
+    token: Option<models::ContinuationToken>,
+) -> Result<(u64, Option<Uuid>)> {
+    if limit == 0 {
+        return Err(Error::InvalidPageLimit);
+    }
+    match token {
+        Some(token) => token.decode(fingerprint),
+        None => Ok((0, None)),
+    }
+}
+
+// Skips the first `offset` results of `iter`.
+fn skip_results<T>(iter: &mut DynIter<'_, T>, offset: u64) -> Result<()> {
+    for _ in 0..offset {
+        match iter.next() {
+            Some(item) => {
+                item?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
 /// Specifies a datastore implementation.
 ///
 /// Note that this trait and its members purposefully do not employ any
@@ -23,6 +214,11 @@ pub trait Datastore {
     /// Creates a new transaction. Some datastore implementations do not
     /// support transactional updates, in which case this will return an
     /// error.
+    ///
+    /// The transaction is itself a datastore. Changes made through it are
+    /// visible to its own reads, but not to anyone else until `commit` is
+    /// called. Concurrent changes to the same data are not detected - the
+    /// last commit wins.
     fn transaction(&self) -> Result<Self>
     where
         Self: Sized,
@@ -30,9 +226,55 @@ pub trait Datastore {
         Err(Error::Unsupported)
     }
 
+    /// Atomically applies all of the changes made in a transaction. The
+    /// transaction can continue to be used afterwards. Returns an error if
+    /// this is not a transaction, or `Error::ConstraintViolation` without
+    /// applying anything if the changes would violate a unique constraint.
+    /// Constraints are checked again on commit, since other changes may
+    /// have been made since the transaction's were. Likewise, if a schema is
+    /// set, `Error::SchemaViolation` is returned if a vertex or edge created
+    /// in the transaction is missing a required property.
+    fn commit(&self) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    /// Discards all of the changes made in a transaction since it was
+    /// created or last committed. Returns an error if this is not a
+    /// transaction.
+    fn rollback(&self) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    /// Creates a read-only view of the datastore as it is now. Reads through
+    /// the snapshot are consistent with each other, regardless of changes
+    /// made to the datastore afterwards; writes return
+    /// `Error::Unsupported`. Some datastore implementations do not support
+    /// snapshots, in which case this will return an error.
+    ///
+    /// A snapshot of a transaction includes the changes it has staged.
+    fn snapshot(&self) -> Result<Box<dyn Datastore + Send + Sync>> {
+        Err(Error::Unsupported)
+    }
+
+    /// Backs up the datastore to a path while it remains online. The backup
+    /// has the datastore's committed contents as of some point during the
+    /// call; changes staged by a transaction are not included. Depending on
+    /// the datastore implementation, the path may be a file or a directory,
+    /// and backing up to the same path again may replace or add to the
+    /// existing backup. Some datastore implementations do not support
+    /// backups, in which case this will return an error.
+    ///
+    /// # Arguments
+    /// * `path`: The path to back up to.
+    fn backup(&self, _path: &Path) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
     /// Creates a new vertex. Returns whether the vertex was successfully
     /// created - if this is false, it's because a vertex with the same UUID
-    /// already exists.
+    /// already exists. If a schema is set, the vertex's type must be in it.
+    /// Outside of a transaction, the type must not have required
+    /// properties; in a transaction, they must be set before it's committed.
     ///
     /// # Arguments
     /// * `vertex`: The vertex to create.
@@ -60,6 +302,68 @@ pub trait Datastore {
     /// * `q`: The query to run.
     fn get_vertices(&self, q: models::VertexQuery) -> Result<Vec<models::Vertex>>;
 
+    /// Gets a range of vertices specified by a query, as an iterator. By
+    /// default, range queries are loaded lazily in batches, and other queries
+    /// are loaded all at once; datastores may override this to produce
+    /// results as they're read.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_vertices_iter(&self, q: models::VertexQuery) -> Result<DynIter<'_, models::Vertex>> {
+        let q = match q {
+            models::VertexQuery::Range(q) => q,
+            q => return Ok(Box::new(self.get_vertices(q)?.into_iter().map(Ok))),
+        };
+
+        Ok(iter_range_vertices(self, q, None))
+    }
+
+    /// Gets a page of vertices specified by a query. Returns the vertices,
+    /// along with a token for getting the next page if there are more. The
+    /// query's own limit, if any, still applies across all pages.
+    ///
+    /// By default, vertex range queries resume from the last vertex
+    /// returned. Other queries are re-run and skip the results that were
+    /// already returned, so pages may be inconsistent if the datastore is
+    /// changed between calls. Datastores may override this to resume all
+    /// queries from where they left off.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of vertices to return.
+    /// * `token`: The token returned with the previous page, if any.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidPageLimit` if `limit` is zero, and
+    /// `Error::InvalidContinuationToken` if the token is malformed or was
+    /// returned for a different query.
+    fn get_vertices_page(
+        &self,
+        q: models::VertexQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::Vertex>> {
+        let fingerprint = models::fingerprint(&q);
+        let (offset, last_id) = decode_page_token(fingerprint, limit, token)?;
+
+        let iter = match q {
+            models::VertexQuery::Range(q) => {
+                let q = models::RangeVertexQuery {
+                    limit: q.limit.saturating_sub(offset.try_into().unwrap_or(u32::MAX)),
+                    ..q
+                };
+                iter_range_vertices(self, q, last_id)
+            }
+            q => {
+                let mut iter = self.get_vertices_iter(q)?;
+                skip_results(&mut iter, offset)?;
+                iter
+            }
+        };
+
+        collect_page(iter, fingerprint, limit, offset, |v| Some(v.id))
+    }
+
     /// Deletes existing vertices specified by a query.
     ///
     /// # Arguments
@@ -69,10 +373,28 @@ pub trait Datastore {
     /// Gets the number of vertices in the datastore.
     fn get_vertex_count(&self) -> Result<u64>;
 
+    /// Gets the number of vertices specified by a query. By default, this
+    /// counts the results of `get_vertices_iter`; datastores may override
+    /// this to count without loading the vertices.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn count_vertices(&self, q: models::VertexQuery) -> Result<u64> {
+        let mut count = 0;
+        for item in self.get_vertices_iter(q)? {
+            item?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
     /// Creates a new edge. If the edge already exists, this will update it
     /// with a new update datetime. Returns whether the edge was successfully
     /// created - if this is false, it's because one of the specified vertices
-    /// is missing.
+    /// is missing. If a schema is set, the edge's type must be in it and
+    /// allow the types of its vertices. Outside of a transaction, the type
+    /// must not have required properties; in a transaction, they must be
+    /// set before it's committed.
     ///
     /// # Arguments
     /// * `key`: The edge to create.
@@ -84,6 +406,58 @@ pub trait Datastore {
     /// * `q`: The query to run.
     fn get_edges(&self, q: models::EdgeQuery) -> Result<Vec<models::Edge>>;
 
+    /// Gets a range of edges specified by a query, as an iterator. By
+    /// default, the edges are loaded all at once; datastores may override
+    /// this to produce results as they're read.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_edges_iter(&self, q: models::EdgeQuery) -> Result<DynIter<'_, models::Edge>> {
+        Ok(Box::new(self.get_edges(q)?.into_iter().map(Ok)))
+    }
+
+    /// Gets a page of edges specified by a query. Returns the edges, along
+    /// with a token for getting the next page if there are more. The query's
+    /// own limit, if any, still applies across all pages.
+    ///
+    /// By default, the query is re-run and skips the results that were
+    /// already returned, so pages may be inconsistent if the datastore is
+    /// changed between calls. Datastores may override this to resume from
+    /// where the previous page left off.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of edges to return.
+    /// * `token`: The token returned with the previous page, if any.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidPageLimit` if `limit` is zero, and
+    /// `Error::InvalidContinuationToken` if the token is malformed or was
+    /// returned for a different query.
+    fn get_edges_page(
+        &self,
+        q: models::EdgeQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::Edge>> {
+        let fingerprint = models::fingerprint(&q);
+        let (offset, _) = decode_page_token(fingerprint, limit, token)?;
+        let mut iter = self.get_edges_iter(q)?;
+        skip_results(&mut iter, offset)?;
+        collect_page(iter, fingerprint, limit, offset, |_| None)
+    }
+
+    /// Gets the shortest paths between two vertices, as ordered lists of
+    /// edges. Returns an empty list if there is no path. Some datastore
+    /// implementations do not support this, in which case this will return
+    /// an error.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_paths(&self, _q: models::PathQuery) -> Result<Vec<Vec<models::Edge>>> {
+        Err(Error::Unsupported)
+    }
+
     /// Deletes a set of edges specified by a query.
     ///
     /// # Arguments
@@ -99,26 +473,106 @@ pub trait Datastore {
     fn get_edge_count(&self, id: Uuid, t: Option<&models::Identifier>, direction: models::EdgeDirection)
         -> Result<u64>;
 
+    /// Gets the number of edges specified by a query. By default, this
+    /// counts the results of `get_edges_iter`; datastores may override this
+    /// to count without loading the edges.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn count_edges(&self, q: models::EdgeQuery) -> Result<u64> {
+        let mut count = 0;
+        for item in self.get_edges_iter(q)? {
+            item?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
     /// Gets vertex properties.
     ///
     /// # Arguments
     /// * `q`: The query to run.
     fn get_vertex_properties(&self, q: models::VertexPropertyQuery) -> Result<Vec<models::VertexProperty>>;
 
+    /// Gets vertex properties, as an iterator. By default, the vertices are
+    /// found with `get_vertices_iter`, and their property values are loaded
+    /// lazily in batches.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_vertex_properties_iter(
+        &self,
+        q: models::VertexPropertyQuery,
+    ) -> Result<DynIter<'_, models::VertexProperty>> {
+        let name = q.name;
+        let vertices = self.get_vertices_iter(q.inner)?;
+        Ok(load_in_batches(vertices, move |vertices| {
+            let ids = vertices.into_iter().map(|v| v.id).collect();
+            self.get_vertex_properties(models::SpecificVertexQuery::new(ids).property(name.clone()))
+        }))
+    }
+
+    /// Gets a page of vertex properties. Returns the properties, along with
+    /// a token for getting the next page if there are more. Vertices without
+    /// the property don't count towards the page's limit.
+    ///
+    /// By default, the query is re-run and skips the results that were
+    /// already returned, like `get_edges_page`.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of properties to return.
+    /// * `token`: The token returned with the previous page, if any.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidPageLimit` if `limit` is zero, and
+    /// `Error::InvalidContinuationToken` if the token is malformed or was
+    /// returned for a different query.
+    fn get_vertex_properties_page(
+        &self,
+        q: models::VertexPropertyQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::VertexProperty>> {
+        let fingerprint = models::fingerprint(&q);
+        let (offset, _) = decode_page_token(fingerprint, limit, token)?;
+        let mut iter = self.get_vertex_properties_iter(q)?;
+        skip_results(&mut iter, offset)?;
+        collect_page(iter, fingerprint, limit, offset, |_| None)
+    }
+
     /// Gets all vertex properties.
     ///
     /// # Arguments
     /// * `q`: The query to run.
     fn get_all_vertex_properties(&self, q: models::VertexQuery) -> Result<Vec<models::VertexProperties>>;
 
-    /// Sets a vertex properties.
+    /// Gets all vertex properties, as an iterator. By default, the vertices
+    /// are found with `get_vertices_iter`, and their properties are loaded
+    /// lazily in batches.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_all_vertex_properties_iter(&self, q: models::VertexQuery) -> Result<DynIter<'_, models::VertexProperties>> {
+        let vertices = self.get_vertices_iter(q)?;
+        Ok(load_in_batches(vertices, move |vertices| {
+            let ids = vertices.into_iter().map(|v| v.id).collect();
+            self.get_all_vertex_properties(models::SpecificVertexQuery::new(ids).into())
+        }))
+    }
+
+    /// Sets a vertex properties. If this would give two vertices covered by
+    /// a unique constraint the same value, nothing is set, and
+    /// `Error::ConstraintViolation` is returned. If a schema is set, the
+    /// value must be of the property's declared type.
     ///
     /// # Arguments
     /// * `q`: The query to run.
     /// * `value`: The property value.
     fn set_vertex_properties(&self, q: models::VertexPropertyQuery, value: serde_json::Value) -> Result<()>;
 
-    /// Deletes vertex properties.
+    /// Deletes vertex properties. If a schema is set, required properties
+    /// can't be deleted.
     ///
     /// # Arguments
     /// * `q`: The query to run.
@@ -130,26 +584,86 @@ pub trait Datastore {
     /// * `q`: The query to run.
     fn get_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<Vec<models::EdgeProperty>>;
 
+    /// Gets edge properties, as an iterator. By default, the edges are found
+    /// with `get_edges_iter`, and their property values are loaded lazily in
+    /// batches.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_edge_properties_iter(&self, q: models::EdgePropertyQuery) -> Result<DynIter<'_, models::EdgeProperty>> {
+        let name = q.name;
+        let edges = self.get_edges_iter(q.inner)?;
+        Ok(load_in_batches(edges, move |edges| {
+            let keys = edges.into_iter().map(|e| e.key).collect();
+            self.get_edge_properties(models::SpecificEdgeQuery::new(keys).property(name.clone()))
+        }))
+    }
+
+    /// Gets a page of edge properties, like `get_vertex_properties_page`.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of properties to return.
+    /// * `token`: The token returned with the previous page, if any.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidPageLimit` if `limit` is zero, and
+    /// `Error::InvalidContinuationToken` if the token is malformed or was
+    /// returned for a different query.
+    fn get_edge_properties_page(
+        &self,
+        q: models::EdgePropertyQuery,
+        limit: u32,
+        token: Option<models::ContinuationToken>,
+    ) -> Result<models::Page<models::EdgeProperty>> {
+        let fingerprint = models::fingerprint(&q);
+        let (offset, _) = decode_page_token(fingerprint, limit, token)?;
+        let mut iter = self.get_edge_properties_iter(q)?;
+        skip_results(&mut iter, offset)?;
+        collect_page(iter, fingerprint, limit, offset, |_| None)
+    }
+
     /// Gets all edge properties.
     ///
     /// # Arguments
     /// * `q`: The query to run.
     fn get_all_edge_properties(&self, q: models::EdgeQuery) -> Result<Vec<models::EdgeProperties>>;
 
-    /// Sets edge properties.
+    /// Gets all edge properties, as an iterator. By default, the edges are
+    /// found with `get_edges_iter`, and their properties are loaded lazily in
+    /// batches.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    fn get_all_edge_properties_iter(&self, q: models::EdgeQuery) -> Result<DynIter<'_, models::EdgeProperties>> {
+        let edges = self.get_edges_iter(q)?;
+        Ok(load_in_batches(edges, move |edges| {
+            let keys = edges.into_iter().map(|e| e.key).collect();
+            self.get_all_edge_properties(models::SpecificEdgeQuery::new(keys).into())
+        }))
+    }
+
+    /// Sets edge properties. Like with `set_vertex_properties`, this returns
+    /// `Error::ConstraintViolation` if it would violate a unique constraint,
+    /// and the value must be of the property's declared type.
     ///
     /// # Arguments
     /// * `q`: The query to run.
     /// * `value`: The property value.
     fn set_edge_properties(&self, q: models::EdgePropertyQuery, value: serde_json::Value) -> Result<()>;
 
-    /// Deletes edge properties.
+    /// Deletes edge properties. If a schema is set, required properties
+    /// can't be deleted.
     ///
     /// # Arguments
     /// * `q`: The query to run.
     fn delete_edge_properties(&self, q: models::EdgePropertyQuery) -> Result<()>;
 
-    /// Bulk inserts many vertices, edges, and/or properties.
+    /// Bulk inserts many vertices, edges, and/or properties. Properties are
+    /// checked against unique constraints, in which case this returns
+    /// `Error::ConstraintViolation`. If a schema is set, the items are
+    /// checked against it, and the vertices and edges they create must have
+    /// all of their required properties among them.
     ///
     /// # Arguments
     /// * `items`: The items to insert.
@@ -177,9 +691,169 @@ pub trait Datastore {
     }
 
     // Enables indexing on a specified property. When indexing is enabled on a
-    // property, it's possible to query on its presence and values.
+    // property, it's possible to query on its presence and values. The index
+    // is built in batches, so other operations can run while it's being
+    // built, but this only returns once it's ready.
     //
     // # Arguments
     // * `name`: The name of the property to index.
     fn index_property(&self, name: models::Identifier) -> Result<()>;
+
+    // Like `index_property`, but builds the index in the background, and
+    // returns as soon as the build has started. Until the index is ready,
+    // queries on the property's presence and values return
+    // `Error::IndexNotReady`. Use `get_index_status` to follow the build.
+    // A build that's interrupted, e.g. because the datastore is closed, is
+    // resumed when the datastore is next opened.
+    //
+    // # Arguments
+    // * `name`: The name of the property to index.
+    fn index_property_in_background(&self, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Disables indexing on a specified property, deleting its index. Queries
+    // on its presence and values will return `Error::NotIndexed` again, and
+    // the property's unique constraints are dropped too. Dropping the index
+    // of a property that isn't indexed does nothing.
+    //
+    // # Arguments
+    // * `name`: The name of the property to stop indexing.
+    fn drop_index(&self, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Gets the names of the indexed properties, in sorted order. This
+    // includes indexes that aren't ready yet.
+    fn get_indexed_properties(&self) -> Result<Vec<models::Identifier>> {
+        Err(Error::Unsupported)
+    }
+
+    // Gets the status of a property's index, or `None` if the property isn't
+    // indexed.
+    //
+    // # Arguments
+    // * `name`: The name of the property.
+    fn get_index_status(&self, _name: models::Identifier) -> Result<Option<models::IndexStatus>> {
+        Err(Error::Unsupported)
+    }
+
+    // Enables indexing on a property of the vertices and edges of one type.
+    // Unlike `index_property`, this doesn't make property queries on their
+    // own possible; instead, property value filters on queries restricted to
+    // the type (e.g. a `PropertyValueVertexQuery` intersected with a type
+    // filtered `RangeVertexQuery`, or a `PipePropertyValueVertexQuery` piped
+    // from one) use the index rather than checking every result. The index
+    // is built before this returns.
+    //
+    // # Arguments
+    // * `t`: The type of the vertices and edges to index.
+    // * `name`: The name of the property to index.
+    fn index_type_property(&self, _t: models::Identifier, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Disables indexing on a property of the vertices and edges of one type,
+    // deleting its index. Dropping an index that doesn't exist does nothing.
+    //
+    // # Arguments
+    // * `t`: The type of the indexed vertices and edges.
+    // * `name`: The name of the property to stop indexing.
+    fn drop_type_index(&self, _t: models::Identifier, _name: models::Identifier) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Gets the type and property name of each index scoped to a type, in
+    // sorted order.
+    fn get_indexed_type_properties(&self) -> Result<Vec<(models::Identifier, models::Identifier)>> {
+        Err(Error::Unsupported)
+    }
+
+    // Enables indexing on a combination of properties, in order. Vertices
+    // and edges are indexed by the values of all of the properties, if they
+    // have them. Chains of piped property value queries that check for
+    // equality (e.g. a `PropertyValueVertexQuery` with
+    // `with_property_equal_to` piped onto it) use the index if they cover at
+    // least its first two properties, rather than filtering the results of
+    // one property's index. Unlike `index_property`, this doesn't make
+    // property queries on their own possible. The index is built before this
+    // returns.
+    //
+    // # Arguments
+    // * `names`: The names of the properties to index, in order. There must
+    //   be at least two, without duplicates; otherwise, this returns
+    //   `Error::Unsupported`.
+    fn index_composite_properties(&self, _names: Vec<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Disables a composite index, deleting it. Dropping an index that doesn't
+    // exist does nothing.
+    //
+    // # Arguments
+    // * `names`: The names of the index's properties, in order.
+    fn drop_composite_index(&self, _names: Vec<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Gets the property names of each composite index, in sorted order.
+    fn get_indexed_composite_properties(&self) -> Result<Vec<Vec<models::Identifier>>> {
+        Err(Error::Unsupported)
+    }
+
+    // Adds a unique constraint on a property, so that no two vertices, and
+    // no two edges, covered by it can have the same value. A vertex and an
+    // edge can still share a value. The property must be indexed, and its
+    // index ready; otherwise, this returns `Error::NotIndexed` or
+    // `Error::IndexNotReady`. If existing values already violate the
+    // constraint, it isn't added, and `Error::ConstraintViolation` is
+    // returned.
+    //
+    // # Arguments
+    // * `name`: The name of the property.
+    // * `t`: If set, the constraint only covers the vertices and edges of
+    //   this type.
+    fn add_unique_constraint(&self, _name: models::Identifier, _t: Option<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Drops a unique constraint. Dropping a constraint that doesn't exist
+    // does nothing.
+    //
+    // # Arguments
+    // * `name`: The name of the property.
+    // * `t`: The type the constraint is scoped to, if any.
+    fn drop_unique_constraint(&self, _name: models::Identifier, _t: Option<models::Identifier>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Gets the property name and scope of each unique constraint, in sorted
+    // order.
+    fn get_unique_constraints(&self) -> Result<Vec<(models::Identifier, Option<models::Identifier>)>> {
+        Err(Error::Unsupported)
+    }
+
+    // Sets the schema that writes are checked against, or removes it if
+    // `schema` is `None`. Writes that don't conform to it return
+    // `Error::SchemaViolation`; existing vertices, edges and properties
+    // aren't checked. The schema can't be set from within a transaction.
+    //
+    // # Arguments
+    // * `schema`: The schema.
+    fn set_schema(&self, _schema: Option<models::Schema>) -> Result<()> {
+        Err(Error::Unsupported)
+    }
+
+    // Gets the schema, if one is set.
+    fn get_schema(&self) -> Result<Option<models::Schema>> {
+        Err(Error::Unsupported)
+    }
+
+    // Summarizes the data in the datastore: the number of vertices and edges
+    // of each type, and the names of the properties that vertices and edges
+    // of each type have. Types without any vertices or edges are left out.
+    // Within a transaction, this includes the transaction's changes.
+    fn get_schema_summary(&self) -> Result<models::SchemaSummary> {
+        Err(Error::Unsupported)
+    }
 }
@@ -1,10 +1,10 @@
 //! Utility functions. These are public because they may be useful for crates
 //! that implement Datastore.
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-use std::io::{Cursor, Error as IoError, Read, Write};
-use std::{i32, i64, str, u8};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::io::{Cursor, Error as IoError, ErrorKind, Read, Write};
+use std::str;
 
 use crate::errors::{ValidationError, ValidationResult};
 use crate::models;
@@ -18,6 +18,18 @@ use uuid::Uuid;
 
 const NODE_ID: [u8; 6] = [0, 0, 0, 0, 0, 0];
 
+// Type tags for encoded JSON values. These are ordered the same way as
+// `models::Json` orders values of different kinds. Zero is reserved for
+// terminating arrays and objects.
+const JSON_TERMINATOR: u8 = 0;
+const JSON_NULL: u8 = 1;
+const JSON_BOOL: u8 = 2;
+const JSON_NUMBER: u8 = 3;
+const JSON_STRING: u8 = 4;
+const JSON_ARRAY: u8 = 5;
+const JSON_OBJECT: u8 = 6;
+const JSON_OBJECT_ENTRY: u8 = 1;
+
 lazy_static! {
     static ref CONTEXT: Context = Context::new(0);
 
@@ -50,7 +62,7 @@ impl<'a> Component<'a> {
             Component::FixedLengthString(s) => s.len(),
             Component::Identifier(t) => t.0.len() + 1,
             Component::DateTime(_) => 8,
-            Component::Json(_) => 8,
+            Component::Json(json) => json_len(&json.0),
         }
     }
 
@@ -66,12 +78,125 @@ impl<'a> Component<'a> {
                 let time_to_end = nanos_since_epoch(&MAX_DATETIME) - nanos_since_epoch(&datetime);
                 cursor.write_u64::<BigEndian>(time_to_end)
             }
-            Component::Json(json) => {
-                let mut hasher = DefaultHasher::new();
-                json.hash(&mut hasher);
-                let hash = hasher.finish();
-                cursor.write_u64::<BigEndian>(hash)
+            Component::Json(json) => write_json(cursor, &json.0),
+        }
+    }
+}
+
+fn escaped_str_len(s: &str) -> usize {
+    s.len() + s.bytes().filter(|b| *b == 0).count() + 2
+}
+
+fn json_len(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Null => 1,
+        serde_json::Value::Bool(_) => 2,
+        serde_json::Value::Number(_) => 17,
+        serde_json::Value::String(v) => 1 + escaped_str_len(v),
+        serde_json::Value::Array(v) => 2 + v.iter().map(json_len).sum::<usize>(),
+        serde_json::Value::Object(v) => {
+            2 + v
+                .iter()
+                .map(|(k, v)| 1 + escaped_str_len(k) + json_len(v))
+                .sum::<usize>()
+        }
+    }
+}
+
+// Writes a string such that byte-wise ordering is preserved, even when it's
+// followed by other content: null bytes are escaped as `0x00 0xFF`, and the
+// string is terminated with `0x00 0x00`.
+fn write_escaped_str(cursor: &mut Cursor<Vec<u8>>, s: &str) -> Result<(), IoError> {
+    for b in s.bytes() {
+        if b == 0 {
+            cursor.write_all(&[0, 0xFF])?;
+        } else {
+            cursor.write_all(&[b])?;
+        }
+    }
+    cursor.write_all(&[0, 0])
+}
+
+// Maps a float to an unsigned integer with the same ordering.
+pub(crate) fn f64_to_ordered_u64(f: f64) -> u64 {
+    // Normalize negative zero, which compares equal to zero
+    let bits = if f == 0.0 { 0 } else { f.to_bits() };
+    if bits & (1 << 63) == 0 {
+        bits ^ (1 << 63)
+    } else {
+        !bits
+    }
+}
+
+fn ordered_u64_to_f64(u: u64) -> f64 {
+    if u & (1 << 63) == 0 {
+        f64::from_bits(!u)
+    } else {
+        f64::from_bits(u ^ (1 << 63))
+    }
+}
+
+// Splits a number into the closest float, and the exact difference between
+// the number and that float. The difference is only non-zero for integers
+// too large to be represented precisely as floats. Numbers are ordered by the
+// float first, and then by the difference.
+pub(crate) fn split_number(n: &serde_json::Number) -> (f64, i64) {
+    let i = match n.as_i64() {
+        Some(i) => i128::from(i),
+        None => match n.as_u64() {
+            Some(i) => i128::from(i),
+            None => return (n.as_f64().unwrap(), 0),
+        },
+    };
+
+    let f = i as f64;
+    (f, (i - f as i128) as i64)
+}
+
+fn join_number(f: f64, diff: i64) -> serde_json::Number {
+    if f.fract() == 0.0 && f >= i64::MIN as f64 && f <= u64::MAX as f64 {
+        let i = f as i128 + i128::from(diff);
+        if let Ok(i) = i64::try_from(i) {
+            return i.into();
+        } else if let Ok(i) = u64::try_from(i) {
+            return i.into();
+        }
+    }
+
+    serde_json::Number::from_f64(f).unwrap()
+}
+
+// Writes a JSON value such that the byte-wise ordering of encoded values
+// matches the ordering of `models::Json`.
+fn write_json(cursor: &mut Cursor<Vec<u8>>, value: &serde_json::Value) -> Result<(), IoError> {
+    match value {
+        serde_json::Value::Null => cursor.write_all(&[JSON_NULL]),
+        serde_json::Value::Bool(v) => cursor.write_all(&[JSON_BOOL, *v as u8]),
+        serde_json::Value::Number(v) => {
+            let (f, diff) = split_number(v);
+            cursor.write_all(&[JSON_NUMBER])?;
+            cursor.write_u64::<BigEndian>(f64_to_ordered_u64(f))?;
+            cursor.write_u64::<BigEndian>((diff as u64) ^ (1 << 63))
+        }
+        serde_json::Value::String(v) => {
+            cursor.write_all(&[JSON_STRING])?;
+            write_escaped_str(cursor, v)
+        }
+        serde_json::Value::Array(v) => {
+            cursor.write_all(&[JSON_ARRAY])?;
+            for sv in v {
+                write_json(cursor, sv)?;
+            }
+            cursor.write_all(&[JSON_TERMINATOR])
+        }
+        serde_json::Value::Object(v) => {
+            cursor.write_all(&[JSON_OBJECT])?;
+            for (sk, sv) in v {
+                cursor.write_all(&[JSON_OBJECT_ENTRY])?;
+                write_escaped_str(cursor, sk)?;
+                write_json(cursor, sv)?;
             }
+            cursor.write_all(&[JSON_TERMINATOR])
         }
     }
 }
@@ -157,6 +282,79 @@ pub fn read_u64<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> u64 {
     cursor.read_u64::<BigEndian>().unwrap()
 }
 
+fn read_escaped_str<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> Result<String, IoError> {
+    let mut buf = Vec::new();
+
+    loop {
+        let b = cursor.read_u8()?;
+
+        if b == 0 {
+            if cursor.read_u8()? == 0 {
+                break;
+            } else {
+                buf.push(0);
+            }
+        } else {
+            buf.push(b);
+        }
+    }
+
+    String::from_utf8(buf).map_err(|err| IoError::new(ErrorKind::InvalidData, err))
+}
+
+fn read_json_value<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, tag: u8) -> Result<serde_json::Value, IoError> {
+    let value = match tag {
+        JSON_NULL => serde_json::Value::Null,
+        JSON_BOOL => serde_json::Value::Bool(cursor.read_u8()? != 0),
+        JSON_NUMBER => {
+            let f = ordered_u64_to_f64(cursor.read_u64::<BigEndian>()?);
+            let diff = (cursor.read_u64::<BigEndian>()? ^ (1 << 63)) as i64;
+            serde_json::Value::Number(join_number(f, diff))
+        }
+        JSON_STRING => serde_json::Value::String(read_escaped_str(cursor)?),
+        JSON_ARRAY => {
+            let mut values = Vec::new();
+            loop {
+                match cursor.read_u8()? {
+                    JSON_TERMINATOR => break,
+                    tag => values.push(read_json_value(cursor, tag)?),
+                }
+            }
+            serde_json::Value::Array(values)
+        }
+        JSON_OBJECT => {
+            let mut values = serde_json::Map::new();
+            while cursor.read_u8()? == JSON_OBJECT_ENTRY {
+                let key = read_escaped_str(cursor)?;
+                let tag = cursor.read_u8()?;
+                values.insert(key, read_json_value(cursor, tag)?);
+            }
+            serde_json::Value::Object(values)
+        }
+        _ => {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                format!("unexpected JSON type tag: {}", tag),
+            ))
+        }
+    };
+    Ok(value)
+}
+
+/// Reads a JSON value from bytes. Numbers with no fractional part are read
+/// back as integers.
+///
+/// # Arguments
+/// * `cursor`: The bytes to read from.
+///
+/// # Errors
+/// Returns an error if the bytes end early, or don't hold a valid encoded
+/// JSON value.
+pub fn read_json<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> Result<models::Json, IoError> {
+    let tag = cursor.read_u8()?;
+    Ok(models::Json::new(read_json_value(cursor, tag)?))
+}
+
 /// Generates a UUID v1. This utility method uses a shared context and node ID
 /// to help ensure generated UUIDs are unique.
 pub fn generate_uuid_v1() -> Uuid {
@@ -189,13 +387,493 @@ pub fn next_uuid(uuid: Uuid) -> ValidationResult<Uuid> {
     Err(ValidationError::CannotIncrementUuid)
 }
 
+/// Gets the vertex type that the results of a query are restricted to, if
+/// any. Property value filters piped from, or intersected with, such a query
+/// can be answered with an index scoped to the type.
+///
+/// # Arguments
+/// * `q`: The vertex query.
+pub fn vertex_query_type(q: &models::VertexQuery) -> Option<&models::Identifier> {
+    match q {
+        models::VertexQuery::Range(q) => q.t.as_ref(),
+        models::VertexQuery::Pipe(q) => q.t.as_ref(),
+        _ => None,
+    }
+}
+
+/// Gets whether a query returns every vertex of a type - i.e. it's a range
+/// query filtered by type, without a start ID or limit. A property value
+/// filter on such a query can be answered entirely by an index scoped to the
+/// type, without running the query.
+///
+/// # Arguments
+/// * `q`: The vertex query.
+pub fn is_full_vertex_type_query(q: &models::VertexQuery) -> bool {
+    match q {
+        models::VertexQuery::Range(q) => q.t.is_some() && q.start_id.is_none() && q.limit == u32::MAX,
+        _ => false,
+    }
+}
+
+/// Gets the edge type that the results of a query are restricted to, if
+/// any. Property value filters piped from, or intersected with, such a query
+/// can be answered with an index scoped to the type.
+///
+/// # Arguments
+/// * `q`: The edge query.
+pub fn edge_query_type(q: &models::EdgeQuery) -> Option<&models::Identifier> {
+    match q {
+        models::EdgeQuery::Pipe(q) => q.t.as_ref(),
+        _ => None,
+    }
+}
+
+/// Gets whether a list of property names can be indexed by a composite
+/// index: there have to be at least two of them, without duplicates.
+///
+/// # Arguments
+/// * `names`: The property names of the index.
+pub fn is_valid_composite_index(names: &[models::Identifier]) -> bool {
+    let unique_names: HashSet<&models::Identifier> = names.iter().collect();
+    names.len() >= 2 && unique_names.len() == names.len()
+}
+
+/// The property values required by a chain of piped property value queries,
+/// each of which filters for a value being equal. A composite index over the
+/// properties can answer the chain with a single lookup.
+#[derive(Debug)]
+pub struct PropertyValueFilters<'a, Q> {
+    /// The names and values of the properties, outermost query first.
+    pub values: Vec<(&'a models::Identifier, &'a serde_json::Value)>,
+    /// The query that the chain is piped from, or `None` if the chain starts
+    /// with a property value query, whose name and value are in `values`
+    /// instead.
+    pub inner: Option<&'a Q>,
+}
+
+/// A lookup in a composite index, for the property values required by a
+/// chain of piped property value queries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeLookup {
+    /// The names of the index's properties.
+    pub names: Vec<models::Identifier>,
+    /// The values of the leading properties of the index to look up.
+    pub values: Vec<models::Json>,
+    /// The names and values required by the chain that aren't covered by the
+    /// lookup, which have to be checked separately.
+    pub remaining: Vec<(models::Identifier, serde_json::Value)>,
+}
+
+impl<'a, Q> PropertyValueFilters<'a, Q> {
+    /// Picks the composite index that covers the most leading properties of
+    /// the chain, if any covers at least two of them. Ties are broken by the
+    /// index's property names, so the choice doesn't depend on the order of
+    /// `indexes`.
+    ///
+    /// # Arguments
+    /// * `indexes`: The property names of each composite index.
+    pub fn composite_lookup<'b, I>(&self, indexes: I) -> Option<CompositeLookup>
+    where
+        I: IntoIterator<Item = &'b Vec<models::Identifier>>,
+    {
+        let (covered, names) = indexes
+            .into_iter()
+            .map(|names| {
+                let covered = names
+                    .iter()
+                    .take_while(|name| self.values.iter().any(|(filter_name, _)| filter_name == name))
+                    .count();
+                (covered, names)
+            })
+            .filter(|(covered, _)| *covered >= 2)
+            .max_by(|(covered, names), (other_covered, other_names)| {
+                covered.cmp(other_covered).then_with(|| other_names.cmp(names))
+            })?;
+
+        // Each covered property is looked up by the first value required of
+        // it. If the chain requires another value too, it's checked
+        // separately, and nothing will match.
+        let mut remaining: Vec<(&models::Identifier, &serde_json::Value)> = self.values.clone();
+        let mut values = Vec::with_capacity(covered);
+        for name in &names[..covered] {
+            let position = remaining
+                .iter()
+                .position(|(filter_name, _)| filter_name == &name)
+                .unwrap();
+            let (_, value) = remaining.remove(position);
+            values.push(models::Json::new(value.clone()));
+        }
+
+        Some(CompositeLookup {
+            names: names.clone(),
+            values,
+            remaining: remaining
+                .into_iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Gets the property values required by a piped property value query and
+/// any such queries it's piped from, provided they all filter for a value
+/// being equal.
+///
+/// # Arguments
+/// * `q`: The piped property value query.
+pub fn vertex_property_value_filters(
+    q: &models::PipePropertyValueVertexQuery,
+) -> Option<PropertyValueFilters<'_, models::VertexQuery>> {
+    if !q.equal {
+        return None;
+    }
+
+    let mut values = vec![(&q.name, &q.value)];
+    let mut inner = &*q.inner;
+    loop {
+        match inner {
+            models::VertexQuery::PipePropertyValue(q) if q.equal => {
+                values.push((&q.name, &q.value));
+                inner = &*q.inner;
+            }
+            models::VertexQuery::PropertyValue(q) => {
+                values.push((&q.name, &q.value));
+                return Some(PropertyValueFilters { values, inner: None });
+            }
+            _ => {
+                return Some(PropertyValueFilters {
+                    values,
+                    inner: Some(inner),
+                })
+            }
+        }
+    }
+}
+
+/// Gets the property values required by a piped property value query and
+/// any such queries it's piped from, provided they all filter for a value
+/// being equal.
+///
+/// # Arguments
+/// * `q`: The piped property value query.
+pub fn edge_property_value_filters(
+    q: &models::PipePropertyValueEdgeQuery,
+) -> Option<PropertyValueFilters<'_, models::EdgeQuery>> {
+    if !q.equal {
+        return None;
+    }
+
+    let mut values = vec![(&q.name, &q.value)];
+    let mut inner = &*q.inner;
+    loop {
+        match inner {
+            models::EdgeQuery::PipePropertyValue(q) if q.equal => {
+                values.push((&q.name, &q.value));
+                inner = &*q.inner;
+            }
+            models::EdgeQuery::PropertyValue(q) => {
+                values.push((&q.name, &q.value));
+                return Some(PropertyValueFilters { values, inner: None });
+            }
+            _ => {
+                return Some(PropertyValueFilters {
+                    values,
+                    inner: Some(inner),
+                })
+            }
+        }
+    }
+}
+
+/// Walks a graph breadth-first from a set of starting vertices, returning
+/// the ids of the vertices whose shortest distance from the start is between
+/// `min_depth` and `max_depth`, inclusive. Each vertex is returned at most
+/// once, in the order it was visited.
+///
+/// # Arguments
+/// * `start_ids`: The ids of the starting vertices, which are at depth 0.
+/// * `min_depth`: The minimum depth of vertices to return.
+/// * `max_depth`: The maximum depth to walk to.
+/// * `limit`: The maximum number of vertices to return.
+/// * `adjacent_ids`: Gets the ids of the vertices one hop away from a
+///   vertex.
+pub fn traverse<I, F, E>(
+    start_ids: I,
+    min_depth: u32,
+    max_depth: u32,
+    limit: u32,
+    mut adjacent_ids: F,
+) -> Result<Vec<Uuid>, E>
+where
+    I: IntoIterator<Item = Uuid>,
+    F: FnMut(Uuid) -> Result<Vec<Uuid>, E>,
+{
+    let limit = limit as usize;
+    let mut visited = HashSet::new();
+    let mut results = Vec::new();
+    let mut frontier: Vec<Uuid> = start_ids.into_iter().filter(|id| visited.insert(*id)).collect();
+    let mut depth = 0;
+
+    while !frontier.is_empty() && depth <= max_depth && results.len() < limit {
+        if depth >= min_depth {
+            results.extend(frontier.iter().take(limit - results.len()));
+        }
+
+        if depth == max_depth {
+            break;
+        }
+
+        let mut next_frontier = Vec::new();
+        for id in frontier {
+            for adjacent_id in adjacent_ids(id)? {
+                if visited.insert(adjacent_id) {
+                    next_frontier.push(adjacent_id);
+                }
+            }
+        }
+
+        frontier = next_frontier;
+        depth += 1;
+    }
+
+    Ok(results)
+}
+
+/// Finds up to `limit` of the shortest paths from one vertex to another,
+/// following outbound edges. Paths never visit a vertex more than once, and
+/// are returned in order of length; ties are broken by comparing edge keys.
+/// If `start_id` and `end_id` are the same, the only path is the empty one.
+///
+/// This uses Yen's algorithm, with breadth-first searches to find each
+/// candidate path.
+///
+/// # Arguments
+/// * `start_id`: The id of the vertex to start from.
+/// * `end_id`: The id of the vertex to end at.
+/// * `max_depth`: The maximum number of edges in a path.
+/// * `limit`: The maximum number of paths to return.
+/// * `outbound_edges`: Gets the outbound edges of a vertex.
+pub fn shortest_paths<F, E>(
+    start_id: Uuid,
+    end_id: Uuid,
+    max_depth: u32,
+    limit: u32,
+    mut outbound_edges: F,
+) -> Result<Vec<Vec<models::Edge>>, E>
+where
+    F: FnMut(Uuid) -> Result<Vec<models::Edge>, E>,
+{
+    let limit = limit as usize;
+    let max_depth = max_depth as usize;
+
+    // Edges are looked up repeatedly, so they're cached. They're sorted so
+    // that the results don't depend on the datastore's iteration order.
+    let mut cache: HashMap<Uuid, Vec<models::Edge>> = HashMap::new();
+    let mut get_edges = |id: Uuid| -> Result<Vec<models::Edge>, E> {
+        if let Some(edges) = cache.get(&id) {
+            return Ok(edges.clone());
+        }
+        let mut edges = outbound_edges(id)?;
+        edges.sort_by(|a, b| a.key.cmp(&b.key));
+        cache.insert(id, edges.clone());
+        Ok(edges)
+    };
+
+    let mut paths: Vec<Vec<models::Edge>> = Vec::new();
+    if limit == 0 {
+        return Ok(paths);
+    }
+
+    match shortest_path(
+        start_id,
+        end_id,
+        max_depth,
+        &HashSet::new(),
+        &HashSet::new(),
+        &mut get_edges,
+    )? {
+        Some(path) => paths.push(path),
+        None => return Ok(paths),
+    }
+
+    let mut candidates: BTreeMap<(usize, Vec<models::EdgeKey>), Vec<models::Edge>> = BTreeMap::new();
+
+    while paths.len() < limit {
+        let previous = paths.last().unwrap().clone();
+
+        // Find paths that branch off of the previous one at each of its
+        // vertices
+        for i in 0..previous.len() {
+            let root = &previous[..i];
+            let spur_id = match root.last() {
+                Some(edge) => edge.key.inbound_id,
+                None => start_id,
+            };
+
+            // Exclude the branches already taken from this root, and the
+            // vertices in the root itself
+            let removed_edges: HashSet<models::EdgeKey> = paths
+                .iter()
+                .filter(|path| path.len() > i && keys_eq(&path[..i], root))
+                .map(|path| path[i].key.clone())
+                .collect();
+            let mut removed_vertices: HashSet<Uuid> = root.iter().map(|edge| edge.key.outbound_id).collect();
+            removed_vertices.remove(&spur_id);
+
+            let spur = shortest_path(
+                spur_id,
+                end_id,
+                max_depth - i,
+                &removed_edges,
+                &removed_vertices,
+                &mut get_edges,
+            )?;
+
+            if let Some(spur) = spur {
+                let mut path = root.to_vec();
+                path.extend(spur);
+                let keys: Vec<models::EdgeKey> = path.iter().map(|edge| edge.key.clone()).collect();
+                if !paths.iter().any(|existing| keys_eq(existing, &path)) {
+                    candidates.insert((path.len(), keys), path);
+                }
+            }
+        }
+
+        let next = match candidates.keys().next() {
+            Some(key) => key.clone(),
+            None => break,
+        };
+        paths.push(candidates.remove(&next).unwrap());
+    }
+
+    Ok(paths)
+}
+
+// Checks whether two paths traverse the same edges.
+fn keys_eq(a: &[models::Edge], b: &[models::Edge]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.key == b.key)
+}
+
+// Finds the shortest path from one vertex to another via a breadth-first
+// search, skipping the given edges and vertices.
+fn shortest_path<F, E>(
+    start_id: Uuid,
+    end_id: Uuid,
+    max_depth: usize,
+    removed_edges: &HashSet<models::EdgeKey>,
+    removed_vertices: &HashSet<Uuid>,
+    get_edges: &mut F,
+) -> Result<Option<Vec<models::Edge>>, E>
+where
+    F: FnMut(Uuid) -> Result<Vec<models::Edge>, E>,
+{
+    if start_id == end_id {
+        return Ok(Some(Vec::new()));
+    }
+
+    // Maps visited vertices to the edge that they were reached by
+    let mut parents: HashMap<Uuid, models::Edge> = HashMap::new();
+    let mut frontier = vec![start_id];
+    let mut depth = 0;
+
+    while !frontier.is_empty() && depth < max_depth {
+        let mut next_frontier = Vec::new();
+
+        for id in frontier {
+            for edge in get_edges(id)? {
+                let next_id = edge.key.inbound_id;
+                if next_id == start_id
+                    || removed_edges.contains(&edge.key)
+                    || removed_vertices.contains(&next_id)
+                    || parents.contains_key(&next_id)
+                {
+                    continue;
+                }
+
+                parents.insert(next_id, edge);
+
+                if next_id == end_id {
+                    let mut path = Vec::new();
+                    let mut id = end_id;
+                    while id != start_id {
+                        let edge = parents.remove(&id).unwrap();
+                        id = edge.key.outbound_id;
+                        path.push(edge);
+                    }
+                    path.reverse();
+                    return Ok(Some(path));
+                }
+
+                next_frontier.push(next_id);
+            }
+        }
+
+        frontier = next_frontier;
+        depth += 1;
+    }
+
+    Ok(None)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{generate_uuid_v1, nanos_since_epoch, next_uuid};
+    use super::{
+        build, generate_uuid_v1, nanos_since_epoch, next_uuid, read_json, vertex_property_value_filters, Component,
+        JSON_STRING,
+    };
+    use crate::models::{Identifier, Json, PropertyValueVertexQuery, RangeVertexQuery, VertexQueryExt};
     use chrono::{DateTime, NaiveDateTime, Utc};
     use core::str::FromStr;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::io::Cursor;
     use uuid::Uuid;
 
+    fn sorted_json_values() -> Vec<Json> {
+        vec![
+            serde_json::json!(null),
+            serde_json::json!(false),
+            serde_json::json!(true),
+            serde_json::json!(-1e300),
+            serde_json::json!(i64::MIN),
+            serde_json::json!(-1.5),
+            serde_json::json!(-1),
+            serde_json::json!(0),
+            serde_json::json!(0.5),
+            serde_json::json!(1),
+            serde_json::json!(9_007_199_254_740_992u64),
+            serde_json::json!(9_007_199_254_740_993u64),
+            serde_json::json!(i64::MAX),
+            serde_json::json!(u64::MAX),
+            serde_json::json!(1e300),
+            serde_json::json!(""),
+            serde_json::json!("\u{0}"),
+            serde_json::json!("a"),
+            serde_json::json!("a\u{0}b"),
+            serde_json::json!("ab"),
+            serde_json::json!([]),
+            serde_json::json!([null]),
+            serde_json::json!([1]),
+            serde_json::json!([1, 2]),
+            serde_json::json!([2]),
+            serde_json::json!({}),
+            serde_json::json!({"": 1}),
+            serde_json::json!({"a": 1}),
+            serde_json::json!({"a": 1, "b": 2}),
+            serde_json::json!({"a": 2}),
+            serde_json::json!({"b": 0}),
+        ]
+        .into_iter()
+        .map(Json::new)
+        .collect()
+    }
+
+    fn hash(value: &Json) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
     #[test]
     fn should_generate_nanos_since_epoch() {
         let datetime = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(61, 62), Utc);
@@ -221,4 +899,101 @@ mod tests {
         let from_uuid = Uuid::from_str("ffffffff-ffff-ffff-ffff-ffffffffffff").unwrap();
         assert!(next_uuid(from_uuid).is_err());
     }
+
+    #[test]
+    fn should_encode_json_in_order() {
+        let values = sorted_json_values();
+        for (i, first) in values.iter().enumerate() {
+            let first_bytes = build(&[Component::Json(first)]);
+            assert_eq!(first_bytes.len(), Component::Json(first).len());
+
+            for (j, second) in values.iter().enumerate() {
+                let second_bytes = build(&[Component::Json(second)]);
+                assert_eq!(first.cmp(second), i.cmp(&j), "{:?} vs {:?}", first, second);
+                assert_eq!(first_bytes.cmp(&second_bytes), i.cmp(&j), "{:?} vs {:?}", first, second);
+            }
+        }
+    }
+
+    #[test]
+    fn should_read_encoded_json() {
+        for value in sorted_json_values() {
+            let mut bytes = build(&[Component::Json(&value)]);
+            bytes.push(0xFF);
+            let mut cursor = Cursor::new(bytes);
+            assert_eq!(read_json(&mut cursor).unwrap(), value);
+            assert_eq!(cursor.position() as usize, cursor.get_ref().len() - 1);
+        }
+    }
+
+    #[test]
+    fn should_fail_to_read_invalid_json() {
+        // An unknown type tag
+        assert!(read_json(&mut Cursor::new(vec![0x7F])).is_err());
+        // A string that isn't valid UTF-8
+        assert!(read_json(&mut Cursor::new(vec![JSON_STRING, 0xC3, 0x28, 0, 0])).is_err());
+        // A string that isn't terminated
+        assert!(read_json(&mut Cursor::new(vec![JSON_STRING, b'a'])).is_err());
+    }
+
+    #[test]
+    fn should_encode_equal_json_numbers_identically() {
+        let pairs = vec![
+            (serde_json::json!(1), serde_json::json!(1.0)),
+            (serde_json::json!(0), serde_json::json!(-0.0)),
+            (serde_json::json!(-7), serde_json::json!(-7.0)),
+            (
+                serde_json::json!(9_007_199_254_740_992u64),
+                serde_json::json!(9_007_199_254_740_992.0),
+            ),
+        ];
+
+        for (first, second) in pairs {
+            let (first, second) = (Json::new(first), Json::new(second));
+            assert_eq!(first, second);
+            assert_eq!(build(&[Component::Json(&first)]), build(&[Component::Json(&second)]));
+            assert_eq!(hash(&first), hash(&second));
+        }
+
+        let first = Json::new(serde_json::json!(9_007_199_254_740_993u64));
+        let second = Json::new(serde_json::json!(9_007_199_254_740_992.0));
+        assert_ne!(first, second);
+        assert_ne!(build(&[Component::Json(&first)]), build(&[Component::Json(&second)]));
+    }
+
+    #[test]
+    fn should_pick_composite_index() {
+        let id = |s: &str| Identifier::new(s).unwrap();
+        let indexes = vec![
+            vec![id("country"), id("region")],
+            vec![id("country"), id("city"), id("street")],
+            vec![id("city"), id("country")],
+        ];
+
+        let q = PropertyValueVertexQuery::new(id("country"), serde_json::json!("fr"))
+            .with_property_equal_to(id("city"), serde_json::json!("paris"))
+            .with_property_equal_to(id("zip"), serde_json::json!(75001));
+        let filters = vertex_property_value_filters(&q).unwrap();
+        assert!(filters.inner.is_none());
+
+        // Both indexes starting with country and city cover two properties,
+        // so the lower sorted one is picked.
+        let lookup = filters.composite_lookup(&indexes).unwrap();
+        assert_eq!(lookup.names, vec![id("city"), id("country")]);
+        assert_eq!(
+            lookup.values,
+            vec![
+                Json::new(serde_json::json!("paris")),
+                Json::new(serde_json::json!("fr"))
+            ]
+        );
+        assert_eq!(lookup.remaining, vec![(id("zip"), serde_json::json!(75001))]);
+
+        let q = RangeVertexQuery::new()
+            .with_property_equal_to(id("country"), serde_json::json!("fr"))
+            .with_property_equal_to(id("street"), serde_json::json!("rue"));
+        let filters = vertex_property_value_filters(&q).unwrap();
+        assert!(filters.inner.is_some());
+        assert_eq!(filters.composite_lookup(&indexes), None);
+    }
 }
@@ -45,6 +45,9 @@ pub trait Plugin: Send + Sync + 'static {
     /// Executes the plugin. Returns JSON that will be sent back to the
     /// calling client.
     ///
+    /// Plugins that read the datastore across several calls can take a
+    /// `datastore.snapshot()` to run against a consistent view of it.
+    ///
     /// # Arguments
     /// * `datastore`: The datastore.
     /// * `arg`: The argument from the calling client.
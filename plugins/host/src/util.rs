@@ -6,7 +6,7 @@ use crate::errors::Error;
 use threadpool::ThreadPool;
 
 const DEFAULT_NUM_THREADS: usize = 8;
-const DEFAULT_QUERY_LIMIT: u32 = u16::max_value() as u32;
+const DEFAULT_QUERY_LIMIT: u32 = u16::MAX as u32;
 
 /// Trait for running an operation on all vertices in a datastore.
 pub trait VertexMapper: Send + Sync + 'static {
@@ -19,14 +19,27 @@ pub trait VertexMapper: Send + Sync + 'static {
         DEFAULT_QUERY_LIMIT
     }
     /// If specified, only vertices of the specified type will be mapped.
+    /// Only used by the default `query`.
     fn t_filter(&self) -> Option<indradb::Identifier> {
         None
     }
+    /// The query for the vertices to map. Defaults to all vertices, filtered
+    /// by `t_filter`.
+    fn query(&self) -> indradb::VertexQuery {
+        let q = indradb::RangeVertexQuery::new();
+        match self.t_filter() {
+            Some(t) => q.t(t).into(),
+            None => q.into(),
+        }
+    }
     /// The map operation.
     fn map(&self, vertex: indradb::Vertex) -> Result<(), Error>;
 }
 
-/// Runs an operation on all vertices in the datastore.
+/// Runs an operation on all vertices matching the mapper's query. If the
+/// datastore supports snapshots, the vertices are read from a snapshot, so
+/// that every page comes from the same view of the datastore. Snapshots of
+/// the built-in datastores don't copy any data, so this is cheap to do.
 ///
 /// # Arguments
 /// * `mapper`: Specified options and the map operation to run.
@@ -35,24 +48,23 @@ pub fn map<M: VertexMapper>(
     mapper: Arc<M>,
     datastore: Arc<dyn indradb::Datastore + Send + Sync + 'static>,
 ) -> Result<(), Error> {
+    let datastore: Arc<dyn indradb::Datastore + Send + Sync + 'static> = match datastore.snapshot() {
+        Ok(snapshot) => Arc::from(snapshot),
+        Err(indradb::Error::Unsupported) => datastore,
+        Err(err) => return Err(err.into()),
+    };
     let pool = ThreadPool::new(max(mapper.num_threads(), 1));
     let query_limit = max(mapper.query_limit(), 1);
-    let t_filter = mapper.t_filter();
+    let q = mapper.query();
     let last_err: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
-    let mut last_id: Option<uuid::Uuid> = None;
+    let mut token: Option<indradb::ContinuationToken> = None;
 
     loop {
         if last_err.lock().unwrap().is_some() {
             break;
         }
 
-        let q = indradb::RangeVertexQuery {
-            limit: query_limit,
-            t: t_filter.clone(),
-            start_id: last_id,
-        };
-
-        let vertices = match datastore.get_vertices(q.into()) {
+        let page = match datastore.get_vertices_page(q.clone(), query_limit, token) {
             Ok(value) => value,
             Err(err) => {
                 *last_err.lock().unwrap() = Some(err.into());
@@ -60,10 +72,9 @@ pub fn map<M: VertexMapper>(
             }
         };
 
-        let is_last_query = vertices.len() < query_limit as usize;
-        if let Some(last_vertex) = vertices.last() {
-            last_id = Some(last_vertex.id);
-        }
+        let vertices = page.results;
+        token = page.token;
+        let is_last_query = token.is_none();
 
         for vertex in vertices {
             let mapper = mapper.clone();
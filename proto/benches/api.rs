@@ -19,7 +19,8 @@ full_bench_impl!({
     rt.spawn(proto::run_server(
         Arc::new(indradb::MemoryDatastore::default()),
         listener,
+        None,
     ));
 
-    proto::tests::ClientDatastore::new(port as u16, rt)
+    proto::tests::ClientDatastore::new(port, rt)
 });
@@ -91,7 +91,7 @@ impl Client {
     /// * `endpoint`: The server endpoint.
     pub async fn new(endpoint: Endpoint) -> Result<Self, ClientError> {
         let client = crate::ProtoClient::connect(endpoint).await?;
-        Ok(Client { 0: client })
+        Ok(Client(client))
     }
 
     /// Pings the server.
@@ -147,6 +147,24 @@ impl Client {
         Ok(vertices)
     }
 
+    /// Gets a page of vertices specified by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of vertices to return.
+    /// * `token`: The token returned with the previous page, if any.
+    pub async fn get_vertices_page(
+        &mut self,
+        q: indradb::VertexQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::Vertex>, ClientError> {
+        let mut q: crate::VertexQuery = q.into();
+        q.pagination = Some((limit, token).into());
+        let res = self.0.get_vertex_page(q).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
     /// Deletes existing vertices specified by a query.
     ///
     /// # Arguments
@@ -163,6 +181,16 @@ impl Client {
         Ok(res.into_inner().count)
     }
 
+    /// Gets the number of vertices specified by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn count_vertices(&mut self, q: indradb::VertexQuery) -> Result<u64, ClientError> {
+        let q: crate::VertexQuery = q.into();
+        let res = self.0.count_vertices(q).await?;
+        Ok(res.into_inner().count)
+    }
+
     /// Creates a new edge. If the edge already exists, this will update it
     /// with a new update datetime. Returns whether the edge was successfully
     /// created - if this is false, it's because one of the specified vertices
@@ -192,6 +220,40 @@ impl Client {
         Ok(edges)
     }
 
+    /// Gets a page of edges specified by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of edges to return.
+    /// * `token`: The token returned with the previous page, if any.
+    pub async fn get_edges_page(
+        &mut self,
+        q: indradb::EdgeQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::Edge>, ClientError> {
+        let mut q: crate::EdgeQuery = q.into();
+        q.pagination = Some((limit, token).into());
+        let res = self.0.get_edge_page(q).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Gets the shortest paths between two vertices.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn get_paths(&mut self, q: indradb::PathQuery) -> Result<Vec<Vec<indradb::Edge>>, ClientError> {
+        let q: crate::PathQuery = q.into();
+        let mut paths = Vec::<Vec<indradb::Edge>>::new();
+        let mut res = self.0.get_paths(q).await?.into_inner();
+
+        while let Some(res) = res.next().await {
+            paths.push(res?.try_into()?);
+        }
+
+        Ok(paths)
+    }
+
     /// Deletes a set of edges specified by a query.
     ///
     /// # Arguments
@@ -219,6 +281,16 @@ impl Client {
         Ok(res.into_inner().count)
     }
 
+    /// Gets the number of edges specified by a query.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    pub async fn count_edges(&mut self, q: indradb::EdgeQuery) -> Result<u64, ClientError> {
+        let q: crate::EdgeQuery = q.into();
+        let res = self.0.count_edges(q).await?;
+        Ok(res.into_inner().count)
+    }
+
     /// Gets vertex properties.
     ///
     /// # Arguments
@@ -238,6 +310,24 @@ impl Client {
         Ok(vertex_properties)
     }
 
+    /// Gets a page of vertex properties.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of properties to return.
+    /// * `token`: The token returned with the previous page, if any.
+    pub async fn get_vertex_properties_page(
+        &mut self,
+        q: indradb::VertexPropertyQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::VertexProperty>, ClientError> {
+        let mut q: crate::VertexPropertyQuery = q.into();
+        q.pagination = Some((limit, token).into());
+        let res = self.0.get_vertex_property_page(q).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
     /// Gets all vertex properties.
     ///
     /// # Arguments
@@ -301,6 +391,24 @@ impl Client {
         Ok(edge_properties)
     }
 
+    /// Gets a page of edge properties.
+    ///
+    /// # Arguments
+    /// * `q`: The query to run.
+    /// * `limit`: The maximum number of properties to return.
+    /// * `token`: The token returned with the previous page, if any.
+    pub async fn get_edge_properties_page(
+        &mut self,
+        q: indradb::EdgePropertyQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::EdgeProperty>, ClientError> {
+        let mut q: crate::EdgePropertyQuery = q.into();
+        q.pagination = Some((limit, token).into());
+        let res = self.0.get_edge_property_page(q).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
     /// Gets all edge properties.
     ///
     /// # Arguments
@@ -353,6 +461,7 @@ impl Client {
     ///   returning.
     /// * The datastore might not verify for correctness; e.g., it might not
     ///   ensure that the relevant vertices exist before inserting an edge.
+    ///
     /// If you want maximum protection, use the equivalent functions in
     /// transactions, which will provide more safeguards.
     ///
@@ -392,6 +501,211 @@ impl Client {
         Ok(())
     }
 
+    /// Enables indexing on a specified property like `index_property`, but
+    /// builds the index in the background, and returns as soon as the build
+    /// has started. Use `get_index_status` to follow the build.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property to index.
+    pub async fn index_property_in_background(&mut self, name: indradb::Identifier) -> Result<(), ClientError> {
+        let request = Request::new(crate::IndexPropertyRequest {
+            name: Some(name.into()),
+        });
+        self.0.index_property_in_background(request).await?;
+        Ok(())
+    }
+
+    /// Disables indexing on a specified property, deleting its index.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property to stop indexing.
+    pub async fn drop_index(&mut self, name: indradb::Identifier) -> Result<(), ClientError> {
+        let request = Request::new(crate::DropIndexRequest {
+            name: Some(name.into()),
+        });
+        self.0.drop_index(request).await?;
+        Ok(())
+    }
+
+    /// Gets the names of the indexed properties, in sorted order.
+    pub async fn get_indexed_properties(&mut self) -> Result<Vec<indradb::Identifier>, ClientError> {
+        let res = self.0.get_indexed_properties(()).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Gets the status of a property's index, or `None` if the property isn't
+    /// indexed.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    pub async fn get_index_status(
+        &mut self,
+        name: indradb::Identifier,
+    ) -> Result<Option<indradb::IndexStatus>, ClientError> {
+        let request = Request::new(crate::GetIndexStatusRequest {
+            name: Some(name.into()),
+        });
+        let res = self.0.get_index_status(request).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Enables indexing on a property of the vertices and edges of one type.
+    /// Property value filters on queries restricted to the type use the
+    /// index.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the vertices and edges to index.
+    /// * `name`: The name of the property to index.
+    pub async fn index_type_property(
+        &mut self,
+        t: indradb::Identifier,
+        name: indradb::Identifier,
+    ) -> Result<(), ClientError> {
+        let request = Request::new(crate::IndexTypePropertyRequest {
+            t: Some(t.into()),
+            name: Some(name.into()),
+        });
+        self.0.index_type_property(request).await?;
+        Ok(())
+    }
+
+    /// Disables indexing on a property of the vertices and edges of one
+    /// type, deleting its index.
+    ///
+    /// # Arguments
+    /// * `t`: The type of the indexed vertices and edges.
+    /// * `name`: The name of the property to stop indexing.
+    pub async fn drop_type_index(
+        &mut self,
+        t: indradb::Identifier,
+        name: indradb::Identifier,
+    ) -> Result<(), ClientError> {
+        let request = Request::new(crate::DropTypeIndexRequest {
+            t: Some(t.into()),
+            name: Some(name.into()),
+        });
+        self.0.drop_type_index(request).await?;
+        Ok(())
+    }
+
+    /// Gets the type and property name of each index scoped to a type, in
+    /// sorted order.
+    pub async fn get_indexed_type_properties(
+        &mut self,
+    ) -> Result<Vec<(indradb::Identifier, indradb::Identifier)>, ClientError> {
+        let res = self.0.get_indexed_type_properties(()).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Enables a composite index over an ordered list of at least two
+    /// property names. Queries piping property value filters together use
+    /// the index when they cover its leading properties.
+    ///
+    /// # Arguments
+    /// * `names`: The names of the properties to index, in order.
+    pub async fn index_composite_properties(&mut self, names: Vec<indradb::Identifier>) -> Result<(), ClientError> {
+        let request = Request::new(crate::IndexCompositePropertiesRequest {
+            names: names.into_iter().map(|name| name.into()).collect(),
+        });
+        self.0.index_composite_properties(request).await?;
+        Ok(())
+    }
+
+    /// Drops a composite index.
+    ///
+    /// # Arguments
+    /// * `names`: The names of the index's properties, in order.
+    pub async fn drop_composite_index(&mut self, names: Vec<indradb::Identifier>) -> Result<(), ClientError> {
+        let request = Request::new(crate::DropCompositeIndexRequest {
+            names: names.into_iter().map(|name| name.into()).collect(),
+        });
+        self.0.drop_composite_index(request).await?;
+        Ok(())
+    }
+
+    /// Gets the property names of each composite index, in sorted order.
+    pub async fn get_indexed_composite_properties(&mut self) -> Result<Vec<Vec<indradb::Identifier>>, ClientError> {
+        let res = self.0.get_indexed_composite_properties(()).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Adds a unique constraint on an indexed property, so that no two
+    /// vertices, and no two edges, covered by it can have the same value.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `t`: If set, the constraint only covers the vertices and edges of
+    ///   this type.
+    pub async fn add_unique_constraint(
+        &mut self,
+        name: indradb::Identifier,
+        t: Option<indradb::Identifier>,
+    ) -> Result<(), ClientError> {
+        let request = Request::new((name, t).into());
+        self.0.add_unique_constraint(request).await?;
+        Ok(())
+    }
+
+    /// Drops a unique constraint.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the property.
+    /// * `t`: The type the constraint is scoped to, if any.
+    pub async fn drop_unique_constraint(
+        &mut self,
+        name: indradb::Identifier,
+        t: Option<indradb::Identifier>,
+    ) -> Result<(), ClientError> {
+        let request = Request::new((name, t).into());
+        self.0.drop_unique_constraint(request).await?;
+        Ok(())
+    }
+
+    /// Gets the property name and scope of each unique constraint, in
+    /// sorted order.
+    pub async fn get_unique_constraints(
+        &mut self,
+    ) -> Result<Vec<(indradb::Identifier, Option<indradb::Identifier>)>, ClientError> {
+        let res = self.0.get_unique_constraints(()).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Sets the schema that writes are checked against, or removes it.
+    ///
+    /// # Arguments
+    /// * `schema`: The schema, or `None` to remove it.
+    pub async fn set_schema(&mut self, schema: Option<indradb::Schema>) -> Result<(), ClientError> {
+        let request = Request::new(schema.into());
+        self.0.set_schema(request).await?;
+        Ok(())
+    }
+
+    /// Gets the schema, if one is set.
+    pub async fn get_schema(&mut self) -> Result<Option<indradb::Schema>, ClientError> {
+        let res = self.0.get_schema(()).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Gets the types of vertices and edges in the datastore, with how many
+    /// there are of each, and the names of the properties that each type
+    /// has.
+    pub async fn get_schema_summary(&mut self) -> Result<indradb::SchemaSummary, ClientError> {
+        let res = self.0.get_schema_summary(()).await?;
+        Ok(res.into_inner().try_into()?)
+    }
+
+    /// Backs up the datastore to a path on the server, while it remains
+    /// online. Depending on the datastore implementation, the path may be a
+    /// file or a directory.
+    ///
+    /// # Arguments
+    /// * `path`: The path to back up to, on the server's filesystem.
+    pub async fn backup(&mut self, path: &str) -> Result<(), ClientError> {
+        let request = Request::new(crate::BackupRequest { path: path.to_string() });
+        self.0.backup(request).await?;
+        Ok(())
+    }
+
     pub async fn execute_plugin(
         &mut self,
         name: &str,
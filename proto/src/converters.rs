@@ -1,6 +1,7 @@
 //! Trait implementations for conveniently converting between protobuf and
 //! native IndraDB models.
 
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::error::Error as StdError;
 use std::fmt;
@@ -177,6 +178,49 @@ impl TryInto<indradb::Vertex> for crate::Vertex {
     }
 }
 
+impl From<indradb::PropertyValueRange> for crate::PropertyValueRange {
+    fn from(range: indradb::PropertyValueRange) -> Self {
+        crate::PropertyValueRange {
+            range: Some(match range {
+                indradb::PropertyValueRange::GreaterThan(value) => {
+                    crate::PropertyValueRangeVariant::GreaterThan(value.into())
+                }
+                indradb::PropertyValueRange::LessThan(value) => {
+                    crate::PropertyValueRangeVariant::LessThan(value.into())
+                }
+                indradb::PropertyValueRange::Between(low, high) => {
+                    crate::PropertyValueRangeVariant::Between(crate::PropertyValueBetween {
+                        low: Some(low.into()),
+                        high: Some(high.into()),
+                    })
+                }
+                indradb::PropertyValueRange::Prefix(prefix) => crate::PropertyValueRangeVariant::Prefix(prefix),
+            }),
+        }
+    }
+}
+
+impl TryInto<indradb::PropertyValueRange> for crate::PropertyValueRange {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::PropertyValueRange, Self::Error> {
+        Ok(match required_field("range", self.range)? {
+            crate::PropertyValueRangeVariant::GreaterThan(value) => {
+                indradb::PropertyValueRange::GreaterThan(value.try_into()?)
+            }
+            crate::PropertyValueRangeVariant::LessThan(value) => {
+                indradb::PropertyValueRange::LessThan(value.try_into()?)
+            }
+            crate::PropertyValueRangeVariant::Between(between) => {
+                let low = required_field("low", between.low)?;
+                let high = required_field("high", between.high)?;
+                indradb::PropertyValueRange::Between(low.try_into()?, high.try_into()?)
+            }
+            crate::PropertyValueRangeVariant::Prefix(prefix) => indradb::PropertyValueRange::Prefix(prefix),
+        })
+    }
+}
+
 impl From<indradb::VertexQuery> for crate::VertexQuery {
     fn from(q: indradb::VertexQuery) -> Self {
         crate::VertexQuery {
@@ -199,6 +243,39 @@ impl From<indradb::VertexQuery> for crate::VertexQuery {
                     proto_q.set_direction(q.direction.into());
                     crate::VertexQueryVariant::Pipe(Box::new(proto_q))
                 }
+                indradb::VertexQuery::Traversal(q) => {
+                    let mut proto_q = crate::TraversalVertexQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        direction: 0,
+                        t: q.t.map(|t| t.into()),
+                        min_depth: q.min_depth,
+                        max_depth: q.max_depth,
+                        limit: q.limit,
+                    };
+                    proto_q.set_direction(q.direction.into());
+                    crate::VertexQueryVariant::Traversal(Box::new(proto_q))
+                }
+                indradb::VertexQuery::Union(q) => {
+                    let proto_q = crate::UnionVertexQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        other: Some(Box::new((*q.other).into())),
+                    };
+                    crate::VertexQueryVariant::Union(Box::new(proto_q))
+                }
+                indradb::VertexQuery::Intersect(q) => {
+                    let proto_q = crate::IntersectVertexQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        other: Some(Box::new((*q.other).into())),
+                    };
+                    crate::VertexQueryVariant::Intersect(Box::new(proto_q))
+                }
+                indradb::VertexQuery::Except(q) => {
+                    let proto_q = crate::ExceptVertexQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        other: Some(Box::new((*q.other).into())),
+                    };
+                    crate::VertexQueryVariant::Except(Box::new(proto_q))
+                }
                 indradb::VertexQuery::PropertyPresence(q) => {
                     let proto_q = crate::PropertyPresenceVertexQuery {
                         name: Some(q.name.into()),
@@ -229,7 +306,24 @@ impl From<indradb::VertexQuery> for crate::VertexQuery {
                     };
                     crate::VertexQueryVariant::PipePropertyValue(Box::new(proto_q))
                 }
+                indradb::VertexQuery::PropertyValueRange(q) => {
+                    let proto_q = crate::PropertyValueRangeVertexQuery {
+                        name: Some(q.name.into()),
+                        range: Some(q.range.into()),
+                    };
+                    crate::VertexQueryVariant::PropertyValueRange(proto_q)
+                }
+                indradb::VertexQuery::PipePropertyValueRange(q) => {
+                    let proto_q = crate::PipePropertyValueRangeVertexQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        name: Some(q.name.into()),
+                        range: Some(q.range.into()),
+                        within: q.within,
+                    };
+                    crate::VertexQueryVariant::PipePropertyValueRange(Box::new(proto_q))
+                }
             }),
+            pagination: None,
         }
     }
 }
@@ -260,6 +354,43 @@ impl TryInto<indradb::VertexQuery> for crate::VertexQuery {
                     inner: Box::new((*inner).try_into()?),
                 })
             }
+            crate::VertexQueryVariant::Traversal(q) => {
+                let direction = q.direction().into();
+                let t = q.t.map(|t| t.try_into()).transpose()?;
+                let inner = required_field("inner", q.inner)?;
+                indradb::VertexQuery::Traversal(indradb::TraversalVertexQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    direction,
+                    t,
+                    min_depth: q.min_depth,
+                    max_depth: q.max_depth,
+                    limit: q.limit,
+                })
+            }
+            crate::VertexQueryVariant::Union(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let other = required_field("other", q.other)?;
+                indradb::VertexQuery::Union(indradb::UnionVertexQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    other: Box::new((*other).try_into()?),
+                })
+            }
+            crate::VertexQueryVariant::Intersect(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let other = required_field("other", q.other)?;
+                indradb::VertexQuery::Intersect(indradb::IntersectVertexQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    other: Box::new((*other).try_into()?),
+                })
+            }
+            crate::VertexQueryVariant::Except(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let other = required_field("other", q.other)?;
+                indradb::VertexQuery::Except(indradb::ExceptVertexQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    other: Box::new((*other).try_into()?),
+                })
+            }
             crate::VertexQueryVariant::PropertyPresence(q) => {
                 let name = required_field("name", q.name)?;
                 indradb::VertexQuery::PropertyPresence(indradb::PropertyPresenceVertexQuery { name: name.try_into()? })
@@ -292,6 +423,25 @@ impl TryInto<indradb::VertexQuery> for crate::VertexQuery {
                     equal: q.equal,
                 })
             }
+            crate::VertexQueryVariant::PropertyValueRange(q) => {
+                let name = required_field("name", q.name)?;
+                let range = required_field("range", q.range)?;
+                indradb::VertexQuery::PropertyValueRange(indradb::PropertyValueRangeVertexQuery {
+                    name: name.try_into()?,
+                    range: range.try_into()?,
+                })
+            }
+            crate::VertexQueryVariant::PipePropertyValueRange(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let name = required_field("name", q.name)?;
+                let range = required_field("range", q.range)?;
+                indradb::VertexQuery::PipePropertyValueRange(indradb::PipePropertyValueRangeVertexQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    name: name.try_into()?,
+                    range: range.try_into()?,
+                    within: q.within,
+                })
+            }
         })
     }
 }
@@ -301,6 +451,7 @@ impl From<indradb::VertexPropertyQuery> for crate::VertexPropertyQuery {
         crate::VertexPropertyQuery {
             inner: Some(q.inner.into()),
             name: Some(q.name.into()),
+            pagination: None,
         }
     }
 }
@@ -337,6 +488,27 @@ impl From<indradb::EdgeQuery> for crate::EdgeQuery {
                     proto_q.set_direction(q.direction.into());
                     crate::EdgeQueryVariant::Pipe(Box::new(proto_q))
                 }
+                indradb::EdgeQuery::Union(q) => {
+                    let proto_q = crate::UnionEdgeQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        other: Some(Box::new((*q.other).into())),
+                    };
+                    crate::EdgeQueryVariant::Union(Box::new(proto_q))
+                }
+                indradb::EdgeQuery::Intersect(q) => {
+                    let proto_q = crate::IntersectEdgeQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        other: Some(Box::new((*q.other).into())),
+                    };
+                    crate::EdgeQueryVariant::Intersect(Box::new(proto_q))
+                }
+                indradb::EdgeQuery::Except(q) => {
+                    let proto_q = crate::ExceptEdgeQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        other: Some(Box::new((*q.other).into())),
+                    };
+                    crate::EdgeQueryVariant::Except(Box::new(proto_q))
+                }
                 indradb::EdgeQuery::PropertyPresence(q) => {
                     let proto_q = crate::PropertyPresenceEdgeQuery {
                         name: Some(q.name.into()),
@@ -367,7 +539,24 @@ impl From<indradb::EdgeQuery> for crate::EdgeQuery {
                     };
                     crate::EdgeQueryVariant::PipePropertyValue(Box::new(proto_q))
                 }
+                indradb::EdgeQuery::PropertyValueRange(q) => {
+                    let proto_q = crate::PropertyValueRangeEdgeQuery {
+                        name: Some(q.name.into()),
+                        range: Some(q.range.into()),
+                    };
+                    crate::EdgeQueryVariant::PropertyValueRange(proto_q)
+                }
+                indradb::EdgeQuery::PipePropertyValueRange(q) => {
+                    let proto_q = crate::PipePropertyValueRangeEdgeQuery {
+                        inner: Some(Box::new((*q.inner).into())),
+                        name: Some(q.name.into()),
+                        range: Some(q.range.into()),
+                        within: q.within,
+                    };
+                    crate::EdgeQueryVariant::PipePropertyValueRange(Box::new(proto_q))
+                }
             }),
+            pagination: None,
         }
     }
 }
@@ -398,6 +587,30 @@ impl TryInto<indradb::EdgeQuery> for crate::EdgeQuery {
                     inner: Box::new((*inner).try_into()?),
                 })
             }
+            crate::EdgeQueryVariant::Union(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let other = required_field("other", q.other)?;
+                indradb::EdgeQuery::Union(indradb::UnionEdgeQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    other: Box::new((*other).try_into()?),
+                })
+            }
+            crate::EdgeQueryVariant::Intersect(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let other = required_field("other", q.other)?;
+                indradb::EdgeQuery::Intersect(indradb::IntersectEdgeQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    other: Box::new((*other).try_into()?),
+                })
+            }
+            crate::EdgeQueryVariant::Except(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let other = required_field("other", q.other)?;
+                indradb::EdgeQuery::Except(indradb::ExceptEdgeQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    other: Box::new((*other).try_into()?),
+                })
+            }
             crate::EdgeQueryVariant::PropertyPresence(q) => {
                 let name = required_field("name", q.name)?;
                 indradb::EdgeQuery::PropertyPresence(indradb::PropertyPresenceEdgeQuery { name: name.try_into()? })
@@ -430,6 +643,25 @@ impl TryInto<indradb::EdgeQuery> for crate::EdgeQuery {
                     equal: q.equal,
                 })
             }
+            crate::EdgeQueryVariant::PropertyValueRange(q) => {
+                let name = required_field("name", q.name)?;
+                let range = required_field("range", q.range)?;
+                indradb::EdgeQuery::PropertyValueRange(indradb::PropertyValueRangeEdgeQuery {
+                    name: name.try_into()?,
+                    range: range.try_into()?,
+                })
+            }
+            crate::EdgeQueryVariant::PipePropertyValueRange(q) => {
+                let inner = required_field("inner", q.inner)?;
+                let name = required_field("name", q.name)?;
+                let range = required_field("range", q.range)?;
+                indradb::EdgeQuery::PipePropertyValueRange(indradb::PipePropertyValueRangeEdgeQuery {
+                    inner: Box::new((*inner).try_into()?),
+                    name: name.try_into()?,
+                    range: range.try_into()?,
+                    within: q.within,
+                })
+            }
         })
     }
 }
@@ -439,6 +671,7 @@ impl From<indradb::EdgePropertyQuery> for crate::EdgePropertyQuery {
         crate::EdgePropertyQuery {
             inner: Some(q.inner.into()),
             name: Some(q.name.into()),
+            pagination: None,
         }
     }
 }
@@ -455,6 +688,48 @@ impl TryInto<indradb::EdgePropertyQuery> for crate::EdgePropertyQuery {
     }
 }
 
+impl From<indradb::PathQuery> for crate::PathQuery {
+    fn from(q: indradb::PathQuery) -> Self {
+        crate::PathQuery {
+            start_id: Some(q.start_id.into()),
+            end_id: Some(q.end_id.into()),
+            t: q.t.map(|t| t.into()),
+            max_depth: q.max_depth,
+            limit: q.limit,
+        }
+    }
+}
+
+impl TryInto<indradb::PathQuery> for crate::PathQuery {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::PathQuery, Self::Error> {
+        Ok(indradb::PathQuery {
+            start_id: required_field("start_id", self.start_id)?.try_into()?,
+            end_id: required_field("end_id", self.end_id)?.try_into()?,
+            t: self.t.map(|t| t.try_into()).transpose()?,
+            max_depth: self.max_depth,
+            limit: self.limit,
+        })
+    }
+}
+
+impl From<Vec<indradb::Edge>> for crate::Path {
+    fn from(edges: Vec<indradb::Edge>) -> Self {
+        crate::Path {
+            edges: edges.into_iter().map(|edge| edge.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<Vec<indradb::Edge>> for crate::Path {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<indradb::Edge>, Self::Error> {
+        self.edges.into_iter().map(|edge| edge.try_into()).collect()
+    }
+}
+
 impl From<indradb::EdgeDirection> for crate::EdgeDirection {
     fn from(direction: indradb::EdgeDirection) -> Self {
         match direction {
@@ -632,6 +907,537 @@ impl TryInto<indradb::Identifier> for crate::IndexPropertyRequest {
     }
 }
 
+impl TryInto<indradb::Identifier> for crate::DropIndexRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Identifier, Self::Error> {
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok(name)
+    }
+}
+
+impl TryInto<Vec<indradb::Identifier>> for crate::IndexedPropertiesResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<indradb::Identifier>, Self::Error> {
+        self.names.into_iter().map(|name| name.try_into()).collect()
+    }
+}
+
+impl From<Vec<indradb::Identifier>> for crate::IndexedPropertiesResponse {
+    fn from(names: Vec<indradb::Identifier>) -> Self {
+        crate::IndexedPropertiesResponse {
+            names: names.into_iter().map(|name| name.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<indradb::Identifier> for crate::GetIndexStatusRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Identifier, Self::Error> {
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok(name)
+    }
+}
+
+impl From<indradb::IndexStatus> for crate::IndexStatus {
+    fn from(status: indradb::IndexStatus) -> Self {
+        crate::IndexStatus {
+            status: Some(match status {
+                indradb::IndexStatus::Building { processed, total } => {
+                    crate::IndexStatusVariant::Building(crate::IndexBuildProgress { processed, total })
+                }
+                indradb::IndexStatus::Ready => crate::IndexStatusVariant::Ready(()),
+                indradb::IndexStatus::Failed(message) => crate::IndexStatusVariant::Failed(message),
+            }),
+        }
+    }
+}
+
+impl TryInto<indradb::IndexStatus> for crate::IndexStatus {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::IndexStatus, Self::Error> {
+        Ok(match required_field("status", self.status)? {
+            crate::IndexStatusVariant::Building(progress) => indradb::IndexStatus::Building {
+                processed: progress.processed,
+                total: progress.total,
+            },
+            crate::IndexStatusVariant::Ready(()) => indradb::IndexStatus::Ready,
+            crate::IndexStatusVariant::Failed(message) => indradb::IndexStatus::Failed(message),
+        })
+    }
+}
+
+impl TryInto<Option<indradb::IndexStatus>> for crate::GetIndexStatusResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Option<indradb::IndexStatus>, Self::Error> {
+        self.status.map(|status| status.try_into()).transpose()
+    }
+}
+
+impl From<Option<indradb::IndexStatus>> for crate::GetIndexStatusResponse {
+    fn from(status: Option<indradb::IndexStatus>) -> Self {
+        crate::GetIndexStatusResponse {
+            status: status.map(|status| status.into()),
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, indradb::Identifier)> for crate::IndexTypePropertyRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, indradb::Identifier), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok((t, name))
+    }
+}
+
+impl TryInto<(indradb::Identifier, indradb::Identifier)> for crate::DropTypeIndexRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, indradb::Identifier), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok((t, name))
+    }
+}
+
+impl TryInto<(indradb::Identifier, indradb::Identifier)> for crate::IndexedTypeProperty {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, indradb::Identifier), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok((t, name))
+    }
+}
+
+impl From<(indradb::Identifier, indradb::Identifier)> for crate::IndexedTypeProperty {
+    fn from((t, name): (indradb::Identifier, indradb::Identifier)) -> Self {
+        crate::IndexedTypeProperty {
+            t: Some(t.into()),
+            name: Some(name.into()),
+        }
+    }
+}
+
+impl TryInto<Vec<(indradb::Identifier, indradb::Identifier)>> for crate::IndexedTypePropertiesResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<(indradb::Identifier, indradb::Identifier)>, Self::Error> {
+        self.type_properties
+            .into_iter()
+            .map(|type_property| type_property.try_into())
+            .collect()
+    }
+}
+
+impl From<Vec<(indradb::Identifier, indradb::Identifier)>> for crate::IndexedTypePropertiesResponse {
+    fn from(type_properties: Vec<(indradb::Identifier, indradb::Identifier)>) -> Self {
+        crate::IndexedTypePropertiesResponse {
+            type_properties: type_properties
+                .into_iter()
+                .map(|type_property| type_property.into())
+                .collect(),
+        }
+    }
+}
+
+impl TryInto<Vec<indradb::Identifier>> for crate::IndexCompositePropertiesRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<indradb::Identifier>, Self::Error> {
+        self.names.into_iter().map(|name| name.try_into()).collect()
+    }
+}
+
+impl TryInto<Vec<indradb::Identifier>> for crate::DropCompositeIndexRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<indradb::Identifier>, Self::Error> {
+        self.names.into_iter().map(|name| name.try_into()).collect()
+    }
+}
+
+impl TryInto<Vec<indradb::Identifier>> for crate::CompositeIndex {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<indradb::Identifier>, Self::Error> {
+        self.names.into_iter().map(|name| name.try_into()).collect()
+    }
+}
+
+impl From<Vec<indradb::Identifier>> for crate::CompositeIndex {
+    fn from(names: Vec<indradb::Identifier>) -> Self {
+        crate::CompositeIndex {
+            names: names.into_iter().map(|name| name.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<Vec<Vec<indradb::Identifier>>> for crate::IndexedCompositePropertiesResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<Vec<indradb::Identifier>>, Self::Error> {
+        self.composite_indexes
+            .into_iter()
+            .map(|composite_index| composite_index.try_into())
+            .collect()
+    }
+}
+
+impl From<Vec<Vec<indradb::Identifier>>> for crate::IndexedCompositePropertiesResponse {
+    fn from(composite_indexes: Vec<Vec<indradb::Identifier>>) -> Self {
+        crate::IndexedCompositePropertiesResponse {
+            composite_indexes: composite_indexes
+                .into_iter()
+                .map(|composite_index| composite_index.into())
+                .collect(),
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, Option<indradb::Identifier>)> for crate::UniqueConstraint {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, Option<indradb::Identifier>), Self::Error> {
+        let name = required_field("name", self.name)?.try_into()?;
+        let t = self.t.map(|t| t.try_into()).transpose()?;
+        Ok((name, t))
+    }
+}
+
+impl From<(indradb::Identifier, Option<indradb::Identifier>)> for crate::UniqueConstraint {
+    fn from((name, t): (indradb::Identifier, Option<indradb::Identifier>)) -> Self {
+        crate::UniqueConstraint {
+            name: Some(name.into()),
+            t: t.map(|t| t.into()),
+        }
+    }
+}
+
+impl TryInto<Vec<(indradb::Identifier, Option<indradb::Identifier>)>> for crate::UniqueConstraintsResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Vec<(indradb::Identifier, Option<indradb::Identifier>)>, Self::Error> {
+        self.constraints
+            .into_iter()
+            .map(|constraint| constraint.try_into())
+            .collect()
+    }
+}
+
+impl From<Vec<(indradb::Identifier, Option<indradb::Identifier>)>> for crate::UniqueConstraintsResponse {
+    fn from(constraints: Vec<(indradb::Identifier, Option<indradb::Identifier>)>) -> Self {
+        crate::UniqueConstraintsResponse {
+            constraints: constraints.into_iter().map(|constraint| constraint.into()).collect(),
+        }
+    }
+}
+
+impl From<indradb::JsonType> for crate::JsonType {
+    fn from(t: indradb::JsonType) -> Self {
+        match t {
+            indradb::JsonType::Any => crate::JsonType::Any,
+            indradb::JsonType::Null => crate::JsonType::Null,
+            indradb::JsonType::Boolean => crate::JsonType::Boolean,
+            indradb::JsonType::Integer => crate::JsonType::Integer,
+            indradb::JsonType::Number => crate::JsonType::Number,
+            indradb::JsonType::String => crate::JsonType::String,
+            indradb::JsonType::Array => crate::JsonType::Array,
+            indradb::JsonType::Object => crate::JsonType::Object,
+        }
+    }
+}
+
+impl From<crate::JsonType> for indradb::JsonType {
+    fn from(t: crate::JsonType) -> Self {
+        match t {
+            crate::JsonType::Any => indradb::JsonType::Any,
+            crate::JsonType::Null => indradb::JsonType::Null,
+            crate::JsonType::Boolean => indradb::JsonType::Boolean,
+            crate::JsonType::Integer => indradb::JsonType::Integer,
+            crate::JsonType::Number => indradb::JsonType::Number,
+            crate::JsonType::String => indradb::JsonType::String,
+            crate::JsonType::Array => indradb::JsonType::Array,
+            crate::JsonType::Object => indradb::JsonType::Object,
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, indradb::PropertySchema)> for crate::PropertySchema {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, indradb::PropertySchema), Self::Error> {
+        let t = self.t().into();
+        let name = required_field("name", self.name)?.try_into()?;
+        Ok((name, indradb::PropertySchema::new(t, self.required)))
+    }
+}
+
+impl From<(indradb::Identifier, indradb::PropertySchema)> for crate::PropertySchema {
+    fn from((name, property): (indradb::Identifier, indradb::PropertySchema)) -> Self {
+        let t: crate::JsonType = property.t.into();
+        crate::PropertySchema {
+            name: Some(name.into()),
+            t: t as i32,
+            required: property.required,
+        }
+    }
+}
+
+// Converts the properties of a vertex or edge type's schema, which are keyed
+// by name, into a list sorted by name.
+fn property_schemas_to_proto(
+    properties: HashMap<indradb::Identifier, indradb::PropertySchema>,
+) -> Vec<crate::PropertySchema> {
+    let mut properties: Vec<(indradb::Identifier, indradb::PropertySchema)> = properties.into_iter().collect();
+    properties.sort_by(|a, b| a.0.cmp(&b.0));
+    properties.into_iter().map(|property| property.into()).collect()
+}
+
+fn identifiers_to_proto(identifiers: HashSet<indradb::Identifier>) -> Vec<crate::Identifier> {
+    let mut identifiers: Vec<indradb::Identifier> = identifiers.into_iter().collect();
+    identifiers.sort();
+    identifiers.into_iter().map(|t| t.into()).collect()
+}
+
+impl TryInto<(indradb::Identifier, indradb::VertexSchema)> for crate::VertexSchema {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, indradb::VertexSchema), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let properties = self
+            .properties
+            .into_iter()
+            .map(|property| property.try_into())
+            .collect::<Result<_, _>>()?;
+        Ok((t, indradb::VertexSchema { properties }))
+    }
+}
+
+impl From<(indradb::Identifier, indradb::VertexSchema)> for crate::VertexSchema {
+    fn from((t, schema): (indradb::Identifier, indradb::VertexSchema)) -> Self {
+        crate::VertexSchema {
+            t: Some(t.into()),
+            properties: property_schemas_to_proto(schema.properties),
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, indradb::EdgeSchema)> for crate::EdgeSchema {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, indradb::EdgeSchema), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let outbound_types = self
+            .outbound_types
+            .into_iter()
+            .map(|t| t.try_into())
+            .collect::<Result<_, _>>()?;
+        let inbound_types = self
+            .inbound_types
+            .into_iter()
+            .map(|t| t.try_into())
+            .collect::<Result<_, _>>()?;
+        let properties = self
+            .properties
+            .into_iter()
+            .map(|property| property.try_into())
+            .collect::<Result<_, _>>()?;
+        Ok((
+            t,
+            indradb::EdgeSchema {
+                outbound_types,
+                inbound_types,
+                properties,
+            },
+        ))
+    }
+}
+
+impl From<(indradb::Identifier, indradb::EdgeSchema)> for crate::EdgeSchema {
+    fn from((t, schema): (indradb::Identifier, indradb::EdgeSchema)) -> Self {
+        crate::EdgeSchema {
+            t: Some(t.into()),
+            outbound_types: identifiers_to_proto(schema.outbound_types),
+            inbound_types: identifiers_to_proto(schema.inbound_types),
+            properties: property_schemas_to_proto(schema.properties),
+        }
+    }
+}
+
+impl TryInto<indradb::Schema> for crate::Schema {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Schema, Self::Error> {
+        let vertex_types = self
+            .vertex_types
+            .into_iter()
+            .map(|schema| schema.try_into())
+            .collect::<Result<_, _>>()?;
+        let edge_types = self
+            .edge_types
+            .into_iter()
+            .map(|schema| schema.try_into())
+            .collect::<Result<_, _>>()?;
+        Ok(indradb::Schema {
+            vertex_types,
+            edge_types,
+        })
+    }
+}
+
+impl From<indradb::Schema> for crate::Schema {
+    fn from(schema: indradb::Schema) -> Self {
+        let mut vertex_types: Vec<(indradb::Identifier, indradb::VertexSchema)> =
+            schema.vertex_types.into_iter().collect();
+        vertex_types.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut edge_types: Vec<(indradb::Identifier, indradb::EdgeSchema)> = schema.edge_types.into_iter().collect();
+        edge_types.sort_by(|a, b| a.0.cmp(&b.0));
+        crate::Schema {
+            vertex_types: vertex_types.into_iter().map(|schema| schema.into()).collect(),
+            edge_types: edge_types.into_iter().map(|schema| schema.into()).collect(),
+        }
+    }
+}
+
+impl TryInto<Option<indradb::Schema>> for crate::SetSchemaRequest {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Option<indradb::Schema>, Self::Error> {
+        self.schema.map(|schema| schema.try_into()).transpose()
+    }
+}
+
+impl From<Option<indradb::Schema>> for crate::SetSchemaRequest {
+    fn from(schema: Option<indradb::Schema>) -> Self {
+        crate::SetSchemaRequest {
+            schema: schema.map(|schema| schema.into()),
+        }
+    }
+}
+
+impl TryInto<Option<indradb::Schema>> for crate::GetSchemaResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<Option<indradb::Schema>, Self::Error> {
+        self.schema.map(|schema| schema.try_into()).transpose()
+    }
+}
+
+impl From<Option<indradb::Schema>> for crate::GetSchemaResponse {
+    fn from(schema: Option<indradb::Schema>) -> Self {
+        crate::GetSchemaResponse {
+            schema: schema.map(|schema| schema.into()),
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, u64)> for crate::TypeCount {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, u64), Self::Error> {
+        Ok((required_field("t", self.t)?.try_into()?, self.count))
+    }
+}
+
+impl From<(indradb::Identifier, u64)> for crate::TypeCount {
+    fn from((t, count): (indradb::Identifier, u64)) -> Self {
+        crate::TypeCount {
+            t: Some(t.into()),
+            count,
+        }
+    }
+}
+
+impl TryInto<(indradb::Identifier, HashSet<indradb::Identifier>)> for crate::TypeProperties {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<(indradb::Identifier, HashSet<indradb::Identifier>), Self::Error> {
+        let t = required_field("t", self.t)?.try_into()?;
+        let names = self
+            .names
+            .into_iter()
+            .map(|name| name.try_into())
+            .collect::<Result<_, _>>()?;
+        Ok((t, names))
+    }
+}
+
+impl From<(indradb::Identifier, HashSet<indradb::Identifier>)> for crate::TypeProperties {
+    fn from((t, names): (indradb::Identifier, HashSet<indradb::Identifier>)) -> Self {
+        crate::TypeProperties {
+            t: Some(t.into()),
+            names: identifiers_to_proto(names),
+        }
+    }
+}
+
+impl TryInto<indradb::SchemaSummary> for crate::GetSchemaSummaryResponse {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::SchemaSummary, Self::Error> {
+        let vertex_types = self
+            .vertex_types
+            .into_iter()
+            .map(|count| count.try_into())
+            .collect::<Result<_, _>>()?;
+        let edge_types = self
+            .edge_types
+            .into_iter()
+            .map(|count| count.try_into())
+            .collect::<Result<_, _>>()?;
+        let vertex_properties = self
+            .vertex_properties
+            .into_iter()
+            .map(|properties| properties.try_into())
+            .collect::<Result<_, _>>()?;
+        let edge_properties = self
+            .edge_properties
+            .into_iter()
+            .map(|properties| properties.try_into())
+            .collect::<Result<_, _>>()?;
+        Ok(indradb::SchemaSummary {
+            vertex_types,
+            edge_types,
+            vertex_properties,
+            edge_properties,
+        })
+    }
+}
+
+impl From<indradb::SchemaSummary> for crate::GetSchemaSummaryResponse {
+    fn from(summary: indradb::SchemaSummary) -> Self {
+        let mut vertex_types: Vec<(indradb::Identifier, u64)> = summary.vertex_types.into_iter().collect();
+        vertex_types.sort();
+        let mut edge_types: Vec<(indradb::Identifier, u64)> = summary.edge_types.into_iter().collect();
+        edge_types.sort();
+        let mut vertex_properties: Vec<(indradb::Identifier, HashSet<indradb::Identifier>)> =
+            summary.vertex_properties.into_iter().collect();
+        vertex_properties.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut edge_properties: Vec<(indradb::Identifier, HashSet<indradb::Identifier>)> =
+            summary.edge_properties.into_iter().collect();
+        edge_properties.sort_by(|a, b| a.0.cmp(&b.0));
+        crate::GetSchemaSummaryResponse {
+            vertex_types: vertex_types.into_iter().map(|count| count.into()).collect(),
+            edge_types: edge_types.into_iter().map(|count| count.into()).collect(),
+            vertex_properties: vertex_properties
+                .into_iter()
+                .map(|properties| properties.into())
+                .collect(),
+            edge_properties: edge_properties
+                .into_iter()
+                .map(|properties| properties.into())
+                .collect(),
+        }
+    }
+}
+
 impl TryInto<(Uuid, Option<indradb::Identifier>, indradb::EdgeDirection)> for crate::GetEdgeCountRequest {
     type Error = ConversionError;
 
@@ -692,6 +1498,121 @@ impl From<(indradb::EdgePropertyQuery, serde_json::Value)> for crate::SetEdgePro
     }
 }
 
+impl From<(u32, Option<indradb::ContinuationToken>)> for crate::Pagination {
+    fn from(value: (u32, Option<indradb::ContinuationToken>)) -> Self {
+        crate::Pagination {
+            limit: value.0,
+            continuation_token: from_continuation_token(value.1),
+        }
+    }
+}
+
+impl From<crate::Pagination> for (u32, Option<indradb::ContinuationToken>) {
+    fn from(value: crate::Pagination) -> Self {
+        (value.limit, to_continuation_token(value.continuation_token))
+    }
+}
+
+impl From<indradb::Page<indradb::Vertex>> for crate::VertexPage {
+    fn from(page: indradb::Page<indradb::Vertex>) -> Self {
+        crate::VertexPage {
+            vertices: page.results.into_iter().map(|v| v.into()).collect(),
+            continuation_token: from_continuation_token(page.token),
+        }
+    }
+}
+
+impl TryInto<indradb::Page<indradb::Vertex>> for crate::VertexPage {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Page<indradb::Vertex>, Self::Error> {
+        let vertices: Result<Vec<indradb::Vertex>, ConversionError> =
+            self.vertices.into_iter().map(|v| v.try_into()).collect();
+        Ok(indradb::Page::new(
+            vertices?,
+            to_continuation_token(self.continuation_token),
+        ))
+    }
+}
+
+impl From<indradb::Page<indradb::Edge>> for crate::EdgePage {
+    fn from(page: indradb::Page<indradb::Edge>) -> Self {
+        crate::EdgePage {
+            edges: page.results.into_iter().map(|e| e.into()).collect(),
+            continuation_token: from_continuation_token(page.token),
+        }
+    }
+}
+
+impl TryInto<indradb::Page<indradb::Edge>> for crate::EdgePage {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Page<indradb::Edge>, Self::Error> {
+        let edges: Result<Vec<indradb::Edge>, ConversionError> = self.edges.into_iter().map(|e| e.try_into()).collect();
+        Ok(indradb::Page::new(
+            edges?,
+            to_continuation_token(self.continuation_token),
+        ))
+    }
+}
+
+impl From<indradb::Page<indradb::VertexProperty>> for crate::VertexPropertyPage {
+    fn from(page: indradb::Page<indradb::VertexProperty>) -> Self {
+        crate::VertexPropertyPage {
+            properties: page.results.into_iter().map(|p| p.into()).collect(),
+            continuation_token: from_continuation_token(page.token),
+        }
+    }
+}
+
+impl TryInto<indradb::Page<indradb::VertexProperty>> for crate::VertexPropertyPage {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Page<indradb::VertexProperty>, Self::Error> {
+        let properties: Result<Vec<indradb::VertexProperty>, ConversionError> =
+            self.properties.into_iter().map(|p| p.try_into()).collect();
+        Ok(indradb::Page::new(
+            properties?,
+            to_continuation_token(self.continuation_token),
+        ))
+    }
+}
+
+impl From<indradb::Page<indradb::EdgeProperty>> for crate::EdgePropertyPage {
+    fn from(page: indradb::Page<indradb::EdgeProperty>) -> Self {
+        crate::EdgePropertyPage {
+            properties: page.results.into_iter().map(|p| p.into()).collect(),
+            continuation_token: from_continuation_token(page.token),
+        }
+    }
+}
+
+impl TryInto<indradb::Page<indradb::EdgeProperty>> for crate::EdgePropertyPage {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<indradb::Page<indradb::EdgeProperty>, Self::Error> {
+        let properties: Result<Vec<indradb::EdgeProperty>, ConversionError> =
+            self.properties.into_iter().map(|p| p.try_into()).collect();
+        Ok(indradb::Page::new(
+            properties?,
+            to_continuation_token(self.continuation_token),
+        ))
+    }
+}
+
+// Empty bytes are used to represent a missing token.
+fn to_continuation_token(bytes: Vec<u8>) -> Option<indradb::ContinuationToken> {
+    if bytes.is_empty() {
+        None
+    } else {
+        Some(indradb::ContinuationToken::from_bytes(bytes))
+    }
+}
+
+fn from_continuation_token(token: Option<indradb::ContinuationToken>) -> Vec<u8> {
+    token.map(|token| token.into_bytes()).unwrap_or_default()
+}
+
 fn to_chrono_time(ts: prost_types::Timestamp) -> DateTime<Utc> {
     Utc.timestamp(ts.seconds, ts.nanos as u32)
 }
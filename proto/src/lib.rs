@@ -6,7 +6,9 @@ tonic::include_proto!("indradb");
 
 pub use bulk_insert_item::Item as BulkInsertItemVariant;
 pub use edge_query::Query as EdgeQueryVariant;
+pub use index_status::Status as IndexStatusVariant;
 pub use indra_db_client::IndraDbClient as ProtoClient;
+pub use property_value_range::Range as PropertyValueRangeVariant;
 pub use vertex_query::Query as VertexQueryVariant;
 
 mod converters;
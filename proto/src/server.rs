@@ -1,8 +1,12 @@
+// Handlers return tonic's `Status` as their error, which is large, but it's
+// what the generated service traits expect.
+#![allow(clippy::result_large_err)]
+
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::error::Error as StdError;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -16,34 +20,78 @@ use tonic::{Request, Response, Status, Streaming};
 
 const CHANNEL_CAPACITY: usize = 100;
 
-async fn send<IT, PT>(tx: mpsc::Sender<Result<PT, Status>>, result: Result<Vec<IT>, indradb::Error>)
+// Sends query results to the client as they're produced. This blocks, so it
+// should be run via `spawn_blocking`.
+fn send<IT, PT>(tx: mpsc::Sender<Result<PT, Status>>, result: Result<indradb::DynIter<'_, IT>, indradb::Error>)
 where
     IT: Into<PT>,
 {
-    match map_indradb_result(result) {
-        Ok(values) => {
-            for value in values {
-                if let Err(err) = tx.send(Ok(value.into())).await {
-                    eprintln!("could not send message to client: {}", err);
-                }
-            }
-        }
+    let iter = match map_indradb_result(result) {
+        Ok(iter) => iter,
         Err(err) => {
-            if let Err(err) = tx.send(Err(err)).await {
+            if let Err(err) = tx.blocking_send(Err(err)) {
                 eprintln!("could not send message to client: {}", err);
             }
+            return;
+        }
+    };
+
+    for value in iter {
+        let value = map_indradb_result(value).map(Into::into);
+        let is_err = value.is_err();
+
+        // Stop iterating if the client has gone away, or after the first
+        // error
+        if let Err(err) = tx.blocking_send(value) {
+            eprintln!("could not send message to client: {}", err);
+            return;
+        }
+        if is_err {
+            return;
         }
     }
 }
 
 fn map_indradb_result<T>(res: Result<T, indradb::Error>) -> Result<T, Status> {
-    res.map_err(|err| Status::internal(format!("{}", err)))
+    res.map_err(|err| match err {
+        indradb::Error::InvalidContinuationToken => Status::invalid_argument(format!("{}", err)),
+        indradb::Error::InvalidPageLimit => Status::invalid_argument(format!("{}", err)),
+        indradb::Error::Unsupported => Status::unimplemented(format!("{}", err)),
+        indradb::Error::IndexNotReady => Status::failed_precondition(format!("{}", err)),
+        indradb::Error::ConstraintViolation => Status::already_exists(format!("{}", err)),
+        indradb::Error::SchemaViolation(_) => Status::invalid_argument(format!("{}", err)),
+        _ => Status::internal(format!("{}", err)),
+    })
+}
+
+// Gets the page limit and continuation token from a query's pagination.
+fn pagination(pagination: Option<crate::Pagination>) -> (u32, Option<indradb::ContinuationToken>) {
+    match pagination {
+        Some(pagination) => pagination.into(),
+        None => (u32::MAX, None),
+    }
 }
 
 fn map_conversion_result<T>(res: Result<T, crate::ConversionError>) -> Result<T, Status> {
     res.map_err(|err| Status::invalid_argument(format!("{}", err)))
 }
 
+// Resolves the path of a backup requested by a client, which must be
+// relative to the backup directory, and can't lead out of it.
+fn backup_path(backup_dir: Option<&Path>, path: &str) -> Result<PathBuf, Status> {
+    let backup_dir = backup_dir.ok_or_else(|| Status::permission_denied("backups are not enabled on this server"))?;
+    let path = Path::new(path);
+    let is_contained = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if path.as_os_str().is_empty() || !is_contained {
+        return Err(Status::invalid_argument(
+            "backup paths must be relative to the backup directory, and can't contain `..`",
+        ));
+    }
+    Ok(backup_dir.join(path))
+}
+
 /// An error that occurred while initializing the server with plugins enabled.
 #[derive(Debug)]
 pub enum InitError {
@@ -137,6 +185,7 @@ struct Plugins {
 pub struct Server<D: indradb::Datastore + Send + Sync + 'static> {
     datastore: Arc<D>,
     plugins: Arc<Plugins>,
+    backup_dir: Option<Arc<PathBuf>>,
 }
 
 impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
@@ -148,6 +197,7 @@ impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
         Self {
             datastore,
             plugins: Arc::new(Plugins::default()),
+            backup_dir: None,
         }
     }
 
@@ -194,8 +244,20 @@ impl<D: indradb::Datastore + Send + Sync + 'static> Server<D> {
                 libraries,
                 entries: plugin_entries,
             }),
+            backup_dir: None,
         })
     }
+
+    /// Enables backups, which clients can only make to paths within a
+    /// directory. Without one, the `Backup` RPC returns `PERMISSION_DENIED`.
+    ///
+    /// # Arguments
+    /// * `backup_dir`: The directory that backups are made to. Paths in
+    ///   backup requests are relative to it.
+    pub fn with_backup_dir(mut self, backup_dir: PathBuf) -> Self {
+        self.backup_dir = Some(Arc::new(backup_dir));
+        self
+    }
 }
 
 #[tonic::async_trait]
@@ -232,12 +294,23 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let datastore = self.datastore.clone();
         let q: indradb::VertexQuery = map_conversion_result(request.into_inner().try_into())?;
         let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::spawn(async move {
-            send(tx, datastore.get_vertices(q)).await;
+        tokio::task::spawn_blocking(move || {
+            send(tx, datastore.get_vertices_iter(q));
         });
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
+    async fn get_vertex_page(
+        &self,
+        request: Request<crate::VertexQuery>,
+    ) -> Result<Response<crate::VertexPage>, Status> {
+        let request = request.into_inner();
+        let (limit, token) = pagination(request.pagination.clone());
+        let q: indradb::VertexQuery = map_conversion_result(request.try_into())?;
+        let page = map_indradb_result(self.datastore.get_vertices_page(q, limit, token))?;
+        Ok(Response::new(page.into()))
+    }
+
     async fn delete_vertices(&self, request: Request<crate::VertexQuery>) -> Result<Response<()>, Status> {
         let q: indradb::VertexQuery = map_conversion_result(request.into_inner().try_into())?;
         map_indradb_result(self.datastore.delete_vertices(q))?;
@@ -249,6 +322,15 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         Ok(Response::new(crate::CountResponse { count: res }))
     }
 
+    async fn count_vertices(
+        &self,
+        request: Request<crate::VertexQuery>,
+    ) -> Result<Response<crate::CountResponse>, Status> {
+        let q: indradb::VertexQuery = map_conversion_result(request.into_inner().try_into())?;
+        let res = map_indradb_result(self.datastore.count_vertices(q))?;
+        Ok(Response::new(crate::CountResponse { count: res }))
+    }
+
     async fn create_edge(&self, request: Request<crate::EdgeKey>) -> Result<Response<crate::CreateResponse>, Status> {
         let key = map_conversion_result(request.into_inner().try_into())?;
         let res = map_indradb_result(self.datastore.create_edge(&key))?;
@@ -260,8 +342,31 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let datastore = self.datastore.clone();
         let q: indradb::EdgeQuery = map_conversion_result(request.into_inner().try_into())?;
         let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::spawn(async move {
-            send(tx, datastore.get_edges(q)).await;
+        tokio::task::spawn_blocking(move || {
+            send(tx, datastore.get_edges_iter(q));
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn get_edge_page(&self, request: Request<crate::EdgeQuery>) -> Result<Response<crate::EdgePage>, Status> {
+        let request = request.into_inner();
+        let (limit, token) = pagination(request.pagination.clone());
+        let q: indradb::EdgeQuery = map_conversion_result(request.try_into())?;
+        let page = map_indradb_result(self.datastore.get_edges_page(q, limit, token))?;
+        Ok(Response::new(page.into()))
+    }
+
+    type GetPathsStream = Pin<Box<dyn Stream<Item = Result<crate::Path, Status>> + Send + Sync + 'static>>;
+    async fn get_paths(&self, request: Request<crate::PathQuery>) -> Result<Response<Self::GetPathsStream>, Status> {
+        let datastore = self.datastore.clone();
+        let q: indradb::PathQuery = map_conversion_result(request.into_inner().try_into())?;
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::task::spawn_blocking(move || {
+            let paths = datastore.get_paths(q);
+            send(
+                tx,
+                paths.map(|paths| -> indradb::DynIter<'_, Vec<indradb::Edge>> { Box::new(paths.into_iter().map(Ok)) }),
+            );
         });
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
@@ -281,6 +386,12 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         Ok(Response::new(crate::CountResponse { count: res }))
     }
 
+    async fn count_edges(&self, request: Request<crate::EdgeQuery>) -> Result<Response<crate::CountResponse>, Status> {
+        let q: indradb::EdgeQuery = map_conversion_result(request.into_inner().try_into())?;
+        let res = map_indradb_result(self.datastore.count_edges(q))?;
+        Ok(Response::new(crate::CountResponse { count: res }))
+    }
+
     type GetVertexPropertiesStream =
         Pin<Box<dyn Stream<Item = Result<crate::VertexProperty, Status>> + Send + Sync + 'static>>;
     async fn get_vertex_properties(
@@ -290,12 +401,23 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let datastore = self.datastore.clone();
         let q = map_conversion_result(request.into_inner().try_into())?;
         let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::spawn(async move {
-            send(tx, datastore.get_vertex_properties(q)).await;
+        tokio::task::spawn_blocking(move || {
+            send(tx, datastore.get_vertex_properties_iter(q));
         });
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
+    async fn get_vertex_property_page(
+        &self,
+        request: Request<crate::VertexPropertyQuery>,
+    ) -> Result<Response<crate::VertexPropertyPage>, Status> {
+        let request = request.into_inner();
+        let (limit, token) = pagination(request.pagination.clone());
+        let q: indradb::VertexPropertyQuery = map_conversion_result(request.try_into())?;
+        let page = map_indradb_result(self.datastore.get_vertex_properties_page(q, limit, token))?;
+        Ok(Response::new(page.into()))
+    }
+
     type GetAllVertexPropertiesStream =
         Pin<Box<dyn Stream<Item = Result<crate::VertexProperties, Status>> + Send + Sync + 'static>>;
     async fn get_all_vertex_properties(
@@ -305,8 +427,8 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let datastore = self.datastore.clone();
         let q: indradb::VertexQuery = map_conversion_result(request.into_inner().try_into())?;
         let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::spawn(async move {
-            send(tx, datastore.get_all_vertex_properties(q)).await;
+        tokio::task::spawn_blocking(move || {
+            send(tx, datastore.get_all_vertex_properties_iter(q));
         });
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
@@ -338,12 +460,23 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let datastore = self.datastore.clone();
         let q: indradb::EdgePropertyQuery = map_conversion_result(request.into_inner().try_into())?;
         let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::spawn(async move {
-            send(tx, datastore.get_edge_properties(q)).await;
+        tokio::task::spawn_blocking(move || {
+            send(tx, datastore.get_edge_properties_iter(q));
         });
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
+    async fn get_edge_property_page(
+        &self,
+        request: Request<crate::EdgePropertyQuery>,
+    ) -> Result<Response<crate::EdgePropertyPage>, Status> {
+        let request = request.into_inner();
+        let (limit, token) = pagination(request.pagination.clone());
+        let q: indradb::EdgePropertyQuery = map_conversion_result(request.try_into())?;
+        let page = map_indradb_result(self.datastore.get_edge_properties_page(q, limit, token))?;
+        Ok(Response::new(page.into()))
+    }
+
     type GetAllEdgePropertiesStream =
         Pin<Box<dyn Stream<Item = Result<crate::EdgeProperties, Status>> + Send + Sync + 'static>>;
     async fn get_all_edge_properties(
@@ -353,8 +486,8 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         let datastore = self.datastore.clone();
         let q: indradb::EdgeQuery = map_conversion_result(request.into_inner().try_into())?;
         let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
-        tokio::spawn(async move {
-            send(tx, datastore.get_all_edge_properties(q)).await;
+        tokio::task::spawn_blocking(move || {
+            send(tx, datastore.get_all_edge_properties_iter(q));
         });
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
@@ -397,6 +530,140 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
         Ok(Response::new(()))
     }
 
+    async fn index_property_in_background(
+        &self,
+        request: Request<crate::IndexPropertyRequest>,
+    ) -> Result<Response<()>, Status> {
+        let name: indradb::Identifier = map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().index_property_in_background(name))?;
+        Ok(Response::new(()))
+    }
+
+    async fn drop_index(&self, request: Request<crate::DropIndexRequest>) -> Result<Response<()>, Status> {
+        let name: indradb::Identifier = map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().drop_index(name))?;
+        Ok(Response::new(()))
+    }
+
+    async fn get_indexed_properties(
+        &self,
+        _: Request<()>,
+    ) -> Result<Response<crate::IndexedPropertiesResponse>, Status> {
+        let names = map_indradb_result(self.datastore.get_indexed_properties())?;
+        Ok(Response::new(names.into()))
+    }
+
+    async fn get_index_status(
+        &self,
+        request: Request<crate::GetIndexStatusRequest>,
+    ) -> Result<Response<crate::GetIndexStatusResponse>, Status> {
+        let name: indradb::Identifier = map_conversion_result(request.into_inner().try_into())?;
+        let status = map_indradb_result(self.datastore.get_index_status(name))?;
+        Ok(Response::new(status.into()))
+    }
+
+    async fn index_type_property(
+        &self,
+        request: Request<crate::IndexTypePropertyRequest>,
+    ) -> Result<Response<()>, Status> {
+        let (t, name): (indradb::Identifier, indradb::Identifier) =
+            map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().index_type_property(t, name))?;
+        Ok(Response::new(()))
+    }
+
+    async fn drop_type_index(&self, request: Request<crate::DropTypeIndexRequest>) -> Result<Response<()>, Status> {
+        let (t, name): (indradb::Identifier, indradb::Identifier) =
+            map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().drop_type_index(t, name))?;
+        Ok(Response::new(()))
+    }
+
+    async fn get_indexed_type_properties(
+        &self,
+        _: Request<()>,
+    ) -> Result<Response<crate::IndexedTypePropertiesResponse>, Status> {
+        let type_properties = map_indradb_result(self.datastore.get_indexed_type_properties())?;
+        Ok(Response::new(type_properties.into()))
+    }
+
+    async fn index_composite_properties(
+        &self,
+        request: Request<crate::IndexCompositePropertiesRequest>,
+    ) -> Result<Response<()>, Status> {
+        let names: Vec<indradb::Identifier> = map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().index_composite_properties(names))?;
+        Ok(Response::new(()))
+    }
+
+    async fn drop_composite_index(
+        &self,
+        request: Request<crate::DropCompositeIndexRequest>,
+    ) -> Result<Response<()>, Status> {
+        let names: Vec<indradb::Identifier> = map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().drop_composite_index(names))?;
+        Ok(Response::new(()))
+    }
+
+    async fn get_indexed_composite_properties(
+        &self,
+        _: Request<()>,
+    ) -> Result<Response<crate::IndexedCompositePropertiesResponse>, Status> {
+        let composite_properties = map_indradb_result(self.datastore.get_indexed_composite_properties())?;
+        Ok(Response::new(composite_properties.into()))
+    }
+
+    async fn add_unique_constraint(&self, request: Request<crate::UniqueConstraint>) -> Result<Response<()>, Status> {
+        let (name, t): (indradb::Identifier, Option<indradb::Identifier>) =
+            map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().add_unique_constraint(name, t))?;
+        Ok(Response::new(()))
+    }
+
+    async fn drop_unique_constraint(&self, request: Request<crate::UniqueConstraint>) -> Result<Response<()>, Status> {
+        let (name, t): (indradb::Identifier, Option<indradb::Identifier>) =
+            map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().drop_unique_constraint(name, t))?;
+        Ok(Response::new(()))
+    }
+
+    async fn get_unique_constraints(
+        &self,
+        _: Request<()>,
+    ) -> Result<Response<crate::UniqueConstraintsResponse>, Status> {
+        let constraints = map_indradb_result(self.datastore.get_unique_constraints())?;
+        Ok(Response::new(constraints.into()))
+    }
+
+    async fn set_schema(&self, request: Request<crate::SetSchemaRequest>) -> Result<Response<()>, Status> {
+        let schema: Option<indradb::Schema> = map_conversion_result(request.into_inner().try_into())?;
+        map_indradb_result(self.datastore.clone().set_schema(schema))?;
+        Ok(Response::new(()))
+    }
+
+    async fn get_schema(&self, _: Request<()>) -> Result<Response<crate::GetSchemaResponse>, Status> {
+        let schema = map_indradb_result(self.datastore.get_schema())?;
+        Ok(Response::new(schema.into()))
+    }
+
+    async fn get_schema_summary(&self, _: Request<()>) -> Result<Response<crate::GetSchemaSummaryResponse>, Status> {
+        let summary = map_indradb_result(self.datastore.get_schema_summary())?;
+        Ok(Response::new(summary.into()))
+    }
+
+    async fn backup(&self, request: Request<crate::BackupRequest>) -> Result<Response<()>, Status> {
+        let path = backup_path(
+            self.backup_dir.as_deref().map(PathBuf::as_path),
+            &request.into_inner().path,
+        )?;
+        let datastore = self.datastore.clone();
+        let result = tokio::task::spawn_blocking(move || datastore.backup(&path))
+            .await
+            .map_err(|err| Status::internal(format!("{}", err)))?;
+        map_indradb_result(result)?;
+        Ok(Response::new(()))
+    }
+
     async fn execute_plugin(
         &self,
         request: Request<crate::ExecutePluginRequest>,
@@ -428,15 +695,24 @@ impl<D: indradb::Datastore + Send + Sync + 'static> crate::indra_db_server::Indr
 /// # Arguments
 /// * `datastore`: The underlying datastore to use.
 /// * `listener`: The TCP listener to run the gRPC server on.
+/// * `backup_dir`: The directory that clients can back up to, if any.
 ///
 /// # Errors
 /// This will return an error if the gRPC fails to start on the given
 /// listener.
-pub async fn run<D>(datastore: Arc<D>, listener: TcpListener) -> Result<(), TonicTransportError>
+pub async fn run<D>(
+    datastore: Arc<D>,
+    listener: TcpListener,
+    backup_dir: Option<PathBuf>,
+) -> Result<(), TonicTransportError>
 where
     D: indradb::Datastore + Send + Sync + 'static,
 {
-    let service = crate::indra_db_server::IndraDbServer::new(Server::new(datastore));
+    let mut server = Server::new(datastore);
+    if let Some(backup_dir) = backup_dir {
+        server = server.with_backup_dir(backup_dir);
+    }
+    let service = crate::indra_db_server::IndraDbServer::new(server);
     let incoming = TcpListenerStream::new(listener);
     TonicServer::builder()
         .add_service(service)
@@ -453,6 +729,7 @@ where
 /// * `listener`: The TCP listener to run the gRPC server on.
 /// * `plugin_path_pattern`: A [glob](https://docs.rs/glob/0.3.0/glob/) to the
 ///   plugin paths to be used.
+/// * `backup_dir`: The directory that clients can back up to, if any.
 ///
 /// # Errors
 /// This will return an error if the gRPC fails to start on the given
@@ -465,6 +742,7 @@ pub async unsafe fn run_with_plugins<D>(
     datastore: Arc<D>,
     listener: TcpListener,
     plugin_path_pattern: &str,
+    backup_dir: Option<PathBuf>,
 ) -> Result<(), InitError>
 where
     D: indradb::Datastore + Send + Sync + 'static,
@@ -474,7 +752,10 @@ where
         plugin_paths.push(entry?);
     }
 
-    let server = Server::new_with_plugins(datastore, plugin_paths)?;
+    let mut server = Server::new_with_plugins(datastore, plugin_paths)?;
+    if let Some(backup_dir) = backup_dir {
+        server = server.with_backup_dir(backup_dir);
+    }
     let service = crate::indra_db_server::IndraDbServer::new(server);
     let incoming = TcpListenerStream::new(listener);
     TonicServer::builder()
@@ -484,3 +765,31 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::backup_path;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn should_only_back_up_to_the_backup_dir() {
+        let dir = Path::new("/backups");
+        assert_eq!(
+            backup_path(Some(dir), "daily/1").unwrap(),
+            PathBuf::from("/backups/daily/1")
+        );
+
+        for path in ["", "/etc/passwd", "../backups2", "daily/../../etc"] {
+            assert_eq!(
+                backup_path(Some(dir), path).unwrap_err().code(),
+                tonic::Code::InvalidArgument
+            );
+        }
+
+        // Backups aren't allowed without a directory for them
+        assert_eq!(
+            backup_path(None, "daily/1").unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+}
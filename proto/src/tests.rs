@@ -5,6 +5,7 @@
 
 use std::cell::RefCell;
 use std::convert::TryInto;
+use std::path::Path;
 use std::rc::Rc;
 use std::time::Duration;
 
@@ -16,13 +17,40 @@ use uuid::Uuid;
 fn map_client_result<T>(result: Result<T, crate::ClientError>) -> Result<T, indradb::Error> {
     result.map_err(|err| {
         match err {
-            // this is the only error variant we need to handle for testing
+            // these are the only error variants we need to handle for testing
             crate::ClientError::Grpc { inner }
                 if inner.code() == tonic::Code::Internal
                     && inner.message() == "query attempted on a property that isn't indexed" =>
             {
                 indradb::Error::NotIndexed
             }
+            crate::ClientError::Grpc { inner } if inner.code() == tonic::Code::FailedPrecondition => {
+                indradb::Error::IndexNotReady
+            }
+            crate::ClientError::Grpc { inner } if inner.code() == tonic::Code::Unimplemented => {
+                indradb::Error::Unsupported
+            }
+            crate::ClientError::Grpc { inner } if inner.code() == tonic::Code::AlreadyExists => {
+                indradb::Error::ConstraintViolation
+            }
+            crate::ClientError::Grpc { inner }
+                if inner.code() == tonic::Code::InvalidArgument && inner.message() == "invalid continuation token" =>
+            {
+                indradb::Error::InvalidContinuationToken
+            }
+            crate::ClientError::Grpc { inner }
+                if inner.code() == tonic::Code::InvalidArgument
+                    && inner.message() == "page limit must be greater than zero" =>
+            {
+                indradb::Error::InvalidPageLimit
+            }
+            crate::ClientError::Grpc { inner }
+                if inner.code() == tonic::Code::InvalidArgument
+                    && inner.message().starts_with("write violates the schema: ") =>
+            {
+                let message = &inner.message()["write violates the schema: ".len()..];
+                indradb::Error::SchemaViolation(message.to_string())
+            }
             // unexpected error variant
             _ => panic!("{}", err),
         }
@@ -87,6 +115,19 @@ impl indradb::Datastore for ClientDatastore {
         )
     }
 
+    fn get_vertices_page(
+        &self,
+        q: indradb::VertexQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::Vertex>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_vertices_page(q, limit, token)),
+        )
+    }
+
     fn delete_vertices(&self, q: indradb::VertexQuery) -> Result<(), indradb::Error> {
         map_client_result(
             self.exec
@@ -103,6 +144,14 @@ impl indradb::Datastore for ClientDatastore {
         )
     }
 
+    fn count_vertices(&self, q: indradb::VertexQuery) -> Result<u64, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().count_vertices(q)),
+        )
+    }
+
     fn create_edge(&self, e: &indradb::EdgeKey) -> Result<bool, indradb::Error> {
         map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().create_edge(e)))
     }
@@ -111,6 +160,23 @@ impl indradb::Datastore for ClientDatastore {
         map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().get_edges(q)))
     }
 
+    fn get_edges_page(
+        &self,
+        q: indradb::EdgeQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::Edge>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_edges_page(q, limit, token)),
+        )
+    }
+
+    fn get_paths(&self, q: indradb::PathQuery) -> Result<Vec<Vec<indradb::Edge>>, indradb::Error> {
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().get_paths(q)))
+    }
+
     fn delete_edges(&self, q: indradb::EdgeQuery) -> Result<(), indradb::Error> {
         map_client_result(
             self.exec
@@ -132,6 +198,10 @@ impl indradb::Datastore for ClientDatastore {
         )
     }
 
+    fn count_edges(&self, q: indradb::EdgeQuery) -> Result<u64, indradb::Error> {
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().count_edges(q)))
+    }
+
     fn get_vertex_properties(
         &self,
         q: indradb::VertexPropertyQuery,
@@ -143,6 +213,19 @@ impl indradb::Datastore for ClientDatastore {
         )
     }
 
+    fn get_vertex_properties_page(
+        &self,
+        q: indradb::VertexPropertyQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::VertexProperty>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_vertex_properties_page(q, limit, token)),
+        )
+    }
+
     fn get_all_vertex_properties(
         &self,
         q: indradb::VertexQuery,
@@ -182,6 +265,19 @@ impl indradb::Datastore for ClientDatastore {
         )
     }
 
+    fn get_edge_properties_page(
+        &self,
+        q: indradb::EdgePropertyQuery,
+        limit: u32,
+        token: Option<indradb::ContinuationToken>,
+    ) -> Result<indradb::Page<indradb::EdgeProperty>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_edge_properties_page(q, limit, token)),
+        )
+    }
+
     fn get_all_edge_properties(&self, q: indradb::EdgeQuery) -> Result<Vec<indradb::EdgeProperties>, indradb::Error> {
         map_client_result(
             self.exec
@@ -218,6 +314,13 @@ impl indradb::Datastore for ClientDatastore {
         )
     }
 
+    fn backup(&self, path: &Path) -> Result<(), indradb::Error> {
+        // The server backs up to the temporary directory, which is where the
+        // tests make their backups
+        let path = path.strip_prefix(std::env::temp_dir()).unwrap().to_str().unwrap();
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().backup(path)))
+    }
+
     fn index_property(&self, name: indradb::Identifier) -> Result<(), indradb::Error> {
         map_client_result(
             self.exec
@@ -225,6 +328,140 @@ impl indradb::Datastore for ClientDatastore {
                 .block_on(self.client.borrow_mut().index_property(name)),
         )
     }
+
+    fn index_property_in_background(&self, name: indradb::Identifier) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().index_property_in_background(name)),
+        )
+    }
+
+    fn drop_index(&self, name: indradb::Identifier) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().drop_index(name)),
+        )
+    }
+
+    fn get_indexed_properties(&self) -> Result<Vec<indradb::Identifier>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_indexed_properties()),
+        )
+    }
+
+    fn get_index_status(&self, name: indradb::Identifier) -> Result<Option<indradb::IndexStatus>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_index_status(name)),
+        )
+    }
+
+    fn index_type_property(&self, t: indradb::Identifier, name: indradb::Identifier) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().index_type_property(t, name)),
+        )
+    }
+
+    fn drop_type_index(&self, t: indradb::Identifier, name: indradb::Identifier) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().drop_type_index(t, name)),
+        )
+    }
+
+    fn get_indexed_type_properties(&self) -> Result<Vec<(indradb::Identifier, indradb::Identifier)>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_indexed_type_properties()),
+        )
+    }
+
+    fn index_composite_properties(&self, names: Vec<indradb::Identifier>) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().index_composite_properties(names)),
+        )
+    }
+
+    fn drop_composite_index(&self, names: Vec<indradb::Identifier>) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().drop_composite_index(names)),
+        )
+    }
+
+    fn get_indexed_composite_properties(&self) -> Result<Vec<Vec<indradb::Identifier>>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_indexed_composite_properties()),
+        )
+    }
+
+    fn add_unique_constraint(
+        &self,
+        name: indradb::Identifier,
+        t: Option<indradb::Identifier>,
+    ) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().add_unique_constraint(name, t)),
+        )
+    }
+
+    fn drop_unique_constraint(
+        &self,
+        name: indradb::Identifier,
+        t: Option<indradb::Identifier>,
+    ) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().drop_unique_constraint(name, t)),
+        )
+    }
+
+    fn get_unique_constraints(
+        &self,
+    ) -> Result<Vec<(indradb::Identifier, Option<indradb::Identifier>)>, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_unique_constraints()),
+        )
+    }
+
+    fn set_schema(&self, schema: Option<indradb::Schema>) -> Result<(), indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().set_schema(schema)),
+        )
+    }
+
+    fn get_schema(&self) -> Result<Option<indradb::Schema>, indradb::Error> {
+        map_client_result(self.exec.borrow_mut().block_on(self.client.borrow_mut().get_schema()))
+    }
+
+    fn get_schema_summary(&self) -> Result<indradb::SchemaSummary, indradb::Error> {
+        map_client_result(
+            self.exec
+                .borrow_mut()
+                .block_on(self.client.borrow_mut().get_schema_summary()),
+        )
+    }
 }
 
 full_test_impl!({
@@ -240,7 +477,8 @@ full_test_impl!({
     rt.spawn(crate::run_server(
         Arc::new(indradb::MemoryDatastore::default()),
         listener,
+        Some(std::env::temp_dir()),
     ));
 
-    ClientDatastore::new(port as u16, rt)
+    ClientDatastore::new(port, rt)
 });
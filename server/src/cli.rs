@@ -1,29 +1,44 @@
-use clap::{value_t, App, Arg, SubCommand};
+use clap::{value_t, App, Arg, ArgMatches, SubCommand};
 use std::ffi::OsString;
+use std::str::FromStr;
 
 pub struct CliArgs {
     pub addr: String,
     pub datastore_args: CliDatastoreArgs,
     pub plugin_path: Option<String>,
+    pub backup_dir: Option<OsString>,
 }
 
 pub enum CliDatastoreArgs {
     Memory {
         path: Option<OsString>,
+        wal: bool,
     },
     Rocksdb {
         path: OsString,
-        max_open_files: i32,
+        config_path: Option<OsString>,
+        max_open_files: Option<i32>,
+        block_cache_size: Option<usize>,
+        bloom_filter_bits_per_key: Option<i32>,
         repair: bool,
+        verify: bool,
+        restore_path: Option<OsString>,
     },
 }
 
 const ADDRESS: &str = "ADDRESS";
 const PLUGIN_PATH: &str = "PLUGIN_PATH";
+const BACKUP_DIR: &str = "BACKUP_DIR";
 const DATABASE_PATH: &str = "DATABASE_PATH";
+const ROCKSDB_CONFIG_PATH: &str = "ROCKSDB_CONFIG_PATH";
 const ROCKSDB_MAX_OPEN_FILES: &str = "ROCKSDB_MAX_OPEN_FILES";
+const ROCKSDB_BLOCK_CACHE_SIZE: &str = "ROCKSDB_BLOCK_CACHE_SIZE";
+const ROCKSDB_BLOOM_FILTER_BITS: &str = "ROCKSDB_BLOOM_FILTER_BITS";
 const ROCKSDB_REPAIR: &str = "ROCKSDB_REPAIR";
+const ROCKSDB_VERIFY: &str = "ROCKSDB_VERIFY";
+const ROCKSDB_RESTORE_PATH: &str = "ROCKSDB_RESTORE_PATH";
 const MEMORY_PERSIST_PATH: &str = "MEMORY_PERSIST_PATH";
+const MEMORY_WAL: &str = "MEMORY_WAL";
 
 pub fn parse_cli_args() -> CliArgs {
     let database_path_argument = Arg::with_name(DATABASE_PATH)
@@ -46,6 +61,12 @@ pub fn parse_cli_args() -> CliArgs {
         .help("Path to plugins")
         .takes_value(true);
 
+    let backup_dir = Arg::with_name(BACKUP_DIR)
+        .long("backup-dir")
+        .value_name(BACKUP_DIR)
+        .help("The directory that clients can back up the datastore to. Backup paths are relative to it. If unspecified, clients can't make backups.")
+        .takes_value(true);
+
     let memory_subcommand = SubCommand::with_name("memory")
         .about("Start an indradb instance backed by memory. This is the default, so including this subcommand is only useful if you want to set options.")
         .arg(
@@ -54,18 +75,45 @@ pub fn parse_cli_args() -> CliArgs {
                 .value_name(MEMORY_PERSIST_PATH)
                 .help("Sets the path to persist images. If unspecified, the datastore will not be persisted.")
                 .takes_value(true)
+        )
+        .arg(
+            Arg::with_name(MEMORY_WAL)
+                .long("wal")
+                .help("Records changes in a write-ahead log next to the persisted image, so that changes made since the last sync aren't lost on a crash.")
+                .requires(MEMORY_PERSIST_PATH)
+                .takes_value(false),
         );
 
     let rocksdb_subcommand = SubCommand::with_name("rocksdb")
         .about("Start an indradb instance backed by rocksdb")
         .arg(&database_path_argument)
+        .arg(
+            Arg::with_name(ROCKSDB_CONFIG_PATH)
+                .long("config")
+                .value_name(ROCKSDB_CONFIG_PATH)
+                .help("Sets the path to a JSON file of RocksDB tuning options. Options that are also set on the command line are overridden.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name(ROCKSDB_MAX_OPEN_FILES)
                 .long("max-open-files")
                 .value_name(ROCKSDB_MAX_OPEN_FILES)
-                .help("Sets the number of maximum open files to have open in RocksDB.")
-                .takes_value(true)
-                .default_value("512"),
+                .help("Sets the number of maximum open files to have open in RocksDB. Defaults to 512.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(ROCKSDB_BLOCK_CACHE_SIZE)
+                .long("block-cache-size")
+                .value_name(ROCKSDB_BLOCK_CACHE_SIZE)
+                .help("Sets the size of the RocksDB block cache, in bytes.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(ROCKSDB_BLOOM_FILTER_BITS)
+                .long("bloom-filter-bits")
+                .value_name(ROCKSDB_BLOOM_FILTER_BITS)
+                .help("Sets the number of bits per key for RocksDB bloom filters. Defaults to 10.")
+                .takes_value(true),
         )
         .arg(
             Arg::with_name(ROCKSDB_REPAIR)
@@ -73,11 +121,26 @@ pub fn parse_cli_args() -> CliArgs {
                 .short("r")
                 .help("Repair the database at the given path rather than staring a server")
                 .takes_value(false),
+        )
+        .arg(
+            Arg::with_name(ROCKSDB_VERIFY)
+                .long("verify")
+                .help("Verify the integrity of the database at the given path rather than starting a server. With --repair, any inconsistencies that are found are also fixed.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name(ROCKSDB_RESTORE_PATH)
+                .long("restore")
+                .value_name(ROCKSDB_RESTORE_PATH)
+                .help("Restore the database at the given path from the latest backup at this path rather than starting a server. Any existing database at the given path is replaced.")
+                .takes_value(true)
+                .conflicts_with_all(&[ROCKSDB_REPAIR, ROCKSDB_VERIFY]),
         );
 
     let matches = App::new("indradb-server")
         .arg(&addr)
         .arg(&plugin_path)
+        .arg(&backup_dir)
         .subcommand(memory_subcommand)
         .subcommand(rocksdb_subcommand)
         .get_matches();
@@ -88,19 +151,38 @@ pub fn parse_cli_args() -> CliArgs {
             if let Some(path) = matches.value_of_os(MEMORY_PERSIST_PATH) {
                 CliDatastoreArgs::Memory {
                     path: Some(path.to_os_string()),
+                    wal: matches.is_present(MEMORY_WAL),
                 }
             } else {
-                CliDatastoreArgs::Memory { path: None }
+                CliDatastoreArgs::Memory { path: None, wal: false }
             }
         } else if let Some(matches) = matches.subcommand_matches("rocksdb") {
             CliDatastoreArgs::Rocksdb {
                 path: matches.value_of_os(DATABASE_PATH).unwrap().to_os_string(),
-                max_open_files: value_t!(matches, ROCKSDB_MAX_OPEN_FILES, i32).unwrap_or_else(|e| e.exit()),
+                config_path: matches.value_of_os(ROCKSDB_CONFIG_PATH).map(|path| path.to_os_string()),
+                max_open_files: optional_value(matches, ROCKSDB_MAX_OPEN_FILES),
+                block_cache_size: optional_value(matches, ROCKSDB_BLOCK_CACHE_SIZE),
+                bloom_filter_bits_per_key: optional_value(matches, ROCKSDB_BLOOM_FILTER_BITS),
                 repair: matches.is_present(ROCKSDB_REPAIR),
+                verify: matches.is_present(ROCKSDB_VERIFY),
+                restore_path: matches
+                    .value_of_os(ROCKSDB_RESTORE_PATH)
+                    .map(|path| path.to_os_string()),
             }
         } else {
-            CliDatastoreArgs::Memory { path: None }
+            CliDatastoreArgs::Memory { path: None, wal: false }
         },
         plugin_path: matches.value_of(PLUGIN_PATH).map(|s| s.to_string()),
+        backup_dir: matches.value_of_os(BACKUP_DIR).map(|path| path.to_os_string()),
+    }
+}
+
+// Parses an optional argument, exiting with an error message if it's set but
+// invalid.
+fn optional_value<T: FromStr>(matches: &ArgMatches, name: &str) -> Option<T> {
+    if matches.is_present(name) {
+        Some(value_t!(matches, name, T).unwrap_or_else(|e| e.exit()))
+    } else {
+        None
     }
 }
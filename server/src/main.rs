@@ -3,8 +3,11 @@ extern crate clap;
 mod cli;
 
 use std::error::Error;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::BufReader;
 use std::net::ToSocketAddrs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::cli::CliDatastoreArgs;
@@ -12,19 +15,27 @@ use crate::cli::CliDatastoreArgs;
 use indradb_proto as proto;
 use tokio::net::TcpListener;
 
-async fn run_server<D>(datastore: D, listener: TcpListener, plugin_path: &Option<String>) -> Result<(), Box<dyn Error>>
+const DEFAULT_ROCKSDB_MAX_OPEN_FILES: i32 = 512;
+
+async fn run_server<D>(
+    datastore: D,
+    listener: TcpListener,
+    plugin_path: &Option<String>,
+    backup_dir: &Option<OsString>,
+) -> Result<(), Box<dyn Error>>
 where
     D: indradb::Datastore + Send + Sync + 'static,
 {
     let binding = listener.local_addr()?;
     println!("grpc://{}", binding);
 
+    let backup_dir = backup_dir.as_ref().map(PathBuf::from);
     if let Some(plugin_path) = plugin_path {
         unsafe {
-            proto::run_server_with_plugins(Arc::new(datastore), listener, plugin_path).await?;
+            proto::run_server_with_plugins(Arc::new(datastore), listener, plugin_path, backup_dir).await?;
         }
     } else {
-        proto::run_server(Arc::new(datastore), listener).await?;
+        proto::run_server(Arc::new(datastore), listener, backup_dir).await?;
     }
 
     Ok(())
@@ -40,27 +51,74 @@ pub async fn main() -> Result<(), Box<dyn Error>> {
     match args.datastore_args {
         CliDatastoreArgs::Rocksdb {
             path,
+            config_path,
             max_open_files,
+            block_cache_size,
+            bloom_filter_bits_per_key,
             repair,
+            verify,
+            restore_path,
         } => {
+            let mut config: indradb::RocksdbConfig = match config_path {
+                Some(config_path) => serde_json::from_reader(BufReader::new(File::open(config_path)?))?,
+                None => indradb::RocksdbConfig::default(),
+            };
+            if max_open_files.is_some() {
+                config.max_open_files = max_open_files;
+            }
+            config.max_open_files.get_or_insert(DEFAULT_ROCKSDB_MAX_OPEN_FILES);
+            if block_cache_size.is_some() {
+                config.block_cache_size = block_cache_size;
+            }
+            if bloom_filter_bits_per_key.is_some() {
+                config.bloom_filter_bits_per_key = bloom_filter_bits_per_key;
+            }
+
             if repair {
-                indradb::RocksdbDatastore::repair(&path, Some(max_open_files))
+                indradb::RocksdbDatastore::repair(&path, config.max_open_files)
                     .expect("Expected to be able to repair the RocksDB datastore");
                 println!("repair successful");
+                if !verify {
+                    return Ok(());
+                }
+            }
+
+            if verify {
+                let datastore = indradb::RocksdbDatastore::new_with_config(&path, &config)
+                    .expect("Expected to be able to create the RocksDB datastore");
+                let inconsistencies = datastore.verify(repair)?;
+                for inconsistency in &inconsistencies {
+                    println!("{}", inconsistency);
+                }
+                if inconsistencies.is_empty() {
+                    println!("verify successful");
+                } else if repair {
+                    println!("fixed {} inconsistencies", inconsistencies.len());
+                } else {
+                    return Err(format!("found {} inconsistencies", inconsistencies.len()).into());
+                }
+                return Ok(());
+            }
+
+            if let Some(restore_path) = restore_path {
+                indradb::RocksdbDatastore::restore(&restore_path, &path)
+                    .expect("Expected to be able to restore the RocksDB datastore");
+                println!("restore successful");
                 return Ok(());
             }
 
-            let datastore = indradb::RocksdbDatastore::new(&path, Some(max_open_files))
+            let datastore = indradb::RocksdbDatastore::new_with_config(&path, &config)
                 .expect("Expected to be able to create the RocksDB datastore");
-            run_server(datastore, listener, &args.plugin_path).await
+            run_server(datastore, listener, &args.plugin_path, &args.backup_dir).await
         }
-        CliDatastoreArgs::Memory { path } => {
+        CliDatastoreArgs::Memory { path, wal } => {
             let datastore = match path {
                 None => indradb::MemoryDatastore::default(),
+                Some(path) if wal => indradb::MemoryDatastore::read_with_wal(path)?,
                 Some(path) if Path::new(path.as_os_str()).exists() => indradb::MemoryDatastore::read(path)?,
                 Some(path) => indradb::MemoryDatastore::create(path)?,
             };
-            run_server(datastore, listener, &args.plugin_path).await
+            run_server(datastore, listener, &args.plugin_path, &args.backup_dir).await
         }
     }
 }